@@ -147,6 +147,21 @@ impl<Meta: ArrayMetadata> AnyDenseArray<Meta> {
     }
 }
 
+/// Reclassify method for `AnyDenseArray`.
+impl<Meta: ArrayMetadata> AnyDenseArray<Meta> {
+    /// Reclassifies the array values using the rules of the reclassification table, returning a new `AnyDenseArray`
+    /// of the requested data type.
+    ///
+    /// This is a type-erased wrapper around `algo::reclassify`.
+    pub fn reclassify(&self, table: &algo::ReclassTable, data_type: ArrayDataType) -> Result<AnyDenseArray<Meta>> {
+        Ok(dispatch_datatype!(
+            data_type,
+            T,
+            dispatch_anydensearray!(self, arr, algo::reclassify::<T, _>(arr, table)?)
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +246,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn anydensearray_reclassify() {
+        let meta = create_test_metadata(2, 2);
+        let data = aligned_vec_from_slice(&[1.5_f64, 4.0, f64::NODATA, 12.0]);
+        let any_raster = AnyDenseArray::F64(DenseArray::<f64, _>::new(meta, data).unwrap());
+
+        let table = algo::ReclassTable::new(vec![algo::ReclassRule::Range {
+            from: 0.0,
+            to: 5.0,
+            new_value: Some(1.0),
+        }]);
+
+        let result = any_raster.reclassify(&table, ArrayDataType::Int16).unwrap();
+        assert_eq!(result.data_type(), ArrayDataType::Int16);
+        assert_eq!(result.cell_value::<i16>(crate::Cell::from_row_col(0, 0)), Some(1));
+        assert_eq!(result.cell_value::<i16>(crate::Cell::from_row_col(0, 1)), Some(1));
+        assert_eq!(result.cell_value::<i16>(crate::Cell::from_row_col(1, 0)), None);
+        assert_eq!(result.cell_value::<i16>(crate::Cell::from_row_col(1, 1)), Some(12));
+    }
+
     #[test]
     fn division_output_type() {
         let meta = create_test_metadata(2, 2);
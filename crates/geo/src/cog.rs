@@ -1,20 +1,32 @@
 //! Cloud Optimized GeoTIFF (COG) support and web tile utilities.
 
-#[cfg(all(feature = "gdal", feature = "raster-io-geotiff"))]
-#[cfg_attr(docsrs, doc(cfg(all(feature = "gdal", feature = "raster-io-geotiff"))))]
+#[cfg(feature = "raster-io-geotiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "raster-io-geotiff")))]
 pub mod debug;
 
+#[cfg(feature = "raster-io-geotiff")]
+mod builder;
+#[cfg(feature = "raster-io-geotiff")]
+mod chunkcache;
 #[cfg(feature = "gdal")]
 mod creation;
+mod creationoptions;
 
+#[cfg(feature = "raster-io-geotiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "raster-io-geotiff")))]
+pub use builder::{create_cog_from_raster, create_cog_from_raster_with_tile_matrix_set};
 #[cfg(feature = "gdal")]
 #[cfg_attr(docsrs, doc(cfg(feature = "gdal")))]
-pub use creation::{CogCreationOptions, PredictorSelection, create_cog_tiles, create_gdal_warp_args, create_multiband_cog_tiles};
+pub use creation::{create_cog_tiles, create_gdal_warp_args, create_multiband_cog_tiles};
+pub use creationoptions::{CogCreationOptions, PredictorSelection};
 
 #[cfg(feature = "raster-io-geotiff")]
 #[cfg_attr(docsrs, doc(cfg(feature = "raster-io-geotiff")))]
 mod webtiles;
 
+#[cfg(feature = "raster-io-geotiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "raster-io-geotiff")))]
+pub use chunkcache::{ChunkCache, ChunkCacheKey, ChunkCacheStats, DEFAULT_CHUNK_CACHE_SIZE};
 #[cfg(feature = "raster-io-geotiff")]
 #[cfg_attr(docsrs, doc(cfg(feature = "raster-io-geotiff")))]
 pub use webtiles::{TileSource, WebTileInfo, WebTilesReader};
@@ -0,0 +1,703 @@
+//! Native creation of Cloud Optimized GeoTIFFs aligned to a tile matrix set (`GoogleMapsCompatible` by default), no GDAL required.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use num::NumCast;
+
+use crate::{
+    Array as _, ArrayDataType, ArrayNum, CellSize, Columns, Error, GeoReference, Point, RasterSize, Result, Rows, Tile, TileMatrixSet,
+    cog::{CogCreationOptions, PredictorSelection},
+    geotiff::{
+        ChunkDataLayout, GdalMetadata, TiffStats,
+        encoder::{self, DataOrder, ImageFileDirectory, TagValue, TiffEncodeOptions},
+        writer,
+    },
+    raster::{
+        DenseRaster, Predictor,
+        algo::{self, Cast as _, Scale},
+    },
+};
+
+const TILING_SCHEME_DOMAIN: &str = "TILING_SCHEME";
+
+/// Tolerance (as a fraction of a tile) when snapping the raster extent to the tile grid
+const ALIGNMENT_TOLERANCE: f64 = 1e-6;
+
+/// Creates a Cloud Optimized GeoTIFF using the `GoogleMapsCompatible` tiling scheme from a raster in web mercator (EPSG:3857).
+/// The raster is resampled (nearest neighbour) to the pixel size of the selected zoom level and the overview pyramid
+/// is generated down to the minimum zoom level. The output layout matches the COGs created by GDAL so the result can be served
+/// using the [`crate::cog::WebTilesReader`].
+pub fn create_cog_from_raster<T: ArrayNum>(raster: &DenseRaster<T>, output: &Path, opts: CogCreationOptions) -> Result<()> {
+    create_cog_from_raster_with_tile_matrix_set(raster, output, opts, &TileMatrixSet::google_maps_compatible())
+}
+
+/// Creates a Cloud Optimized GeoTIFF aligned to the tiles of the given tile matrix set, the raster has to be in the CRS of the tile matrix set.
+/// The name of the tile matrix set is stored in the `TILING_SCHEME` metadata.
+pub fn create_cog_from_raster_with_tile_matrix_set<T: ArrayNum>(
+    raster: &DenseRaster<T>,
+    output: &Path,
+    opts: CogCreationOptions,
+    tms: &TileMatrixSet,
+) -> Result<()> {
+    if !is_in_crs(raster.metadata(), tms) {
+        return Err(Error::InvalidArgument(format!(
+            "COG creation for the {} tiling scheme requires a raster in the {} projection",
+            tms.id, tms.crs
+        )));
+    }
+
+    if opts.tile_size == 0 || !opts.tile_size.is_multiple_of(Tile::TILE_SIZE) {
+        return Err(Error::InvalidArgument(format!(
+            "COG tile size must be a multiple of {}, got {}",
+            Tile::TILE_SIZE,
+            opts.tile_size
+        )));
+    }
+
+    if opts.scale {
+        let unscaled = raster.cast::<f64>();
+        return match opts.output_data_type {
+            Some(ArrayDataType::Uint8) | None => {
+                write_cog(&Scale::<f64, u8>::scale(&unscaled, None)?, Some(u8::MAX.into()), output, &opts, tms)
+            }
+            Some(ArrayDataType::Uint16) => write_cog(
+                &Scale::<f64, u16>::scale(&unscaled, None)?,
+                Some(u16::MAX.into()),
+                output,
+                &opts,
+                tms,
+            ),
+            Some(_) => Err(Error::InvalidArgument(
+                "Scaling only supports output data type: u8 or u16".to_string(),
+            )),
+        };
+    }
+
+    match opts.output_data_type {
+        Some(data_type) if data_type != T::TYPE => {
+            crate::dispatch_datatype_nowrap!(data_type, TDest, write_cog(&raster.cast::<TDest>(), None, output, &opts, tms))
+        }
+        _ => write_cog(raster, None, output, &opts, tms),
+    }
+}
+
+fn is_in_crs(geo_reference: &GeoReference, tms: &TileMatrixSet) -> bool {
+    geo_reference.projection().trim() == tms.crs.to_string() || geo_reference.epsg() == Some(tms.crs)
+}
+
+/// The nodata value of the output: the provided override, the nodata of the input if it is representable
+/// in the output type or the default nodata value of the output type otherwise
+fn output_nodata<T: ArrayNum>(geo_reference: &GeoReference, nodata_override: Option<f64>) -> f64 {
+    nodata_override
+        .or_else(|| geo_reference.nodata().filter(|nod| <T as NumCast>::from(*nod).is_some()))
+        .unwrap_or_else(|| T::TYPE.default_nodata_value())
+}
+
+fn predictor_for_type<T: ArrayNum>(opts: &CogCreationOptions) -> Option<Predictor> {
+    opts.compression?;
+
+    match opts.predictor? {
+        PredictorSelection::Horizontal => Some(Predictor::Horizontal),
+        PredictorSelection::FloatingPoint => Some(Predictor::FloatingPoint),
+        PredictorSelection::Automatic => match T::TYPE {
+            ArrayDataType::Float32 | ArrayDataType::Float64 => Some(Predictor::FloatingPoint),
+            _ => Some(Predictor::Horizontal),
+        },
+    }
+}
+
+/// Rounds the value down, values within the alignment tolerance of the next integer are rounded up
+fn snap_floor(value: f64) -> f64 {
+    if (value - value.round()).abs() < ALIGNMENT_TOLERANCE {
+        value.round()
+    } else {
+        value.floor()
+    }
+}
+
+/// Rounds the value up, values within the alignment tolerance of the previous integer are rounded down
+fn snap_ceil(value: f64) -> f64 {
+    if (value - value.round()).abs() < ALIGNMENT_TOLERANCE {
+        value.round()
+    } else {
+        value.ceil()
+    }
+}
+
+/// The zoom levels of the COG: the zoom level of the full resolution image and the number of overviews
+fn zoom_levels(geo_reference: &GeoReference, opts: &CogCreationOptions, tms: &TileMatrixSet) -> Result<(i32, usize)> {
+    let max_zoom = tms.zoom_level_for_cell_size(geo_reference.cell_size_x(), opts.zoom_level_strategy, opts.tile_size);
+
+    let overview_count = match opts.min_zoom {
+        Some(min_zoom) => (max_zoom - min_zoom).max(0) as usize,
+        None => {
+            // Keep adding overviews until the raster fits in a single tile
+            let scale = geo_reference.cell_size_x() / tms.cell_size(max_zoom, opts.tile_size)?;
+            let mut cols = (geo_reference.columns().count() as f64 * scale).ceil() as u64;
+            let mut rows = (geo_reference.rows().count() as f64 * scale).ceil() as u64;
+            let mut count = 0;
+            while cols > opts.tile_size as u64 || rows > opts.tile_size as u64 {
+                cols = cols.div_ceil(2);
+                rows = rows.div_ceil(2);
+                count += 1;
+            }
+            count
+        }
+    };
+
+    Ok((max_zoom, overview_count))
+}
+
+/// Calculates the full resolution grid of the COG, the extent is expanded to the tile grid of the lowest aligned zoom level
+fn full_resolution_geo_reference(
+    geo_reference: &GeoReference,
+    max_zoom: i32,
+    aligned_levels: usize,
+    opts: &CogCreationOptions,
+    tms: &TileMatrixSet,
+) -> Result<GeoReference> {
+    let pixel_size = tms.cell_size(max_zoom, opts.tile_size)?;
+    let tile_extent = pixel_size * opts.tile_size as f64 * f64::powi(2.0, aligned_levels as i32 - 1);
+    let matrix_bounds = tms.tile_matrix(max_zoom)?.bounds();
+    let origin = matrix_bounds.top_left();
+
+    let bbox = geo_reference.bounding_box();
+    let left = (origin.x() + snap_floor((bbox.top_left().x() - origin.x()) / tile_extent) * tile_extent).max(origin.x());
+    let right =
+        (origin.x() + snap_ceil((bbox.bottom_right().x() - origin.x()) / tile_extent) * tile_extent).min(matrix_bounds.bottom_right().x());
+    let top = (origin.y() - snap_floor((origin.y() - bbox.top_left().y()) / tile_extent) * tile_extent).min(origin.y());
+    let bottom =
+        (origin.y() - snap_ceil((origin.y() - bbox.bottom_right().y()) / tile_extent) * tile_extent).max(matrix_bounds.bottom_right().y());
+
+    let raster_size = RasterSize::with_rows_cols(
+        Rows(((top - bottom) / pixel_size).round().max(1.0) as i32),
+        Columns(((right - left) / pixel_size).round().max(1.0) as i32),
+    );
+
+    let mut result = GeoReference::with_top_left_origin(
+        tms.crs.to_string(),
+        raster_size,
+        Point::new(left, top),
+        CellSize::square(pixel_size),
+        Option::<f64>::None,
+    );
+    result.set_scale(geo_reference.scale());
+    Ok(result)
+}
+
+/// Nearest neighbour resampling of the input raster to the full resolution grid of the COG
+fn resample_full_resolution<T: ArrayNum>(raster: &DenseRaster<T>, target: &GeoReference, nodata: T) -> Vec<T> {
+    let source = raster.metadata();
+    let cols = target.columns().count();
+    let rows = target.rows().count();
+
+    let mut data = vec![nodata; target.raster_size().cell_count()];
+    for row in 0..rows {
+        let y = target.cell_center(crate::Cell::from_row_col(row, 0)).y();
+        let source_row = source.y_to_row(y);
+        if source_row < 0 || source_row >= source.rows().count() {
+            continue;
+        }
+
+        for col in 0..cols {
+            let x = target.cell_center(crate::Cell::from_row_col(row, col)).x();
+            let source_col = source.x_to_col(x);
+            if source_col < 0 || source_col >= source.columns().count() {
+                continue;
+            }
+
+            if let Some(v) = raster.cell_value(crate::Cell::from_row_col(source_row, source_col)) {
+                data[(row * cols + col) as usize] = v;
+            }
+        }
+    }
+
+    data
+}
+
+/// Creates the next overview level using nearest neighbour resampling
+fn downsample<T: ArrayNum>(data: &[T], raster_size: RasterSize) -> (Vec<T>, RasterSize) {
+    let cols = raster_size.cols.count() as usize;
+    let rows = raster_size.rows.count() as usize;
+    let overview_cols = cols.div_ceil(2);
+    let overview_rows = rows.div_ceil(2);
+
+    let mut overview = Vec::with_capacity(overview_rows * overview_cols);
+    for row in 0..overview_rows {
+        let source_row = (row * 2 + 1).min(rows - 1);
+        for col in 0..overview_cols {
+            overview.push(data[source_row * cols + (col * 2 + 1).min(cols - 1)]);
+        }
+    }
+
+    (
+        overview,
+        RasterSize::with_rows_cols(Rows(overview_rows as i32), Columns(overview_cols as i32)),
+    )
+}
+
+/// Statistics of the full resolution image, stored in the GDAL metadata
+fn raster_statistics<T: ArrayNum>(data: &[T], nodata: T) -> Result<Option<TiffStats>> {
+    #[allow(clippy::eq_op)]
+    let nodata_is_nan = nodata != nodata;
+    #[allow(clippy::eq_op)]
+    let values: Vec<T> = data
+        .iter()
+        .copied()
+        .filter(|v| if nodata_is_nan { *v == *v } else { *v != nodata })
+        .collect();
+
+    Ok(algo::values_statistics(values, &[])?.map(|stats| TiffStats {
+        minimum_value: stats.min.to_f64().unwrap_or(f64::NAN),
+        maximum_value: stats.max.to_f64().unwrap_or(f64::NAN),
+        mean: stats.mean,
+        standard_deviation: stats.stddev,
+        valid_pixel_percentage: stats.value_count as f64 * 100.0 / data.len() as f64,
+    }))
+}
+
+/// The GDAL structural metadata that marks the file as a COG
+fn gdal_ghost_area() -> Vec<u8> {
+    const GHOST_METADATA: &str = "LAYOUT=IFDS_BEFORE_DATA\nBLOCK_ORDER=ROW_MAJOR\nBLOCK_LEADER=SIZE_AS_UINT4\nBLOCK_TRAILER=LAST_4_BYTES_REPEATED\nKNOWN_INCOMPATIBLE_EDITION=NO\n";
+    format!("GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{GHOST_METADATA}", GHOST_METADATA.len()).into_bytes()
+}
+
+fn write_cog<T: ArrayNum>(
+    raster: &DenseRaster<T>,
+    nodata_override: Option<f64>,
+    output: &Path,
+    opts: &CogCreationOptions,
+    tms: &TileMatrixSet,
+) -> Result<()> {
+    let nodata_value = output_nodata::<T>(raster.metadata(), nodata_override);
+    let nodata: T = NumCast::from(nodata_value).unwrap_or(T::NODATA);
+    let predictor = predictor_for_type::<T>(opts);
+
+    let (max_zoom, overview_count) = zoom_levels(raster.metadata(), opts, tms)?;
+    let aligned_levels = match opts.aligned_levels {
+        Some(levels) if levels >= 1 && levels as usize <= overview_count + 1 => levels as usize,
+        Some(levels) => {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid number of aligned levels {levels}, the COG contains {} level(s)",
+                overview_count + 1
+            )));
+        }
+        None if opts.min_zoom.is_some() => overview_count + 1,
+        None => 1,
+    };
+
+    let mut geo_reference = full_resolution_geo_reference(raster.metadata(), max_zoom, aligned_levels, opts, tms)?;
+    geo_reference.set_nodata(Some(nodata_value));
+
+    let layout = ChunkDataLayout::Tiled(opts.tile_size);
+    let mut data = resample_full_resolution(raster, &geo_reference, nodata);
+    let mut raster_size = geo_reference.raster_size();
+
+    let mut metadata = GdalMetadata {
+        max_zoom: Some(max_zoom),
+        ..Default::default()
+    };
+    if !tms.is_google_maps_compatible() {
+        // Tile matrix sets that can not be looked up by their id are stored with their full definition
+        let name = match TileMatrixSet::from_id(&tms.id) {
+            Ok(known) if known.matches(tms) => tms.id.clone(),
+            _ => tms.to_json(),
+        };
+        metadata.set_item(TILING_SCHEME_DOMAIN, "NAME", name);
+    }
+    if aligned_levels > 1 {
+        metadata.set_item(TILING_SCHEME_DOMAIN, "ALIGNED_LEVELS", aligned_levels);
+    }
+    metadata.band_mut(0).statistics = raster_statistics(&data, nodata)?;
+    writer::apply_raster_scale(&mut metadata, geo_reference.scale());
+
+    let mut ifds = Vec::with_capacity(overview_count + 1);
+    for level in 0..=overview_count {
+        if level > 0 {
+            (data, raster_size) = downsample(&data, raster_size);
+        }
+
+        let mut ifd = ImageFileDirectory::new(layout);
+        writer::add_image_structure_tags::<T>(&mut ifd, raster_size, opts.compression, predictor)?;
+        if level == 0 {
+            writer::add_geo_tags(&mut ifd, &geo_reference)?;
+            writer::add_gdal_metadata_tag(&mut ifd, &metadata);
+        } else {
+            ifd.set_tag(tiff::tags::Tag::NewSubfileType, TagValue::Long(vec![1])); // Reduced resolution image
+            ifd.set_tag(tiff::tags::Tag::GdalNodata, TagValue::Ascii(nodata_value.to_string()));
+        }
+
+        ifd.chunks = writer::encode_chunks(
+            &data,
+            raster_size,
+            layout,
+            Some(nodata_value),
+            opts.compression,
+            predictor,
+            opts.allow_sparse,
+        )?;
+        ifds.push(ifd);
+    }
+
+    let encode_options = TiffEncodeOptions {
+        ghost_area: Some(gdal_ghost_area()),
+        block_leader_trailer: true,
+        force_bigtiff: false,
+        data_order: DataOrder::ReverseDirectoryOrder,
+    };
+
+    inf::fs::create_directory_for_file(output)?;
+    let mut writer = BufWriter::new(File::create(output)?);
+    encoder::write_tiff(&ifds, &encode_options, &mut writer)?;
+    std::io::Write::flush(&mut writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use approx::assert_relative_eq;
+
+    use crate::{
+        Cell, DenseArray, FIRST_BAND, RasterScale, ZoomLevelStrategy,
+        array::ArrayInterop as _,
+        cog::{TileSource, WebTilesReader},
+        geotiff::{GeoTiffMetadata, GeoTiffReader, io},
+        raster::Compression,
+    };
+
+    use super::*;
+
+    const NODATA: f64 = -1.0;
+
+    /// Creates a raster in web mercator with a pixel size close to zoom level 10 (256px tiles)
+    fn test_raster<T: ArrayNum>(rows: i32, cols: i32) -> DenseRaster<T> {
+        let raster_size = RasterSize::with_rows_cols(Rows(rows), Columns(cols));
+        let geo_reference = GeoReference::with_top_left_origin(
+            "EPSG:3857",
+            raster_size,
+            Point::new(450000.0, 6600000.0),
+            CellSize::square(150.0),
+            Some(NODATA),
+        );
+
+        let data: Vec<T> = (0..raster_size.cell_count())
+            .map(|i| {
+                if i % 11 == 0 {
+                    NumCast::from(NODATA).unwrap_or(T::NODATA)
+                } else {
+                    NumCast::from(i % 100).unwrap()
+                }
+            })
+            .collect();
+
+        DenseArray::new_init_nodata(geo_reference, inf::allocate::aligned_vec_from_slice(&data)).unwrap()
+    }
+
+    fn options(tile_size: u32) -> CogCreationOptions {
+        CogCreationOptions {
+            tile_size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn create_cog() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let raster = test_raster::<i16>(600, 700);
+
+        create_cog_from_raster(&raster, &output, options(256))?;
+        assert!(io::file_is_cog(&output));
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.data_layout, ChunkDataLayout::Tiled(256));
+        assert_eq!(meta.data_type, ArrayDataType::Int16);
+        assert_eq!(meta.compression, Some(Compression::Lzw));
+        assert_eq!(meta.predictor, Some(Predictor::Horizontal));
+        assert_eq!(meta.geo_reference.nodata(), Some(NODATA));
+        assert_relative_eq!(
+            meta.geo_reference.cell_size_x(),
+            Tile::pixel_size_at_zoom_level(10, 256),
+            epsilon = 1e-6
+        );
+        assert!(meta.geo_reference.top_left().x() <= raster.metadata().top_left().x());
+        assert!(meta.geo_reference.top_left().y() >= raster.metadata().top_left().y());
+        assert!(meta.geo_reference.bottom_right().x() >= raster.metadata().bottom_right().x());
+        assert!(meta.geo_reference.bottom_right().y() <= raster.metadata().bottom_right().y());
+        assert_eq!(meta.overviews.len(), 3); // zoom levels 8 to 10
+        assert!(meta.overviews.last().is_some_and(|ov| ov.chunk_locations.len() == 1));
+
+        let stats = meta.statistics.expect("Statistics should be present");
+        assert_eq!(stats.minimum_value, 0.0);
+        assert_eq!(stats.maximum_value, 99.0);
+
+        // Every input cell should be present at the nearest cell of the cog
+        let cog = GeoTiffReader::from_file(&output)?.read_raster_as::<i16, GeoReference>()?;
+        for (row, col) in [(0, 0), (1, 1), (300, 250), (599, 699)] {
+            let cell = Cell::from_row_col(row, col);
+            let cog_cell = cog.metadata().point_to_cell(raster.metadata().cell_center(cell));
+            assert_eq!(cog.cell_value(cog_cell), raster.cell_value(cell), "Mismatch at {cell:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn statistics_of_large_values() -> Result<()> {
+        // The naive sum of squares variance loses all precision for values of this magnitude
+        let data = [1e9 + 1.0, 1e9 + 2.0, f64::NAN, 1e9 + 3.0];
+        let stats = raster_statistics(&data, f64::NAN)?.expect("Statistics should be present");
+        assert_eq!(stats.minimum_value, 1e9 + 1.0);
+        assert_eq!(stats.maximum_value, 1e9 + 3.0);
+        assert_relative_eq!(stats.mean, 1e9 + 2.0);
+        assert_relative_eq!(stats.standard_deviation, (2.0f64 / 3.0).sqrt(), epsilon = 1e-6);
+        assert_eq!(stats.valid_pixel_percentage, 75.0);
+
+        assert!(raster_statistics(&[f64::NAN; 4], f64::NAN)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_is_servable() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+
+        for (min_zoom, aligned_levels) in [(Some(7), None), (None, Some(2)), (None, None)] {
+            let opts = CogCreationOptions {
+                min_zoom,
+                aligned_levels,
+                ..options(512)
+            };
+            create_cog_from_raster(&test_raster::<f32>(900, 1100), &output, opts)?;
+
+            let meta = GeoTiffMetadata::from_file(&output)?;
+            assert_eq!(meta.predictor, Some(Predictor::FloatingPoint));
+            assert_eq!(meta.chunk_row_length(), 512);
+            if min_zoom.is_some() {
+                assert_eq!(meta.overviews.len(), 3); // zoom levels 7 to 9
+            }
+
+            let reader = WebTilesReader::new(meta)?;
+            assert_eq!(reader.tile_info().max_zoom, 9);
+            if min_zoom.is_some() {
+                assert_eq!(reader.tile_info().min_zoom, 7);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_options() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let raster = test_raster::<f64>(300, 400);
+
+        {
+            let opts = CogCreationOptions {
+                compression: Some(Compression::Zstd),
+                predictor: Some(PredictorSelection::FloatingPoint),
+                output_data_type: Some(ArrayDataType::Float32),
+                zoom_level_strategy: ZoomLevelStrategy::Manual(9),
+                ..options(256)
+            };
+            create_cog_from_raster(&raster, &output, opts)?;
+            let meta = GeoTiffMetadata::from_file(&output)?;
+            assert_eq!(meta.data_type, ArrayDataType::Float32);
+            assert_eq!(meta.compression, Some(Compression::Zstd));
+            assert_eq!(meta.predictor, Some(Predictor::FloatingPoint));
+            assert_relative_eq!(
+                meta.geo_reference.cell_size_x(),
+                Tile::pixel_size_at_zoom_level(9, 256),
+                epsilon = 1e-6
+            );
+        }
+
+        {
+            let opts = CogCreationOptions {
+                compression: None,
+                output_data_type: Some(ArrayDataType::Uint8),
+                ..options(256)
+            };
+            create_cog_from_raster(&raster, &output, opts)?;
+            let meta = GeoTiffMetadata::from_file(&output)?;
+            assert_eq!(meta.data_type, ArrayDataType::Uint8);
+            assert_eq!(meta.compression, None);
+            assert_eq!(meta.predictor, None);
+            assert_eq!(meta.geo_reference.nodata(), Some(ArrayDataType::Uint8.default_nodata_value()));
+        }
+
+        {
+            let opts = CogCreationOptions {
+                predictor: Some(PredictorSelection::Horizontal),
+                ..options(256)
+            };
+            assert!(create_cog_from_raster(&raster, &output, opts).is_err());
+        }
+
+        for aligned_levels in [0, 100] {
+            let opts = CogCreationOptions {
+                aligned_levels: Some(aligned_levels),
+                ..options(256)
+            };
+            assert!(create_cog_from_raster(&raster, &output, opts).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_sparse() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let raster = test_raster::<u8>(100, 100);
+
+        // Aligning to zoom level 7 tiles adds tiles that only contain nodata
+        for allow_sparse in [true, false] {
+            let opts = CogCreationOptions {
+                allow_sparse,
+                min_zoom: Some(7),
+                ..options(256)
+            };
+            create_cog_from_raster(&raster, &output, opts)?;
+
+            let meta = GeoTiffMetadata::from_file(&output)?;
+            let sparse_tiles = meta.overviews[0].chunk_locations.iter().filter(|loc| loc.is_sparse()).count();
+            assert_eq!(sparse_tiles > 0, allow_sparse);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_scaled() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let raster = test_raster::<f32>(300, 400);
+
+        let opts = CogCreationOptions {
+            scale: true,
+            output_data_type: Some(ArrayDataType::Uint16),
+            ..options(256)
+        };
+        create_cog_from_raster(&raster, &output, opts)?;
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.data_type, ArrayDataType::Uint16);
+        assert_eq!(meta.geo_reference.nodata(), Some(u16::MAX as f64));
+        let scale: RasterScale = meta.geo_reference.scale().expect("Scale should be present");
+        assert_relative_eq!(scale.offset, 0.0);
+        assert_relative_eq!(scale.scale, 99.0 / (u16::MAX - 1) as f64);
+
+        let opts = CogCreationOptions {
+            scale: true,
+            output_data_type: Some(ArrayDataType::Int32),
+            ..options(256)
+        };
+        assert!(create_cog_from_raster(&raster, &output, opts).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_european_laea_tiles() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let tms = TileMatrixSet::european_etrs89_laea_quad();
+
+        let (mut geo_reference, data) = test_raster::<u8>(600, 700).into_raw_parts();
+        geo_reference.set_projection("EPSG:3035".to_string());
+        geo_reference.set_extent(
+            Point::new(3_900_000.0, 3_100_000.0),
+            geo_reference.raster_size(),
+            CellSize::square(140.0),
+        );
+        let raster = DenseArray::new(geo_reference, data).unwrap();
+
+        // A web mercator cog can not be created from this raster
+        assert!(create_cog_from_raster(&raster, &output, options(256)).is_err());
+
+        let opts = CogCreationOptions {
+            min_zoom: Some(5),
+            ..options(256)
+        };
+        create_cog_from_raster_with_tile_matrix_set(&raster, &output, opts, &tms)?;
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.geo_reference.projection(), "EPSG:3035");
+        assert_relative_eq!(meta.geo_reference.cell_size_x(), tms.cell_size(7, 256)?);
+        assert_eq!(meta.tile_matrix_set()?, tms);
+
+        let reader = WebTilesReader::new(meta)?;
+        assert_eq!(reader.tile_matrix_set().id, "EuropeanETRS89_LAEAQuad");
+        assert_eq!(reader.tile_info().min_zoom, 5);
+        assert_eq!(reader.tile_info().max_zoom, 7);
+
+        let cog = GeoTiffReader::from_file(&output)?.read_raster_as::<u8, GeoReference>()?;
+        let point = Point::new(3_950_000.0, 3_150_000.0);
+        for zoom in 5..=7 {
+            let tile = tms.tile_for_point(point, zoom)?;
+            assert!(matches!(reader.tile_source(&tile), Some(TileSource::Aligned(_))));
+
+            if zoom == 7 {
+                let tile_data = reader
+                    .read_tile_data(&tile, FIRST_BAND, File::open(&output)?)?
+                    .expect("Tile should contain data");
+                let tile_cell = tms.tile_georeference(&tile, 256)?.point_to_cell(point);
+                let cog_cell = cog.metadata().point_to_cell(point);
+                assert_eq!(tile_data.cell_value::<u8>(tile_cell), cog.cell_value(cog_cell));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn create_cog_custom_tile_matrix_set() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        let mut tms = TileMatrixSet::european_etrs89_laea_quad();
+        tms.id = "CustomLaeaQuad".to_string();
+        tms.tile_matrices.truncate(10);
+
+        let (mut geo_reference, data) = test_raster::<u8>(600, 700).into_raw_parts();
+        geo_reference.set_projection("EPSG:3035".to_string());
+        geo_reference.set_extent(
+            Point::new(3_900_000.0, 3_100_000.0),
+            geo_reference.raster_size(),
+            CellSize::square(140.0),
+        );
+        let raster = DenseArray::new(geo_reference, data).unwrap();
+
+        let opts = CogCreationOptions {
+            min_zoom: Some(5),
+            ..options(256)
+        };
+        create_cog_from_raster_with_tile_matrix_set(&raster, &output, opts, &tms)?;
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        let read_tms = meta.tile_matrix_set()?;
+        assert_eq!(read_tms.id, "CustomLaeaQuad");
+        assert!(read_tms.matches(&tms));
+
+        let reader = WebTilesReader::new(meta)?;
+        assert_eq!(reader.tile_matrix_set().id, "CustomLaeaQuad");
+        assert_eq!(reader.tile_info().min_zoom, 5);
+        assert_eq!(reader.tile_info().max_zoom, 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_cog_requires_web_mercator() {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let (mut geo_reference, data) = test_raster::<u8>(10, 10).into_raw_parts();
+        geo_reference.set_projection("EPSG:31370".to_string());
+        let raster = DenseArray::new(geo_reference, data).unwrap();
+        assert!(create_cog_from_raster(&raster, &tmp.path().join("cog.tif"), options(256)).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Bounded cache of decoded COG chunks that can be shared between readers and threads.
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{Array as _, ArrayNum, DenseArray, Result, geotiff::BandIndex};
+
+/// Default maximum size of the decoded chunk data in the cache (256 MiB)
+pub const DEFAULT_CHUNK_CACHE_SIZE: usize = 256 * 1024 * 1024;
+
+/// Identifies a decoded chunk: the file it belongs to, the overview index and the offset of the chunk in the file.
+/// The band is part of the key because the chunks of pixel interleaved tiffs are decoded per band.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkCacheKey {
+    pub source: Arc<Path>,
+    pub overview: usize,
+    pub chunk_offset: u64,
+    pub band: BandIndex,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub size_bytes: usize,
+}
+
+struct CacheEntry {
+    chunk: Arc<dyn Any + Send + Sync>,
+    size_bytes: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<ChunkCacheKey, CacheEntry>,
+    usage_order: BTreeMap<u64, ChunkCacheKey>,
+    size_bytes: usize,
+    usage_counter: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: &ChunkCacheKey) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.usage_counter += 1;
+        let usage = self.usage_counter;
+
+        let entry = self.entries.get_mut(key)?;
+        self.usage_order.remove(&entry.last_used);
+        entry.last_used = usage;
+        self.usage_order.insert(usage, key.clone());
+        Some(entry.chunk.clone())
+    }
+
+    fn remove(&mut self, key: &ChunkCacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.usage_order.remove(&entry.last_used);
+            self.size_bytes -= entry.size_bytes;
+        }
+    }
+
+    /// Evicts the least recently used chunks until the cache fits the maximum size
+    fn evict(&mut self, max_size_bytes: usize) {
+        while self.size_bytes > max_size_bytes {
+            let Some((_, key)) = self.usage_order.pop_first() else {
+                break;
+            };
+
+            if let Some(entry) = self.entries.remove(&key) {
+                self.size_bytes -= entry.size_bytes;
+            }
+        }
+    }
+}
+
+/// Thread safe least recently used cache of decoded COG chunks, the size of the cached chunk data is bounded.
+/// A single cache can be shared (using an `Arc`) between the readers of multiple files.
+pub struct ChunkCache {
+    max_size_bytes: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl std::fmt::Debug for ChunkCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkCache")
+            .field("max_size_bytes", &self.max_size_bytes)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl Default for ChunkCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_CACHE_SIZE)
+    }
+}
+
+impl ChunkCache {
+    pub fn new(max_size_bytes: usize) -> Self {
+        Self {
+            max_size_bytes,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn max_size_bytes(&self) -> usize {
+        self.max_size_bytes
+    }
+
+    /// Returns the cached chunk, a chunk that was cached with a different data type is not returned
+    pub fn get<T: ArrayNum>(&self, key: &ChunkCacheKey) -> Option<Arc<DenseArray<T>>> {
+        let chunk = self.lock().touch(key).and_then(|chunk| chunk.downcast::<DenseArray<T>>().ok());
+        match chunk {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        chunk
+    }
+
+    /// Adds the chunk to the cache, chunks that are larger than the maximum cache size are not cached
+    pub fn insert<T: ArrayNum>(&self, key: ChunkCacheKey, chunk: Arc<DenseArray<T>>) {
+        let size_bytes = std::mem::size_of_val(chunk.as_slice());
+        if size_bytes > self.max_size_bytes {
+            return;
+        }
+
+        let mut state = self.lock();
+        state.remove(&key);
+        state.usage_counter += 1;
+        let last_used = state.usage_counter;
+        state.usage_order.insert(last_used, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                chunk,
+                size_bytes,
+                last_used,
+            },
+        );
+        state.size_bytes += size_bytes;
+        state.evict(self.max_size_bytes);
+    }
+
+    /// Returns the cached chunk or decodes and caches it when it is not present.
+    /// The decoding is done without holding the cache lock, so other threads can use the cache in the meantime.
+    pub fn get_or_insert_with<T: ArrayNum>(
+        &self,
+        key: &ChunkCacheKey,
+        decode: impl FnOnce() -> Result<DenseArray<T>>,
+    ) -> Result<Arc<DenseArray<T>>> {
+        if let Some(chunk) = self.get(key) {
+            return Ok(chunk);
+        }
+
+        let chunk = Arc::new(decode()?);
+        self.insert(key.clone(), chunk.clone());
+        Ok(chunk)
+    }
+
+    pub fn stats(&self) -> ChunkCacheStats {
+        let state = self.lock();
+        ChunkCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: state.entries.len(),
+            size_bytes: state.size_bytes,
+        }
+    }
+
+    /// Removes all the cached chunks, the hit and miss counters are kept
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.usage_order.clear();
+        state.size_bytes = 0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // A panic while holding the lock can not leave the cache in an inconsistent state that matters for the users
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArrayDataType, ArrayMetadata as _, RasterMetadata, RasterSize,
+        cog::WebTilesReader,
+        geotiff::{FIRST_BAND, GeoTiffMetadata},
+        testutils,
+    };
+
+    use super::*;
+
+    fn key(chunk_offset: u64) -> ChunkCacheKey {
+        ChunkCacheKey {
+            source: Arc::from(Path::new("raster.tif")),
+            overview: 0,
+            chunk_offset,
+            band: FIRST_BAND,
+        }
+    }
+
+    fn chunk(value: u8) -> DenseArray<u8> {
+        DenseArray::filled_with(Some(value), RasterMetadata::sized(RasterSize::square(16), ArrayDataType::Uint8))
+    }
+
+    #[test]
+    fn cache_hits_and_misses() -> Result<()> {
+        let cache = ChunkCache::new(1024);
+
+        let mut decode_count = 0;
+        for _ in 0..3 {
+            let cached = cache.get_or_insert_with(&key(100), || {
+                decode_count += 1;
+                Ok(chunk(1))
+            })?;
+            assert_eq!(*cached, chunk(1));
+        }
+
+        assert_eq!(decode_count, 1);
+        assert_eq!(
+            cache.stats(),
+            ChunkCacheStats {
+                hits: 2,
+                misses: 1,
+                entries: 1,
+                size_bytes: 256,
+            }
+        );
+
+        // Chunks cached with a different data type are not returned
+        assert!(cache.get::<u16>(&key(100)).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn least_recently_used_chunks_are_evicted() {
+        // Room for 3 chunks of 256 bytes
+        let cache = ChunkCache::new(800);
+        for offset in 0..3 {
+            cache.insert(key(offset), Arc::new(chunk(offset as u8)));
+        }
+
+        // Use the first chunk so the second chunk becomes the least recently used
+        assert!(cache.get::<u8>(&key(0)).is_some());
+        cache.insert(key(3), Arc::new(chunk(3)));
+
+        assert!(cache.get::<u8>(&key(1)).is_none());
+        for offset in [0, 2, 3] {
+            assert!(cache.get::<u8>(&key(offset)).is_some());
+        }
+        assert_eq!(cache.stats().entries, 3);
+        assert!(cache.stats().size_bytes <= cache.max_size_bytes());
+
+        // Chunks larger than the cache are not cached
+        let large = DenseArray::<u8>::filled_with(Some(1), RasterMetadata::sized(RasterSize::square(64), ArrayDataType::Uint8));
+        cache.insert(key(4), Arc::new(large));
+        assert!(cache.get::<u8>(&key(4)).is_none());
+        assert_eq!(cache.stats().entries, 3);
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().size_bytes, 0);
+    }
+
+    #[test]
+    fn web_tiles_reader_uses_cache() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile_google_maps_compatible.tif");
+        let meta = GeoTiffMetadata::from_file(&path)?;
+        let cache = Arc::new(ChunkCache::default());
+        let cog = WebTilesReader::new(meta.clone())?;
+        let cached_cog = WebTilesReader::new(meta)?.with_chunk_cache(cache.clone(), &path);
+        let mut file = std::fs::File::open(&path)?;
+
+        let tile_info = cog.tile_info();
+        let mut tiles = Vec::new();
+        for zoom_level in tile_info.min_zoom..=tile_info.max_zoom {
+            tiles.extend(
+                cog.zoom_level_tile_sources(zoom_level)
+                    .into_iter()
+                    .flat_map(|sources| sources.keys().copied()),
+            );
+        }
+
+        for tile in &tiles {
+            for band in [FIRST_BAND, BandIndex::new(3).unwrap()] {
+                assert_eq!(
+                    cached_cog.read_tile_data(tile, band, &mut file)?,
+                    cog.read_tile_data(tile, band, &mut file)?
+                );
+            }
+        }
+
+        // All the chunks are cached, reading the tiles again does not decode any chunks
+        let stats = cache.stats();
+        assert!(stats.entries > 0);
+        for tile in &tiles {
+            cached_cog.read_tile_data(tile, FIRST_BAND, &mut file)?;
+        }
+        assert_eq!(cache.stats().misses, stats.misses);
+        assert!(cache.stats().hits > stats.hits);
+
+        Ok(())
+    }
+}
@@ -1,46 +1,12 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    ArrayDataType, Error, GeoReference, Result, Tile, ZoomLevelStrategy, crs,
+    ArrayDataType, Error, GeoReference, Result, Tile, ZoomLevelStrategy,
+    cog::{CogCreationOptions, PredictorSelection},
+    crs,
     raster::{self, Compression},
 };
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum PredictorSelection {
-    Horizontal,
-    FloatingPoint,
-    Automatic,
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct CogCreationOptions {
-    pub min_zoom: Option<i32>,
-    pub zoom_level_strategy: ZoomLevelStrategy,
-    pub tile_size: u32,
-    pub compression: Option<Compression>,
-    pub predictor: Option<PredictorSelection>,
-    pub allow_sparse: bool,
-    pub output_data_type: Option<ArrayDataType>,
-    pub aligned_levels: Option<i32>,
-    pub scale: bool,
-}
-
-impl Default for CogCreationOptions {
-    fn default() -> Self {
-        Self {
-            min_zoom: None,
-            zoom_level_strategy: ZoomLevelStrategy::Closest,
-            tile_size: 512,
-            compression: Some(Compression::Lzw),
-            predictor: Some(PredictorSelection::Automatic),
-            allow_sparse: true,
-            output_data_type: None,
-            aligned_levels: None,
-            scale: false,
-        }
-    }
-}
-
 fn gdal_bool_name(value: bool) -> &'static str {
     match value {
         true => "TRUE",
@@ -53,6 +19,12 @@ fn gdal_compression_name(compression: Option<Compression>) -> &'static str {
         Some(Compression::Lzw) => "LZW",
         Some(Compression::Zstd) => "ZSTD",
         Some(Compression::Deflate) => "DEFLATE",
+        Some(Compression::PackBits) => "PACKBITS",
+        Some(Compression::Jpeg) => "JPEG",
+        Some(Compression::WebP) => "WEBP",
+        Some(Compression::Lerc) => "LERC",
+        Some(Compression::LercDeflate) => "LERC_DEFLATE",
+        Some(Compression::LercZstd) => "LERC_ZSTD",
         None => "NONE",
     }
 }
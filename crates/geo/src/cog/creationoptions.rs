@@ -0,0 +1,37 @@
+use crate::{ArrayDataType, ZoomLevelStrategy, raster::Compression};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PredictorSelection {
+    Horizontal,
+    FloatingPoint,
+    Automatic,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CogCreationOptions {
+    pub min_zoom: Option<i32>,
+    pub zoom_level_strategy: ZoomLevelStrategy,
+    pub tile_size: u32,
+    pub compression: Option<Compression>,
+    pub predictor: Option<PredictorSelection>,
+    pub allow_sparse: bool,
+    pub output_data_type: Option<ArrayDataType>,
+    pub aligned_levels: Option<i32>,
+    pub scale: bool,
+}
+
+impl Default for CogCreationOptions {
+    fn default() -> Self {
+        Self {
+            min_zoom: None,
+            zoom_level_strategy: ZoomLevelStrategy::Closest,
+            tile_size: 512,
+            compression: Some(Compression::Lzw),
+            predictor: Some(PredictorSelection::Automatic),
+            allow_sparse: true,
+            output_data_type: None,
+            aligned_levels: None,
+            scale: false,
+        }
+    }
+}
@@ -0,0 +1,689 @@
+//! Validation of the Cloud Optimized GeoTIFF layout, reports the deviations from the structure GDAL uses for COGs.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+    path::Path,
+};
+
+use crate::{
+    RasterSize, Result, Tile,
+    constants::EARTH_CIRCUMFERENCE_M,
+    geotiff::{
+        ChunkDataLayout, GeoTiffMetadata, Interleave, ParseFromBufferError, TiffChunkLocation, TiffOverview,
+        gdalghostdata::{BlockLeader, BlockTrailer, CogBlockOrder, CogLayout, GdalGhostData},
+        io,
+    },
+};
+
+/// Images that are larger than this in any direction need to be tiled and need overviews to be a valid COG
+const MAX_UNTILED_IMAGE_SIZE: i32 = 512;
+/// Tolerance (as a fraction of a pixel or a tile) when comparing the raster geometry with the web tiling scheme
+const WEB_TILING_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CogValidationOptions {
+    /// Check that every level matches a zoom level of the `GoogleMapsCompatible` web tiling scheme
+    pub web_tiling: bool,
+}
+
+/// An image directory of the COG, the overview index 0 refers to the full resolution image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CogLevel {
+    Overview(usize),
+    /// The overview of the internal mask that matches the image overview with the same index
+    MaskOverview(usize),
+}
+
+impl fmt::Display for CogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CogLevel::Overview(index) => write!(f, "overview {index}"),
+            CogLevel::MaskOverview(index) => write!(f, "mask overview {index}"),
+        }
+    }
+}
+
+/// A deviation from the COG layout, the overview index 0 refers to the full resolution image
+#[derive(Debug, Clone, PartialEq)]
+pub enum CogIssue {
+    /// The GDAL structural metadata is missing, the layout guarantees of the file are unknown
+    MissingGhostHeader,
+    /// The GDAL structural metadata marks the layout of the file as not COG compatible
+    IncompatibleGhostHeader,
+    NotTiled {
+        raster_size: RasterSize,
+    },
+    MissingOverviews {
+        raster_size: RasterSize,
+    },
+    /// The tile width or length is not a multiple of 16 as required by the TIFF specification
+    InvalidBlockSize {
+        tile_width: u32,
+        tile_length: u32,
+    },
+    /// The image directories are not all located before the start of the tile data
+    IfdAfterData {
+        first_data_offset: u64,
+    },
+    /// The overview is not smaller than the previous level
+    OverviewSizeOrder {
+        overview: usize,
+        raster_size: RasterSize,
+    },
+    /// The data of the overview is not located before the data of the larger previous level
+    OverviewDataOrder {
+        overview: usize,
+    },
+    /// The chunks of the overview are not located in the block order declared in the ghost header
+    BlockOrder {
+        overview: usize,
+        chunk_index: usize,
+    },
+    /// Chunks that are not preceded by their size while the ghost header declares a block leader
+    InvalidBlockLeader {
+        level: CogLevel,
+        chunk_count: usize,
+    },
+    /// Chunks that are not followed by a copy of their last 4 bytes while the ghost header declares a block trailer
+    InvalidBlockTrailer {
+        level: CogLevel,
+        chunk_count: usize,
+    },
+    /// The tile size is not supported by the web tiling scheme
+    WebTileSize {
+        tile_size: u32,
+    },
+    /// The cell size of the level does not match the pixel size of a web zoom level
+    WebCellSize {
+        overview: usize,
+        cell_size: f64,
+        zoom_level_cell_size: f64,
+    },
+    /// The origin of the level is not aligned with the tile grid of its zoom level
+    WebTileAlignment {
+        overview: usize,
+        zoom_level: i32,
+    },
+}
+
+impl fmt::Display for CogIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CogIssue::MissingGhostHeader => write!(f, "The file does not contain the GDAL structural metadata (ghost header)"),
+            CogIssue::IncompatibleGhostHeader => write!(f, "The GDAL structural metadata does not describe a COG layout"),
+            CogIssue::NotTiled { raster_size } => write!(f, "The image of {raster_size} is larger than 512x512 but is not tiled"),
+            CogIssue::MissingOverviews { raster_size } => {
+                write!(f, "The image of {raster_size} is larger than 512x512 but has no overviews")
+            }
+            CogIssue::InvalidBlockSize { tile_width, tile_length } => {
+                write!(f, "The tile size {tile_width}x{tile_length} is not a multiple of 16")
+            }
+            CogIssue::IfdAfterData { first_data_offset } => write!(
+                f,
+                "The image directories are not all located before the start of the tile data at offset {first_data_offset}"
+            ),
+            CogIssue::OverviewSizeOrder { overview, raster_size } => {
+                write!(f, "Overview {overview} of {raster_size} is not smaller than the previous level")
+            }
+            CogIssue::OverviewDataOrder { overview } => {
+                write!(
+                    f,
+                    "The data of overview {overview} is not located before the data of the previous level"
+                )
+            }
+            CogIssue::BlockOrder { overview, chunk_index } => write!(
+                f,
+                "Chunk {chunk_index} of overview {overview} is not located in the block order declared in the ghost header"
+            ),
+            CogIssue::InvalidBlockLeader { level, chunk_count } => {
+                write!(f, "{chunk_count} chunk(s) of {level} are not preceded by their size")
+            }
+            CogIssue::InvalidBlockTrailer { level, chunk_count } => {
+                write!(
+                    f,
+                    "{chunk_count} chunk(s) of {level} are not followed by a copy of their last 4 bytes"
+                )
+            }
+            CogIssue::WebTileSize { tile_size } => write!(f, "The tile size {tile_size} is not a multiple of {}", Tile::TILE_SIZE),
+            CogIssue::WebCellSize {
+                overview,
+                cell_size,
+                zoom_level_cell_size,
+            } => write!(
+                f,
+                "The cell size {cell_size} of overview {overview} does not match the zoom level cell size {zoom_level_cell_size}"
+            ),
+            CogIssue::WebTileAlignment { overview, zoom_level } => {
+                write!(
+                    f,
+                    "Overview {overview} is not aligned with the tile grid of zoom level {zoom_level}"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CogValidationReport {
+    /// Issues that make the file an invalid COG
+    pub errors: Vec<CogIssue>,
+    /// Issues that don't prevent the use of the file as a COG but make reading it less efficient
+    pub warnings: Vec<CogIssue>,
+}
+
+impl CogValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+pub fn validate_cog_file(path: &Path, options: &CogValidationOptions) -> Result<CogValidationReport> {
+    validate_cog(&mut File::open(path)?, options)
+}
+
+/// Validates the layout of the COG, issues with the layout are reported in the returned report.
+/// An error is only returned when the tiff structure can not be read.
+pub fn validate_cog(reader: &mut (impl Read + Seek), options: &CogValidationOptions) -> Result<CogValidationReport> {
+    let mut header = Vec::with_capacity(io::COG_HEADER_SIZE);
+    reader.seek(SeekFrom::Start(0))?;
+    io::append_from_stream_to_buffer(&mut header, reader, io::COG_HEADER_SIZE)?;
+    let ghost_data = GdalGhostData::from_tiff_header_buffer(&header);
+
+    reader.seek(SeekFrom::Start(0))?;
+    let meta = GeoTiffMetadata::from_reader(reader)?;
+
+    let mut report = CogValidationReport::default();
+    match &ghost_data {
+        None => report.warnings.push(CogIssue::MissingGhostHeader),
+        Some(ghost) if ghost.known_incompatible_edition || ghost.layout != Some(CogLayout::IfdsBeforeData) => {
+            report.errors.push(CogIssue::IncompatibleGhostHeader)
+        }
+        Some(_) => {}
+    }
+
+    check_structure(&meta, &mut report);
+    check_ifd_locations(&meta, reader, &mut report)?;
+    check_overview_order(&meta, &mut report);
+    check_block_order(&meta, ghost_data.as_ref(), &mut report);
+    if let Some(ghost) = &ghost_data {
+        check_leaders_and_trailers(&meta, ghost, reader, &mut report)?;
+    }
+
+    if options.web_tiling {
+        check_web_tiling(&meta, &mut report);
+    }
+
+    Ok(report)
+}
+
+fn check_structure(meta: &GeoTiffMetadata, report: &mut CogValidationReport) {
+    let raster_size = meta.geo_reference.raster_size();
+    let is_large = raster_size.cols.count() > MAX_UNTILED_IMAGE_SIZE || raster_size.rows.count() > MAX_UNTILED_IMAGE_SIZE;
+
+    if !meta.is_tiled() && is_large {
+        report.errors.push(CogIssue::NotTiled { raster_size });
+    }
+
+    // The metadata reader only accepts square tiles
+    if let ChunkDataLayout::Tiled(tile_size) = meta.data_layout
+        && !tile_size.is_multiple_of(16)
+    {
+        report.errors.push(CogIssue::InvalidBlockSize {
+            tile_width: tile_size,
+            tile_length: tile_size,
+        });
+    }
+
+    if meta.overviews.len() <= 1 && is_large {
+        report.warnings.push(CogIssue::MissingOverviews { raster_size });
+    }
+}
+
+/// The overviews of the image and of the internal mask (if present)
+fn all_overviews(meta: &GeoTiffMetadata) -> impl Iterator<Item = (CogLevel, &TiffOverview)> {
+    let mask_overviews = meta.mask.iter().flat_map(|mask| {
+        mask.overviews
+            .iter()
+            .enumerate()
+            .map(|(index, overview)| (CogLevel::MaskOverview(index), overview))
+    });
+
+    meta.overviews
+        .iter()
+        .enumerate()
+        .map(|(index, overview)| (CogLevel::Overview(index), overview))
+        .chain(mask_overviews)
+}
+
+fn data_range(chunks: &[TiffChunkLocation]) -> Option<Range<u64>> {
+    let mut data_chunks = chunks.iter().filter(|chunk| !chunk.is_sparse());
+    let first = data_chunks.next()?;
+    Some(data_chunks.fold(first.range_to_fetch(), |range, chunk| {
+        range.start.min(chunk.offset)..range.end.max(chunk.offset + chunk.size)
+    }))
+}
+
+/// The image directories are located before the tile data when the metadata can be parsed from the bytes that precede the first chunk
+fn check_ifd_locations(meta: &GeoTiffMetadata, reader: &mut (impl Read + Seek), report: &mut CogValidationReport) -> Result<()> {
+    let Some(first_data_offset) = all_overviews(meta)
+        .filter_map(|(_, overview)| data_range(&overview.chunk_locations))
+        .map(|range| range.start)
+        .min()
+    else {
+        return Ok(());
+    };
+
+    let mut header = Vec::new();
+    reader.seek(SeekFrom::Start(0))?;
+    io::append_from_stream_to_buffer(&mut header, reader, first_data_offset as usize)?;
+    match GeoTiffMetadata::from_buffer(header) {
+        Ok(_) => Ok(()),
+        Err(ParseFromBufferError::BufferTooSmall(_)) => {
+            report.errors.push(CogIssue::IfdAfterData { first_data_offset });
+            Ok(())
+        }
+        Err(ParseFromBufferError::Error(err)) => Err(err),
+    }
+}
+
+fn check_overview_order(meta: &GeoTiffMetadata, report: &mut CogValidationReport) {
+    for (index, pair) in meta.overviews.windows(2).enumerate() {
+        let (previous, overview) = (&pair[0], &pair[1]);
+        if overview.raster_size.cell_count() >= previous.raster_size.cell_count() {
+            report.errors.push(CogIssue::OverviewSizeOrder {
+                overview: index + 1,
+                raster_size: overview.raster_size,
+            });
+        }
+
+        // The smallest overview is located first in the file, so it can be fetched with a single request
+        if let (Some(previous_data), Some(overview_data)) = (data_range(&previous.chunk_locations), data_range(&overview.chunk_locations))
+            && overview_data.end > previous_data.start
+        {
+            report.errors.push(CogIssue::OverviewDataOrder { overview: index + 1 });
+        }
+    }
+}
+
+/// The indices of the chunk locations of the overview in the order the chunks are expected in the file
+fn declared_chunk_order(meta: &GeoTiffMetadata, overview: &TiffOverview, block_order: &CogBlockOrder) -> Option<Vec<usize>> {
+    let planes = match meta.interleave {
+        Interleave::Pixel => 1,
+        Interleave::Band | Interleave::Tile => meta.band_count as usize,
+    };
+
+    let chunks_per_plane = overview.chunk_locations.len() / planes.max(1);
+    let chunk_size = meta.chunk_row_length() as usize;
+    let (chunks_across, chunks_down) = match meta.data_layout {
+        ChunkDataLayout::Tiled(_) => (
+            (overview.raster_size.cols.count() as usize).div_ceil(chunk_size),
+            (overview.raster_size.rows.count() as usize).div_ceil(chunk_size),
+        ),
+        ChunkDataLayout::Striped(_) => (1, chunks_per_plane),
+    };
+
+    if chunks_across * chunks_down != chunks_per_plane || chunks_per_plane * planes != overview.chunk_locations.len() {
+        return None;
+    }
+
+    let spatial_order: Vec<usize> = match block_order {
+        CogBlockOrder::RowMajor => (0..chunks_per_plane).collect(),
+        CogBlockOrder::ColumnMajor => (0..chunks_across)
+            .flat_map(|col| (0..chunks_down).map(move |row| row * chunks_across + col))
+            .collect(),
+    };
+
+    Some(match meta.interleave {
+        Interleave::Pixel => spatial_order,
+        Interleave::Band => (0..planes)
+            .flat_map(|plane| spatial_order.iter().map(move |index| plane * chunks_per_plane + index))
+            .collect(),
+        Interleave::Tile => spatial_order
+            .iter()
+            .flat_map(|index| (0..planes).map(move |plane| plane * chunks_per_plane + index))
+            .collect(),
+    })
+}
+
+fn check_block_order(meta: &GeoTiffMetadata, ghost_data: Option<&GdalGhostData>, report: &mut CogValidationReport) {
+    let block_order = ghost_data
+        .and_then(|ghost| ghost.block_order.clone())
+        .unwrap_or(CogBlockOrder::RowMajor);
+
+    for (index, overview) in meta.overviews.iter().enumerate() {
+        let Some(chunk_order) = declared_chunk_order(meta, overview, &block_order) else {
+            continue;
+        };
+
+        let mut previous_offset = 0;
+        for chunk_index in chunk_order {
+            let chunk = &overview.chunk_locations[chunk_index];
+            if chunk.is_sparse() {
+                continue;
+            }
+
+            if chunk.offset < previous_offset {
+                report.errors.push(CogIssue::BlockOrder {
+                    overview: index,
+                    chunk_index,
+                });
+                break;
+            }
+
+            previous_offset = chunk.offset;
+        }
+    }
+}
+
+fn check_leaders_and_trailers(
+    meta: &GeoTiffMetadata,
+    ghost: &GdalGhostData,
+    reader: &mut (impl Read + Seek),
+    report: &mut CogValidationReport,
+) -> Result<()> {
+    let check_leader = ghost.block_leader == Some(BlockLeader::SizeAsUint4);
+    let check_trailer = ghost.block_trailer == Some(BlockTrailer::Last4BytesRepeated);
+    if !check_leader && !check_trailer {
+        return Ok(());
+    }
+
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    for (level, overview) in all_overviews(meta) {
+        let mut invalid_leaders = 0;
+        let mut invalid_trailers = 0;
+
+        for chunk in overview.chunk_locations.iter().filter(|chunk| !chunk.is_sparse()) {
+            if check_leader {
+                let leader = read_u32_at(reader, chunk.offset.checked_sub(4), file_size)?;
+                if leader != Some(chunk.size as u32) {
+                    invalid_leaders += 1;
+                }
+            }
+
+            if check_trailer {
+                let last_bytes = read_u32_at(reader, (chunk.offset + chunk.size).checked_sub(4), file_size)?;
+                let trailer = read_u32_at(reader, Some(chunk.offset + chunk.size), file_size)?;
+                if chunk.size < 4 || trailer.is_none() || trailer != last_bytes {
+                    invalid_trailers += 1;
+                }
+            }
+        }
+
+        if invalid_leaders > 0 {
+            report.errors.push(CogIssue::InvalidBlockLeader {
+                level,
+                chunk_count: invalid_leaders,
+            });
+        }
+
+        if invalid_trailers > 0 {
+            report.errors.push(CogIssue::InvalidBlockTrailer {
+                level,
+                chunk_count: invalid_trailers,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the little endian value (GDAL always writes the leader in little endian), None if the value is outside of the file
+fn read_u32_at(reader: &mut (impl Read + Seek), offset: Option<u64>, file_size: u64) -> Result<Option<u32>> {
+    let Some(offset) = offset.filter(|offset| offset + 4 <= file_size) else {
+        return Ok(None);
+    };
+
+    let mut bytes = [0; 4];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(u32::from_le_bytes(bytes)))
+}
+
+fn check_web_tiling(meta: &GeoTiffMetadata, report: &mut CogValidationReport) {
+    let tile_size = meta.chunk_row_length();
+    if !meta.is_tiled() || tile_size == 0 || !tile_size.is_multiple_of(Tile::TILE_SIZE) {
+        report.errors.push(CogIssue::WebTileSize { tile_size });
+        return;
+    }
+
+    let geo_reference = &meta.geo_reference;
+    let top_left = geo_reference.top_left();
+    let raster_width = geo_reference.cell_size_x() * geo_reference.columns().count() as f64;
+    let max_zoom = Tile::zoom_level_for_pixel_size(geo_reference.cell_size_x(), crate::ZoomLevelStrategy::Closest, tile_size);
+
+    for (index, overview) in meta.overviews.iter().enumerate() {
+        let zoom_level = max_zoom - index as i32;
+        let cell_size = raster_width / overview.raster_size.cols.count() as f64;
+        let zoom_level_cell_size = Tile::pixel_size_at_zoom_level(zoom_level, tile_size);
+        if (cell_size - zoom_level_cell_size).abs() > zoom_level_cell_size * WEB_TILING_TOLERANCE {
+            report.errors.push(CogIssue::WebCellSize {
+                overview: index,
+                cell_size,
+                zoom_level_cell_size,
+            });
+            continue;
+        }
+
+        // Levels that are not aligned can still be served, but every web tile is stitched from multiple chunks
+        let tile_span = zoom_level_cell_size * tile_size as f64;
+        let tiles_x = (top_left.x() + EARTH_CIRCUMFERENCE_M / 2.0) / tile_span;
+        let tiles_y = (EARTH_CIRCUMFERENCE_M / 2.0 - top_left.y()) / tile_span;
+        if (tiles_x - tiles_x.round()).abs() > WEB_TILING_TOLERANCE || (tiles_y - tiles_y.round()).abs() > WEB_TILING_TOLERANCE {
+            report.warnings.push(CogIssue::WebTileAlignment {
+                overview: index,
+                zoom_level,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Array as _, CellSize, GeoReference, Point,
+        cog::{CogCreationOptions, create_cog_from_raster},
+        geotiff::{
+            encoder::{self, DataOrder, ImageFileDirectory, TiffEncodeOptions},
+            writer,
+        },
+        raster::DenseRaster,
+        testutils,
+    };
+
+    use super::*;
+
+    fn test_raster() -> DenseRaster<u8> {
+        let geo_reference = GeoReference::with_top_left_origin(
+            "EPSG:3857",
+            RasterSize::square(600),
+            Point::new(450000.0, 6600000.0),
+            CellSize::square(150.0),
+            Some(255.0),
+        );
+
+        DenseRaster::filled_with(Some(7), geo_reference)
+    }
+
+    fn web_tiling() -> CogValidationOptions {
+        CogValidationOptions { web_tiling: true }
+    }
+
+    #[test]
+    fn created_cog_is_valid() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("cog.tif");
+        create_cog_from_raster(
+            &test_raster(),
+            &output,
+            CogCreationOptions {
+                tile_size: 256,
+                ..Default::default()
+            },
+        )?;
+
+        let report = validate_cog_file(&output, &web_tiling())?;
+        assert!(report.is_valid(), "{:?}", report.errors);
+        // Only the full resolution level is aligned by default
+        assert!(
+            report
+                .warnings
+                .iter()
+                .all(|issue| matches!(issue, CogIssue::WebTileAlignment { overview, .. } if *overview > 0))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn gdal_cog_is_valid() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile_google_maps_compatible.tif");
+        let report = validate_cog_file(&path, &web_tiling())?;
+        assert!(report.is_valid(), "{:?}", report.errors);
+
+        Ok(())
+    }
+
+    #[test]
+    fn regular_tiff_is_not_a_cog() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("landusebyte.tif");
+        let report = validate_cog_file(&path, &CogValidationOptions::default())?;
+        assert!(report.warnings.contains(&CogIssue::MissingGhostHeader));
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_layout() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("invalid.tif");
+
+        // A tiff with a ghost header that declares leaders and trailers, but with the data in the wrong order and without them
+        let raster = test_raster();
+        let mut ifds = Vec::new();
+        for raster_size in [RasterSize::square(600), RasterSize::square(300)] {
+            let data: Vec<u8> = (0..raster_size.cell_count()).map(|i| (i % 251) as u8).collect();
+            let layout = ChunkDataLayout::Tiled(256);
+            let mut ifd = ImageFileDirectory::new(layout);
+            writer::add_image_structure_tags::<u8>(&mut ifd, raster_size, None, None)?;
+            if ifds.is_empty() {
+                writer::add_geo_tags(&mut ifd, raster.metadata())?;
+            }
+            ifd.chunks = writer::encode_chunks(&data, raster_size, layout, Some(255.0), None, None, false)?;
+            ifds.push(ifd);
+        }
+
+        let ghost_metadata = "LAYOUT=IFDS_BEFORE_DATA\nBLOCK_ORDER=ROW_MAJOR\nBLOCK_LEADER=SIZE_AS_UINT4\nBLOCK_TRAILER=LAST_4_BYTES_REPEATED\nKNOWN_INCOMPATIBLE_EDITION=NO\n";
+        let options = TiffEncodeOptions {
+            ghost_area: Some(format!("GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{ghost_metadata}", ghost_metadata.len()).into_bytes()),
+            block_leader_trailer: false,
+            force_bigtiff: false,
+            data_order: DataOrder::DirectoryOrder,
+        };
+        encoder::write_tiff(&ifds, &options, &mut File::create(&output)?)?;
+
+        let report = validate_cog_file(&output, &CogValidationOptions::default())?;
+        assert!(report.errors.contains(&CogIssue::OverviewDataOrder { overview: 1 }));
+        for overview in [0, 1] {
+            assert!(
+                report
+                    .errors
+                    .iter()
+                    .any(|issue| matches!(issue, CogIssue::InvalidBlockLeader { level, .. } if *level == CogLevel::Overview(overview)))
+            );
+            assert!(
+                report
+                    .errors
+                    .iter()
+                    .any(|issue| matches!(issue, CogIssue::InvalidBlockTrailer { level, .. } if *level == CogLevel::Overview(overview)))
+            );
+        }
+        assert!(!report.errors.iter().any(|issue| matches!(issue, CogIssue::IfdAfterData { .. })));
+
+        // The cell size of 150m does not match a web zoom level
+        let report = validate_cog_file(&output, &web_tiling())?;
+        assert!(
+            report
+                .errors
+                .iter()
+                .any(|issue| matches!(issue, CogIssue::WebCellSize { overview: 0, .. }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn ifd_after_data() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("multiband_cog_interleave_band.tif");
+        let mut meta = GeoTiffMetadata::from_file(&path)?;
+
+        let mut report = CogValidationReport::default();
+        check_ifd_locations(&meta, &mut File::open(&path)?, &mut report)?;
+        assert!(report.is_valid(), "{:?}", report.errors);
+
+        // Pretend the tile data starts right after the tiff header
+        meta.overviews[0].chunk_locations[0].offset = 16;
+        check_ifd_locations(&meta, &mut File::open(&path)?, &mut report)?;
+        assert_eq!(report.errors, vec![CogIssue::IfdAfterData { first_data_offset: 16 }]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_tile_size() -> Result<()> {
+        let mut meta = GeoTiffMetadata::from_file(&testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile.tif"))?;
+        meta.data_layout = ChunkDataLayout::Tiled(200);
+
+        let mut report = CogValidationReport::default();
+        check_structure(&meta, &mut report);
+        assert!(report.errors.contains(&CogIssue::InvalidBlockSize {
+            tile_width: 200,
+            tile_length: 200
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn mask_overviews_are_tagged() -> Result<()> {
+        let mut meta = GeoTiffMetadata::from_file(&testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile.tif"))?;
+        meta.mask = Some(Box::new(meta.clone()));
+
+        let overview_count = meta.overviews.len();
+        let levels: Vec<CogLevel> = all_overviews(&meta).map(|(level, _)| level).collect();
+        assert_eq!(levels.len(), overview_count * 2);
+        assert_eq!(levels[0], CogLevel::Overview(0));
+        assert_eq!(levels[overview_count], CogLevel::MaskOverview(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn declared_order() {
+        let mut meta = GeoTiffMetadata::from_file(&testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile.tif")).unwrap();
+        meta.data_layout = ChunkDataLayout::Tiled(256);
+        meta.band_count = 2;
+        let overview = TiffOverview {
+            raster_size: RasterSize::with_rows_cols(crate::Rows(512), crate::Columns(768)),
+            chunk_locations: vec![TiffChunkLocation { offset: 1, size: 1 }; 12],
+        };
+
+        meta.interleave = Interleave::Band;
+        assert_eq!(
+            declared_chunk_order(&meta, &overview, &CogBlockOrder::RowMajor),
+            Some((0..12).collect())
+        );
+        assert_eq!(
+            declared_chunk_order(&meta, &overview, &CogBlockOrder::ColumnMajor),
+            Some(vec![0, 3, 1, 4, 2, 5, 6, 9, 7, 10, 8, 11])
+        );
+
+        meta.interleave = Interleave::Tile;
+        assert_eq!(
+            declared_chunk_order(&meta, &overview, &CogBlockOrder::RowMajor),
+            Some(vec![0, 6, 1, 7, 2, 8, 3, 9, 4, 10, 5, 11])
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use crate::{
     AnyDenseArray, Array as _, ArrayDataType, ArrayInterop, ArrayMetadata as _, ArrayNum, Cell, CellSize, Columns, DenseArray, Error,
-    GeoReference, GeoTransform, Point, RasterMetadata, RasterScale, Result, Rows, ZoomLevelStrategy,
+    GeoReference, GeoTransform, Point, RasterMetadata, RasterScale, Result, Rows, TileMatrixSet, ZoomLevelStrategy,
     geotiff::{
-        self, BandIndex, GeoTiffMetadata, TiffChunkLocation, TiffOverview, TiffStats, io,
+        self, BandIndex, FIRST_BAND, GeoTiffMetadata, TiffChunkLocation, TiffOverview, TiffStats, io,
         tileio::{self},
         utils,
     },
@@ -11,11 +11,15 @@ use crate::{
 use std::{
     collections::HashMap,
     io::{Read, Seek},
+    path::Path,
+    sync::Arc,
 };
 
-use crate::{LatLonBounds, RasterSize, Tile, crs};
+use crate::{LatLonBounds, RasterSize, Rect, Tile};
 
-use inf::allocate::AlignedVecUnderConstruction;
+use super::chunkcache::{ChunkCache, ChunkCacheKey};
+
+use inf::{allocate::AlignedVecUnderConstruction, cast};
 use num::NumCast;
 use simd_macro::simd_bounds;
 
@@ -41,8 +45,10 @@ struct ZoomLevelInfo {
 /// `WebTiles` is a structure that holds the necessary information to read Web xyz tiles from a cog.
 /// It is constructed from the metadata of a COG file, which contains information about the tiff tile layout and the locations of the tiff tiles in the COG.
 /// Tiff tiles don't always have a one-to-one mapping to web tiles, so this structure contains the necessary information to create web tiles from 1 or more tiff tiles.
+/// The tile indices are defined by a `TileMatrixSet`, `GoogleMapsCompatible` xyz tiles are used by default.
 pub struct WebTiles {
     geo_reference: GeoReference,
+    tile_matrix_set: TileMatrixSet,
     zoom_levels: Vec<ZoomLevelInfo>,
 }
 
@@ -59,24 +65,27 @@ pub fn stride_groups<T>(slice: &[T], stride: usize) -> impl Iterator<Item = impl
     (0..stride).map(move |offset| slice.iter().skip(offset).step_by(stride))
 }
 
-fn warp_web_mercator_bounds_to_lat_lon(bounds: &GeoReference) -> LatLonBounds {
-    let top_left = crs::web_mercator_to_lat_lon(bounds.top_left());
-    let bottom_right = crs::web_mercator_to_lat_lon(bounds.bottom_right());
-
-    LatLonBounds::hull(top_left, bottom_right)
-}
-
 impl WebTiles {
+    /// Creates the web tiles using the tiling scheme of the COG metadata, `GoogleMapsCompatible` if the COG does not specify one.
     pub fn from_cog_metadata(meta: &GeoTiffMetadata) -> Result<Self> {
+        Self::from_cog_metadata_with_tile_matrix_set(meta, meta.tile_matrix_set()?)
+    }
+
+    /// Creates the web tiles for a COG that is aligned to the given tile matrix set.
+    /// The COG has to be in the CRS of the tile matrix set.
+    pub fn from_cog_metadata_with_tile_matrix_set(meta: &GeoTiffMetadata, tile_matrix_set: TileMatrixSet) -> Result<Self> {
+        let tms = &tile_matrix_set;
         let mut zoom_levels = vec![ZoomLevelInfo::default(); 22];
 
         let tile_size = meta.chunk_row_length();
-        let mut zoom_level = Tile::zoom_level_for_pixel_size(meta.geo_reference.cell_size_x(), ZoomLevelStrategy::Closest, tile_size);
-        if (Tile::pixel_size_at_zoom_level(zoom_level, tile_size) - meta.geo_reference.cell_size_x()).abs() > 1e-6 {
+        let mut zoom_level = tms.zoom_level_for_cell_size(meta.geo_reference.cell_size_x(), ZoomLevelStrategy::Closest, tile_size);
+        let zoom_level_cell_size = tms.cell_size(zoom_level, tile_size)?;
+        if (zoom_level_cell_size - meta.geo_reference.cell_size_x()).abs() > 1e-6 {
             return Err(Error::Runtime(format!(
-                "The main COG file content is not scaled to match a web zoom level, COG pixel size {}, zoom level {zoom_level} pixel size {}",
-                Tile::pixel_size_at_zoom_level(zoom_level, tile_size),
-                meta.geo_reference.cell_size_x()
+                "The main COG file content is not scaled to match a {} zoom level, COG pixel size {}, zoom level {zoom_level} pixel size {}",
+                tms.id,
+                meta.geo_reference.cell_size_x(),
+                zoom_level_cell_size,
             )));
         }
 
@@ -87,13 +96,16 @@ impl WebTiles {
         let offset = Point::new(cell_size.x() / 2.0, cell_size.y() / 2.0);
 
         for overview in &meta.overviews {
-            let top_left_coordinate = crs::web_mercator_to_lat_lon(meta.geo_reference.top_left());
-            let bottom_right_center_coordinate = crs::web_mercator_to_lat_lon(meta.geo_reference.bottom_right() - offset);
-            let top_left_tile = Tile::for_coordinate(top_left_coordinate, zoom_level);
-            let bottom_right_tile = Tile::for_coordinate(bottom_right_center_coordinate, zoom_level);
+            if zoom_level < 0 {
+                // Overviews coarser than the first zoom level of the tile matrix set cannot be served
+                break;
+            }
+
+            let top_left_tile = tms.tile_for_point(meta.geo_reference.top_left(), zoom_level)?;
+            let bottom_right_tile = tms.tile_for_point(meta.geo_reference.bottom_right() - offset, zoom_level)?;
 
-            let tl_diff = meta.geo_reference.top_left() - crs::lat_lon_to_web_mercator(top_left_tile.upper_left());
-            let br_diff = meta.geo_reference.bottom_right() - crs::lat_lon_to_web_mercator(bottom_right_tile.lower_right());
+            let tl_diff = meta.geo_reference.top_left() - tms.tile_bounds(&top_left_tile)?.top_left();
+            let br_diff = meta.geo_reference.bottom_right() - tms.tile_bounds(&bottom_right_tile)?.bottom_right();
 
             let top_left_aligned = tl_diff.x().abs() < 1e-6 && tl_diff.y().abs() < 1e-6;
             let bottom_right_aligned = br_diff.x().abs() < 1e-6 && br_diff.y().abs() < 1e-6;
@@ -109,7 +121,8 @@ impl WebTiles {
             }
 
             if tile_aligned {
-                let tiles = generate_tiles_for_extent(meta.geo_reference.geo_transform(), overview.raster_size, tile_size, zoom_level);
+                let tiles =
+                    generate_tiles_for_extent(tms, meta.geo_reference.geo_transform(), overview.raster_size, tile_size, zoom_level)?;
                 if meta.band_count == 1 {
                     tiles.into_iter().zip(&overview.chunk_locations).for_each(|(web_tile, cog_tile)| {
                         zoom_levels[web_tile.z as usize]
@@ -132,20 +145,20 @@ impl WebTiles {
                 }
             } else {
                 let overview_geo_ref = GeoReference::with_bottom_left_origin(
-                    "EPSG:3857",
+                    tms.crs.to_string(),
                     overview.raster_size,
                     meta.geo_reference.bottom_left(),
-                    CellSize::square(Tile::pixel_size_at_zoom_level(zoom_level, tile_size)),
+                    CellSize::square(tms.cell_size(zoom_level, tile_size)?),
                     Option::<f64>::None,
                 );
 
-                let tiles = generate_tiles_for_extent_unaligned(&meta.geo_reference, zoom_level, tile_size);
+                let tiles = generate_tiles_for_extent_unaligned(tms, &meta.geo_reference, zoom_level, tile_size)?;
                 if let Ok(cog_tile_bounds) =
-                    create_cog_tile_web_mercator_bounds(overview, &overview_geo_ref, zoom_level, tile_size, meta.band_count)
+                    create_cog_tile_bounds(tms, overview, &overview_geo_ref, zoom_level, tile_size, meta.band_count)
                 {
                     for tile in &tiles {
                         let mut tile_sources = Vec::new();
-                        let web_tile_georef = GeoReference::from_tile(tile, tile_size as usize, 1);
+                        let web_tile_georef = tms.tile_georeference(tile, tile_size)?;
 
                         for (cog_tiles, bounds) in &cog_tile_bounds {
                             if web_tile_georef.intersects(bounds)?
@@ -179,9 +192,14 @@ impl WebTiles {
         Ok(WebTiles {
             zoom_levels,
             geo_reference: meta.geo_reference.clone(),
+            tile_matrix_set,
         })
     }
 
+    pub fn tile_matrix_set(&self) -> &TileMatrixSet {
+        &self.tile_matrix_set
+    }
+
     pub fn tile_source(&self, tile: &Tile) -> Option<&TileSource> {
         self.zoom_levels.get(tile.z as usize).and_then(|level| level.tiles.get(tile))
     }
@@ -247,10 +265,30 @@ impl WebTiles {
         if min_tile_x == i32::MAX {
             // No tiles with aligned data at the maximum zoom level
             // Fall back to the tiff georeference
-            return warp_web_mercator_bounds_to_lat_lon(&self.geo_reference);
+            return self.lat_lon_bounds(Ok(Rect::from_nw_se(
+                self.geo_reference.top_left(),
+                self.geo_reference.bottom_right(),
+            )));
+        }
+
+        if self.tile_matrix_set.is_google_maps_compatible() {
+            return LatLonBounds::hull(min_tile.upper_left(), max_tile.lower_right());
         }
 
-        LatLonBounds::hull(min_tile.upper_left(), max_tile.lower_right())
+        let tms = &self.tile_matrix_set;
+        self.lat_lon_bounds(
+            tms.tile_bounds(&min_tile)
+                .and_then(|min_bounds| Ok(Rect::from_nw_se(min_bounds.top_left(), tms.tile_bounds(&max_tile)?.bottom_right()))),
+        )
+    }
+
+    fn lat_lon_bounds(&self, bounds: Result<Rect<f64>>) -> LatLonBounds {
+        bounds
+            .and_then(|bounds| self.tile_matrix_set.lat_lon_bounds(&bounds))
+            .unwrap_or_else(|err| {
+                log::warn!("Failed to calculate the data bounds: {err}");
+                LatLonBounds::empty()
+            })
     }
 }
 
@@ -265,9 +303,14 @@ fn trim_empty_zoom_levels(zoom_levels: &mut Vec<ZoomLevelInfo>) {
     }
 }
 
-fn generate_tiles_for_extent(geo_transform: GeoTransform, raster_size: RasterSize, tile_size: u32, zoom: i32) -> Vec<Tile> {
-    let top_left = crs::web_mercator_to_lat_lon(geo_transform.top_left());
-    let top_left_tile = Tile::for_coordinate(top_left, zoom);
+fn generate_tiles_for_extent(
+    tms: &TileMatrixSet,
+    geo_transform: GeoTransform,
+    raster_size: RasterSize,
+    tile_size: u32,
+    zoom: i32,
+) -> Result<Vec<Tile>> {
+    let top_left_tile = tms.tile_for_point(geo_transform.top_left(), zoom)?;
 
     let tiles_wide = (raster_size.cols.count() as u32).div_ceil(tile_size);
     let tiles_high = (raster_size.rows.count() as u32).div_ceil(tile_size);
@@ -286,17 +329,13 @@ fn generate_tiles_for_extent(geo_transform: GeoTransform, raster_size: RasterSiz
         }
     }
 
-    tiles
+    Ok(tiles)
 }
 
-fn generate_tiles_for_extent_unaligned(geo_ref: &GeoReference, zoom_level: i32, tile_size: u32) -> Vec<Tile> {
+fn generate_tiles_for_extent_unaligned(tms: &TileMatrixSet, geo_ref: &GeoReference, zoom_level: i32, tile_size: u32) -> Result<Vec<Tile>> {
     // The geo_transform is from the highest zoom level, the origin does not match unaligned zoom levels
-
-    let top_left = crs::web_mercator_to_lat_lon(geo_ref.top_left());
-    let bottom_right = crs::web_mercator_to_lat_lon(geo_ref.bottom_right());
-
-    let top_left_tile = Tile::for_coordinate(top_left, zoom_level);
-    let bottom_right_tile = Tile::for_coordinate(bottom_right, zoom_level);
+    let top_left_tile = tms.tile_for_point(geo_ref.top_left(), zoom_level)?;
+    let bottom_right_tile = tms.tile_for_point(geo_ref.bottom_right(), zoom_level)?;
 
     assert!(
         tile_size.is_multiple_of(Tile::TILE_SIZE),
@@ -321,14 +360,15 @@ fn generate_tiles_for_extent_unaligned(geo_ref: &GeoReference, zoom_level: i32,
         }
     }
 
-    tiles
+    Ok(tiles)
 }
 
-// For the given overview, create the web mercator bounds of each tiff tile at the given zoom level paired with the chunk locations for each band
+// For the given overview, create the tile matrix set bounds of each tiff tile at the given zoom level paired with the chunk locations for each band
 // Returns a vector of (chunk locations, geo reference) tuples
-// The geo reference is the bounding box of the COG tile in web mercator coordinates
+// The geo reference is the bounding box of the COG tile in tile matrix set coordinates
 // The chunk locations is a list of chunk locations for each band of the COG tile
-fn create_cog_tile_web_mercator_bounds(
+fn create_cog_tile_bounds(
+    tms: &TileMatrixSet,
     overview: &TiffOverview,
     geo_reference: &GeoReference, // georeference of the full cog image
     zoom_level: i32,
@@ -337,7 +377,7 @@ fn create_cog_tile_web_mercator_bounds(
 ) -> Result<Vec<(Vec<TiffChunkLocation>, GeoReference)>> {
     let mut web_tiles = Vec::with_capacity(overview.chunk_locations.len());
 
-    let cell_size = CellSize::square(Tile::pixel_size_at_zoom_level(zoom_level, tile_size));
+    let cell_size = CellSize::square(tms.cell_size(zoom_level, tile_size)?);
     let geo_ref_zoom_level = geotiff::utils::change_georef_cell_size(geo_reference, cell_size);
 
     let tiles_wide = (overview.raster_size.cols.count() as u32).div_ceil(tile_size) as usize;
@@ -374,7 +414,7 @@ fn create_cog_tile_web_mercator_bounds(
             let lower_left_cell = Cell::from_row_col(current_source_cell.row + tile_height.count() - 1, current_source_cell.col);
 
             let cog_tile_geo_ref = GeoReference::with_bottom_left_origin(
-                crs::epsg::WGS84_WEB_MERCATOR.to_string(),
+                tms.crs.to_string(),
                 RasterSize::with_rows_cols(tile_height, tile_width),
                 geo_ref_zoom_level.cell_lower_left(lower_left_cell),
                 cell_size,
@@ -404,10 +444,27 @@ pub struct WebTileInfo {
     pub statistics: Option<TiffStats>,
 }
 
+/// Reads the mask values of the web tiles from the internal mask or the alpha band of the COG.
+/// The reader has no nodata value so the mask values are never converted.
+#[derive(Debug, Clone)]
+struct WebTilesMask {
+    reader: Box<WebTilesReader>,
+    band: BandIndex,
+}
+
+/// The cache that is used to store the decoded chunks of the COG file
+#[derive(Debug, Clone)]
+struct CachedSource {
+    cache: Arc<ChunkCache>,
+    source: Arc<Path>,
+}
+
 #[derive(Debug, Clone)]
 pub struct WebTilesReader {
     web_tiles: WebTiles,
     cog_meta: GeoTiffMetadata,
+    mask: Option<WebTilesMask>,
+    chunk_cache: Option<CachedSource>,
 }
 
 impl WebTilesReader {
@@ -417,8 +474,57 @@ impl WebTilesReader {
 
     pub fn new(cog_meta: GeoTiffMetadata) -> Result<Self> {
         let web_tiles = WebTiles::from_cog_metadata(&cog_meta)?;
+        Self::from_web_tiles(web_tiles, cog_meta)
+    }
 
-        Ok(Self { web_tiles, cog_meta })
+    /// Creates a reader for a COG that is aligned to the given tile matrix set, overriding the tiling scheme of the COG metadata.
+    pub fn with_tile_matrix_set(cog_meta: GeoTiffMetadata, tile_matrix_set: TileMatrixSet) -> Result<Self> {
+        let web_tiles = WebTiles::from_cog_metadata_with_tile_matrix_set(&cog_meta, tile_matrix_set)?;
+        Self::from_web_tiles(web_tiles, cog_meta)
+    }
+
+    fn from_web_tiles(web_tiles: WebTiles, cog_meta: GeoTiffMetadata) -> Result<Self> {
+        let mask = if let Some(mask_meta) = cog_meta.mask.as_deref() {
+            if mask_meta.data_layout != cog_meta.data_layout {
+                return Err(Error::Runtime("The internal mask of the COG has a different tile layout".into()));
+            }
+
+            Some(WebTilesMask {
+                reader: Box::new(Self::mask_reader(mask_meta.clone(), web_tiles.tile_matrix_set())?),
+                band: FIRST_BAND,
+            })
+        } else if let Some(alpha_band) = cog_meta.alpha_band {
+            Some(WebTilesMask {
+                reader: Box::new(Self::mask_reader(cog_meta.clone(), web_tiles.tile_matrix_set())?),
+                band: alpha_band,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            web_tiles,
+            cog_meta,
+            mask,
+            chunk_cache: None,
+        })
+    }
+
+    /// Stores the decoded chunks of the COG in the provided cache, `source` identifies the COG file in the cache.
+    /// The cache can be shared between the readers of different files.
+    pub fn with_chunk_cache(mut self, cache: Arc<ChunkCache>, source: &Path) -> Self {
+        self.chunk_cache = Some(CachedSource {
+            cache,
+            source: Arc::from(source),
+        });
+        self
+    }
+
+    fn mask_reader(mut mask_meta: GeoTiffMetadata, tile_matrix_set: &TileMatrixSet) -> Result<Self> {
+        mask_meta.geo_reference.set_nodata(None);
+        mask_meta.alpha_band = None;
+        mask_meta.mask = None;
+        Self::with_tile_matrix_set(mask_meta, tile_matrix_set.clone())
     }
 
     pub fn tile_info(&self) -> WebTileInfo {
@@ -442,6 +548,10 @@ impl WebTilesReader {
         &self.cog_meta
     }
 
+    pub fn tile_matrix_set(&self) -> &TileMatrixSet {
+        self.web_tiles.tile_matrix_set()
+    }
+
     /// For a given tile, returns which cog tiles are used to construct the tile data.
     /// In case of an aligned overview, this will be a single cog tile.
     /// In case of an unaligned overview, this will be a list of cog tiles with their cutout information.
@@ -562,16 +672,16 @@ impl WebTilesReader {
                     cog_chunks.len()
                 );
                 Ok(match self.data_type() {
-                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.parse_tile_data_as::<u8>(cog_chunks[0])?),
-                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.parse_tile_data_as::<u16>(cog_chunks[0])?),
-                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.parse_tile_data_as::<u32>(cog_chunks[0])?),
-                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.parse_tile_data_as::<u64>(cog_chunks[0])?),
-                    ArrayDataType::Int8 => AnyDenseArray::I8(self.parse_tile_data_as::<i8>(cog_chunks[0])?),
-                    ArrayDataType::Int16 => AnyDenseArray::I16(self.parse_tile_data_as::<i16>(cog_chunks[0])?),
-                    ArrayDataType::Int32 => AnyDenseArray::I32(self.parse_tile_data_as::<i32>(cog_chunks[0])?),
-                    ArrayDataType::Int64 => AnyDenseArray::I64(self.parse_tile_data_as::<i64>(cog_chunks[0])?),
-                    ArrayDataType::Float32 => AnyDenseArray::F32(self.parse_tile_data_as::<f32>(cog_chunks[0])?),
-                    ArrayDataType::Float64 => AnyDenseArray::F64(self.parse_tile_data_as::<f64>(cog_chunks[0])?),
+                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.parse_tile_data_as::<u8>(cog_chunks[0], band)?),
+                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.parse_tile_data_as::<u16>(cog_chunks[0], band)?),
+                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.parse_tile_data_as::<u32>(cog_chunks[0], band)?),
+                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.parse_tile_data_as::<u64>(cog_chunks[0], band)?),
+                    ArrayDataType::Int8 => AnyDenseArray::I8(self.parse_tile_data_as::<i8>(cog_chunks[0], band)?),
+                    ArrayDataType::Int16 => AnyDenseArray::I16(self.parse_tile_data_as::<i16>(cog_chunks[0], band)?),
+                    ArrayDataType::Int32 => AnyDenseArray::I32(self.parse_tile_data_as::<i32>(cog_chunks[0], band)?),
+                    ArrayDataType::Int64 => AnyDenseArray::I64(self.parse_tile_data_as::<i64>(cog_chunks[0], band)?),
+                    ArrayDataType::Float32 => AnyDenseArray::F32(self.parse_tile_data_as::<f32>(cog_chunks[0], band)?),
+                    ArrayDataType::Float64 => AnyDenseArray::F64(self.parse_tile_data_as::<f64>(cog_chunks[0], band)?),
                 })
             }
             TileSource::Unaligned(tile_sources) => {
@@ -583,16 +693,16 @@ impl WebTilesReader {
                     cog_chunks.len()
                 );
                 Ok(match self.data_type() {
-                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Int8 => AnyDenseArray::I8(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Int16 => AnyDenseArray::I16(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Int32 => AnyDenseArray::I32(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Int64 => AnyDenseArray::I64(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Float32 => AnyDenseArray::F32(self.merge_tile_sources(tile_sources, cog_chunks)?),
-                    ArrayDataType::Float64 => AnyDenseArray::F64(self.merge_tile_sources(tile_sources, cog_chunks)?),
+                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Int8 => AnyDenseArray::I8(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Int16 => AnyDenseArray::I16(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Int32 => AnyDenseArray::I32(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Int64 => AnyDenseArray::I64(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Float32 => AnyDenseArray::F32(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
+                    ArrayDataType::Float64 => AnyDenseArray::F64(self.merge_tile_sources(tile_sources, cog_chunks, band)?),
                 })
             }
             TileSource::MultiBandUnaligned(band_tile_sources) => {
@@ -612,16 +722,16 @@ impl WebTilesReader {
                 }
 
                 Ok(match self.data_type() {
-                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Int8 => AnyDenseArray::I8(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Int16 => AnyDenseArray::I16(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Int32 => AnyDenseArray::I32(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Int64 => AnyDenseArray::I64(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Float32 => AnyDenseArray::F32(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
-                    ArrayDataType::Float64 => AnyDenseArray::F64(self.merge_tile_sources(&band_cog_chunks, cog_chunks)?),
+                    ArrayDataType::Uint8 => AnyDenseArray::U8(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Uint16 => AnyDenseArray::U16(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Uint32 => AnyDenseArray::U32(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Uint64 => AnyDenseArray::U64(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Int8 => AnyDenseArray::I8(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Int16 => AnyDenseArray::I16(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Int32 => AnyDenseArray::I32(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Int64 => AnyDenseArray::I64(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Float32 => AnyDenseArray::F32(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
+                    ArrayDataType::Float64 => AnyDenseArray::F64(self.merge_tile_sources(&band_cog_chunks, cog_chunks, band)?),
                 })
             }
         }
@@ -701,12 +811,95 @@ impl WebTilesReader {
         }
     }
 
+    /// The tile source of the mask of the tile and the band of the mask within that source, `None` if the COG has no mask.
+    /// The mask is the internal mask or the alpha band of the COG, the parsed mask chunks are applied with `apply_tile_mask`.
+    pub fn mask_tile_source(&self, tile: &Tile) -> Option<(&TileSource, BandIndex)> {
+        let mask = self.mask.as_ref()?;
+        mask.reader.tile_source(tile).map(|tile_source| (tile_source, mask.band))
+    }
+
+    /// Sets the cells of the tile data (parsed with `parse_tile_data`) that are masked to nodata.
+    /// `mask_chunks` are the chunks of the `mask_tile_source` of the tile, as described in `parse_tile_data`.
+    pub fn apply_tile_mask(
+        &self,
+        tile_data: AnyDenseArray,
+        band: BandIndex,
+        mask_source: &TileSource,
+        mask_chunks: &[&[u8]],
+    ) -> Result<AnyDenseArray> {
+        match &self.mask {
+            Some(mask) if self.cog_meta.is_masked_band(band) => {
+                let mask_data = mask.reader.parse_tile_data(mask_source, mask.band, mask_chunks)?;
+                let valid = crate::dispatch_anydensearray!(&mask_data, arr, tile_validity(arr));
+                Ok(crate::apply_to_anydensearray!(tile_data, arr, apply_tile_validity(arr, &valid)?))
+            }
+            _ => Ok(tile_data),
+        }
+    }
+
+    /// Reads the tile data of the band, cells that are masked by the internal mask or the alpha band are set to nodata.
     #[simd_bounds]
     pub fn read_tile_data_as<T: ArrayNum>(
         &self,
         tile: &Tile,
         band: BandIndex,
         reader: &mut (impl Read + Seek),
+    ) -> Result<Option<DenseArray<T>>> {
+        let tile_data = self.read_band_tile_data_as::<T>(tile, band, reader)?;
+
+        match (&self.mask, tile_data) {
+            (Some(mask), Some(tile_data)) if self.cog_meta.is_masked_band(band) && !tile_data.is_empty() => {
+                match Self::read_tile_validity(mask, tile, reader)? {
+                    Some(valid) => Ok(Some(apply_tile_validity(tile_data, &valid)?)),
+                    None => Ok(Some(tile_data)),
+                }
+            }
+            (_, tile_data) => Ok(tile_data),
+        }
+    }
+
+    fn read_tile_validity(mask: &WebTilesMask, tile: &Tile, reader: &mut (impl Read + Seek)) -> Result<Option<Vec<bool>>> {
+        let mask_reader = &mask.reader;
+        Ok(match mask_reader.data_type() {
+            ArrayDataType::Uint8 => mask_reader
+                .read_band_tile_data_as::<u8>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Uint16 => mask_reader
+                .read_band_tile_data_as::<u16>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Uint32 => mask_reader
+                .read_band_tile_data_as::<u32>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Uint64 => mask_reader
+                .read_band_tile_data_as::<u64>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Int8 => mask_reader
+                .read_band_tile_data_as::<i8>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Int16 => mask_reader
+                .read_band_tile_data_as::<i16>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Int32 => mask_reader
+                .read_band_tile_data_as::<i32>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Int64 => mask_reader
+                .read_band_tile_data_as::<i64>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Float32 => mask_reader
+                .read_band_tile_data_as::<f32>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+            ArrayDataType::Float64 => mask_reader
+                .read_band_tile_data_as::<f64>(tile, mask.band, reader)?
+                .map(|m| tile_validity(&m)),
+        })
+    }
+
+    #[simd_bounds]
+    fn read_band_tile_data_as<T: ArrayNum>(
+        &self,
+        tile: &Tile,
+        band: BandIndex,
+        reader: &mut (impl Read + Seek),
     ) -> Result<Option<DenseArray<T>>> {
         if T::TYPE != self.cog_meta.data_type {
             return Err(Error::InvalidArgument(format!(
@@ -724,33 +917,11 @@ impl WebTilesReader {
             )));
         }
 
+        let overview = (self.web_tiles.max_zoom() - tile.z()) as usize;
         if let Some(tile_source) = self.tile_source(tile) {
             match tile_source {
-                TileSource::Aligned(cog_tile) => Ok(Some(tileio::read_tile_data(
-                    cog_tile,
-                    self.cog_meta.chunk_row_length(),
-                    self.cog_meta.geo_reference.nodata(),
-                    self.cog_meta.compression,
-                    self.cog_meta.predictor,
-                    reader,
-                )?)),
-                TileSource::Unaligned(tile_sources) => {
-                    let cog_chunks: Vec<Vec<u8>> = tile_sources
-                        .iter()
-                        .flat_map(|(cog_tile_offset, _)| -> Result<Vec<u8>> {
-                            if cog_tile_offset.is_sparse() {
-                                return Ok(vec![]);
-                            }
-
-                            let mut chunk = vec![0; cog_tile_offset.size as usize];
-                            io::read_chunk(cog_tile_offset, reader, &mut chunk)?;
-                            Ok(chunk)
-                        })
-                        .collect();
-
-                    let cog_chunk_refs: Vec<&[u8]> = cog_chunks.iter().map(|chunk| chunk.as_slice()).collect();
-                    Ok(Some(self.merge_tile_sources(tile_sources, &cog_chunk_refs)?))
-                }
+                TileSource::Aligned(cog_tile) => Ok(Some(self.read_aligned_tile_as(cog_tile, overview, band, reader)?)),
+                TileSource::Unaligned(tile_sources) => Ok(Some(self.read_unaligned_tile_as(tile_sources, overview, band, reader)?)),
                 TileSource::MultiBandAligned(band_locations) => {
                     let band_index = band.get() - 1; // to 0-based index
                     if band_index >= band_locations.len() {
@@ -761,13 +932,10 @@ impl WebTilesReader {
                         )));
                     }
 
-                    let cog_tile = &band_locations[band_index];
-                    Ok(Some(tileio::read_tile_data(
-                        cog_tile,
-                        self.cog_meta.chunk_row_length(),
-                        self.cog_meta.geo_reference.nodata(),
-                        self.cog_meta.compression,
-                        self.cog_meta.predictor,
+                    Ok(Some(self.read_aligned_tile_as(
+                        &band_locations[band_index],
+                        overview,
+                        band,
                         reader,
                     )?))
                 }
@@ -778,21 +946,7 @@ impl WebTilesReader {
                         .map(|(band_chunks, cutout)| (band_chunks[band_index], cutout.clone()))
                         .collect::<Vec<_>>();
 
-                    let cog_chunks: Vec<Vec<u8>> = tile_sources
-                        .iter()
-                        .flat_map(|(cog_tile_offset, _)| -> Result<Vec<u8>> {
-                            if cog_tile_offset.is_sparse() {
-                                return Ok(vec![]);
-                            }
-
-                            let mut chunk = vec![0; cog_tile_offset.size as usize];
-                            io::read_chunk(cog_tile_offset, reader, &mut chunk)?;
-                            Ok(chunk)
-                        })
-                        .collect();
-
-                    let cog_chunk_refs: Vec<&[u8]> = cog_chunks.iter().map(|chunk| chunk.as_slice()).collect();
-                    Ok(Some(self.merge_tile_sources(&tile_sources, &cog_chunk_refs)?))
+                    Ok(Some(self.read_unaligned_tile_as(&tile_sources, overview, band, reader)?))
                 }
             }
         } else {
@@ -803,7 +957,7 @@ impl WebTilesReader {
     #[simd_bounds]
     /// Parses the tile data from a byte slice into a `DenseArray<T>`.
     /// Only call this for parsing tiled data layout.
-    fn parse_tile_data_as<T: ArrayNum>(&self, tile_data: &[u8]) -> Result<DenseArray<T>> {
+    fn parse_tile_data_as<T: ArrayNum>(&self, tile_data: &[u8], band: BandIndex) -> Result<DenseArray<T>> {
         assert!(self.cog_meta.is_tiled(), "expected tiled data layout");
         let tile_size = self.cog_meta.chunk_row_length();
 
@@ -823,20 +977,85 @@ impl WebTilesReader {
             )));
         }
 
-        let tile_data = tileio::parse_tile_data(
-            tile_size,
-            self.cog_meta.geo_reference.nodata(),
-            self.cog_meta.compression,
-            self.cog_meta.predictor,
-            None,
-            tile_data,
-        )?;
+        tileio::parse_band_tile_data(&self.cog_meta, band, tile_size, self.cog_meta.geo_reference.nodata(), tile_data)
+    }
+
+    #[simd_bounds]
+    /// Reads a cog tile that matches a web tile, sparse tiles result in an empty array
+    fn read_aligned_tile_as<T: ArrayNum>(
+        &self,
+        cog_tile: &TiffChunkLocation,
+        overview: usize,
+        band: BandIndex,
+        reader: &mut (impl Read + Seek),
+    ) -> Result<DenseArray<T>> {
+        if cog_tile.size == 0 {
+            return Ok(DenseArray::empty());
+        }
+
+        Ok(Arc::unwrap_or_clone(self.read_chunk_as(cog_tile, overview, band, reader)?))
+    }
+
+    #[simd_bounds]
+    /// Reads the cog tiles that overlap with a web tile and merges them into a single tile
+    fn read_unaligned_tile_as<T: ArrayNum>(
+        &self,
+        tile_sources: &[(TiffChunkLocation, CutOut)],
+        overview: usize,
+        band: BandIndex,
+        reader: &mut (impl Read + Seek),
+    ) -> Result<DenseArray<T>> {
+        let tile_raster_size = RasterSize::square(self.cog_meta.chunk_row_length() as i32);
+        let mut arr = DenseArray::filled_with_nodata(RasterMetadata::sized_with_nodata(tile_raster_size, NumCast::from(T::NODATA)));
+
+        for (cog_location, cutout) in tile_sources {
+            if cog_location.is_sparse() {
+                continue; // Skip sparse tiles, they are already filled with nodata
+            }
+
+            let tile_cutout = self.read_chunk_as::<T>(cog_location, overview, band, reader)?;
+            utils::merge_tile_chunk_into_buffer(cutout, &tile_cutout, arr.as_mut_slice(), tile_raster_size);
+        }
+
+        Ok(arr)
+    }
+
+    #[simd_bounds]
+    /// Reads and decodes a cog tile, the decoded tile is taken from the chunk cache when available
+    fn read_chunk_as<T: ArrayNum>(
+        &self,
+        cog_tile: &TiffChunkLocation,
+        overview: usize,
+        band: BandIndex,
+        reader: &mut (impl Read + Seek),
+    ) -> Result<Arc<DenseArray<T>>> {
+        let mut decode = || {
+            let mut chunk = vec![0; cog_tile.size as usize];
+            io::read_chunk(cog_tile, reader, &mut chunk)?;
+            self.parse_tile_data_as(&chunk, band)
+        };
 
-        Ok(tile_data)
+        match &self.chunk_cache {
+            Some(cached) => {
+                let key = ChunkCacheKey {
+                    source: cached.source.clone(),
+                    overview,
+                    chunk_offset: cog_tile.offset,
+                    band,
+                };
+                cached.cache.get_or_insert_with(&key, decode)
+            }
+            None => decode().map(Arc::new),
+        }
     }
 
     #[simd_bounds]
-    fn merge_tile_sources<T: ArrayNum>(&self, tile_sources: &[(TiffChunkLocation, CutOut)], cog_chunks: &[&[u8]]) -> Result<DenseArray<T>> {
+    fn merge_tile_sources<T: ArrayNum>(
+        &self,
+        tile_sources: &[(TiffChunkLocation, CutOut)],
+        cog_chunks: &[&[u8]],
+        band: BandIndex,
+    ) -> Result<DenseArray<T>> {
         let tile_size = self.cog_metadata().chunk_row_length() as usize;
         let tile_raster_size = RasterSize::square(tile_size as i32);
 
@@ -847,7 +1066,7 @@ impl WebTilesReader {
                 continue; // Skip sparse tiles, they are already filled with nodata
             }
 
-            let tile_cutout = self.parse_tile_data_as::<T>(cog_chunck)?;
+            let tile_cutout = self.parse_tile_data_as::<T>(cog_chunck, band)?;
             utils::merge_tile_chunk_into_buffer(cutout, &tile_cutout, arr.as_mut_slice(), tile_raster_size);
         }
 
@@ -855,6 +1074,31 @@ impl WebTilesReader {
     }
 }
 
+/// The validity of the cells of a mask tile, cells with a zero mask value are masked.
+/// Sparse mask tiles (empty arrays) mask the entire tile.
+fn tile_validity<M: ArrayNum>(mask: &DenseArray<M>) -> Vec<bool> {
+    mask.as_slice().iter().map(|&value| value != M::zero()).collect()
+}
+
+#[simd_bounds]
+fn apply_tile_validity<T: ArrayNum>(tile_data: DenseArray<T>, valid: &[bool]) -> Result<DenseArray<T>> {
+    let (meta, mut data) = tile_data.into_raw_parts();
+    let nodata = cast::option::<T>(meta.nodata()).unwrap_or(T::NODATA);
+    for (index, value) in data.iter_mut().enumerate() {
+        if !valid.get(index).copied().unwrap_or(false) {
+            *value = nodata;
+        }
+    }
+
+    // The masked cells need a nodata value when the COG does not have one
+    let meta = match meta.nodata() {
+        Some(_) => meta,
+        None => RasterMetadata::sized_with_nodata(meta.size(), NumCast::from(T::NODATA)),
+    };
+
+    DenseArray::new(meta, data)
+}
+
 #[cfg(test)]
 #[cfg(feature = "gdal")]
 mod tests {
@@ -1387,7 +1631,7 @@ mod tests {
         let cog_path = create_unaligned_test_cog(tmp.path(), COG_TILE_SIZE)?;
         let cog = WebTilesReader::new(GeoTiffMetadata::from_file(&cog_path)?)?;
 
-        let tiles = super::generate_tiles_for_extent_unaligned(&cog.cog_metadata().geo_reference, 7, COG_TILE_SIZE);
+        let tiles = super::generate_tiles_for_extent_unaligned(cog.tile_matrix_set(), &cog.cog_metadata().geo_reference, 7, COG_TILE_SIZE)?;
         assert_eq!(tiles.len(), 6);
         assert_eq!(
             tiles,
@@ -1410,7 +1654,8 @@ mod tests {
         let cog_path = create_unaligned_test_cog(tmp.path(), COG_TILE_SIZE * 2)?;
         let cog = WebTilesReader::new(GeoTiffMetadata::from_file(&cog_path)?)?;
 
-        let tiles = super::generate_tiles_for_extent_unaligned(&cog.cog_metadata().geo_reference, 7, COG_TILE_SIZE * 2);
+        let tiles =
+            super::generate_tiles_for_extent_unaligned(cog.tile_matrix_set(), &cog.cog_metadata().geo_reference, 7, COG_TILE_SIZE * 2)?;
         assert_eq!(tiles.len(), 6);
         assert_eq!(
             tiles,
@@ -1437,7 +1682,8 @@ mod tests {
         let cog_tiles = meta.overviews.get(zoom_level_8_index).unwrap().chunk_locations.clone();
 
         let cog = WebTilesReader::new(GeoTiffMetadata::from_file(&cog_path)?)?;
-        let bounds = super::create_cog_tile_web_mercator_bounds(
+        let bounds = super::create_cog_tile_bounds(
+            &TileMatrixSet::google_maps_compatible(),
             cog.overview(8).unwrap(),
             &cog.cog_metadata().geo_reference,
             8,
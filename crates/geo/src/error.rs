@@ -57,6 +57,9 @@ pub enum Error {
     #[cfg(feature = "vector-io-csv")]
     #[error("CSV error: {0}")]
     CsvError(#[from] csv::Error),
+    #[cfg(feature = "http")]
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
     #[cfg(feature = "polars")]
     #[error("Polars error: {0}")]
     PolarsError(#[from] polars::error::PolarsError),
@@ -436,6 +436,17 @@ impl GeoReference {
         None
     }
 
+    /// Returns true when the projection is a geographic coordinate system, so the cell sizes are expressed in degrees.
+    /// Always returns false when no spatial reference backend ('proj4rs' or 'gdal') is enabled.
+    pub fn is_geographic(&self) -> bool {
+        #[cfg(any(feature = "proj4rs", feature = "gdal"))]
+        if !self.projection.is_empty() {
+            return crate::srs::SpatialReference::from_definition(&self.projection).is_ok_and(|srs| srs.is_geographic());
+        }
+
+        false
+    }
+
     pub fn projected_epsg(&self) -> Option<Epsg> {
         if !self.projection.is_empty() {
             return projection_to_epsg(self.projection.as_str());
@@ -519,6 +530,43 @@ impl GeoReference {
         x_aligned && y_aligned
     }
 
+    /// Create a new `GeoReference` with the given cell size that covers the extent of this `GeoReference`.
+    /// The top left corner is kept and the extent is grown to fit a whole number of cells.
+    /// When the cell sizes are integer multiples of each other the result is aligned with this `GeoReference`.
+    pub fn with_cell_size(&self, cell_size: CellSize) -> Result<GeoReference> {
+        if cell_size.x() <= 0.0 || cell_size.y() >= 0.0 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid cell size for a north up raster: {cell_size:?}"
+            )));
+        }
+
+        let cells_to_fit = |length: f64, cell_size: f64| -> i32 {
+            let cells = length / cell_size;
+            // Avoid an extra row or column caused by floating point rounding
+            if (cells - cells.round()).abs() < 1e-9 {
+                cells.round() as i32
+            } else {
+                cells.ceil() as i32
+            }
+        };
+
+        let width = (self.cell_size_x() * self.columns().count() as f64).abs();
+        let height = (self.cell_size_y() * self.rows().count() as f64).abs();
+
+        let size = RasterSize::with_rows_cols(
+            Rows(cells_to_fit(height, cell_size.y().abs())),
+            Columns(cells_to_fit(width, cell_size.x())),
+        );
+
+        Ok(GeoReference::with_top_left_origin(
+            self.projection.clone(),
+            size,
+            self.top_left(),
+            cell_size,
+            self.nodata,
+        ))
+    }
+
     #[cfg(any(feature = "proj", feature = "proj4rs"))]
     pub fn warped(&self, opts: &crate::raster::algo::WarpOptions) -> Result<Self> {
         crate::raster::algo::warp_georeference(self, opts)
@@ -540,23 +588,25 @@ impl GeoReference {
         /// Create a new `GeoReference` that is aligned to the XYZ tile grid used for serving tiles.
         /// Such an aligned grid is used as a warping target for rasters from which tiles can be extracted
         /// and served as XYZ tiles.
-        use crate::crs;
+        self.aligned_to_tile_matrix_set(&crate::TileMatrixSet::google_maps_compatible(), zoom_level, tile_size)
+    }
 
+    #[cfg(feature = "gdal")]
+    /// Create a new `GeoReference` in the CRS of the tile matrix set that is aligned to its tiles at the given zoom level.
+    /// Such an aligned grid is used as a warping target for rasters from which tiles of the tile matrix set can be extracted.
+    pub fn aligned_to_tile_matrix_set(&self, tms: &crate::TileMatrixSet, zoom_level: i32, tile_size: u32) -> Result<GeoReference> {
         if self.projection.is_empty() {
             return Err(Error::InvalidArgument(
                 "Cannot align metadata without projection information".to_string(),
             ));
         }
 
-        let wgs84_meta = self.warped_to_epsg(crs::epsg::WGS84_WEB_MERCATOR)?;
-
-        let top_left = crs::web_mercator_to_lat_lon(wgs84_meta.top_left());
-        let bottom_right = crs::web_mercator_to_lat_lon(wgs84_meta.bottom_right());
+        let warped_meta = self.warped_to_epsg(tms.crs)?;
 
-        let top_left_tile = crate::Tile::for_coordinate(top_left, zoom_level).web_mercator_bounds();
-        let bottom_right_tile = crate::Tile::for_coordinate(bottom_right, zoom_level).web_mercator_bounds();
+        let top_left_tile = tms.tile_bounds(&tms.tile_for_point(warped_meta.top_left(), zoom_level)?)?;
+        let bottom_right_tile = tms.tile_bounds(&tms.tile_for_point(warped_meta.bottom_right(), zoom_level)?)?;
 
-        let cell_size = (top_left_tile.bottom_right().x() - top_left_tile.top_left().x()) / tile_size as f64;
+        let cell_size = tms.cell_size(zoom_level, tile_size)?;
         let raster_size = RasterSize {
             rows: Rows(((top_left_tile.top_left().y() - bottom_right_tile.bottom_right().y()) / cell_size).ceil() as i32),
             cols: Columns(((bottom_right_tile.bottom_right().x() - top_left_tile.top_left().x()) / cell_size).ceil() as i32),
@@ -564,7 +614,7 @@ impl GeoReference {
 
         let mut result = GeoReference::default();
         result.set_extent(top_left_tile.bottom_left(), raster_size, CellSize::square(cell_size));
-        result.set_projection_from_epsg(crs::epsg::WGS84_WEB_MERCATOR)?;
+        result.set_projection_from_epsg(tms.crs)?;
         result.set_nodata(self.nodata);
         Ok(result)
     }
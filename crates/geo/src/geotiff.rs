@@ -1,19 +1,32 @@
 //! GeoTIFF format reading, writing, and processing capabilities.
 
+mod attributetable;
 mod decoder;
-mod gdalghostdata;
+pub(crate) mod encoder;
+pub(crate) mod gdalghostdata;
 mod gdalmetadata;
+#[cfg(feature = "http")]
+mod httpsource;
 pub mod io;
+#[cfg(feature = "lerc")]
+mod lerc;
 mod metadata;
 mod projectioninfo;
+mod rangereader;
 mod reader;
 pub mod tileio;
 pub(crate) mod utils;
+pub(crate) mod writer;
 
 pub use crate::bandindex::{BandIndex, FIRST_BAND};
 
 use projectioninfo::ProjectionInfo;
 
-pub use gdalmetadata::{BandMetadata, GdalMetadata, TiffStats};
-pub use metadata::{GeoTiffMetadata, ParseFromBufferError};
-pub use reader::{ChunkDataLayout, GeoTiffReader, TiffChunkLocation, TiffOverview};
+pub use attributetable::{AttributeTableRow, RasterAttributeTable, palette_legend};
+pub use gdalmetadata::{BandMetadata, GdalMetadata, MetadataDomains, TiffStats, parse_gdal_metadata, serialize_gdal_metadata};
+#[cfg(feature = "http")]
+pub use httpsource::HttpRangeSource;
+pub use metadata::{GeoTiffMetadata, Interleave, ParseFromBufferError};
+pub use rangereader::{RangeReader, RangeSource};
+pub use reader::{ChunkDataLayout, ChunkDecoding, GeoTiffReader, TiffChunkLocation, TiffOverview};
+pub use writer::{write_geotiff, write_geotiff_with_metadata};
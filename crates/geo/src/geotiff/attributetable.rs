@@ -0,0 +1,531 @@
+//! Palettes (TIFF ColorMap tag) and GDAL raster attribute tables of categoric rasters.
+
+use std::path::{Path, PathBuf};
+
+use inf::{
+    Color, Legend,
+    legend::{LegendCategory, MappingConfig},
+};
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{Error, Result};
+
+use super::gdalmetadata::escape_xml;
+
+// GDAL raster attribute table field types
+const FIELD_TYPE_INTEGER: i32 = 0;
+const FIELD_TYPE_REAL: i32 = 1;
+const FIELD_TYPE_STRING: i32 = 2;
+
+// GDAL raster attribute table field usages
+const FIELD_USAGE_GENERIC: i32 = 0;
+const FIELD_USAGE_NAME: i32 = 2;
+const FIELD_USAGE_MIN: i32 = 3;
+const FIELD_USAGE_MIN_MAX: i32 = 5;
+const FIELD_USAGE_RED: i32 = 6;
+const FIELD_USAGE_GREEN: i32 = 7;
+const FIELD_USAGE_BLUE: i32 = 8;
+const FIELD_USAGE_ALPHA: i32 = 9;
+
+/// A row of a raster attribute table, the value is the raster value the row applies to
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeTableRow {
+    pub value: f64,
+    pub label: Option<String>,
+    pub color: Option<Color>,
+}
+
+/// The raster attribute table of the first band, stored by GDAL in the .aux.xml sidecar or in the GDAL metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RasterAttributeTable {
+    pub rows: Vec<AttributeTableRow>,
+}
+
+impl RasterAttributeTable {
+    /// Parses the raster attribute table of the first band from a GDAL .aux.xml sidecar file
+    pub fn from_aux_xml_file(path: &Path) -> Result<Option<Self>> {
+        parse_raster_attribute_table(&std::fs::read_to_string(path)?)
+    }
+
+    /// Creates a categoric legend with a category for every row.
+    /// Rows without a color use the color of the palette entry for the value, or are transparent when there is no such entry.
+    /// Integer values result in a `Legend::CategoricNumeric`, otherwise a `Legend::CategoricString` keyed on the values is created.
+    pub fn to_legend(&self, palette: Option<&[Color]>, mapping_config: Option<MappingConfig>) -> Result<Legend> {
+        let category = |row: &AttributeTableRow| {
+            let palette_color = palette.and_then(|palette| {
+                (row.value >= 0.0 && row.value.fract() == 0.0)
+                    .then(|| palette.get(row.value as usize).copied())
+                    .flatten()
+            });
+
+            LegendCategory {
+                color: row.color.or(palette_color).unwrap_or_default(),
+                name: row.label.clone().unwrap_or_else(|| row.value.to_string()),
+            }
+        };
+
+        Ok(if self.has_integer_values() {
+            Legend::categoric(
+                self.rows.iter().map(|row| (row.value as i64, category(row))).collect(),
+                mapping_config,
+            )?
+        } else {
+            // The string legend matches on the textual representation of the value as f32
+            Legend::categoric_string(
+                self.rows
+                    .iter()
+                    .map(|row| ((row.value as f32).to_string(), category(row)))
+                    .collect(),
+                mapping_config,
+            )?
+        })
+    }
+
+    fn has_integer_values(&self) -> bool {
+        self.rows.iter().all(|row| row.value.fract() == 0.0)
+    }
+
+    /// Serializes the table to the GDAL `GDALRasterAttributeTable` XML element
+    pub(crate) fn to_xml(&self) -> String {
+        let has_labels = self.rows.iter().any(|row| row.label.is_some());
+        let has_colors = self.rows.iter().any(|row| row.color.is_some());
+
+        let mut fields = vec![(
+            "Value",
+            if self.has_integer_values() {
+                FIELD_TYPE_INTEGER
+            } else {
+                FIELD_TYPE_REAL
+            },
+            FIELD_USAGE_MIN_MAX,
+        )];
+        if has_labels {
+            fields.push(("Name", FIELD_TYPE_STRING, FIELD_USAGE_NAME));
+        }
+        if has_colors {
+            fields.extend([
+                ("Red", FIELD_TYPE_INTEGER, FIELD_USAGE_RED),
+                ("Green", FIELD_TYPE_INTEGER, FIELD_USAGE_GREEN),
+                ("Blue", FIELD_TYPE_INTEGER, FIELD_USAGE_BLUE),
+                ("Alpha", FIELD_TYPE_INTEGER, FIELD_USAGE_ALPHA),
+            ]);
+        }
+
+        let mut xml = String::from("  <GDALRasterAttributeTable tableType=\"thematic\">\n");
+        for (index, (name, field_type, usage)) in fields.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <FieldDefn index=\"{index}\"><Name>{name}</Name><Type>{field_type}</Type><Usage>{usage}</Usage></FieldDefn>\n"
+            ));
+        }
+
+        for (index, row) in self.rows.iter().enumerate() {
+            xml.push_str(&format!("    <Row index=\"{index}\"><F>{}</F>", row.value));
+            if has_labels {
+                xml.push_str(&format!("<F>{}</F>", escape_xml(row.label.as_deref().unwrap_or_default())));
+            }
+            if has_colors {
+                let color = row.color.unwrap_or_default();
+                for component in [color.r, color.g, color.b, color.a] {
+                    xml.push_str(&format!("<F>{component}</F>"));
+                }
+            }
+            xml.push_str("</Row>\n");
+        }
+        xml.push_str("  </GDALRasterAttributeTable>\n");
+
+        xml
+    }
+}
+
+/// Creates a categoric legend with a category for every palette entry, the palette index is the category value
+pub fn palette_legend(palette: &[Color], mapping_config: Option<MappingConfig>) -> Result<Legend> {
+    Ok(Legend::categoric(
+        palette
+            .iter()
+            .enumerate()
+            .map(|(index, color)| {
+                (
+                    index as i64,
+                    LegendCategory {
+                        color: *color,
+                        name: index.to_string(),
+                    },
+                )
+            })
+            .collect(),
+        mapping_config,
+    )?)
+}
+
+/// Converts the values of the TIFF ColorMap tag (all the red values, followed by the green and blue values) to colors
+pub(crate) fn palette_from_color_map(color_map: &[u16]) -> Result<Vec<Color>> {
+    if color_map.is_empty() || !color_map.len().is_multiple_of(3) {
+        return Err(Error::InvalidArgument(format!("Invalid tiff color map size: {}", color_map.len())));
+    }
+
+    // The color map values are in the range 0-65535
+    let entries = color_map.len() / 3;
+    let component = |index: usize| (color_map[index] >> 8) as u8;
+    Ok((0..entries)
+        .map(|i| Color::rgb(component(i), component(entries + i), component(2 * entries + i)))
+        .collect())
+}
+
+#[derive(Default)]
+struct FieldDefinition {
+    name: String,
+    field_type: i32,
+    usage: i32,
+}
+
+/// Parses the first `GDALRasterAttributeTable` element of the first band, this element can be part of a GDAL metadata
+/// document or of a PAM dataset (.aux.xml sidecar) where the tables are located in the `PAMRasterBand` elements.
+pub(crate) fn parse_raster_attribute_table(xml: &str) -> Result<Option<RasterAttributeTable>> {
+    let mut band: Option<String> = None;
+    let mut in_table = false;
+    let mut row0_min: Option<f64> = None;
+    let mut bin_size: Option<f64> = None;
+    let mut fields: Vec<FieldDefinition> = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut current_field: Option<FieldDefinition> = None;
+    let mut current_row: Option<Vec<String>> = None;
+    let mut text = String::new();
+
+    for e in EventReader::from_str(xml) {
+        match e {
+            Ok(XmlEvent::StartElement { name, attributes, .. }) => {
+                let attribute = |attr_name: &str| {
+                    attributes
+                        .iter()
+                        .find(|attr| attr.name.local_name == attr_name)
+                        .map(|attr| attr.value.clone())
+                };
+
+                text.clear();
+                match name.local_name.as_str() {
+                    "PAMRasterBand" => band = attribute("band"),
+                    "GDALRasterAttributeTable" if band.as_deref().is_none_or(|band| band == "1") => {
+                        in_table = true;
+                        row0_min = attribute("Row0Min").and_then(|v| v.parse().ok());
+                        bin_size = attribute("BinSize").and_then(|v| v.parse().ok());
+                    }
+                    "FieldDefn" if in_table => current_field = Some(FieldDefinition::default()),
+                    "Row" if in_table => current_row = Some(Vec::new()),
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::Characters(data)) => text.push_str(&data),
+            Ok(XmlEvent::EndElement { name }) if in_table => match name.local_name.as_str() {
+                "Name" => {
+                    if let Some(field) = current_field.as_mut() {
+                        field.name = text.trim().to_string();
+                    }
+                }
+                "Type" => {
+                    if let Some(field) = current_field.as_mut() {
+                        field.field_type = text.trim().parse().unwrap_or(FIELD_TYPE_STRING);
+                    }
+                }
+                "Usage" => {
+                    if let Some(field) = current_field.as_mut() {
+                        field.usage = text.trim().parse().unwrap_or(FIELD_USAGE_GENERIC);
+                    }
+                }
+                "FieldDefn" => fields.extend(current_field.take()),
+                "F" => {
+                    if let Some(row) = current_row.as_mut() {
+                        row.push(std::mem::take(&mut text));
+                    }
+                }
+                "Row" => rows.extend(current_row.take()),
+                "GDALRasterAttributeTable" => {
+                    return Ok(Some(create_table(&fields, &rows, row0_min, bin_size)));
+                }
+                _ => {}
+            },
+            Ok(XmlEvent::EndElement { name }) if name.local_name == "PAMRasterBand" => band = None,
+            Err(e) => {
+                return Err(Error::Runtime(format!("XML parse error: {e}")));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+fn find_field(fields: &[FieldDefinition], usages: &[i32], name: &str) -> Option<usize> {
+    fields
+        .iter()
+        .position(|field| usages.contains(&field.usage))
+        .or_else(|| fields.iter().position(|field| field.name.eq_ignore_ascii_case(name)))
+}
+
+fn create_table(fields: &[FieldDefinition], rows: &[Vec<String>], row0_min: Option<f64>, bin_size: Option<f64>) -> RasterAttributeTable {
+    let value_field = find_field(fields, &[FIELD_USAGE_MIN_MAX, FIELD_USAGE_MIN], "value");
+    let label_field = find_field(fields, &[FIELD_USAGE_NAME], "name").or_else(|| {
+        // Fall back to the first string column (e.g. "Class_Name")
+        fields.iter().position(|field| field.field_type == FIELD_TYPE_STRING)
+    });
+    let color_fields = [
+        find_field(fields, &[FIELD_USAGE_RED], "red"),
+        find_field(fields, &[FIELD_USAGE_GREEN], "green"),
+        find_field(fields, &[FIELD_USAGE_BLUE], "blue"),
+        find_field(fields, &[FIELD_USAGE_ALPHA], "alpha"),
+    ];
+
+    let color_component = |row: &[String], field: Option<usize>| -> Option<u8> {
+        let index = field?;
+        let value = row.get(index)?.trim().parse::<f64>().ok()?;
+        // Real color columns contain values in the range 0-1
+        let value = if fields[index].field_type == FIELD_TYPE_REAL {
+            value * 255.0
+        } else {
+            value
+        };
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+
+    let rows = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(index, row)| {
+            let value = match value_field {
+                Some(field) => row.get(field)?.trim().parse::<f64>().ok()?,
+                // Linear binning: the row index determines the value
+                None => row0_min.unwrap_or(0.0) + index as f64 * bin_size.unwrap_or(1.0),
+            };
+
+            let color = match color_fields.map(|field| color_component(row, field)) {
+                [Some(r), Some(g), Some(b), alpha] => Some(Color::rgba(r, g, b, alpha.unwrap_or(255))),
+                _ => None,
+            };
+
+            Some(AttributeTableRow {
+                value,
+                label: label_field.and_then(|field| row.get(field).filter(|label| !label.is_empty()).cloned()),
+                color,
+            })
+        })
+        .collect();
+
+    RasterAttributeTable { rows }
+}
+
+/// Returns the path of the GDAL .aux.xml sidecar of a raster file
+pub(crate) fn aux_xml_path(path: &Path) -> PathBuf {
+    let mut aux_path = path.as_os_str().to_owned();
+    aux_path.push(".aux.xml");
+    aux_path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArrayDataType, CellSize, Point, RasterSize,
+        geotiff::{ChunkDataLayout, GdalMetadata, GeoTiffMetadata, encoder, writer},
+        raster::GeoTiffWriteOptions,
+    };
+
+    use super::*;
+
+    const AUX_XML: &str = r#"<PAMDataset>
+  <PAMRasterBand band="1">
+    <GDALRasterAttributeTable tableType="thematic">
+      <FieldDefn index="0"><Name>Value</Name><Type>0</Type><Usage>0</Usage></FieldDefn>
+      <FieldDefn index="1"><Name>Count</Name><Type>0</Type><Usage>1</Usage></FieldDefn>
+      <FieldDefn index="2"><Name>Class_Name</Name><Type>2</Type><Usage>0</Usage></FieldDefn>
+      <FieldDefn index="3"><Name>Red</Name><Type>0</Type><Usage>6</Usage></FieldDefn>
+      <FieldDefn index="4"><Name>Green</Name><Type>0</Type><Usage>7</Usage></FieldDefn>
+      <FieldDefn index="5"><Name>Blue</Name><Type>0</Type><Usage>8</Usage></FieldDefn>
+      <Row index="0"><F>1</F><F>120</F><F>Forest &amp; shrubs</F><F>0</F><F>128</F><F>0</F></Row>
+      <Row index="1"><F>3</F><F>20</F><F>Water</F><F>0</F><F>0</F><F>255</F></Row>
+      <Row index="2"><F>4</F><F>5</F><F>Urban</F><F></F><F></F><F></F></Row>
+    </GDALRasterAttributeTable>
+  </PAMRasterBand>
+  <PAMRasterBand band="2">
+    <GDALRasterAttributeTable>
+      <FieldDefn index="0"><Name>Value</Name><Type>0</Type><Usage>5</Usage></FieldDefn>
+      <Row index="0"><F>10</F></Row>
+    </GDALRasterAttributeTable>
+  </PAMRasterBand>
+</PAMDataset>"#;
+
+    #[test]
+    fn parse_aux_xml_attribute_table() -> Result<()> {
+        let table = parse_raster_attribute_table(AUX_XML)?.expect("Table should be present");
+
+        assert_eq!(
+            table.rows,
+            vec![
+                AttributeTableRow {
+                    value: 1.0,
+                    label: Some("Forest & shrubs".to_string()),
+                    color: Some(Color::rgb(0, 128, 0)),
+                },
+                AttributeTableRow {
+                    value: 3.0,
+                    label: Some("Water".to_string()),
+                    color: Some(Color::rgb(0, 0, 255)),
+                },
+                AttributeTableRow {
+                    value: 4.0,
+                    label: Some("Urban".to_string()),
+                    color: None,
+                },
+            ]
+        );
+
+        // Rows without a color use the palette color
+        let palette = [Color::rgb(255, 0, 0); 8];
+        let legend = table.to_legend(Some(&palette), None)?;
+        assert!(matches!(legend, Legend::CategoricNumeric(_)));
+        assert_eq!(legend.color_for_value(1u8, None), Color::rgb(0, 128, 0));
+        assert_eq!(legend.color_for_value(3u8, None), Color::rgb(0, 0, 255));
+        assert_eq!(legend.color_for_value(4u8, None), Color::rgb(255, 0, 0));
+
+        assert!(parse_raster_attribute_table("<PAMDataset></PAMDataset>")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn linear_binning_attribute_table() -> Result<()> {
+        let xml = r#"<GDALRasterAttributeTable Row0Min="0.5" BinSize="1.5" tableType="athematic">
+  <FieldDefn index="0"><Name>Name</Name><Type>2</Type><Usage>2</Usage></FieldDefn>
+  <FieldDefn index="1"><Name>R</Name><Type>1</Type><Usage>6</Usage></FieldDefn>
+  <FieldDefn index="2"><Name>G</Name><Type>1</Type><Usage>7</Usage></FieldDefn>
+  <FieldDefn index="3"><Name>B</Name><Type>1</Type><Usage>8</Usage></FieldDefn>
+  <FieldDefn index="4"><Name>A</Name><Type>1</Type><Usage>9</Usage></FieldDefn>
+  <Row index="0"><F>Low</F><F>1</F><F>0</F><F>0</F><F>1</F></Row>
+  <Row index="1"><F>High</F><F>0</F><F>0</F><F>1</F><F>0.5</F></Row>
+</GDALRasterAttributeTable>"#;
+
+        let table = parse_raster_attribute_table(xml)?.expect("Table should be present");
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0].value, 0.5);
+        assert_eq!(table.rows[1].value, 2.0);
+        assert_eq!(table.rows[0].color, Some(Color::rgba(255, 0, 0, 255)));
+        assert_eq!(table.rows[1].color, Some(Color::rgba(0, 0, 255, 128)));
+
+        // Non integer values result in a string legend
+        let legend = table.to_legend(None, None)?;
+        assert!(matches!(legend, Legend::CategoricString(_)));
+        assert_eq!(legend.color_for_value(0.5f32, None), Color::rgb(255, 0, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_palette_and_aux_xml() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("palette.tif");
+
+        let raster_size = RasterSize::square(16);
+        let geo_reference = crate::GeoReference::with_top_left_origin(
+            "EPSG:31370",
+            raster_size,
+            Point::new(22000.0, 245000.0),
+            CellSize::square(100.0),
+            Some(0.0),
+        );
+        let data: Vec<u8> = (0..raster_size.cell_count()).map(|i| (i % 4) as u8).collect();
+
+        let layout = ChunkDataLayout::Striped(16);
+        let mut ifd = encoder::ImageFileDirectory::new(layout);
+        writer::add_image_structure_tags::<u8>(&mut ifd, raster_size, None, None)?;
+        writer::add_geo_tags(&mut ifd, &geo_reference)?;
+        ifd.set_tag(tiff::tags::Tag::PhotometricInterpretation, encoder::TagValue::Short(vec![3]));
+        let mut color_map = vec![0u16; 3 * 256];
+        color_map[1] = 65535; // red for value 1
+        color_map[256 + 2] = 32768; // green for value 2
+        color_map[2 * 256 + 3] = 65535; // blue for value 3
+        ifd.set_tag(tiff::tags::Tag::ColorMap, encoder::TagValue::Short(color_map));
+        ifd.chunks = writer::encode_chunks(&data, raster_size, layout, Some(0.0), None, None, false)?;
+        encoder::write_tiff(&[ifd], &encoder::TiffEncodeOptions::default(), &mut std::fs::File::create(&output)?)?;
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.data_type, ArrayDataType::Uint8);
+        let palette = meta.palette.as_ref().expect("Palette should be present");
+        assert_eq!(palette.len(), 256);
+        assert_eq!(
+            palette[0..4],
+            [
+                Color::rgb(0, 0, 0),
+                Color::rgb(255, 0, 0),
+                Color::rgb(0, 128, 0),
+                Color::rgb(0, 0, 255)
+            ]
+        );
+        assert!(meta.attribute_table.is_none());
+
+        let legend = meta.categoric_legend(None)?.expect("Legend should be available");
+        assert_eq!(legend.color_for_value(2u8, None), Color::rgb(0, 128, 0));
+
+        // The attribute table of the sidecar file takes precedence, the palette provides the missing colors
+        std::fs::write(aux_xml_path(&output), AUX_XML)?;
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.attribute_table.as_ref().map(|table| table.rows.len()), Some(3));
+
+        let legend = meta.categoric_legend(None)?.expect("Legend should be available");
+        assert_eq!(legend.color_for_value(1u8, None), Color::rgb(0, 128, 0));
+        assert_eq!(legend.color_for_value(4u8, None), Color::rgb(0, 0, 0));
+        assert_eq!(legend.color_for_value(2u8, None), Color::default());
+
+        // A sidecar file that cannot be parsed is ignored
+        std::fs::write(aux_xml_path(&output), "<PAMDataset><PAMRasterBand band=\"1\">")?;
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        assert!(meta.attribute_table.is_none());
+        assert!(meta.palette.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_attribute_table_in_gdal_metadata() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("rat.tif");
+
+        let raster_size = RasterSize::square(8);
+        let geo_reference = crate::GeoReference::with_top_left_origin(
+            "EPSG:31370",
+            raster_size,
+            Point::new(22000.0, 245000.0),
+            CellSize::square(100.0),
+            Some(255.0),
+        );
+        let data = vec![1u8; raster_size.cell_count()];
+
+        let table = RasterAttributeTable {
+            rows: vec![
+                AttributeTableRow {
+                    value: 1.0,
+                    label: Some("<Agriculture>".to_string()),
+                    color: Some(Color::rgba(255, 255, 0, 200)),
+                },
+                AttributeTableRow {
+                    value: 2.0,
+                    label: None,
+                    color: None,
+                },
+            ],
+        };
+
+        let metadata = GdalMetadata {
+            attribute_table: Some(table.clone()),
+            ..Default::default()
+        };
+        writer::write_geotiff_with_metadata(&output, &geo_reference, &data, &GeoTiffWriteOptions::default(), &metadata)?;
+
+        let meta = GeoTiffMetadata::from_file(&output)?;
+        let read_table = meta.attribute_table.as_ref().expect("Table should be present");
+        assert_eq!(read_table.rows[0], table.rows[0]);
+        assert_eq!(read_table.rows[1].value, 2.0);
+        assert_eq!(read_table.rows[1].label, None);
+
+        let legend = meta.categoric_legend(None)?.expect("Legend should be available");
+        assert_eq!(legend.color_for_value(1u8, None), Color::rgba(255, 255, 0, 200));
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::io::{Read, Seek};
 
+use inf::Color;
 use itertools::Itertools;
 use tiff::{
     decoder::{Decoder, ifd::Value},
@@ -7,16 +8,20 @@ use tiff::{
 };
 
 use crate::{
-    ArrayDataType, Columns, Error, GeoReference, RasterScale, RasterSize, Result, Rows, crs,
+    ArrayDataType, Columns, Error, GeoReference, RasterScale, RasterSize, Result, Rows,
     geotiff::{
-        ChunkDataLayout, GeoTiffMetadata, TiffChunkLocation, gdalmetadata, metadata::Interleave, projectioninfo::ModelType,
-        reader::TiffOverview,
+        BandIndex, ChunkDataLayout, GeoTiffMetadata, TiffChunkLocation, attributetable, gdalmetadata, metadata::Interleave,
+        projectioninfo::RasterType, reader::TiffOverview,
     },
     raster::{Compression, Predictor},
 };
 
 use super::ProjectionInfo;
 
+const LERC_PARAMETERS_TAG: u16 = 50674;
+/// Bit of the NewSubfileType tag that marks an image directory as a transparency mask
+const SUBFILE_TYPE_MASK: u32 = 4;
+
 pub fn parse_geotiff_metadata<R: Read + Seek>(stream: R) -> Result<GeoTiffMetadata> {
     let mut decoder = tiff::decoder::Decoder::new(stream)?.with_limits(tiff::decoder::Limits::unlimited());
     parse_cog_header(&mut decoder)
@@ -26,20 +31,6 @@ fn is_tiled<R: Read + Seek>(decoder: &mut Decoder<R>) -> bool {
     decoder.get_chunk_type() == tiff::decoder::ChunkType::Tile
 }
 
-#[allow(dead_code)]
-fn band_count<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<usize> {
-    let color_type = decoder.colortype()?;
-    let num_bands: usize = match color_type {
-        tiff::ColorType::Multiband { bit_depth: _, num_samples } => num_samples as usize,
-        tiff::ColorType::Gray(_) => 1,
-        tiff::ColorType::RGB(_) => 3,
-        _ => {
-            return Err(Error::Runtime("Unsupported tiff color type".into()));
-        }
-    };
-    Ok(num_bands)
-}
-
 fn read_pixel_scale<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<(f64, f64)> {
     if let Ok(values) = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag) {
         if values.len() < 2 {
@@ -140,61 +131,215 @@ fn read_nodata_value<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<
     }
 }
 
-fn read_projection_info<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<ProjectionInfo>> {
-    let key_dir = if let Ok(key_dir) = decoder.get_tag_u16_vec(Tag::GeoKeyDirectoryTag) {
-        key_dir
+/// The LercParameters tag contains the LERC version and the additional compression applied to the LERC data
+fn read_lerc_compression<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Compression> {
+    let params = decoder.get_tag_u32_vec(Tag::Unknown(LERC_PARAMETERS_TAG)).unwrap_or_default();
+    match params.get(1) {
+        None | Some(0) => Ok(Compression::Lerc),
+        Some(1) => Ok(Compression::LercDeflate),
+        Some(2) => Ok(Compression::LercZstd),
+        Some(code) => Err(Error::InvalidArgument(format!("Unsupported LERC additional compression ({code})"))),
+    }
+}
+
+fn read_jpeg_tables<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<Vec<u8>>> {
+    match decoder.find_tag(Tag::JPEGTables)? {
+        Some(tables) => {
+            let tables = tables.into_u8_vec()?;
+            if tables.len() < 4 {
+                return Err(Error::InvalidArgument("Invalid JPEGTables tag".into()));
+            }
+
+            Ok(Some(tables))
+        }
+        None => Ok(None),
+    }
+}
+
+fn read_compression<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<Compression>> {
+    Ok(match decoder.get_tag_u32(Tag::Compression)? {
+        1 => None,
+        5 => Some(Compression::Lzw),
+        7 => Some(Compression::Jpeg),
+        8 => Some(Compression::Deflate),
+        32773 => Some(Compression::PackBits),
+        34887 => Some(read_lerc_compression(decoder)?),
+        50000 => Some(Compression::Zstd),
+        50001 => Some(Compression::WebP),
+        code => {
+            return Err(Error::InvalidArgument(format!("Unsupported tiff compression ({code})")));
+        }
+    })
+}
+
+fn read_predictor<R: Read + Seek>(decoder: &mut Decoder<R>) -> Option<Predictor> {
+    match decoder.get_tag_u32(Tag::Predictor) {
+        Ok(2) => Some(Predictor::Horizontal),
+        Ok(3) => Some(Predictor::FloatingPoint),
+        _ => None,
+    }
+}
+
+fn read_data_layout<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<ChunkDataLayout> {
+    if is_tiled(decoder) {
+        let tile_size = decoder.get_tag_u32(Tag::TileWidth)?;
+        if tile_size != decoder.get_tag_u32(Tag::TileLength)? {
+            return Err(Error::InvalidArgument("Only square tiles are supported".into()));
+        }
+
+        Ok(ChunkDataLayout::Tiled(tile_size))
     } else {
+        Ok(ChunkDataLayout::Striped(decoder.get_tag_u32(Tag::RowsPerStrip)?))
+    }
+}
+
+fn read_subfile_type<R: Read + Seek>(decoder: &mut Decoder<R>) -> u32 {
+    decoder.get_tag_u32(Tag::NewSubfileType).unwrap_or(0)
+}
+
+/// The ExtraSamples tag marks the samples that are not color channels, associated (1) or unassociated (2) alpha samples are used as mask
+fn read_alpha_band<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<BandIndex>> {
+    const ASSOCIATED_ALPHA: u32 = 1;
+    const UNASSOCIATED_ALPHA: u32 = 2;
+
+    let Ok(extra_samples) = decoder.get_tag_u32_vec(Tag::ExtraSamples) else {
         return Ok(None);
     };
 
-    if key_dir.len() < 4 {
+    let samples_per_pixel = decoder.get_tag_u32(Tag::SamplesPerPixel)? as usize;
+    let first_extra_band = samples_per_pixel.saturating_sub(extra_samples.len());
+    Ok(extra_samples
+        .iter()
+        .position(|&sample| sample == ASSOCIATED_ALPHA || sample == UNASSOCIATED_ALPHA)
+        .and_then(|index| BandIndex::new(first_extra_band + index + 1)))
+}
+
+/// Reads the palette of tiffs with a palette color photometric interpretation
+fn read_palette<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<Vec<Color>>> {
+    const PHOTOMETRIC_PALETTE: u32 = 3;
+
+    if decoder.get_tag_u32(Tag::PhotometricInterpretation).ok() != Some(PHOTOMETRIC_PALETTE) {
         return Ok(None);
     }
 
-    if key_dir[0] != 1 {
-        return Err(Error::Runtime(format!("Unexpected key directory version: {}", key_dir[0])));
+    match decoder.get_tag_u16_vec(Tag::ColorMap) {
+        Ok(color_map) => Ok(Some(attributetable::palette_from_color_map(&color_map)?)),
+        Err(_) => Ok(None),
     }
+}
 
-    let mut proj_info = ProjectionInfo::default();
-
-    for key in key_dir[4..].as_chunks::<4>().0 {
-        match key[0] {
-            1024 => {
-                // Geographic Type GeoKey
-                if key[1] == 0 {
-                    match key[2] {
-                        1 => proj_info.model_type = ModelType::Projected,
-                        2 => proj_info.model_type = ModelType::Geographic,
-                        3 => proj_info.model_type = ModelType::Geocentric,
-                        _ => {
-                            return Err(Error::Runtime(format!("Unsupported model type: {}", key[2])));
-                        }
-                    }
-                } else {
-                    return Err(Error::Runtime("Only inline model keys are supported".into()));
-                }
-            }
-            2048 => {
-                // Geographic Coordinate Reference System GeoKey
-                if key[1] == 0 && key[2] == 1 {
-                    proj_info.geographic_epsg = Some(crs::Epsg::from(key[3]));
-                } else {
-                    return Err(Error::Runtime("Only inline EPSG codes are supported".into()));
-                }
-            }
-            3072 => {
-                // Projected Coordinate Reference System GeoKey
-                if key[1] == 0 && key[2] == 1 {
-                    proj_info.projected_epsg = Some(crs::Epsg::from(key[3]));
-                } else {
-                    return Err(Error::Runtime("Only inline EPSG codes are supported".into()));
-                }
-            }
-            _ => {}
+/// Reads the chunk locations of the current image directory
+fn read_overview<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<TiffOverview> {
+    let image_width = decoder.get_tag_u32(Tag::ImageWidth)?;
+    let image_height = decoder.get_tag_u32(Tag::ImageLength)?;
+
+    let (offsets, byte_counts) = if is_tiled(decoder) {
+        (
+            decoder.get_tag_u64_vec(Tag::TileOffsets)?,
+            decoder.get_tag_u64_vec(Tag::TileByteCounts)?,
+        )
+    } else {
+        (
+            decoder.get_tag_u64_vec(Tag::StripOffsets)?,
+            decoder.get_tag_u64_vec(Tag::StripByteCounts)?,
+        )
+    };
+
+    debug_assert_eq!(offsets.len(), byte_counts.len());
+
+    let mut tile_locations = Vec::with_capacity(offsets.len());
+    offsets.iter().zip(byte_counts.iter()).for_each(|(offset, byte_count)| {
+        tile_locations.push(TiffChunkLocation {
+            offset: *offset,
+            size: *byte_count,
+        });
+    });
+
+    Ok(TiffOverview {
+        raster_size: RasterSize::with_rows_cols(Rows(image_height as i32), Columns(image_width as i32)),
+        chunk_locations: tile_locations,
+    })
+}
+
+/// Reads the layout of a mask image directory, masks contain a single band with 1 or 8 bits per sample.
+/// The overviews and georeference are filled in once all image directories are parsed.
+fn read_mask_metadata<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffMetadata> {
+    let bits_per_sample = decoder.get_tag_u32(Tag::BitsPerSample)?;
+    if bits_per_sample != 1 && bits_per_sample != 8 {
+        return Err(Error::InvalidArgument(format!("Unsupported mask bit depth: {bits_per_sample}")));
+    }
+
+    let compression = read_compression(decoder)?;
+    let jpeg_tables = match compression {
+        Some(Compression::Jpeg) => read_jpeg_tables(decoder)?,
+        _ => None,
+    };
+
+    Ok(GeoTiffMetadata {
+        data_layout: read_data_layout(decoder)?,
+        band_count: 1,
+        data_type: ArrayDataType::Uint8,
+        bits_per_sample,
+        compression,
+        predictor: read_predictor(decoder),
+        statistics: None,
+        geo_reference: GeoReference::default(),
+        overviews: Vec::new(),
+        interleave: Interleave::Band,
+        gdal_ghost_data: None,
+        band_metadata: Vec::new(),
+        metadata_domains: Default::default(),
+        palette: None,
+        attribute_table: None,
+        jpeg_tables,
+        alpha_band: None,
+        mask: None,
+    })
+}
+
+/// Orders the mask overviews to match the raster overviews, overviews without a mask get an empty chunk list
+fn match_mask_overviews(overviews: &[TiffOverview], mut mask_overviews: Vec<TiffOverview>) -> Vec<TiffOverview> {
+    overviews
+        .iter()
+        .map(
+            |overview| match mask_overviews.iter().position(|mask| mask.raster_size == overview.raster_size) {
+                Some(index) => mask_overviews.remove(index),
+                None => TiffOverview {
+                    raster_size: overview.raster_size,
+                    chunk_locations: Vec::new(),
+                },
+            },
+        )
+        .collect()
+}
+
+fn read_interleave<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    samples_per_pixel: u32,
+    gdal_metadata: Option<&gdalmetadata::GdalMetadata>,
+) -> Interleave {
+    const PLANAR_CONFIG_CHUNKY: u32 = 1;
+
+    let planar_config = decoder.get_tag_u32(Tag::PlanarConfiguration).unwrap_or(PLANAR_CONFIG_CHUNKY);
+    if samples_per_pixel > 1 && planar_config == PLANAR_CONFIG_CHUNKY {
+        // All the samples of a pixel are stored together in the same chunk
+        Interleave::Pixel
+    } else {
+        match gdal_metadata.and_then(|m| m.interleave) {
+            Some(Interleave::Tile) => Interleave::Tile,
+            _ => Interleave::Band,
         }
     }
+}
 
-    Ok(Some(proj_info))
+fn read_projection_info<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Option<ProjectionInfo>> {
+    let Ok(key_dir) = decoder.get_tag_u16_vec(Tag::GeoKeyDirectoryTag) else {
+        return Ok(None);
+    };
+
+    let double_params = decoder.get_tag_f64_vec(Tag::GeoDoubleParamsTag).unwrap_or_default();
+    let ascii_params = decoder.get_tag_ascii_string(Tag::GeoAsciiParamsTag).unwrap_or_default();
+    ProjectionInfo::from_geo_keys(&key_dir, &double_params, &ascii_params)
 }
 
 fn parse_cog_header<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffMetadata> {
@@ -216,7 +361,12 @@ fn parse_cog_header<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffM
         }
     };
 
-    let data_type = match (decoder.get_tag(Tag::SampleFormat)?, bits_per_sample) {
+    // Sample format defaults to unsigned integer data when the tag is missing
+    let sample_format = decoder.find_tag(Tag::SampleFormat)?.unwrap_or(Value::Short(1));
+    let data_type = match (sample_format, bits_per_sample) {
+        // Samples with less than 8 bits are unpacked to a byte per sample
+        (Value::Short(1), 1..8) => ArrayDataType::Uint8,
+        (Value::List(ref list), 1..8) if list.iter().all(|v| matches!(v, Value::Short(1))) => ArrayDataType::Uint8,
         (Value::Short(1), 8) => ArrayDataType::Uint8,
         (Value::Short(1), 16) => ArrayDataType::Uint16,
         (Value::Short(1), 32) => ArrayDataType::Uint32,
@@ -245,79 +395,45 @@ fn parse_cog_header<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffM
     };
 
     let samples_per_pixel = decoder.get_tag_u32(Tag::SamplesPerPixel)?;
-    let compression = match decoder.get_tag_u32(Tag::Compression)? {
-        1 => None,
-        5 => Some(Compression::Lzw),
-        8 => Some(Compression::Deflate),
-        50000 => Some(Compression::Zstd),
-        _ => {
-            return Err(Error::InvalidArgument(format!(
-                "Only LZW and ZSTD compressed COGs are supported ({})",
-                decoder.get_tag_u32(Tag::Compression)?
-            )));
-        }
-    };
-
-    let predictor = match decoder.get_tag_u32(Tag::Predictor) {
-        Ok(2) => Some(Predictor::Horizontal),
-        Ok(3) => Some(Predictor::FloatingPoint),
+    let compression = read_compression(decoder)?;
+    let jpeg_tables = match compression {
+        Some(Compression::Jpeg) => read_jpeg_tables(decoder)?,
         _ => None,
     };
 
+    let predictor = read_predictor(decoder);
+    let alpha_band = read_alpha_band(decoder)?;
+    let palette = read_palette(decoder)?;
     let gdal_metadata = read_gdal_metadata(decoder)?;
     let statistics = gdal_metadata.as_ref().and_then(|m| m.statistics.clone());
-    let interleave = gdal_metadata.as_ref().and_then(|m| m.interleave).unwrap_or(Interleave::Band);
-    let geo_transform = read_geo_transform(decoder)?;
+    let interleave = read_interleave(decoder, samples_per_pixel, gdal_metadata.as_ref());
+    let mut geo_transform = read_geo_transform(decoder)?;
     let raster_size = read_raster_size(decoder)?;
     let nodata = read_nodata_value(decoder)?;
     let projection = read_projection_info(decoder)?;
-    let is_tiled = is_tiled(decoder);
-
-    let data_layout = if is_tiled {
-        let tile_size = decoder.get_tag_u32(Tag::TileWidth)?;
-        if tile_size != decoder.get_tag_u32(Tag::TileLength)? {
-            return Err(Error::InvalidArgument("Only square tiles are supported".into()));
-        }
-
-        ChunkDataLayout::Tiled(tile_size)
-    } else {
-        let rows = decoder.get_tag_u32(Tag::RowsPerStrip)?;
-        ChunkDataLayout::Striped(rows)
-    };
+    if projection.as_ref().is_some_and(|proj| proj.raster_type == RasterType::PixelIsPoint) {
+        // The tie point refers to the center of the pixel, shift the origin to the corner like GDAL does
+        geo_transform[0] -= (geo_transform[1] + geo_transform[2]) * 0.5;
+        geo_transform[3] -= (geo_transform[4] + geo_transform[5]) * 0.5;
+    }
+    let data_layout = read_data_layout(decoder)?;
 
     // Now loop over the image directories to collect the tile offsets and sizes for the main raster image and all overviews.
+    // Mask image directories are collected separately, they are matched with the overviews based on their size.
     let mut overviews = Vec::new();
+    let mut mask: Option<GeoTiffMetadata> = None;
 
     loop {
-        let image_width = decoder.get_tag_u32(Tag::ImageWidth)?;
-        let image_height = decoder.get_tag_u32(Tag::ImageLength)?;
-
-        let (offsets, byte_counts) = if is_tiled {
-            (
-                decoder.get_tag_u64_vec(Tag::TileOffsets)?,
-                decoder.get_tag_u64_vec(Tag::TileByteCounts)?,
-            )
+        let overview = read_overview(decoder)?;
+        if read_subfile_type(decoder) & SUBFILE_TYPE_MASK != 0 {
+            let mask_meta = match mask.as_mut() {
+                Some(mask_meta) => mask_meta,
+                None => mask.insert(read_mask_metadata(decoder)?),
+            };
+            mask_meta.overviews.push(overview);
         } else {
-            (
-                decoder.get_tag_u64_vec(Tag::StripOffsets)?,
-                decoder.get_tag_u64_vec(Tag::StripByteCounts)?,
-            )
-        };
-
-        debug_assert_eq!(offsets.len(), byte_counts.len());
-
-        let mut tile_locations = Vec::with_capacity(offsets.len());
-        offsets.iter().zip(byte_counts.iter()).for_each(|(offset, byte_count)| {
-            tile_locations.push(TiffChunkLocation {
-                offset: *offset,
-                size: *byte_count,
-            });
-        });
-
-        overviews.push(TiffOverview {
-            raster_size: RasterSize::with_rows_cols(Rows(image_height as i32), Columns(image_width as i32)),
-            chunk_locations: tile_locations,
-        });
+            overviews.push(overview);
+        }
 
         if !decoder.more_images() {
             break;
@@ -326,9 +442,7 @@ fn parse_cog_header<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffM
         decoder.next_image()?;
     }
 
-    let epsg = projection
-        .and_then(|proj| proj.epsg().map(|epsg| epsg.to_string()))
-        .unwrap_or_default();
+    let projection = projection.and_then(|proj| proj.projection()).unwrap_or_default();
 
     // Extract scale/offset from GDAL metadata (use first band if available)
     let raster_scale = gdal_metadata.as_ref().and_then(|m| {
@@ -340,19 +454,42 @@ fn parse_cog_header<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<GeoTiffM
         })
     });
 
-    let band_metadata = gdal_metadata.as_ref().map(|m| m.band_metadata.clone()).unwrap_or_default();
+    let (band_metadata, metadata_domains, attribute_table) = gdal_metadata
+        .map(|m| (m.band_metadata, m.domains, m.attribute_table))
+        .unwrap_or_default();
+    let geo_reference = GeoReference::new(projection, raster_size, geo_transform.into(), nodata, raster_scale);
+
+    let mask = mask.map(|mut mask_meta| {
+        // Sparse mask chunks are read as 0, so all the cells of the chunk are masked
+        mask_meta.geo_reference = GeoReference::new(
+            geo_reference.projection(),
+            raster_size,
+            geo_reference.geo_transform(),
+            Some(0.0),
+            None,
+        );
+        mask_meta.overviews = match_mask_overviews(&overviews, mask_meta.overviews);
+        Box::new(mask_meta)
+    });
 
     Ok(GeoTiffMetadata {
         data_layout,
         data_type,
+        bits_per_sample: bits_per_sample as u32,
         band_count: samples_per_pixel,
         compression,
         predictor,
-        geo_reference: GeoReference::new(epsg, raster_size, geo_transform.into(), nodata, raster_scale),
+        geo_reference,
         statistics,
         overviews,
         interleave,
         gdal_ghost_data: None,
         band_metadata,
+        metadata_domains,
+        palette,
+        attribute_table,
+        jpeg_tables,
+        alpha_band,
+        mask,
     })
 }
@@ -0,0 +1,364 @@
+//! Low level TIFF file structure serialization.
+//! Produces little-endian classic TIFF or BigTIFF files from a list of image file directories
+//! with already encoded chunk data.
+
+use std::{collections::BTreeMap, io::Write};
+
+use tiff::tags::{Tag, Type};
+
+use crate::{Error, Result, geotiff::ChunkDataLayout};
+
+type TagMap = BTreeMap<u16, TagValue>;
+
+const CLASSIC_TIFF_HEADER_SIZE: u64 = 8;
+const BIGTIFF_HEADER_SIZE: u64 = 16;
+
+/// The value of a single TIFF tag
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagValue {
+    Short(Vec<u16>),
+    Long(Vec<u32>),
+    Long8(Vec<u64>),
+    Double(Vec<f64>),
+    Ascii(String),
+}
+
+impl TagValue {
+    fn tiff_type(&self) -> Type {
+        match self {
+            TagValue::Short(_) => Type::SHORT,
+            TagValue::Long(_) => Type::LONG,
+            TagValue::Long8(_) => Type::LONG8,
+            TagValue::Double(_) => Type::DOUBLE,
+            TagValue::Ascii(_) => Type::ASCII,
+        }
+    }
+
+    fn count(&self) -> u64 {
+        match self {
+            TagValue::Short(v) => v.len() as u64,
+            TagValue::Long(v) => v.len() as u64,
+            TagValue::Long8(v) => v.len() as u64,
+            TagValue::Double(v) => v.len() as u64,
+            TagValue::Ascii(s) => s.len() as u64 + 1, // null terminator
+        }
+    }
+
+    fn byte_size(&self) -> u64 {
+        match self {
+            TagValue::Short(v) => v.len() as u64 * 2,
+            TagValue::Long(v) => v.len() as u64 * 4,
+            TagValue::Long8(v) => v.len() as u64 * 8,
+            TagValue::Double(v) => v.len() as u64 * 8,
+            TagValue::Ascii(s) => s.len() as u64 + 1,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            TagValue::Short(v) => v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            TagValue::Long(v) => v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            TagValue::Long8(v) => v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            TagValue::Double(v) => v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            TagValue::Ascii(s) => s.bytes().chain(std::iter::once(0)).collect(),
+        }
+    }
+}
+
+/// A single image (full resolution image, overview or mask) that will be written to the TIFF file.
+/// The chunk offset and byte count tags are generated by the encoder, they should not be present in the tags.
+#[derive(Debug, Clone)]
+pub struct ImageFileDirectory {
+    pub tags: TagMap,
+    pub layout: ChunkDataLayout,
+    /// The encoded chunks in the order of the offsets array, `None` for sparse chunks
+    pub chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl ImageFileDirectory {
+    pub fn new(layout: ChunkDataLayout) -> Self {
+        Self {
+            tags: TagMap::new(),
+            layout,
+            chunks: Vec::new(),
+        }
+    }
+
+    pub fn set_tag(&mut self, tag: Tag, value: TagValue) {
+        self.tags.insert(tag.to_u16(), value);
+    }
+
+    fn offset_tags(&self) -> (Tag, Tag) {
+        match self.layout {
+            ChunkDataLayout::Tiled(_) => (Tag::TileOffsets, Tag::TileByteCounts),
+            ChunkDataLayout::Striped(_) => (Tag::StripOffsets, Tag::StripByteCounts),
+        }
+    }
+}
+
+/// The order in which the chunk data of the directories is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataOrder {
+    /// The data of the first directory is written first
+    #[default]
+    DirectoryOrder,
+    /// The data of the last directory is written first (e.g. smallest overview first for COGs)
+    ReverseDirectoryOrder,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TiffEncodeOptions {
+    /// Data that is written right after the TIFF header (e.g. the GDAL ghost area)
+    pub ghost_area: Option<Vec<u8>>,
+    /// Precede every chunk with its size and follow it with a repetition of its last 4 bytes
+    pub block_leader_trailer: bool,
+    /// Always write a BigTIFF, even when the data fits in a classic TIFF
+    pub force_bigtiff: bool,
+    /// The order in which the chunk data is written after the directories
+    pub data_order: DataOrder,
+}
+
+struct Format {
+    bigtiff: bool,
+}
+
+impl Format {
+    fn header_size(&self) -> u64 {
+        if self.bigtiff {
+            BIGTIFF_HEADER_SIZE
+        } else {
+            CLASSIC_TIFF_HEADER_SIZE
+        }
+    }
+
+    fn inline_capacity(&self) -> u64 {
+        if self.bigtiff { 8 } else { 4 }
+    }
+
+    fn offset_size(&self) -> u64 {
+        if self.bigtiff { 8 } else { 4 }
+    }
+
+    fn entry_count_size(&self) -> u64 {
+        if self.bigtiff { 8 } else { 2 }
+    }
+
+    fn entry_size(&self) -> u64 {
+        if self.bigtiff { 20 } else { 12 }
+    }
+
+    fn offsets_value(&self, values: Vec<u64>) -> Result<TagValue> {
+        if self.bigtiff {
+            Ok(TagValue::Long8(values))
+        } else {
+            Ok(TagValue::Long(
+                values
+                    .into_iter()
+                    .map(|v| u32::try_from(v).map_err(|_| Error::Runtime("Offset does not fit in a classic TIFF".into())))
+                    .collect::<Result<Vec<u32>>>()?,
+            ))
+        }
+    }
+
+    fn directory_size(&self, tags: &TagMap) -> u64 {
+        let external_size: u64 = tags
+            .values()
+            .map(|v| v.byte_size())
+            .filter(|size| *size > self.inline_capacity())
+            .map(|size| size + size % 2)
+            .sum();
+
+        self.entry_count_size() + tags.len() as u64 * self.entry_size() + self.offset_size() + external_size
+    }
+
+    fn write_offset(&self, buf: &mut Vec<u8>, offset: u64) {
+        if self.bigtiff {
+            buf.extend_from_slice(&offset.to_le_bytes());
+        } else {
+            buf.extend_from_slice(&(offset as u32).to_le_bytes());
+        }
+    }
+
+    /// Serializes a directory that will be located at `directory_offset` in the file
+    fn serialize_directory(&self, tags: &TagMap, directory_offset: u64, next_directory_offset: u64) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.directory_size(tags) as usize);
+        let mut external = Vec::new();
+        let external_start = directory_offset + self.entry_count_size() + tags.len() as u64 * self.entry_size() + self.offset_size();
+
+        if self.bigtiff {
+            buf.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+        } else {
+            buf.extend_from_slice(&(tags.len() as u16).to_le_bytes());
+        }
+
+        for (tag, value) in tags {
+            buf.extend_from_slice(&tag.to_le_bytes());
+            buf.extend_from_slice(&value.tiff_type().to_u16().to_le_bytes());
+            if self.bigtiff {
+                buf.extend_from_slice(&value.count().to_le_bytes());
+            } else {
+                buf.extend_from_slice(&(value.count() as u32).to_le_bytes());
+            }
+
+            let mut value_bytes = value.to_bytes();
+            if value_bytes.len() as u64 <= self.inline_capacity() {
+                value_bytes.resize(self.inline_capacity() as usize, 0);
+                buf.extend_from_slice(&value_bytes);
+            } else {
+                self.write_offset(&mut buf, external_start + external.len() as u64);
+                if value_bytes.len() % 2 != 0 {
+                    value_bytes.push(0); // Values must start on a word boundary
+                }
+                external.extend_from_slice(&value_bytes);
+            }
+        }
+
+        self.write_offset(&mut buf, next_directory_offset);
+        buf.extend_from_slice(&external);
+        buf
+    }
+}
+
+fn write(stream: &mut impl Write, data: &[u8], pos: &mut u64) -> Result<()> {
+    stream.write_all(data)?;
+    *pos += data.len() as u64;
+    Ok(())
+}
+
+/// The directories in the order their chunk data is written
+fn data_order<'a>(ifds: &'a [ImageFileDirectory], options: &TiffEncodeOptions) -> Vec<(usize, &'a ImageFileDirectory)> {
+    let mut ordered: Vec<_> = ifds.iter().enumerate().collect();
+    if options.data_order == DataOrder::ReverseDirectoryOrder {
+        ordered.reverse();
+    }
+    ordered
+}
+
+fn leader_trailer_size(options: &TiffEncodeOptions) -> u64 {
+    if options.block_leader_trailer { 8 } else { 0 }
+}
+
+fn data_size(ifds: &[ImageFileDirectory], options: &TiffEncodeOptions) -> u64 {
+    ifds.iter()
+        .flat_map(|ifd| ifd.chunks.iter().flatten())
+        .map(|chunk| chunk.len() as u64 + leader_trailer_size(options))
+        .sum()
+}
+
+/// Adds the chunk offsets and byte count tags to the directories, returns the offset of every directory
+fn layout_directories(ifds: &[ImageFileDirectory], format: &Format, options: &TiffEncodeOptions) -> Result<(Vec<TagMap>, Vec<u64>)> {
+    // First pass: insert placeholders so the directory sizes are known
+    let mut directories = Vec::with_capacity(ifds.len());
+    for ifd in ifds {
+        let mut tags = ifd.tags.clone();
+        let (offsets_tag, byte_counts_tag) = ifd.offset_tags();
+        tags.insert(offsets_tag.to_u16(), format.offsets_value(vec![0; ifd.chunks.len()])?);
+        tags.insert(byte_counts_tag.to_u16(), format.offsets_value(vec![0; ifd.chunks.len()])?);
+        directories.push(tags);
+    }
+
+    let mut pos = format.header_size() + options.ghost_area.as_ref().map_or(0, |g| g.len() as u64);
+    let mut directory_offsets = Vec::with_capacity(ifds.len());
+    for tags in &directories {
+        pos += pos % 2;
+        directory_offsets.push(pos);
+        pos += format.directory_size(tags);
+    }
+
+    // Second pass: calculate the chunk data locations
+    let mut chunk_offsets: Vec<Vec<u64>> = ifds.iter().map(|ifd| vec![0; ifd.chunks.len()]).collect();
+    for (index, ifd) in data_order(ifds, options) {
+        for (chunk, chunk_offset) in ifd.chunks.iter().zip(chunk_offsets[index].iter_mut()) {
+            if let Some(chunk) = chunk {
+                if options.block_leader_trailer {
+                    pos += 4;
+                }
+
+                *chunk_offset = pos;
+                pos += chunk.len() as u64;
+
+                if options.block_leader_trailer {
+                    pos += 4;
+                }
+            }
+        }
+    }
+
+    for ((tags, ifd), offsets) in directories.iter_mut().zip(ifds).zip(chunk_offsets) {
+        let (offsets_tag, byte_counts_tag) = ifd.offset_tags();
+        let byte_counts = ifd.chunks.iter().map(|c| c.as_ref().map_or(0, |c| c.len() as u64)).collect();
+        tags.insert(offsets_tag.to_u16(), format.offsets_value(offsets)?);
+        tags.insert(byte_counts_tag.to_u16(), format.offsets_value(byte_counts)?);
+    }
+
+    Ok((directories, directory_offsets))
+}
+
+/// Writes the image file directories and their chunk data as a little-endian TIFF file.
+/// All the directories are written before the chunk data, the chunk data is written in the configured data order.
+/// A BigTIFF is written when the file would exceed the 4GB limit of a classic TIFF.
+pub fn write_tiff(ifds: &[ImageFileDirectory], options: &TiffEncodeOptions, stream: &mut impl Write) -> Result<()> {
+    if ifds.is_empty() {
+        return Err(Error::InvalidArgument("No images provided to write".into()));
+    }
+
+    let classic_format = Format { bigtiff: false };
+    let header_and_directory_size: u64 = classic_format.header_size()
+        + options.ghost_area.as_ref().map_or(0, |g| g.len() as u64)
+        + ifds
+            .iter()
+            .map(|ifd| classic_format.directory_size(&ifd.tags) + 16 * ifd.chunks.len() as u64 + 64)
+            .sum::<u64>();
+    let bigtiff = options.force_bigtiff || header_and_directory_size + data_size(ifds, options) > u32::MAX as u64;
+    let format = Format { bigtiff };
+
+    let (directories, directory_offsets) = layout_directories(ifds, &format, options)?;
+
+    let mut pos = 0u64;
+
+    // Header
+    let mut header = Vec::with_capacity(format.header_size() as usize);
+    header.extend_from_slice(b"II");
+    if bigtiff {
+        header.extend_from_slice(&43u16.to_le_bytes());
+        header.extend_from_slice(&8u16.to_le_bytes()); // Bytesize of offsets
+        header.extend_from_slice(&0u16.to_le_bytes());
+    } else {
+        header.extend_from_slice(&42u16.to_le_bytes());
+    }
+    format.write_offset(&mut header, directory_offsets[0]);
+    write(stream, &header, &mut pos)?;
+
+    if let Some(ghost_area) = &options.ghost_area {
+        write(stream, ghost_area, &mut pos)?;
+    }
+
+    for (index, (tags, offset)) in directories.iter().zip(&directory_offsets).enumerate() {
+        if pos < *offset {
+            let padding = vec![0; (*offset - pos) as usize];
+            write(stream, &padding, &mut pos)?;
+        }
+
+        let next_offset = directory_offsets.get(index + 1).copied().unwrap_or(0);
+        write(stream, &format.serialize_directory(tags, *offset, next_offset), &mut pos)?;
+    }
+
+    for (_, ifd) in data_order(ifds, options) {
+        for chunk in ifd.chunks.iter().flatten() {
+            if options.block_leader_trailer {
+                write(stream, &(chunk.len() as u32).to_le_bytes(), &mut pos)?;
+            }
+
+            write(stream, chunk, &mut pos)?;
+
+            if options.block_leader_trailer {
+                let mut trailer = [0u8; 4];
+                let tail = &chunk[chunk.len().saturating_sub(4)..];
+                trailer[4 - tail.len()..].copy_from_slice(tail);
+                write(stream, &trailer, &mut pos)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,9 +1,18 @@
+use std::collections::BTreeMap;
+
 use crate::Error;
 use xml::reader::{EventReader, XmlEvent};
 
-use super::gdalghostdata::Interleave;
+use super::{
+    attributetable::{self, RasterAttributeTable},
+    gdalghostdata::Interleave,
+};
+
+const TILING_SCHEME_DOMAIN: &str = "TILING_SCHEME";
+const GOOGLE_MAPS_COMPATIBLE: &str = "GoogleMapsCompatible";
+const IMAGE_STRUCTURE_DOMAIN: &str = "IMAGE_STRUCTURE";
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(
     target_arch = "wasm32",
     derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
@@ -18,7 +27,7 @@ pub struct TiffStats {
 }
 
 /// Band-specific metadata including offset and scale values
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct BandMetadata {
     /// The band/sample index (0-based)
     pub sample: u32,
@@ -26,19 +35,58 @@ pub struct BandMetadata {
     pub offset: Option<f64>,
     /// Scale value for the band
     pub scale: Option<f64>,
+    /// Description of the band
+    pub description: Option<String>,
+    /// Unit of the band values (e.g. "m")
+    pub unit: Option<String>,
+    /// Statistics of the band values
+    pub statistics: Option<TiffStats>,
 }
 
+/// Metadata items that have no dedicated field, grouped per domain (the default domain has an empty name)
+pub type MetadataDomains = BTreeMap<String, BTreeMap<String, String>>;
+
 /// Complete GDAL metadata parsed from TIFF tags
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct GdalMetadata {
-    /// Statistical information (min, max, mean, stddev, etc.)
+    /// Statistical information (min, max, mean, stddev, etc.) of the first band that has statistics
     pub statistics: Option<TiffStats>,
-    /// Per-band metadata (offset, scale)
+    /// Per-band metadata (offset, scale, description, unit, statistics)
     pub band_metadata: Vec<BandMetadata>,
     /// Interleave mode from `IMAGE_STRUCTURE` domain
     pub interleave: Option<Interleave>,
     /// Maximum zoom level from `TILING_SCHEME` domain
     pub max_zoom: Option<i32>,
+    /// The other dataset level metadata items
+    pub domains: MetadataDomains,
+    /// Raster attribute table of the first band
+    pub attribute_table: Option<RasterAttributeTable>,
+}
+
+impl GdalMetadata {
+    /// Returns the metadata of the band, the band is added if it is not present yet
+    pub fn band_mut(&mut self, sample: u32) -> &mut BandMetadata {
+        let index = match self.band_metadata.iter().position(|b| b.sample == sample) {
+            Some(index) => index,
+            None => {
+                self.band_metadata.push(BandMetadata {
+                    sample,
+                    ..Default::default()
+                });
+                self.band_metadata.len() - 1
+            }
+        };
+
+        &mut self.band_metadata[index]
+    }
+
+    /// Sets a metadata item in the provided domain, use an empty domain name for the default domain
+    pub fn set_item(&mut self, domain: &str, name: &str, value: impl ToString) {
+        self.domains
+            .entry(domain.to_string())
+            .or_default()
+            .insert(name.to_string(), value.to_string());
+    }
 }
 
 // GDAL metadata can have various formats:
@@ -52,13 +100,108 @@ pub struct GdalMetadata {
 //   <Item name="ZOOM_LEVEL" domain="TILING_SCHEME">10</Item>
 //   <Item name="OFFSET" sample="0" role="offset">0</Item>
 //   <Item name="SCALE" sample="0" role="scale">0.031372549019600002</Item>
+//   <Item name="DESCRIPTION" sample="0" role="description">Elevation</Item>
+//   <Item name="UNITTYPE" sample="0" role="unittype">m</Item>
 //   <Item name="INTERLEAVE" domain="IMAGE_STRUCTURE">TILE</Item>
+//   <Item name="AREA_OR_POINT">Area</Item>
 // </GDALMetadata>
 
+/// A single `Item` element of the GDAL metadata
+#[derive(Default)]
+struct MetadataItem {
+    name: String,
+    value: String,
+    domain: Option<String>,
+    sample: Option<u32>,
+    role: Option<String>,
+}
+
+impl MetadataItem {
+    fn band(sample: u32, name: &str, value: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            sample: Some(sample),
+            ..Default::default()
+        }
+    }
+
+    fn band_with_role(sample: u32, name: &str, role: &str, value: impl ToString) -> Self {
+        Self {
+            role: Some(role.to_string()),
+            ..Self::band(sample, name, value)
+        }
+    }
+
+    fn domain(domain: &str, name: &str, value: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: (!domain.is_empty()).then(|| domain.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+const STATISTICS_ITEMS: [&str; 5] = [
+    "STATISTICS_MINIMUM",
+    "STATISTICS_MAXIMUM",
+    "STATISTICS_MEAN",
+    "STATISTICS_STDDEV",
+    "STATISTICS_VALID_PERCENT",
+];
+
+fn statistics_value<'a>(stats: &'a mut TiffStats, name: &str) -> Option<&'a mut f64> {
+    match name {
+        "STATISTICS_MINIMUM" => Some(&mut stats.minimum_value),
+        "STATISTICS_MAXIMUM" => Some(&mut stats.maximum_value),
+        "STATISTICS_MEAN" => Some(&mut stats.mean),
+        "STATISTICS_STDDEV" => Some(&mut stats.standard_deviation),
+        "STATISTICS_VALID_PERCENT" => Some(&mut stats.valid_pixel_percentage),
+        _ => None,
+    }
+}
+
+fn apply_item(metadata: &mut GdalMetadata, item: MetadataItem) -> crate::Result<()> {
+    let domain = item.domain.as_deref().unwrap_or_default();
+    if domain.is_empty() && STATISTICS_ITEMS.contains(&item.name.as_str()) {
+        // Statistics without sample attribute are considered to be the statistics of the first band
+        let stats = metadata.band_mut(item.sample.unwrap_or(0)).statistics.get_or_insert_default();
+        if let Some(value) = statistics_value(stats, &item.name) {
+            *value = item.value.trim().parse::<f64>().unwrap_or_default();
+        }
+
+        return Ok(());
+    }
+
+    if let Some(sample) = item.sample {
+        match (item.name.as_str(), item.role.as_deref()) {
+            ("OFFSET", Some("offset")) => metadata.band_mut(sample).offset = item.value.trim().parse::<f64>().ok(),
+            ("SCALE", Some("scale")) => metadata.band_mut(sample).scale = item.value.trim().parse::<f64>().ok(),
+            ("DESCRIPTION", Some("description")) => metadata.band_mut(sample).description = Some(item.value),
+            ("UNITTYPE", Some("unittype")) => metadata.band_mut(sample).unit = Some(item.value),
+            _ => {}
+        }
+
+        return Ok(());
+    }
+
+    match (domain, item.name.as_str()) {
+        // Other tiling schemes are kept in the metadata domains
+        (TILING_SCHEME_DOMAIN, "NAME") if item.value == GOOGLE_MAPS_COMPATIBLE => {}
+        (TILING_SCHEME_DOMAIN, "ZOOM_LEVEL") => metadata.max_zoom = item.value.trim().parse::<i32>().ok(),
+        (IMAGE_STRUCTURE_DOMAIN, "INTERLEAVE") => metadata.interleave = super::gdalghostdata::parse_interleave_mode(&item.value),
+        _ => metadata.set_item(domain, &item.name, item.value),
+    }
+
+    Ok(())
+}
+
 /// Parse GDAL metadata XML into a structured format
 ///
 /// This function parses the complete GDAL metadata XML from TIFF tags,
-/// including statistics, band-specific offset/scale values, and interleave mode.
+/// including statistics, band-specific offset/scale values, descriptions, units and interleave mode.
+/// Items that have no dedicated field are stored in the metadata domains.
 ///
 /// # Arguments
 /// * `xml` - The XML string containing GDAL metadata
@@ -68,120 +211,35 @@ pub struct GdalMetadata {
 /// * `Err` if XML parsing fails
 pub fn parse_gdal_metadata(xml: &str) -> crate::Result<GdalMetadata> {
     let mut metadata = GdalMetadata::default();
-    let mut stats = TiffStats::default();
-    let mut has_statistics = false;
-
-    let mut current_name: Option<String> = None;
-    let mut current_domain: Option<String> = None;
-    let mut current_sample: Option<u32> = None;
-    let mut current_role: Option<String> = None;
+    let mut current_item: Option<MetadataItem> = None;
 
     for e in EventReader::from_str(xml) {
         match e {
             Ok(XmlEvent::StartElement { name, attributes, .. }) if name.local_name == "Item" => {
-                current_name = None;
-                current_domain = None;
-                current_sample = None;
-                current_role = None;
-
+                let mut item = MetadataItem::default();
                 for attr in attributes {
                     match attr.name.local_name.as_str() {
-                        "name" => current_name = Some(attr.value),
-                        "domain" => current_domain = Some(attr.value),
-                        "sample" => current_sample = attr.value.parse::<u32>().ok(),
-                        "role" => current_role = Some(attr.value),
+                        "name" => item.name = attr.value,
+                        "domain" => item.domain = Some(attr.value),
+                        "sample" => item.sample = attr.value.parse::<u32>().ok(),
+                        "role" => item.role = Some(attr.value),
                         _ => {}
                     }
                 }
+
+                current_item = Some(item);
             }
             Ok(XmlEvent::Characters(data)) => {
-                if let Some(ref name) = current_name {
-                    match name.as_str() {
-                        "STATISTICS_MINIMUM" => {
-                            stats.minimum_value = data.parse::<f64>().unwrap_or_default();
-                            has_statistics = true;
-                        }
-                        "STATISTICS_MAXIMUM" => {
-                            stats.maximum_value = data.parse::<f64>().unwrap_or_default();
-                            has_statistics = true;
-                        }
-                        "STATISTICS_MEAN" => {
-                            stats.mean = data.parse::<f64>().unwrap_or_default();
-                            has_statistics = true;
-                        }
-                        "STATISTICS_STDDEV" => {
-                            stats.standard_deviation = data.parse::<f64>().unwrap_or_default();
-                            has_statistics = true;
-                        }
-                        "STATISTICS_VALID_PERCENT" => {
-                            stats.valid_pixel_percentage = data.parse::<f64>().unwrap_or_default();
-                            has_statistics = true;
-                        }
-                        "NAME" => {
-                            if let Some(ref domain) = current_domain
-                                && domain == "TILING_SCHEME"
-                                && data != "GoogleMapsCompatible"
-                            {
-                                return Err(Error::Runtime(format!("Unsupported TILING_SCHEME: {data}")));
-                            }
-                        }
-                        "ZOOM_LEVEL" => {
-                            if let Some(ref domain) = current_domain
-                                && domain == "TILING_SCHEME"
-                            {
-                                metadata.max_zoom = data.parse::<i32>().ok();
-                            }
-                        }
-                        "OFFSET" => {
-                            if let Some(role) = &current_role
-                                && role == "offset"
-                                && let Some(sample) = current_sample
-                            {
-                                let offset_value = data.parse::<f64>().ok();
-                                if let Some(band) = metadata.band_metadata.iter_mut().find(|b| b.sample == sample) {
-                                    band.offset = offset_value;
-                                } else {
-                                    metadata.band_metadata.push(BandMetadata {
-                                        sample,
-                                        offset: offset_value,
-                                        scale: None,
-                                    });
-                                }
-                            }
-                        }
-                        "SCALE" => {
-                            if let Some(role) = &current_role
-                                && role == "scale"
-                                && let Some(sample) = current_sample
-                            {
-                                let scale_value = data.parse::<f64>().ok();
-                                if let Some(band) = metadata.band_metadata.iter_mut().find(|b| b.sample == sample) {
-                                    band.scale = scale_value;
-                                } else {
-                                    metadata.band_metadata.push(BandMetadata {
-                                        sample,
-                                        offset: None,
-                                        scale: scale_value,
-                                    });
-                                }
-                            }
-                        }
-                        "INTERLEAVE" => {
-                            if let Some(ref domain) = current_domain
-                                && domain == "IMAGE_STRUCTURE"
-                            {
-                                metadata.interleave = super::gdalghostdata::parse_interleave_mode(&data);
-                            }
-                        }
-                        _ => {}
-                    }
+                if let Some(item) = current_item.as_mut() {
+                    item.value.push_str(&data);
                 }
             }
             Ok(XmlEvent::EndElement { name }) if name.local_name == "Item" => {
-                current_name = None;
-                current_domain = None;
-                current_sample = None;
-                current_role = None;
+                if let Some(item) = current_item.take()
+                    && !item.name.is_empty()
+                {
+                    apply_item(&mut metadata, item)?;
+                }
             }
             Err(e) => {
                 return Err(Error::Runtime(format!("XML parse error: {e}")));
@@ -192,12 +250,133 @@ pub fn parse_gdal_metadata(xml: &str) -> crate::Result<GdalMetadata> {
 
     // Sort band metadata by sample index for consistent ordering
     metadata.band_metadata.sort_by_key(|b| b.sample);
+    metadata.statistics = metadata.band_metadata.iter().find_map(|b| b.statistics.clone());
+    metadata.attribute_table = attributetable::parse_raster_attribute_table(xml)?;
 
-    if has_statistics {
-        metadata.statistics = Some(stats);
+    Ok(metadata)
+}
+
+fn interleave_mode_name(interleave: Interleave) -> &'static str {
+    match interleave {
+        Interleave::Band => "BAND",
+        Interleave::Pixel => "PIXEL",
+        Interleave::Tile => "TILE",
     }
+}
 
-    Ok(metadata)
+pub(crate) fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn band_metadata_items(band: &BandMetadata) -> Vec<MetadataItem> {
+    let sample = band.sample;
+    let mut items = Vec::new();
+    if let Some(stats) = &band.statistics {
+        items.extend([
+            MetadataItem::band(sample, "STATISTICS_MAXIMUM", stats.maximum_value),
+            MetadataItem::band(sample, "STATISTICS_MEAN", stats.mean),
+            MetadataItem::band(sample, "STATISTICS_MINIMUM", stats.minimum_value),
+            MetadataItem::band(sample, "STATISTICS_STDDEV", stats.standard_deviation),
+            MetadataItem::band(sample, "STATISTICS_VALID_PERCENT", stats.valid_pixel_percentage),
+        ]);
+    }
+
+    if let Some(offset) = band.offset {
+        items.push(MetadataItem::band_with_role(sample, "OFFSET", "offset", offset));
+    }
+    if let Some(scale) = band.scale {
+        items.push(MetadataItem::band_with_role(sample, "SCALE", "scale", scale));
+    }
+    if let Some(description) = &band.description {
+        items.push(MetadataItem::band_with_role(sample, "DESCRIPTION", "description", description));
+    }
+    if let Some(unit) = &band.unit {
+        items.push(MetadataItem::band_with_role(sample, "UNITTYPE", "unittype", unit));
+    }
+
+    items
+}
+
+/// Serialize the GDAL metadata to the XML format of the `GDAL_METADATA` tiff tag
+///
+/// The dataset statistics are written as statistics of the first band when none of the bands has statistics.
+/// The raster attribute table is stored as a `GDALRasterAttributeTable` element.
+/// The output can be parsed again with `parse_gdal_metadata`.
+pub fn serialize_gdal_metadata(metadata: &GdalMetadata) -> String {
+    let mut items = Vec::new();
+    for (domain, domain_items) in &metadata.domains {
+        items.extend(domain_items.iter().map(|(name, value)| MetadataItem::domain(domain, name, value)));
+    }
+
+    if let Some(max_zoom) = metadata.max_zoom {
+        if !metadata
+            .domains
+            .get(TILING_SCHEME_DOMAIN)
+            .is_some_and(|items| items.contains_key("NAME"))
+        {
+            items.push(MetadataItem::domain(TILING_SCHEME_DOMAIN, "NAME", GOOGLE_MAPS_COMPATIBLE));
+        }
+        items.push(MetadataItem::domain(TILING_SCHEME_DOMAIN, "ZOOM_LEVEL", max_zoom));
+    }
+
+    if let Some(interleave) = metadata.interleave {
+        items.push(MetadataItem::domain(
+            IMAGE_STRUCTURE_DOMAIN,
+            "INTERLEAVE",
+            interleave_mode_name(interleave),
+        ));
+    }
+
+    let mut band_metadata = metadata.band_metadata.clone();
+    if let Some(stats) = &metadata.statistics
+        && band_metadata.iter().all(|b| b.statistics.is_none())
+    {
+        match band_metadata.iter_mut().find(|b| b.sample == 0) {
+            Some(band) => band.statistics = Some(stats.clone()),
+            None => band_metadata.push(BandMetadata {
+                statistics: Some(stats.clone()),
+                ..Default::default()
+            }),
+        }
+    }
+    band_metadata.sort_by_key(|b| b.sample);
+
+    for band in &band_metadata {
+        items.extend(band_metadata_items(band));
+    }
+
+    let mut xml = String::from("<GDALMetadata>\n");
+    for item in items {
+        xml.push_str(&format!("  <Item name=\"{}\"", escape_xml(&item.name)));
+        if let Some(domain) = &item.domain {
+            xml.push_str(&format!(" domain=\"{}\"", escape_xml(domain)));
+        }
+        if let Some(sample) = item.sample {
+            xml.push_str(&format!(" sample=\"{sample}\""));
+        }
+        if let Some(role) = &item.role {
+            xml.push_str(&format!(" role=\"{role}\""));
+        }
+        xml.push_str(&format!(">{}</Item>\n", escape_xml(&item.value)));
+    }
+
+    if let Some(attribute_table) = &metadata.attribute_table {
+        xml.push_str(&attribute_table.to_xml());
+    }
+    xml.push_str("</GDALMetadata>\n");
+
+    xml
 }
 
 #[cfg(test)]
@@ -389,4 +568,108 @@ mod tests {
         // Should not parse interleave from wrong domain
         assert_eq!(metadata.interleave, None);
     }
+
+    #[test]
+    fn parse_gdal_metadata_descriptions_units_and_domains() {
+        let xml = r#"
+<GDALMetadata>
+  <Item name="AREA_OR_POINT">Area</Item>
+  <Item name="ALIGNED_LEVELS" domain="TILING_SCHEME">3</Item>
+  <Item name="STATISTICS_MAXIMUM" sample="1">12.5</Item>
+  <Item name="DESCRIPTION" sample="1" role="description">Height &amp; depth</Item>
+  <Item name="UNITTYPE" sample="1" role="unittype">m</Item>
+</GDALMetadata>
+        "#;
+        let metadata = parse_gdal_metadata(xml).expect("Should parse successfully");
+
+        assert_eq!(metadata.domains[""]["AREA_OR_POINT"], "Area");
+        assert_eq!(metadata.domains["TILING_SCHEME"]["ALIGNED_LEVELS"], "3");
+        assert_eq!(metadata.band_metadata.len(), 1);
+
+        let band = &metadata.band_metadata[0];
+        assert_eq!(band.sample, 1);
+        assert_eq!(band.description.as_deref(), Some("Height & depth"));
+        assert_eq!(band.unit.as_deref(), Some("m"));
+        assert_eq!(band.statistics.as_ref().map(|s| s.maximum_value), Some(12.5));
+        assert_eq!(metadata.statistics, band.statistics);
+    }
+
+    #[test]
+    fn serialize_gdal_metadata_round_trip() {
+        let mut metadata = GdalMetadata {
+            interleave: Some(Interleave::Tile),
+            max_zoom: Some(12),
+            ..Default::default()
+        };
+        metadata.set_item("", "AREA_OR_POINT", "Area");
+        metadata.set_item("CUSTOM", "SOURCE", "<model \"run\" 1 & 2>");
+
+        for sample in [1, 0] {
+            let band = metadata.band_mut(sample);
+            band.offset = Some(-10.0);
+            band.scale = Some(0.1);
+            band.description = Some(format!("Band {sample}"));
+            band.unit = Some("°C".to_string());
+            band.statistics = Some(TiffStats {
+                minimum_value: 0.0,
+                maximum_value: 254.0,
+                mean: 119.11901635438,
+                standard_deviation: 58.60474035626,
+                valid_pixel_percentage: 45.34,
+            });
+        }
+
+        let xml = serialize_gdal_metadata(&metadata);
+        assert!(xml.contains(r#"<Item name="SOURCE" domain="CUSTOM">&lt;model &quot;run&quot; 1 &amp; 2&gt;</Item>"#));
+        assert!(xml.contains(r#"<Item name="UNITTYPE" sample="0" role="unittype">°C</Item>"#));
+
+        let parsed = parse_gdal_metadata(&xml).expect("Should parse successfully");
+        metadata.band_metadata.sort_by_key(|b| b.sample);
+        metadata.statistics = metadata.band_metadata[0].statistics.clone();
+        assert_eq!(parsed, metadata);
+    }
+
+    #[test]
+    fn tiling_scheme_other_than_google_maps() {
+        let xml = r#"
+<GDALMetadata>
+  <Item name="NAME" domain="TILING_SCHEME">EuropeanETRS89_LAEAQuad</Item>
+  <Item name="ZOOM_LEVEL" domain="TILING_SCHEME">7</Item>
+</GDALMetadata>
+        "#;
+        let metadata = parse_gdal_metadata(xml).expect("Should parse successfully");
+        assert_eq!(metadata.max_zoom, Some(7));
+        assert_eq!(metadata.domains["TILING_SCHEME"]["NAME"], "EuropeanETRS89_LAEAQuad");
+
+        let xml = serialize_gdal_metadata(&metadata);
+        assert_eq!(xml.matches(r#"<Item name="NAME" domain="TILING_SCHEME">"#).count(), 1);
+        assert_eq!(parse_gdal_metadata(&xml).expect("Should parse successfully"), metadata);
+    }
+
+    #[test]
+    fn serialize_gdal_metadata_dataset_statistics() {
+        let stats = TiffStats {
+            minimum_value: 1.0,
+            maximum_value: 2.0,
+            mean: 1.5,
+            standard_deviation: 0.5,
+            valid_pixel_percentage: 100.0,
+        };
+
+        let metadata = GdalMetadata {
+            statistics: Some(stats.clone()),
+            ..Default::default()
+        };
+
+        // Without band statistics the dataset statistics are stored as statistics of the first band
+        let parsed = parse_gdal_metadata(&serialize_gdal_metadata(&metadata)).expect("Should parse successfully");
+        assert_eq!(parsed.statistics, Some(stats.clone()));
+        assert_eq!(parsed.band_metadata.len(), 1);
+        assert_eq!(parsed.band_metadata[0].statistics, Some(stats));
+
+        assert_eq!(
+            parse_gdal_metadata(&serialize_gdal_metadata(&GdalMetadata::default())).expect("Should parse successfully"),
+            GdalMetadata::default()
+        );
+    }
 }
@@ -0,0 +1,308 @@
+//! HTTP range request source to read tiff data from a web server or object store.
+
+use std::{ops::Range, time::Duration};
+
+use reqwest::{
+    StatusCode, Url,
+    blocking::{Client, Response},
+    header,
+};
+
+use crate::{Error, Result, geotiff::rangereader::RangeSource};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A `RangeSource` that fetches the data using HTTP range requests.
+/// Supports `http` and `https` urls, redirects are followed and connections are reused between requests.
+/// Servers that do not support range requests result in an error instead of downloading the full content.
+///
+/// The requests are performed with the blocking `reqwest` client, which panics when it is created, used or dropped
+/// on a thread that runs an async (tokio) runtime. In async code (e.g. a tile server) create and use the source,
+/// and the readers that own it, inside `tokio::task::spawn_blocking` or on a dedicated thread.
+#[derive(Debug, Clone)]
+pub struct HttpRangeSource {
+    url: Url,
+    client: Client,
+    timeout: Duration,
+}
+
+impl HttpRangeSource {
+    pub fn new(url: &str) -> Result<Self> {
+        let url = Url::parse(url).map_err(|err| Error::InvalidArgument(format!("Invalid url '{url}': {err}")))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::InvalidArgument(format!("Only http and https urls are supported: {url}")));
+        }
+
+        Ok(Self {
+            url,
+            client: Client::new(),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Performs a GET request for the byte range, fails when the server does not respond with partial content
+    fn request_range(&self, range: Range<u64>) -> Result<Response> {
+        let response = self
+            .client
+            .get(self.url.clone())
+            .header(header::RANGE, format!("bytes={}-{}", range.start, range.end - 1))
+            .timeout(self.timeout)
+            .send()?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => Ok(response),
+            StatusCode::OK => Err(Error::Runtime(format!(
+                "The server does not support range requests for {}",
+                self.url
+            ))),
+            status => Err(Error::Runtime(format!("HTTP request for {} failed with status {status}", self.url))),
+        }
+    }
+}
+
+impl RangeSource for HttpRangeSource {
+    fn size(&mut self) -> Result<u64> {
+        self.request_range(0..1)?
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| range.rsplit_once('/'))
+            .and_then(|(_, size)| size.trim().parse().ok())
+            .ok_or_else(|| Error::Runtime(format!("Invalid Content-Range header for {}", self.url)))
+    }
+
+    fn fetch_range(&mut self, range: Range<u64>) -> Result<Vec<u8>> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let data = self.request_range(range.clone())?.bytes()?;
+        if data.len() as u64 != range.end - range.start {
+            return Err(Error::Runtime(format!(
+                "HTTP request for {} returned {} bytes instead of the requested {}",
+                self.url,
+                data.len(),
+                range.end - range.start
+            )));
+        }
+
+        Ok(data.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead as _, BufReader, Write as _},
+        net::{TcpListener, TcpStream},
+        path::Path,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        GeoReference,
+        cog::WebTilesReader,
+        geotiff::{FIRST_BAND, GeoTiffMetadata, GeoTiffReader, RangeReader},
+        testutils,
+    };
+
+    use super::*;
+
+    /// Local stand-in for a web server that supports range requests, keeps track of the requested ranges.
+    /// `/data/raster.tif` serves the file, `/redirect.tif` redirects to it and `/norange.tif` ignores the range header.
+    struct TestServer {
+        address: String,
+        url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TestServer {
+        fn serve_file(path: &Path) -> TestServer {
+            let data = Arc::new(std::fs::read(path).unwrap());
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let server_requests = requests.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else { break };
+                    let data = data.clone();
+                    let requests = server_requests.clone();
+                    std::thread::spawn(move || Self::handle_connection(stream, &data, &requests));
+                }
+            });
+
+            TestServer {
+                url: format!("{address}/data/raster.tif"),
+                address,
+                requests,
+            }
+        }
+
+        /// Handles the requests on a connection until the client closes it
+        fn handle_connection(mut stream: TcpStream, data: &[u8], requests: &Mutex<Vec<String>>) {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    return;
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let mut range = None;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("range")
+                        && let Some(value) = value.trim().strip_prefix("bytes=")
+                    {
+                        let (start, end) = value.split_once('-').unwrap();
+                        range = Some((start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap()));
+                        requests.lock().unwrap().push(value.to_string());
+                    }
+                }
+
+                let response = match (path.as_str(), range) {
+                    ("/redirect.tif", _) => b"HTTP/1.1 302 Found\r\nLocation: /data/raster.tif\r\nContent-Length: 0\r\n\r\n".to_vec(),
+                    ("/data/raster.tif", Some((start, end))) => {
+                        let end = end.min(data.len() - 1);
+                        let mut response = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {start}-{end}/{}\r\nContent-Length: {}\r\n\r\n",
+                            data.len(),
+                            end + 1 - start
+                        )
+                        .into_bytes();
+                        response.extend_from_slice(&data[start..=end]);
+                        response
+                    }
+                    ("/data/raster.tif" | "/norange.tif", _) => {
+                        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", data.len()).into_bytes();
+                        response.extend_from_slice(data);
+                        response
+                    }
+                    _ => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec(),
+                };
+
+                if stream.write_all(&response).is_err() {
+                    return;
+                }
+            }
+        }
+
+        fn request_count(&self) -> usize {
+            self.requests.lock().unwrap().len()
+        }
+    }
+
+    #[test]
+    fn parse_url() {
+        assert!(HttpRangeSource::new("http://localhost:8080/cogs/raster.tif").is_ok());
+        assert!(HttpRangeSource::new("https://example.com/raster.tif").is_ok());
+        assert!(HttpRangeSource::new("ftp://example.com/raster.tif").is_err());
+        assert!(HttpRangeSource::new("/data/raster.tif").is_err());
+    }
+
+    #[test]
+    fn fetch_ranges() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("landusebyte.tif");
+        let data = std::fs::read(&path)?;
+        let server = TestServer::serve_file(&path);
+
+        let mut source = HttpRangeSource::new(&server.url)?;
+        assert_eq!(source.size()?, data.len() as u64);
+        assert_eq!(source.fetch_range(100..1100)?, data[100..1100]);
+
+        let mut redirected = HttpRangeSource::new(&format!("{}/redirect.tif", server.address))?;
+        assert_eq!(redirected.size()?, data.len() as u64);
+        assert_eq!(redirected.fetch_range(100..1100)?, data[100..1100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn range_requests_not_supported() -> Result<()> {
+        let server = TestServer::serve_file(&testutils::workspace_test_data_dir().join("landusebyte.tif"));
+
+        let mut source = HttpRangeSource::new(&format!("{}/norange.tif", server.address))?;
+        assert!(source.size().is_err());
+        assert!(source.fetch_range(100..1100).is_err());
+
+        let mut missing = HttpRangeSource::new(&format!("{}/missing.tif", server.address))?;
+        assert!(missing.size().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_cog_over_http() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("multiband_cog_interleave_band.tif");
+        let server = TestServer::serve_file(&path);
+
+        let meta = GeoTiffMetadata::from_reader(&mut RangeReader::new(HttpRangeSource::new(&server.url)?)?)?;
+        assert_eq!(meta.band_count, GeoTiffMetadata::from_file(&path)?.band_count);
+
+        let mut local = GeoTiffReader::from_file(&path)?;
+        let mut remote = GeoTiffReader::from_url(&server.url)?;
+        let requests_after_header = server.request_count();
+
+        let band = crate::geotiff::BandIndex::new(2).unwrap();
+        let overview_index = remote.metadata().overviews.len() - 1;
+        let chunk_ranges: Vec<_> = remote.metadata().overviews[overview_index]
+            .band_chunk_locations(remote.metadata(), band)
+            .iter()
+            .map(|chunk| chunk.range_to_fetch())
+            .collect();
+        remote.reader_mut().prefetch(&chunk_ranges)?;
+        let requests_after_prefetch = server.request_count();
+        assert!(requests_after_prefetch - requests_after_header <= chunk_ranges.len());
+
+        let remote_overview = remote.read_overview_band_as::<u8, GeoReference>(overview_index, band)?;
+        assert_eq!(
+            server.request_count(),
+            requests_after_prefetch,
+            "Prefetched chunks should be cached"
+        );
+        assert_eq!(
+            remote_overview,
+            local.read_overview_band_as::<u8, GeoReference>(overview_index, band)?
+        );
+
+        assert_eq!(
+            remote.read_raster_band_as::<u8, GeoReference>(band)?,
+            local.read_raster_band_as::<u8, GeoReference>(band)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_web_tiles_over_http() -> Result<()> {
+        let path = testutils::workspace_test_data_dir().join("multiband_cog_interleave_tile_google_maps_compatible.tif");
+        let server = TestServer::serve_file(&path);
+
+        let mut reader = RangeReader::new(HttpRangeSource::new(&server.url)?)?;
+        let cog = WebTilesReader::new(GeoTiffMetadata::from_reader(&mut reader)?)?;
+        let mut file = std::fs::File::open(&path)?;
+
+        for zoom_level in [15, 17] {
+            let tiles = cog.zoom_level_tile_sources(zoom_level).expect("zoom level should be available");
+            for tile in tiles.keys() {
+                let remote_tile = cog.read_tile_data(tile, FIRST_BAND, &mut reader)?;
+                let local_tile = cog.read_tile_data(tile, FIRST_BAND, &mut file)?;
+                assert_eq!(remote_tile, local_tile);
+            }
+        }
+
+        Ok(())
+    }
+}
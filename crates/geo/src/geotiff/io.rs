@@ -212,7 +212,7 @@ pub fn read_chunk_data_into_buffer_cb<T: ArrayNum>(
         tile_data.fill(cast::option(nodata).ok_or_else(|| Error::Runtime("Invalid nodata value".into()))?);
     } else {
         let cog_chunk = read_chunk_cb(*chunk)?;
-        parse_chunk_data_into_buffer(row_length, compression, predictor, &cog_chunk, tile_data)?;
+        parse_chunk_data_into_buffer(row_length, nodata, compression, predictor, &cog_chunk, tile_data)?;
     }
 
     Ok(())
@@ -221,6 +221,7 @@ pub fn read_chunk_data_into_buffer_cb<T: ArrayNum>(
 #[simd_bounds]
 pub fn parse_chunk_data_into_buffer<T: ArrayNum>(
     row_length: u32,
+    nodata: Option<f64>,
     compression: Option<Compression>,
     predictor: Option<Predictor>,
     chunk_data: &[u8],
@@ -228,6 +229,145 @@ pub fn parse_chunk_data_into_buffer<T: ArrayNum>(
 ) -> Result<()> {
     debug_assert!(chunk_data.len() > 4);
 
+    decompress_chunk_data_into_buffer(compression, nodata, chunk_data, decoded_chunk_data)?;
+    unpredict_chunk_data(predictor, row_length, 1, decoded_chunk_data)
+}
+
+/// Parses a chunk of a pixel interleaved tiff, where the chunk contains the values of all the bands.
+/// Only the values of the requested band are stored in the `decoded_band_data` buffer.
+#[simd_bounds]
+pub fn parse_pixel_interleaved_chunk_data_into_buffer<T: ArrayNum>(
+    row_length: u32,
+    nodata: Option<f64>,
+    samples_per_pixel: usize,
+    band_index: BandIndex,
+    compression: Option<Compression>,
+    predictor: Option<Predictor>,
+    chunk_data: &[u8],
+    decoded_band_data: &mut [T],
+) -> Result<()> {
+    let band_index0 = band_index.get() - 1;
+    if band_index0 >= samples_per_pixel {
+        return Err(Error::InvalidArgument(format!(
+            "Band index {} out of range, the tiff contains {samples_per_pixel} bands",
+            band_index.get()
+        )));
+    }
+
+    let mut interleaved_data = vec![T::zero(); decoded_band_data.len() * samples_per_pixel];
+    decompress_chunk_data_into_buffer(compression, nodata, chunk_data, &mut interleaved_data)?;
+    unpredict_chunk_data(predictor, row_length, samples_per_pixel, &mut interleaved_data)?;
+
+    for (dest, src) in decoded_band_data
+        .iter_mut()
+        .zip(interleaved_data.iter().skip(band_index0).step_by(samples_per_pixel))
+    {
+        *dest = *src;
+    }
+
+    Ok(())
+}
+
+/// Parses the chunk data of the requested band, taking the interleaving of the tiff into account.
+#[simd_bounds]
+pub fn parse_band_chunk_data_into_buffer<T: ArrayNum>(
+    meta: &GeoTiffMetadata,
+    band_index: BandIndex,
+    row_length: u32,
+    chunk_data: &[u8],
+    decoded_chunk_data: &mut [T],
+) -> Result<()> {
+    let chunk_data = meta.complete_chunk_data(chunk_data);
+    if meta.has_sub_byte_samples() {
+        parse_sub_byte_chunk_data_into_buffer(meta, band_index, row_length, &chunk_data, decoded_chunk_data)
+    } else if meta.interleave == Interleave::Pixel && meta.band_count > 1 {
+        parse_pixel_interleaved_chunk_data_into_buffer(
+            row_length,
+            meta.geo_reference.nodata(),
+            meta.band_count as usize,
+            band_index,
+            meta.compression,
+            meta.predictor,
+            &chunk_data,
+            decoded_chunk_data,
+        )
+    } else {
+        parse_chunk_data_into_buffer(
+            row_length,
+            meta.geo_reference.nodata(),
+            meta.compression,
+            meta.predictor,
+            &chunk_data,
+            decoded_chunk_data,
+        )
+    }
+}
+
+/// Parses the chunk data of tiffs with less than 8 bits per sample, every sample of the requested band is unpacked to a byte.
+fn parse_sub_byte_chunk_data_into_buffer<T: ArrayNum>(
+    meta: &GeoTiffMetadata,
+    band_index: BandIndex,
+    row_length: u32,
+    chunk_data: &[u8],
+    decoded_chunk_data: &mut [T],
+) -> Result<()> {
+    if T::TYPE != ArrayDataType::Uint8 {
+        return Err(Error::InvalidArgument(format!(
+            "Tiffs with {} bit(s) per sample can only be read as u8, not {:?}",
+            meta.bits_per_sample,
+            T::TYPE
+        )));
+    }
+
+    if meta.predictor.is_some() {
+        return Err(Error::Runtime(
+            "Predictors are not supported for tiffs with less than 8 bits per sample".into(),
+        ));
+    }
+
+    let (samples_per_pixel, sample_offset) = match meta.interleave {
+        Interleave::Pixel => (meta.band_count as usize, band_index.get() - 1),
+        Interleave::Band | Interleave::Tile => (1, 0),
+    };
+
+    let row_length = row_length as usize;
+    let row_count = decoded_chunk_data.len() / row_length;
+    let packed_row_size = (row_length * samples_per_pixel * meta.bits_per_sample as usize).div_ceil(8);
+    let mut packed_data = vec![0u8; packed_row_size * row_count];
+    decompress_chunk_data_into_buffer(meta.compression, meta.geo_reference.nodata(), chunk_data, &mut packed_data)?;
+
+    utils::unpack_sub_byte_samples(
+        &packed_data,
+        meta.bits_per_sample,
+        samples_per_pixel,
+        sample_offset,
+        bytemuck::cast_slice_mut(decoded_chunk_data),
+        row_length,
+    );
+
+    Ok(())
+}
+
+/// Combines the shared JPEG tables with the abbreviated JPEG data of a chunk into a complete JPEG stream.
+/// The tables are stored as a JPEG stream without image data, so the end of image marker of the tables
+/// and the start of image marker of the chunk are skipped.
+pub fn jpeg_stream_with_tables(jpeg_tables: &[u8], chunk_data: &[u8]) -> Vec<u8> {
+    let tables = &jpeg_tables[..jpeg_tables.len().saturating_sub(2)];
+    let image_data = chunk_data.get(2..).unwrap_or_default();
+
+    let mut stream = Vec::with_capacity(tables.len() + image_data.len());
+    stream.extend_from_slice(tables);
+    stream.extend_from_slice(image_data);
+    stream
+}
+
+/// The nodata value is used for the pixels that are marked as invalid by codecs that store a validity mask (LERC)
+fn decompress_chunk_data_into_buffer<T: ArrayNum>(
+    compression: Option<Compression>,
+    #[allow(unused)] nodata: Option<f64>,
+    chunk_data: &[u8],
+    decoded_chunk_data: &mut [T],
+) -> Result<()> {
     match compression {
         Some(Compression::Lzw) => lzw_decompress_to::<T>(chunk_data, decoded_chunk_data)?,
         Some(Compression::Zstd) => zstd_decompress_to::<T>(chunk_data, decoded_chunk_data)?,
@@ -239,6 +379,33 @@ pub fn parse_chunk_data_into_buffer<T: ArrayNum>(
                 "Deflate decompression requires the 'deflate' feature to be enabled".into(),
             ));
         }
+        Some(Compression::PackBits) => packbits_decompress_to::<T>(chunk_data, decoded_chunk_data)?,
+        #[cfg(feature = "jpeg")]
+        Some(Compression::Jpeg) => jpeg_decompress_to::<T>(chunk_data, decoded_chunk_data)?,
+        #[cfg(not(feature = "jpeg"))]
+        Some(Compression::Jpeg) => {
+            return Err(Error::Runtime(
+                "JPEG decompression requires the 'jpeg' feature to be enabled".into(),
+            ));
+        }
+        #[cfg(feature = "webp")]
+        Some(Compression::WebP) => webp_decompress_to::<T>(chunk_data, decoded_chunk_data)?,
+        #[cfg(not(feature = "webp"))]
+        Some(Compression::WebP) => {
+            return Err(Error::Runtime(
+                "WebP decompression requires the 'webp' feature to be enabled".into(),
+            ));
+        }
+        #[cfg(feature = "lerc")]
+        Some(compression @ (Compression::Lerc | Compression::LercDeflate | Compression::LercZstd)) => {
+            lerc_decompress_to::<T>(compression, nodata, chunk_data, decoded_chunk_data)?
+        }
+        #[cfg(not(feature = "lerc"))]
+        Some(Compression::Lerc | Compression::LercDeflate | Compression::LercZstd) => {
+            return Err(Error::Runtime(
+                "LERC decompression requires the 'lerc' feature to be enabled".into(),
+            ));
+        }
         None => {
             if chunk_data.len() != std::mem::size_of_val(decoded_chunk_data) {
                 return Err(Error::Runtime(format!(
@@ -252,17 +419,26 @@ pub fn parse_chunk_data_into_buffer<T: ArrayNum>(
         }
     };
 
+    Ok(())
+}
+
+fn unpredict_chunk_data<T: ArrayNum>(
+    predictor: Option<Predictor>,
+    row_length: u32,
+    samples_per_pixel: usize,
+    decoded_chunk_data: &mut [T],
+) -> Result<()> {
     match predictor {
         None => {}
         Some(Predictor::Horizontal) => {
-            utils::unpredict_horizontal(decoded_chunk_data, row_length);
+            utils::unpredict_horizontal(decoded_chunk_data, row_length, samples_per_pixel);
         }
         Some(Predictor::FloatingPoint) => match T::TYPE {
             ArrayDataType::Float32 => {
-                utils::unpredict_fp32(bytemuck::cast_slice_mut(decoded_chunk_data), row_length);
+                utils::unpredict_fp32(bytemuck::cast_slice_mut(decoded_chunk_data), row_length, samples_per_pixel);
             }
             ArrayDataType::Float64 => {
-                utils::unpredict_fp64(bytemuck::cast_slice_mut(decoded_chunk_data), row_length);
+                utils::unpredict_fp64(bytemuck::cast_slice_mut(decoded_chunk_data), row_length, samples_per_pixel);
             }
             _ => return Err(Error::Runtime("Floating point predictor only supported for f32 and f64".into())),
         },
@@ -271,11 +447,67 @@ pub fn parse_chunk_data_into_buffer<T: ArrayNum>(
     Ok(())
 }
 
+/// Applies the predictor and compression to the chunk data, the result can be written to a tiff file as is.
+/// The chunk data is modified in place when a horizontal predictor is used.
+pub fn encode_chunk_data<T: ArrayNum>(
+    row_length: u32,
+    compression: Option<Compression>,
+    predictor: Option<Predictor>,
+    chunk_data: &mut [T],
+) -> Result<Vec<u8>> {
+    let predicted_fp_data;
+    let chunk_bytes: &[u8] = match predictor {
+        None => bytemuck::cast_slice(chunk_data),
+        Some(Predictor::Horizontal) => {
+            utils::predict_horizontal(chunk_data, row_length)?;
+            bytemuck::cast_slice(chunk_data)
+        }
+        Some(Predictor::FloatingPoint) => {
+            predicted_fp_data = match T::TYPE {
+                ArrayDataType::Float32 => {
+                    let values: &[f32] = bytemuck::cast_slice(chunk_data);
+                    utils::predict_fp(values.iter().map(|v| v.to_be_bytes()), values.len(), row_length)
+                }
+                ArrayDataType::Float64 => {
+                    let values: &[f64] = bytemuck::cast_slice(chunk_data);
+                    utils::predict_fp(values.iter().map(|v| v.to_be_bytes()), values.len(), row_length)
+                }
+                _ => {
+                    return Err(Error::InvalidArgument(
+                        "Floating point predictor only supported for f32 and f64".into(),
+                    ));
+                }
+            };
+            &predicted_fp_data
+        }
+    };
+
+    match compression {
+        Some(Compression::Lzw) => lzw_compress(chunk_bytes),
+        Some(Compression::Zstd) => Ok(zstd_compress(chunk_bytes)),
+        #[cfg(feature = "deflate")]
+        Some(Compression::Deflate) => deflate_compress(chunk_bytes),
+        #[cfg(not(feature = "deflate"))]
+        Some(Compression::Deflate) => Err(Error::Runtime(
+            "Deflate compression requires the 'deflate' feature to be enabled".into(),
+        )),
+        Some(Compression::PackBits) => Ok(packbits_compress(chunk_bytes)),
+        Some(
+            compression @ (Compression::Jpeg | Compression::WebP | Compression::Lerc | Compression::LercDeflate | Compression::LercZstd),
+        ) => Err(Error::InvalidArgument(format!(
+            "{compression:?} compression is not supported for writing"
+        ))),
+        None => Ok(chunk_bytes.to_vec()),
+    }
+}
+
+/// Reads the data of the requested band from the chunk, taking the interleaving of the tiff into account.
 #[simd_bounds]
-fn read_chunk_data_into_buffer_cb_as<T: ArrayNum>(
+pub fn read_band_chunk_data_into_buffer_cb<T: ArrayNum>(
     meta: &GeoTiffMetadata,
+    band_index: BandIndex,
     chunk: &TiffChunkLocation,
-    read_chunk_cb: impl FnMut(TiffChunkLocation) -> Result<Vec<u8>>,
+    mut read_chunk_cb: impl FnMut(TiffChunkLocation) -> Result<Vec<u8>>,
     chunk_data: &mut [T],
 ) -> Result<()> {
     let row_length = meta.chunk_row_length();
@@ -288,16 +520,12 @@ fn read_chunk_data_into_buffer_cb_as<T: ArrayNum>(
         )));
     }
 
-    // io function handles the sparse check
-    read_chunk_data_into_buffer_cb(
-        chunk,
-        row_length,
-        meta.geo_reference.nodata(),
-        meta.compression,
-        meta.predictor,
-        read_chunk_cb,
-        chunk_data,
-    )?;
+    if chunk.is_sparse() {
+        chunk_data.fill(cast::option(meta.geo_reference.nodata()).ok_or_else(|| Error::Runtime("Invalid nodata value".into()))?);
+    } else {
+        let cog_chunk = read_chunk_cb(*chunk)?;
+        parse_band_chunk_data_into_buffer(meta, band_index, row_length, &cog_chunk, chunk_data)?;
+    }
 
     Ok(())
 }
@@ -314,7 +542,7 @@ pub fn merge_overview_into_buffer<T: ArrayNum, M: ArrayMetadata>(
     assert!(band_index.get() <= meta.band_count as usize);
     debug_assert_eq!(buffer.len(), overview.raster_size.cell_count());
     let raster_size = &overview.raster_size;
-    let mut geo_reference = meta.geo_reference.clone();
+    let mut geo_reference = meta.band_geo_reference(band_index);
     let nodata = cast::option::<T>(geo_reference.nodata()).unwrap_or(T::NODATA);
 
     let right_edge_cols = match raster_size.cols.count() as usize % tile_size as usize {
@@ -323,48 +551,7 @@ pub fn merge_overview_into_buffer<T: ArrayNum, M: ArrayMetadata>(
     };
 
     let tiles_per_row = (raster_size.cols.count() as usize).div_ceil(tile_size as usize);
-    let tiles_per_column = (raster_size.rows.count() as usize).div_ceil(tile_size as usize);
-    let tile_count = tiles_per_row * tiles_per_column;
-
-    // The orderinge of the chunks does not depend on the interleave, they are always stored in row-major order
-    let chunks = &overview.chunk_locations;
-    if meta.interleave == Interleave::Pixel {
-        unimplemented!("Pixel interleave tiff reading not implemented yet");
-    }
-
-    // BAND Interleave
-    // - **Physical layout**: `[Band0_Tile0, Band0_Tile1, Band0_Tile2, ..., Band1_Tile0, Band1_Tile1, ...]`
-    // - **TileOffsets array**: `[Band0_Tile0_offset, Band0_Tile1_offset, ..., Band1_Tile0_offset, ...]` (sequential offsets within each band)
-    // - **Optimized for**: Reading one band at a time (sequential I/O for single band)
-    // - **Use case**: When you typically analyze one band independently (e.g., reading just the Red channel)
-
-    // ### TILE Interleave
-    // - **Physical layout**: `[Tile0_Band0, Tile0_Band1, Tile0_Band2, ..., Tile1_Band0, Tile1_Band1, ...]`
-    // - **TileOffsets array**: Still indexed as `[Band0_tiles..., Band1_tiles...]` BUT the offsets jump around
-    // - **Optimized for**: Reading all bands for a spatial region (sequential I/O for multi-band tile)
-    // - **Use case**: When you typically need RGB data together (e.g., displaying true color images)
-
-    // The **TileOffsets array is always logically organized in BAND order** in both modes. The TIFF format allows the TileOffsets array to point to any physical locations in the file. So:
-
-    // - The `chunk_locations` array (which comes from TileOffsets) is always indexed as: `[Band0_tiles..., Band1_tiles..., Band2_tiles...]`
-    // - The current code using `.skip(band_index * tile_count).take(tile_count)` is correct for BOTH interleave modes
-    // - The INTERLEAVE metadata tells you about file I/O performance characteristics, not about how to index the arrays
-
-    let band_index0 = band_index.get() - 1; // to 0-based index
-
-    // Apply band-specific scale/offset if available
-    if let Some(band_meta) = meta.band_metadata.get(band_index0) {
-        let band_scale = match (band_meta.scale, band_meta.offset) {
-            (Some(scale), Some(offset)) => Some(crate::RasterScale { scale, offset }),
-            (Some(scale), None) => Some(crate::RasterScale { scale, offset: 0.0 }),
-            (None, Some(offset)) => Some(crate::RasterScale { scale: 1.0, offset }),
-            (None, None) => None,
-        };
-
-        geo_reference.set_scale(band_scale);
-    }
-
-    let chunk_iter = chunks.iter().skip(band_index0 * tile_count).take(tile_count).enumerate();
+    let chunk_iter = overview.band_chunk_locations(meta, band_index).iter().enumerate();
 
     let mut tile_buf = vec![nodata; tile_size as usize * tile_size as usize];
     for (chunk_index, chunk_offset) in chunk_iter {
@@ -373,7 +560,7 @@ pub fn merge_overview_into_buffer<T: ArrayNum, M: ArrayMetadata>(
         let is_right_edge = (chunk_index + 1) % tiles_per_row == 0;
         let row_size = if is_right_edge { right_edge_cols } else { tile_size as usize };
 
-        read_chunk_data_into_buffer_cb_as(meta, chunk_offset, &mut read_chunk_cb, &mut tile_buf)?;
+        read_band_chunk_data_into_buffer_cb(meta, band_index, chunk_offset, &mut read_chunk_cb, &mut tile_buf)?;
         for (tile_row_index, tile_row_data) in tile_buf.chunks_mut(tile_size as usize).enumerate() {
             let start_row = chunk_row_index * tile_size as usize + tile_row_index;
             if start_row >= raster_size.rows.count() as usize {
@@ -440,3 +627,400 @@ fn deflate_decompress_to<T: ArrayNum>(data: &[u8], decode_buf: &mut [T]) -> Resu
 
     Ok(())
 }
+
+fn packbits_decompress_to<T: ArrayNum>(data: &[u8], decode_buf: &mut [T]) -> Result<()> {
+    let decode_buf_byte: &mut [u8] = bytemuck::cast_slice_mut(decode_buf);
+    let invalid_data = || Error::Runtime("Invalid PackBits data".into());
+
+    let mut pos = 0;
+    let mut bytes_written = 0;
+    while pos < data.len() && bytes_written < decode_buf_byte.len() {
+        let header = data[pos] as i8;
+        pos += 1;
+
+        match header {
+            // No operation, skip to the next header byte
+            -128 => {}
+            // Copy the next header + 1 bytes literally
+            0.. => {
+                let count = header as usize + 1;
+                let literal = data.get(pos..pos + count).ok_or_else(invalid_data)?;
+                decode_buf_byte
+                    .get_mut(bytes_written..bytes_written + count)
+                    .ok_or_else(invalid_data)?
+                    .copy_from_slice(literal);
+                pos += count;
+                bytes_written += count;
+            }
+            // Repeat the next byte 1 - header times
+            _ => {
+                let count = (1 - header as isize) as usize;
+                let value = *data.get(pos).ok_or_else(invalid_data)?;
+                decode_buf_byte
+                    .get_mut(bytes_written..bytes_written + count)
+                    .ok_or_else(invalid_data)?
+                    .fill(value);
+                pos += 1;
+                bytes_written += count;
+            }
+        }
+    }
+
+    if bytes_written != decode_buf_byte.len() {
+        return Err(Error::Runtime("PackBits decompression did not write all tile pixels".into()));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "jpeg")]
+fn jpeg_decompress_to<T: ArrayNum>(data: &[u8], decode_buf: &mut [T]) -> Result<()> {
+    use zune_jpeg::zune_core::{bytestream::ZCursor, colorspace::ColorSpace, options::DecoderOptions};
+
+    if T::TYPE != ArrayDataType::Uint8 {
+        return Err(Error::Runtime(format!(
+            "JPEG decompression is only supported for 8-bit data ({:?})",
+            T::TYPE
+        )));
+    }
+
+    let mut decoder = zune_jpeg::JpegDecoder::new(ZCursor::new(data));
+    decoder
+        .decode_headers()
+        .map_err(|e| Error::Runtime(format!("Failed to decode JPEG header: {e:?}")))?;
+
+    // YCbCr data is converted to RGB, like libtiff does for JPEG compressed tiffs, other color spaces are kept as is
+    if let Some(colorspace) = decoder.input_colorspace() {
+        let out_colorspace = match colorspace {
+            ColorSpace::YCbCr => ColorSpace::RGB,
+            _ => colorspace,
+        };
+
+        decoder.set_options(DecoderOptions::default().jpeg_set_out_colorspace(out_colorspace));
+    }
+
+    let pixels = decoder
+        .decode()
+        .map_err(|e| Error::Runtime(format!("JPEG decompression failed: {e:?}")))?;
+    copy_decompressed_bytes("JPEG", &pixels, decode_buf)
+}
+
+#[cfg(feature = "webp")]
+fn webp_decompress_to<T: ArrayNum>(data: &[u8], decode_buf: &mut [T]) -> Result<()> {
+    if T::TYPE != ArrayDataType::Uint8 {
+        return Err(Error::Runtime(format!(
+            "WebP decompression is only supported for 8-bit data ({:?})",
+            T::TYPE
+        )));
+    }
+
+    let mut decoder = image_webp::WebPDecoder::new(std::io::Cursor::new(data))
+        .map_err(|e| Error::Runtime(format!("Failed to decode WebP header: {e}")))?;
+
+    let (width, height) = decoder.dimensions();
+    let pixel_count = width as usize * height as usize;
+    let samples_per_pixel = if decoder.has_alpha() { 4 } else { 3 };
+    let mut pixels = vec![0; pixel_count * samples_per_pixel];
+    decoder
+        .read_image(&mut pixels)
+        .map_err(|e| Error::Runtime(format!("WebP decompression failed: {e}")))?;
+
+    if samples_per_pixel == 3 && std::mem::size_of_val(decode_buf) == pixel_count * 4 {
+        // The tiff contains an alpha band, but the image is fully opaque
+        pixels = pixels.chunks_exact(3).flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX]).collect();
+    }
+
+    copy_decompressed_bytes("WebP", &pixels, decode_buf)
+}
+
+#[cfg(any(feature = "jpeg", feature = "webp"))]
+fn copy_decompressed_bytes<T: ArrayNum>(codec: &str, data: &[u8], decode_buf: &mut [T]) -> Result<()> {
+    let decode_buf_byte: &mut [u8] = bytemuck::cast_slice_mut(decode_buf);
+    if data.len() != decode_buf_byte.len() {
+        return Err(Error::Runtime(format!(
+            "{codec} decompressed data size ({}) does not match the expected size {}",
+            data.len(),
+            decode_buf_byte.len()
+        )));
+    }
+
+    decode_buf_byte.copy_from_slice(data);
+    Ok(())
+}
+
+#[cfg(feature = "lerc")]
+fn lerc_decompress_to<T: ArrayNum>(compression: Compression, nodata: Option<f64>, data: &[u8], decode_buf: &mut [T]) -> Result<()> {
+    let lerc_blob = match compression {
+        Compression::LercZstd => {
+            let mut blob = Vec::new();
+            StreamingDecoder::new(data)
+                .map_err(|_| Error::Runtime("Failed to create Zstd decoder".into()))?
+                .read_to_end(&mut blob)?;
+            std::borrow::Cow::Owned(blob)
+        }
+        #[cfg(feature = "deflate")]
+        Compression::LercDeflate => {
+            let mut blob = Vec::new();
+            ZlibDecoder::new(data).read_to_end(&mut blob)?;
+            std::borrow::Cow::Owned(blob)
+        }
+        #[cfg(not(feature = "deflate"))]
+        Compression::LercDeflate => {
+            return Err(Error::Runtime(
+                "LERC_DEFLATE decompression requires the 'deflate' feature to be enabled".into(),
+            ));
+        }
+        _ => std::borrow::Cow::Borrowed(data),
+    };
+
+    super::lerc::decode_into(&lerc_blob, cast::option::<T>(nodata).unwrap_or(T::NODATA), decode_buf)
+}
+
+/// PackBits run length encoding, runs of 2 or more equal bytes are stored as a repeat run
+fn packbits_compress(data: &[u8]) -> Vec<u8> {
+    const MAX_RUN_LENGTH: usize = 128;
+
+    let mut result = Vec::with_capacity(data.len() + data.len() / MAX_RUN_LENGTH + 1);
+    let mut pos = 0;
+    while pos < data.len() {
+        let run_length = data[pos..]
+            .iter()
+            .take(MAX_RUN_LENGTH)
+            .take_while(|&&value| value == data[pos])
+            .count();
+
+        if run_length > 1 {
+            result.push((1 - run_length as isize) as u8);
+            result.push(data[pos]);
+            pos += run_length;
+        } else {
+            // Literal run until the next run of 3 equal bytes starts
+            let start = pos;
+            while pos < data.len() && pos - start < MAX_RUN_LENGTH {
+                if pos + 2 < data.len() && data[pos] == data[pos + 1] && data[pos] == data[pos + 2] {
+                    break;
+                }
+
+                pos += 1;
+            }
+
+            result.push((pos - start - 1) as u8);
+            result.extend_from_slice(&data[start..pos]);
+        }
+    }
+
+    result
+}
+
+fn lzw_compress(data: &[u8]) -> Result<Vec<u8>> {
+    // Use MSB bit order and 8 as the initial code size, which is standard for TIFF LZW
+    weezl::encode::Encoder::with_tiff_size_switch(BitOrder::Msb, 8)
+        .encode(data)
+        .map_err(|e| Error::Runtime(format!("LZW compression failed: {e}")))
+}
+
+fn zstd_compress(data: &[u8]) -> Vec<u8> {
+    ruzstd::encoding::compress_to_vec(data, ruzstd::encoding::CompressionLevel::Fastest)
+}
+
+#[cfg(feature = "deflate")]
+fn deflate_compress(data: &[u8]) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    // TIFF deflate uses zlib wrapper (RFC 1950), not raw deflate (RFC 1951)
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packbits_decompress() -> Result<()> {
+        // Example from the TIFF 6.0 specification
+        let encoded = [
+            0xFE, 0xAA, 0x02, 0x80, 0x00, 0x2A, 0xFD, 0xAA, 0x03, 0x80, 0x00, 0x2A, 0x22, 0xF7, 0xAA,
+        ];
+        let expected = [
+            0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0x22, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+            0xAA, 0xAA, 0xAA,
+        ];
+
+        let mut decoded = vec![0u8; expected.len()];
+        packbits_decompress_to(&encoded, &mut decoded)?;
+        assert_eq!(decoded, expected);
+
+        let mut too_large = vec![0u8; expected.len() + 1];
+        assert!(packbits_decompress_to(&encoded, &mut too_large).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn packbits_round_trip() -> Result<()> {
+        let mut data: Vec<u8> = (0..1000).map(|i| (i / 7) as u8).collect();
+        data.extend(std::iter::repeat_n(42, 500));
+        data.extend((0..300).map(|i| (i * 31 % 17) as u8));
+
+        let encoded = encode_chunk_data(100, Some(Compression::PackBits), None, &mut data.clone())?;
+        assert!(encoded.len() < data.len());
+
+        let mut decoded = vec![0u8; data.len()];
+        parse_chunk_data_into_buffer(100, None, Some(Compression::PackBits), None, &encoded, &mut decoded)?;
+        assert_eq!(decoded, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn jpeg_tables_are_merged() {
+        let tables = [0xFF, 0xD8, 0xFF, 0xDB, 0x01, 0xFF, 0xD9];
+        let chunk = [0xFF, 0xD8, 0xFF, 0xDA, 0x02, 0xFF, 0xD9];
+        assert_eq!(
+            jpeg_stream_with_tables(&tables, &chunk),
+            [0xFF, 0xD8, 0xFF, 0xDB, 0x01, 0xFF, 0xDA, 0x02, 0xFF, 0xD9]
+        );
+    }
+
+    #[test]
+    fn unsupported_write_compression() {
+        for compression in [Compression::Jpeg, Compression::WebP, Compression::Lerc] {
+            assert!(encode_chunk_data(4, Some(compression), None, &mut [0u8; 16]).is_err());
+        }
+    }
+
+    /// Reading tiffs with chunks produced by encoders of the codecs that are only supported for reading
+    #[cfg(any(feature = "jpeg", feature = "lerc", feature = "webp"))]
+    mod read_compressed {
+        use crate::{
+            Array as _, CellSize, Columns, GeoReference, Point, RasterSize, Rows,
+            geotiff::{
+                ChunkDataLayout, GeoTiffReader,
+                encoder::{self, ImageFileDirectory, TagValue, TiffEncodeOptions},
+                writer,
+            },
+        };
+        use tiff::tags::Tag;
+
+        use super::*;
+
+        const TILE_SIZE: u32 = 16;
+
+        /// Writes a tiff consisting of a single tile with already compressed data and opens it for reading
+        fn single_tile_tiff<T: ArrayNum>(
+            dir: &Path,
+            compression: Compression,
+            samples_per_pixel: u16,
+            extra_tags: Vec<(Tag, TagValue)>,
+            tile: Vec<u8>,
+        ) -> Result<GeoTiffReader> {
+            let size = RasterSize::with_rows_cols(Rows(TILE_SIZE as i32), Columns(TILE_SIZE as i32));
+            let geo_reference = GeoReference::with_top_left_origin(
+                "",
+                size,
+                Point::new(0.0, 0.0),
+                CellSize::square(1.0),
+                Some(T::NODATA.to_f64().unwrap()),
+            );
+
+            let mut ifd = ImageFileDirectory::new(ChunkDataLayout::Tiled(TILE_SIZE));
+            writer::add_image_structure_tags::<T>(&mut ifd, size, Some(compression), None)?;
+            writer::add_geo_tags(&mut ifd, &geo_reference)?;
+            ifd.set_tag(Tag::SamplesPerPixel, TagValue::Short(vec![samples_per_pixel]));
+            ifd.set_tag(
+                Tag::BitsPerSample,
+                TagValue::Short(vec![T::TYPE.bytes() as u16 * 8; samples_per_pixel as usize]),
+            );
+            for (tag, value) in extra_tags {
+                ifd.set_tag(tag, value);
+            }
+            ifd.chunks = vec![Some(tile)];
+
+            let path = dir.join(format!("{compression:?}.tif"));
+            encoder::write_tiff(&[ifd], &TiffEncodeOptions::default(), &mut BufWriter::new(File::create(&path)?))?;
+            GeoTiffReader::from_file(&path)
+        }
+
+        #[test]
+        #[cfg(feature = "lerc")]
+        fn read_lerc_tiff() -> Result<()> {
+            const LERC_PARAMETERS_TAG: u16 = 50674;
+
+            let tmp = tempfile::tempdir()?;
+            let values: Vec<u16> = (0..TILE_SIZE * TILE_SIZE).map(|v| (v * 37 % 1000) as u16).collect();
+            let mut image = lerc::Image::from_pixels(TILE_SIZE, TILE_SIZE, values.clone()).unwrap();
+            image.valid_masks[0].set_invalid(3);
+            let blob = lerc::encode(&image, lerc::Precision::Lossless).unwrap();
+
+            for (compression, additional_compression) in [(Compression::Lerc, 0), (Compression::LercDeflate, 1), (Compression::LercZstd, 2)]
+            {
+                let tile = match compression {
+                    #[cfg(feature = "deflate")]
+                    Compression::LercDeflate => deflate_compress(&blob)?,
+                    #[cfg(not(feature = "deflate"))]
+                    Compression::LercDeflate => continue,
+                    Compression::LercZstd => zstd_compress(&blob),
+                    _ => blob.clone(),
+                };
+
+                let parameters = (Tag::Unknown(LERC_PARAMETERS_TAG), TagValue::Long(vec![4, additional_compression]));
+                let mut reader = single_tile_tiff::<u16>(tmp.path(), compression, 1, vec![parameters], tile)?;
+                assert_eq!(reader.metadata().compression, Some(compression));
+
+                let raster = reader.read_raster_as::<u16, GeoReference>()?;
+                for (index, value) in raster.iter_opt().enumerate() {
+                    assert_eq!(value, (index != 3).then_some(values[index]));
+                }
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        #[cfg(feature = "jpeg")]
+        fn read_jpeg_tiff() -> Result<()> {
+            let tmp = tempfile::tempdir()?;
+            let values: Vec<u8> = (0..TILE_SIZE * TILE_SIZE)
+                .map(|v| (v / TILE_SIZE * 8 + v % TILE_SIZE * 4) as u8)
+                .collect();
+
+            let mut tile = Vec::new();
+            jpeg_encoder::Encoder::new(&mut tile, 95)
+                .encode(&values, TILE_SIZE as u16, TILE_SIZE as u16, jpeg_encoder::ColorType::Luma)
+                .unwrap();
+
+            let mut reader = single_tile_tiff::<u8>(tmp.path(), Compression::Jpeg, 1, Vec::new(), tile)?;
+            let raster = reader.read_raster_as::<u8, GeoReference>()?;
+            for (&value, &expected) in raster.as_slice().iter().zip(&values) {
+                assert!(value.abs_diff(expected) <= 4, "{value} != {expected}");
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        #[cfg(feature = "webp")]
+        fn read_webp_tiff() -> Result<()> {
+            let tmp = tempfile::tempdir()?;
+            let pixel_count = (TILE_SIZE * TILE_SIZE) as usize;
+            let rgb: Vec<u8> = (0..pixel_count * 3).map(|v| (v * 7 % 256) as u8).collect();
+
+            let mut tile = Vec::new();
+            image_webp::WebPEncoder::new(&mut tile)
+                .encode(&rgb, TILE_SIZE, TILE_SIZE, image_webp::ColorType::Rgb8)
+                .unwrap();
+
+            let photometric_rgb = (Tag::PhotometricInterpretation, TagValue::Short(vec![2]));
+            let mut reader = single_tile_tiff::<u8>(tmp.path(), Compression::WebP, 3, vec![photometric_rgb], tile)?;
+            for band in 0..3 {
+                let raster = reader.read_raster_band_as::<u8, GeoReference>(BandIndex::new(band + 1).unwrap())?;
+                assert_eq!(raster.as_slice(), rgb.iter().skip(band).step_by(3).copied().collect::<Vec<_>>());
+            }
+            assert_eq!(reader.metadata().band_count, 3);
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Decoding of LERC (Limited Error Raster Compression) compressed tiff chunks using the `lerc-rs` crate.
+
+use lerc::{SampleData, bitmask::BitMask};
+use num::{NumCast, ToPrimitive};
+
+use crate::{ArrayNum, Error, Result};
+
+/// Decodes the LERC blob into the output buffer, the values of multi dimensional blobs are interleaved per pixel.
+/// Pixels that are marked as invalid in the LERC mask are set to the provided nodata value.
+/// An error is returned when a decoded value can not be represented in the output type.
+pub fn decode_into<T: ArrayNum>(blob: &[u8], nodata: T, output: &mut [T]) -> Result<()> {
+    let image = lerc::decode(blob).map_err(|err| Error::Runtime(format!("Failed to decode LERC data: {err}")))?;
+
+    let depth = image.depth as usize;
+    if image.bands != 1 || image.num_pixels() * depth != output.len() {
+        return Err(Error::Runtime(format!(
+            "LERC blob size ({}x{}x{}, {} bands) does not match the expected size {}",
+            image.height,
+            image.width,
+            image.depth,
+            image.bands,
+            output.len()
+        )));
+    }
+
+    let mask = image.mask();
+    match &image.data {
+        SampleData::I8(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::U8(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::I16(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::U16(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::I32(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::U32(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::F32(values) => copy_values(values, mask, depth, nodata, output),
+        SampleData::F64(values) => copy_values(values, mask, depth, nodata, output),
+    }
+}
+
+fn copy_values<TSrc: ToPrimitive + Copy + std::fmt::Display, T: ArrayNum>(
+    values: &[TSrc],
+    mask: Option<&BitMask>,
+    depth: usize,
+    nodata: T,
+    output: &mut [T],
+) -> Result<()> {
+    for (index, (dest, &value)) in output.iter_mut().zip(values).enumerate() {
+        let valid = mask.is_none_or(|mask| mask.is_valid(index / depth));
+        *dest = if valid {
+            NumCast::from(value).ok_or_else(|| Error::Runtime(format!("LERC value {value} can not be represented as {:?}", T::TYPE)))?
+        } else {
+            nodata
+        };
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lerc::{Image, Precision};
+
+    use crate::Nodata as _;
+
+    use super::*;
+
+    #[test]
+    fn masked_pixels_are_nodata() -> Result<()> {
+        let mut image = Image::from_pixels(3, 2, vec![1_u16, 2, 3, 4, 5, 6]).unwrap();
+        image.valid_masks[0] = BitMask::new(6);
+        for index in [0, 2, 3, 4] {
+            image.valid_masks[0].set_valid(index);
+        }
+
+        let blob = lerc::encode(&image, Precision::Lossless).unwrap();
+        let mut output = vec![0_u16; 6];
+        decode_into(&blob, u16::NODATA, &mut output)?;
+        assert_eq!(output, [1, u16::NODATA, 3, 4, 5, u16::NODATA]);
+
+        // The masked pixels get the nodata value of the file
+        decode_into(&blob, 0, &mut output)?;
+        assert_eq!(output, [1, 0, 3, 4, 5, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_dimensions() -> Result<()> {
+        let values: Vec<f32> = (0..24).map(|v| v as f32 * 0.25).collect();
+        let image = Image {
+            width: 4,
+            height: 2,
+            depth: 3,
+            data_type: lerc::DataType::Float,
+            valid_masks: vec![BitMask::all_valid(8)],
+            data: SampleData::F32(values.clone()),
+            ..Default::default()
+        };
+
+        let blob = lerc::encode(&image, Precision::Lossless).unwrap();
+        let mut output = vec![0.0_f32; 24];
+        decode_into(&blob, f32::NODATA, &mut output)?;
+        assert_eq!(output, values);
+
+        // The values are converted to the type of the output buffer
+        let mut output = vec![0.0_f64; 24];
+        decode_into(&blob, f64::NODATA, &mut output)?;
+        assert_eq!(output, values.iter().map(|&v| v as f64).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_blobs() {
+        let blob = lerc::encode_slice(4, 4, &[7_u8; 16], Precision::Lossless).unwrap();
+
+        let mut output = vec![0_u8; 16];
+        assert!(decode_into(&blob, u8::NODATA, &mut output).is_ok());
+        assert!(decode_into(&blob, u8::NODATA, &mut [0_u8; 15]).is_err());
+        assert!(decode_into(&blob[..blob.len() - 4], u8::NODATA, &mut output).is_err());
+        assert!(decode_into(b"Lerc3 invalid", u8::NODATA, &mut output).is_err());
+    }
+
+    #[test]
+    fn values_out_of_range() {
+        let blob = lerc::encode_slice(2, 2, &[1_i16, -5, 3, 4], Precision::Lossless).unwrap();
+        assert!(decode_into(&blob, u8::NODATA, &mut [0_u8; 4]).is_err());
+
+        let mut output = [0_i32; 4];
+        assert!(decode_into(&blob, i32::NODATA, &mut output).is_ok());
+        assert_eq!(output, [1, -5, 3, 4]);
+    }
+}
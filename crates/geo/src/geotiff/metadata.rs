@@ -1,16 +1,20 @@
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::Seek;
+use std::io::{Read, Seek};
 use std::path::Path;
 
 use crate::geotiff::{
-    ChunkDataLayout, TiffStats, decoder,
+    ChunkDataLayout, TiffStats,
+    attributetable::{self, RasterAttributeTable},
+    decoder,
     gdalghostdata::GdalGhostData,
-    gdalmetadata::BandMetadata,
+    gdalmetadata::{BandMetadata, MetadataDomains},
     io::{self, CogHeaderReader},
     reader::TiffOverview,
 };
 use crate::raster::{Compression, Predictor};
-use crate::{ArrayDataType, Error, GeoReference, Result};
+use crate::{ArrayDataType, Error, GeoReference, RasterScale, Result, TileMatrixSet, geotiff::BandIndex};
+use inf::{Color, Legend, legend::MappingConfig};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interleave {
@@ -28,6 +32,8 @@ pub struct GeoTiffMetadata {
     pub data_layout: ChunkDataLayout,
     pub band_count: u32,
     pub data_type: ArrayDataType,
+    /// Number of bits of a sample, samples with less than 8 bits are unpacked to `u8` when reading
+    pub bits_per_sample: u32,
     pub compression: Option<Compression>,
     pub predictor: Option<Predictor>,
     pub statistics: Option<TiffStats>,
@@ -35,8 +41,21 @@ pub struct GeoTiffMetadata {
     pub overviews: Vec<TiffOverview>,
     pub interleave: Interleave,
     pub gdal_ghost_data: Option<GdalGhostData>, // Additional GDAL ghost metadata if the file was created with GDAL
-    /// Per-band metadata (scale/offset) from GDAL metadata XML
+    /// Per-band metadata (scale/offset, description, unit, statistics) from GDAL metadata XML
     pub band_metadata: Vec<BandMetadata>,
+    /// The other dataset metadata items from GDAL metadata XML
+    pub metadata_domains: MetadataDomains,
+    /// Colors of the palette (ColorMap tag) of palette color tiffs, the value of a cell is the index in the palette
+    pub palette: Option<Vec<Color>>,
+    /// Raster attribute table from GDAL metadata XML or the .aux.xml sidecar file
+    pub attribute_table: Option<RasterAttributeTable>,
+    /// Shared JPEG tables (JPEGTables tag) of JPEG compressed tiffs, the chunks only contain the abbreviated image data
+    pub jpeg_tables: Option<Vec<u8>>,
+    /// Band that contains the alpha channel (ExtraSamples tag), cells with a zero alpha value are read as nodata
+    pub alpha_band: Option<BandIndex>,
+    /// Metadata of the internal mask image directories (NewSubfileType mask), the mask overviews match the raster overviews.
+    /// Cells with a zero mask value are read as nodata
+    pub mask: Option<Box<GeoTiffMetadata>>,
 }
 
 pub enum ParseFromBufferError {
@@ -45,15 +64,30 @@ pub enum ParseFromBufferError {
 }
 
 impl GeoTiffMetadata {
+    /// Parses the metadata of the file, the raster attribute table is also read from the GDAL .aux.xml sidecar file if present
     pub fn from_file(path: &Path) -> Result<Self> {
-        let mut file_reader = File::open(path)?;
+        let mut meta = Self::from_reader(&mut File::open(path)?)?;
+        let aux_xml_path = attributetable::aux_xml_path(path);
+        if meta.attribute_table.is_none() && aux_xml_path.is_file() {
+            // The sidecar file is optional, a file that cannot be parsed does not prevent reading the tiff
+            meta.attribute_table = RasterAttributeTable::from_aux_xml_file(&aux_xml_path).unwrap_or_else(|err| {
+                log::warn!("Ignoring the raster attribute table in {}: {err}", aux_xml_path.display());
+                None
+            });
+        }
+
+        Ok(meta)
+    }
 
+    /// Parses the metadata from a reader positioned anywhere in the tiff data.
+    /// For COGs the header is read in as few io calls as possible, which matters for readers with expensive io calls (e.g. `RangeReader`).
+    pub fn from_reader(file_reader: &mut (impl Read + Seek)) -> Result<Self> {
         let mut buffer = Vec::with_capacity(io::COG_HEADER_SIZE);
-        io::append_from_stream_to_buffer(&mut buffer, &mut file_reader, io::COG_HEADER_SIZE)?;
+        io::append_from_stream_to_buffer(&mut buffer, file_reader, io::COG_HEADER_SIZE)?;
         let ghost_data = GdalGhostData::from_tiff_header_buffer(&buffer);
         file_reader.seek(std::io::SeekFrom::Start(0))?;
 
-        let mut cog_buffer_reader = CogHeaderReader::from_stream(&mut file_reader, io::COG_HEADER_SIZE)?;
+        let mut cog_buffer_reader = CogHeaderReader::from_stream(file_reader, io::COG_HEADER_SIZE)?;
         if ghost_data.as_ref().is_some_and(|ghost| ghost.is_cog()) {
             cog_buffer_reader.seek(std::io::SeekFrom::Start(0))?;
 
@@ -69,7 +103,7 @@ impl GeoTiffMetadata {
                         if io_err.kind() == std::io::ErrorKind::UnexpectedEof =>
                     {
                         // If the error is an EOF, we need more data to parse the header
-                        cog_buffer_reader.increase_buffer_size(&mut file_reader)?;
+                        cog_buffer_reader.increase_buffer_size(file_reader)?;
                         log::debug!("Cog header dit not fit in default header size, retry with increased buffer size");
                     }
                     Ok(mut meta) => {
@@ -81,7 +115,7 @@ impl GeoTiffMetadata {
             }
         } else {
             file_reader.seek(std::io::SeekFrom::Start(0))?;
-            decoder::parse_geotiff_metadata(&mut file_reader)
+            decoder::parse_geotiff_metadata(file_reader)
         }
     }
 
@@ -99,6 +133,41 @@ impl GeoTiffMetadata {
         }
     }
 
+    /// Creates a categoric legend from the raster attribute table or the palette, `None` if the tiff has neither.
+    /// The colors of the attribute table rows default to the palette colors.
+    pub fn categoric_legend(&self, mapping_config: Option<MappingConfig>) -> Result<Option<Legend>> {
+        let legend = match (&self.attribute_table, &self.palette) {
+            (Some(table), palette) => table.to_legend(palette.as_deref(), mapping_config)?,
+            (None, Some(palette)) => attributetable::palette_legend(palette, mapping_config)?,
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(legend))
+    }
+
+    /// The tile matrix set of the `TILING_SCHEME` metadata, `GoogleMapsCompatible` if the tiff does not specify one.
+    /// The name is the id of a built-in tile matrix set or the JSON definition of a custom one,
+    /// unknown ids fall back to `GoogleMapsCompatible`.
+    pub fn tile_matrix_set(&self) -> Result<TileMatrixSet> {
+        let Some(name) = self.metadata_domains.get("TILING_SCHEME").and_then(|items| items.get("NAME")) else {
+            return Ok(TileMatrixSet::google_maps_compatible());
+        };
+
+        if name.trim_start().starts_with('{') {
+            #[cfg(feature = "serde")]
+            return TileMatrixSet::from_json(name);
+            #[cfg(not(feature = "serde"))]
+            return Err(Error::InvalidArgument(
+                "Reading a custom tile matrix set definition requires the serde feature".into(),
+            ));
+        }
+
+        Ok(TileMatrixSet::from_id(name).unwrap_or_else(|_| {
+            log::warn!("Unknown tile matrix set '{name}', using GoogleMapsCompatible");
+            TileMatrixSet::google_maps_compatible()
+        }))
+    }
+
     pub fn chunk_row_length(&self) -> u32 {
         match self.data_layout {
             ChunkDataLayout::Tiled(size) => size,
@@ -109,6 +178,55 @@ impl GeoTiffMetadata {
     pub fn is_tiled(&self) -> bool {
         matches!(self.data_layout, ChunkDataLayout::Tiled(_))
     }
+
+    /// True if the samples are packed with less than 8 bits per sample
+    pub fn has_sub_byte_samples(&self) -> bool {
+        self.bits_per_sample < 8
+    }
+
+    /// True if the validity of the cells of the band is stored in an internal mask or an alpha band
+    pub fn is_masked_band(&self, band_index: BandIndex) -> bool {
+        self.mask.is_some() || self.alpha_band.is_some_and(|alpha_band| alpha_band != band_index)
+    }
+
+    /// Checks if the band index is valid for this tiff
+    pub fn check_band_index(&self, band_index: BandIndex) -> Result<()> {
+        if band_index.get() > self.band_count as usize {
+            return Err(Error::InvalidArgument(format!(
+                "Band index {} out of range, the tiff contains {} band(s)",
+                band_index.get(),
+                self.band_count
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The georeference of the requested band, with the band specific scale and offset applied when available
+    pub fn band_geo_reference(&self, band_index: BandIndex) -> GeoReference {
+        let mut geo_reference = self.geo_reference.clone();
+        if let Some(band_meta) = self.band_metadata.get(band_index.get() - 1) {
+            let band_scale = match (band_meta.scale, band_meta.offset) {
+                (Some(scale), Some(offset)) => Some(RasterScale { scale, offset }),
+                (Some(scale), None) => Some(RasterScale { scale, offset: 0.0 }),
+                (None, Some(offset)) => Some(RasterScale { scale: 1.0, offset }),
+                (None, None) => None,
+            };
+
+            geo_reference.set_scale(band_scale);
+        }
+
+        geo_reference
+    }
+
+    /// Returns the chunk data as a stream that can be decoded on its own.
+    /// For JPEG compressed chunks the shared JPEG tables are prepended, other chunks are returned as is.
+    pub fn complete_chunk_data<'a>(&self, chunk_data: &'a [u8]) -> Cow<'a, [u8]> {
+        match (&self.compression, &self.jpeg_tables) {
+            (Some(Compression::Jpeg), Some(tables)) => Cow::Owned(io::jpeg_stream_with_tables(tables, chunk_data)),
+            _ => Cow::Borrowed(chunk_data),
+        }
+    }
 }
 
 #[cfg(test)]
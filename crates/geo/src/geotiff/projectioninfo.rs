@@ -1,4 +1,79 @@
-use crate::crs;
+use std::collections::HashMap;
+
+use crate::{Error, Result, crs};
+
+/// GeoKey identifiers as defined in the GeoTIFF specification
+pub(crate) mod geokey {
+    pub const MODEL_TYPE: u16 = 1024;
+    pub const RASTER_TYPE: u16 = 1025;
+    pub const GEOGRAPHIC_TYPE: u16 = 2048;
+    pub const GEOG_GEODETIC_DATUM: u16 = 2050;
+    pub const GEOG_ANGULAR_UNITS: u16 = 2054;
+    pub const GEOG_ELLIPSOID: u16 = 2056;
+    pub const GEOG_SEMI_MAJOR_AXIS: u16 = 2057;
+    pub const GEOG_SEMI_MINOR_AXIS: u16 = 2058;
+    pub const GEOG_INV_FLATTENING: u16 = 2059;
+    pub const GEOG_PRIME_MERIDIAN_LONG: u16 = 2061;
+    pub const GEOG_TOWGS84: u16 = 2062;
+    pub const PROJECTED_TYPE: u16 = 3072;
+    pub const PROJECTION: u16 = 3074;
+    pub const PROJ_COORD_TRANS: u16 = 3075;
+    pub const PROJ_LINEAR_UNITS: u16 = 3076;
+    pub const PROJ_LINEAR_UNIT_SIZE: u16 = 3077;
+    pub const PROJ_STD_PARALLEL1: u16 = 3078;
+    pub const PROJ_STD_PARALLEL2: u16 = 3079;
+    pub const PROJ_NAT_ORIGIN_LONG: u16 = 3080;
+    pub const PROJ_NAT_ORIGIN_LAT: u16 = 3081;
+    pub const PROJ_FALSE_EASTING: u16 = 3082;
+    pub const PROJ_FALSE_NORTHING: u16 = 3083;
+    pub const PROJ_FALSE_ORIGIN_LONG: u16 = 3084;
+    pub const PROJ_FALSE_ORIGIN_LAT: u16 = 3085;
+    pub const PROJ_FALSE_ORIGIN_EASTING: u16 = 3086;
+    pub const PROJ_FALSE_ORIGIN_NORTHING: u16 = 3087;
+    pub const PROJ_CENTER_LONG: u16 = 3088;
+    pub const PROJ_CENTER_LAT: u16 = 3089;
+    pub const PROJ_SCALE_AT_NAT_ORIGIN: u16 = 3092;
+    pub const PROJ_STRAIGHT_VERT_POLE_LONG: u16 = 3095;
+
+    pub const RASTER_PIXEL_IS_AREA: u16 = 1;
+    pub const RASTER_PIXEL_IS_POINT: u16 = 2;
+    /// Value of a GeoKey that indicates the parameters are provided by the other keys
+    pub const USER_DEFINED: u16 = 32767;
+}
+
+/// Locations of the GeoKey values that are not stored in the key directory itself
+const GEO_KEY_DIRECTORY_TAG: u16 = 34735;
+const GEO_DOUBLE_PARAMS_TAG: u16 = 34736;
+const GEO_ASCII_PARAMS_TAG: u16 = 34737;
+
+/// Geographic coordinate systems (and their datum) that can be used as the base of user-defined projections
+const GEOGRAPHIC_CRS_DEFINITIONS: &[(u16, u16, &str)] = &[
+    (4326, 6326, "+datum=WGS84"),
+    (4258, 6258, "+ellps=GRS80 +towgs84=0,0,0,0,0,0,0"),
+    (4283, 6283, "+ellps=GRS80 +towgs84=0,0,0,0,0,0,0"),
+    (4269, 6269, "+datum=NAD83"),
+    (4267, 6267, "+datum=NAD27"),
+    (4277, 6277, "+datum=OSGB36"),
+    (4230, 6230, "+ellps=intl +towgs84=-87,-98,-121,0,0,0,0"),
+    (
+        4313,
+        6313,
+        "+ellps=intl +towgs84=-106.8686,52.2978,-103.7239,0.3366,-0.457,1.8422,-1.2747",
+    ),
+    (4322, 6322, "+ellps=WGS72 +towgs84=0,0,4.5,0,0,0.554,0.2263"),
+];
+
+const ELLIPSOID_NAMES: &[(u16, &str)] = &[
+    (7001, "airy"),
+    (7004, "bessel"),
+    (7008, "clrk66"),
+    (7012, "clrk80"),
+    (7019, "GRS80"),
+    (7022, "intl"),
+    (7024, "krass"),
+    (7030, "WGS84"),
+    (7043, "WGS72"),
+];
 
 #[derive(Debug, Clone, Default)]
 pub enum ModelType {
@@ -8,14 +83,92 @@ pub enum ModelType {
     Geocentric,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RasterType {
+    #[default]
+    PixelIsArea,
+    /// The tie points refer to the center of the pixels instead of the upper left corner
+    PixelIsPoint,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum GeoKeyValue {
+    Short(u16),
+    Double(Vec<f64>),
+    Ascii(String),
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ProjectionInfo {
     pub model_type: ModelType,
+    pub raster_type: RasterType,
     pub projected_epsg: Option<crs::Epsg>,
     pub geographic_epsg: Option<crs::Epsg>,
+    keys: HashMap<u16, GeoKeyValue>,
 }
 
 impl ProjectionInfo {
+    /// Parses the GeoKey directory, the values of the keys are stored inline or in the double and ascii parameter tags
+    pub fn from_geo_keys(key_dir: &[u16], double_params: &[f64], ascii_params: &str) -> Result<Option<Self>> {
+        if key_dir.len() < 4 {
+            return Ok(None);
+        }
+
+        if key_dir[0] != 1 {
+            return Err(Error::Runtime(format!("Unexpected key directory version: {}", key_dir[0])));
+        }
+
+        let mut proj_info = ProjectionInfo::default();
+        for key in key_dir[4..].as_chunks::<4>().0 {
+            let [key_id, location, count, value] = *key;
+            let range = value as usize..value as usize + count as usize;
+            let key_value = match location {
+                0 => GeoKeyValue::Short(value),
+                GEO_KEY_DIRECTORY_TAG => GeoKeyValue::Short(
+                    *key_dir
+                        .get(value as usize)
+                        .ok_or_else(|| Error::Runtime(format!("Invalid value location of GeoKey {key_id}")))?,
+                ),
+                GEO_DOUBLE_PARAMS_TAG => GeoKeyValue::Double(
+                    double_params
+                        .get(range)
+                        .ok_or_else(|| Error::Runtime(format!("Invalid value location of GeoKey {key_id}")))?
+                        .to_vec(),
+                ),
+                GEO_ASCII_PARAMS_TAG => GeoKeyValue::Ascii(
+                    ascii_params
+                        .get(range)
+                        .unwrap_or_default()
+                        .trim_end_matches(['|', '\0'])
+                        .to_string(),
+                ),
+                _ => {
+                    return Err(Error::Runtime(format!("Unsupported GeoKey value location: {location}")));
+                }
+            };
+
+            proj_info.keys.insert(key_id, key_value);
+        }
+
+        proj_info.model_type = match proj_info.short(geokey::MODEL_TYPE) {
+            None | Some(1) => ModelType::Projected,
+            Some(2) => ModelType::Geographic,
+            Some(3) => ModelType::Geocentric,
+            Some(model_type) => {
+                return Err(Error::Runtime(format!("Unsupported model type: {model_type}")));
+            }
+        };
+
+        if proj_info.short(geokey::RASTER_TYPE) == Some(geokey::RASTER_PIXEL_IS_POINT) {
+            proj_info.raster_type = RasterType::PixelIsPoint;
+        }
+
+        proj_info.geographic_epsg = proj_info.epsg_code(geokey::GEOGRAPHIC_TYPE).map(crs::Epsg::from);
+        proj_info.projected_epsg = proj_info.epsg_code(geokey::PROJECTED_TYPE).map(crs::Epsg::from);
+
+        Ok(Some(proj_info))
+    }
+
     pub fn epsg(&self) -> Option<crs::Epsg> {
         match self.model_type {
             ModelType::Projected => self.projected_epsg,
@@ -23,4 +176,363 @@ impl ProjectionInfo {
             ModelType::Geocentric => None,
         }
     }
+
+    /// The coordinate reference system definition: the EPSG code when available, otherwise a PROJ string
+    /// of the user-defined coordinate system. None if the coordinate system is missing or not supported.
+    pub fn projection(&self) -> Option<String> {
+        if let Some(epsg) = self.epsg() {
+            return Some(epsg.to_string());
+        }
+
+        match self.model_type {
+            ModelType::Projected => self.projected_proj_string(),
+            ModelType::Geographic => Some(format!("+proj=longlat {} +no_defs", self.geographic_proj_params()?)),
+            ModelType::Geocentric => None,
+        }
+    }
+
+    fn short(&self, key: u16) -> Option<u16> {
+        match self.keys.get(&key) {
+            Some(GeoKeyValue::Short(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn doubles(&self, key: u16) -> Option<&[f64]> {
+        match self.keys.get(&key) {
+            Some(GeoKeyValue::Double(values)) => Some(values),
+            _ => None,
+        }
+    }
+
+    fn double(&self, key: u16) -> Option<f64> {
+        self.doubles(key).and_then(|values| values.first().copied())
+    }
+
+    /// The code of an EPSG key, None for undefined and user-defined values
+    fn epsg_code(&self, key: u16) -> Option<u16> {
+        self.short(key).filter(|code| *code != 0 && *code != geokey::USER_DEFINED)
+    }
+
+    /// The first available angle of the keys converted to degrees
+    fn angle(&self, keys: &[u16]) -> Option<f64> {
+        const ANGULAR_UNIT_RADIAN: u16 = 9101;
+        const ANGULAR_UNIT_GRAD: u16 = 9105;
+
+        let value = keys.iter().find_map(|key| self.double(*key))?;
+        Some(match self.short(geokey::GEOG_ANGULAR_UNITS) {
+            Some(ANGULAR_UNIT_RADIAN) => value.to_degrees(),
+            Some(ANGULAR_UNIT_GRAD) => value * 0.9,
+            _ => value,
+        })
+    }
+
+    /// The ellipsoid, datum shift and prime meridian of the geographic coordinate system as PROJ parameters
+    fn geographic_proj_params(&self) -> Option<String> {
+        // Unknown geographic coordinate systems are built from the datum and ellipsoid keys
+        let known_definition = self
+            .epsg_code(geokey::GEOGRAPHIC_TYPE)
+            .and_then(|code| GEOGRAPHIC_CRS_DEFINITIONS.iter().find(|(geog_code, _, _)| *geog_code == code))
+            .or_else(|| {
+                self.epsg_code(geokey::GEOG_GEODETIC_DATUM)
+                    .and_then(|code| GEOGRAPHIC_CRS_DEFINITIONS.iter().find(|(_, datum_code, _)| *datum_code == code))
+            });
+
+        let mut params = match known_definition {
+            Some((_, _, definition)) => definition.to_string(),
+            None => self.ellipsoid_proj_params()?,
+        };
+
+        if known_definition.is_none()
+            && let Some(towgs84) = self.doubles(geokey::GEOG_TOWGS84)
+        {
+            let values: Vec<String> = towgs84.iter().map(|v| v.to_string()).collect();
+            params.push_str(&format!(" +towgs84={}", values.join(",")));
+        }
+
+        if let Some(prime_meridian) = self.angle(&[geokey::GEOG_PRIME_MERIDIAN_LONG]).filter(|pm| *pm != 0.0) {
+            params.push_str(&format!(" +pm={prime_meridian}"));
+        }
+
+        Some(params)
+    }
+
+    fn ellipsoid_proj_params(&self) -> Option<String> {
+        if let Some(code) = self.epsg_code(geokey::GEOG_ELLIPSOID) {
+            let (_, name) = ELLIPSOID_NAMES.iter().find(|(ellipsoid_code, _)| *ellipsoid_code == code)?;
+            return Some(format!("+ellps={name}"));
+        }
+
+        let semi_major = self.double(geokey::GEOG_SEMI_MAJOR_AXIS)?;
+        Some(
+            match (self.double(geokey::GEOG_SEMI_MINOR_AXIS), self.double(geokey::GEOG_INV_FLATTENING)) {
+                (Some(semi_minor), _) => format!("+a={semi_major} +b={semi_minor}"),
+                (None, Some(inv_flattening)) if inv_flattening != 0.0 => format!("+a={semi_major} +rf={inv_flattening}"),
+                _ => format!("+a={semi_major} +b={semi_major}"),
+            },
+        )
+    }
+
+    /// The PROJ units parameter and the size of the unit in meters
+    fn linear_units(&self) -> Option<(String, f64)> {
+        const LINEAR_UNIT_METER: u16 = 9001;
+        const LINEAR_UNIT_FOOT: u16 = 9002;
+        const LINEAR_UNIT_US_SURVEY_FOOT: u16 = 9003;
+
+        match self.short(geokey::PROJ_LINEAR_UNITS) {
+            None | Some(LINEAR_UNIT_METER) => Some(("+units=m".to_string(), 1.0)),
+            Some(LINEAR_UNIT_FOOT) => Some(("+units=ft".to_string(), 0.3048)),
+            Some(LINEAR_UNIT_US_SURVEY_FOOT) => Some(("+units=us-ft".to_string(), 1200.0 / 3937.0)),
+            Some(geokey::USER_DEFINED) => {
+                let unit_size = self.double(geokey::PROJ_LINEAR_UNIT_SIZE)?;
+                Some((format!("+to_meter={unit_size}"), unit_size))
+            }
+            Some(_) => None,
+        }
+    }
+
+    fn projected_proj_string(&self) -> Option<String> {
+        const UTM_NORTH_ZONES: std::ops::RangeInclusive<u16> = 16001..=16060;
+        const UTM_SOUTH_ZONES: std::ops::RangeInclusive<u16> = 16101..=16160;
+
+        let geographic = self.geographic_proj_params()?;
+        let (units, to_meter) = self.linear_units()?;
+        let projection = match self.epsg_code(geokey::PROJECTION) {
+            Some(code) if UTM_NORTH_ZONES.contains(&code) => format!("+proj=utm +zone={}", code - 16000),
+            Some(code) if UTM_SOUTH_ZONES.contains(&code) => format!("+proj=utm +zone={} +south", code - 16100),
+            _ => self.coordinate_transformation_proj_params(to_meter)?,
+        };
+
+        Some(format!("{projection} {geographic} {units} +no_defs"))
+    }
+
+    /// The PROJ parameters of the user-defined coordinate transformation, the false easting and northing are converted to meters
+    fn coordinate_transformation_proj_params(&self, to_meter: f64) -> Option<String> {
+        const CT_TRANSVERSE_MERCATOR: u16 = 1;
+        const CT_MERCATOR: u16 = 7;
+        const CT_LAMBERT_CONF_CONIC_2SP: u16 = 8;
+        const CT_LAMBERT_CONF_CONIC_1SP: u16 = 9;
+        const CT_LAMBERT_AZIM_EQUAL_AREA: u16 = 10;
+        const CT_ALBERS_EQUAL_AREA: u16 = 11;
+        const CT_POLAR_STEREOGRAPHIC: u16 = 15;
+        const CT_OBLIQUE_STEREOGRAPHIC: u16 = 16;
+        const CT_EQUIRECTANGULAR: u16 = 17;
+
+        let angle = |keys: &[u16]| self.angle(keys).unwrap_or(0.0);
+        let linear = |keys: &[u16]| keys.iter().find_map(|key| self.double(*key)).unwrap_or(0.0) * to_meter;
+        let scale = self.double(geokey::PROJ_SCALE_AT_NAT_ORIGIN).unwrap_or(1.0);
+
+        let nat_origin_lat = angle(&[geokey::PROJ_NAT_ORIGIN_LAT, geokey::PROJ_FALSE_ORIGIN_LAT, geokey::PROJ_CENTER_LAT]);
+        let nat_origin_long = angle(&[
+            geokey::PROJ_NAT_ORIGIN_LONG,
+            geokey::PROJ_FALSE_ORIGIN_LONG,
+            geokey::PROJ_CENTER_LONG,
+        ]);
+        let false_easting = linear(&[geokey::PROJ_FALSE_EASTING, geokey::PROJ_FALSE_ORIGIN_EASTING]);
+        let false_northing = linear(&[geokey::PROJ_FALSE_NORTHING, geokey::PROJ_FALSE_ORIGIN_NORTHING]);
+        let false_offsets = format!("+x_0={false_easting} +y_0={false_northing}");
+
+        Some(match self.short(geokey::PROJ_COORD_TRANS)? {
+            CT_TRANSVERSE_MERCATOR => format!("+proj=tmerc +lat_0={nat_origin_lat} +lon_0={nat_origin_long} +k={scale} {false_offsets}"),
+            CT_MERCATOR => match self.angle(&[geokey::PROJ_STD_PARALLEL1]) {
+                Some(lat_ts) => format!("+proj=merc +lat_ts={lat_ts} +lon_0={nat_origin_long} {false_offsets}"),
+                None => format!("+proj=merc +lon_0={nat_origin_long} +k={scale} {false_offsets}"),
+            },
+            CT_LAMBERT_CONF_CONIC_2SP => {
+                let lat_0 = angle(&[geokey::PROJ_FALSE_ORIGIN_LAT, geokey::PROJ_NAT_ORIGIN_LAT]);
+                let lon_0 = angle(&[geokey::PROJ_FALSE_ORIGIN_LONG, geokey::PROJ_NAT_ORIGIN_LONG]);
+                let x_0 = linear(&[geokey::PROJ_FALSE_ORIGIN_EASTING, geokey::PROJ_FALSE_EASTING]);
+                let y_0 = linear(&[geokey::PROJ_FALSE_ORIGIN_NORTHING, geokey::PROJ_FALSE_NORTHING]);
+                format!(
+                    "+proj=lcc +lat_1={} +lat_2={} +lat_0={lat_0} +lon_0={lon_0} +x_0={x_0} +y_0={y_0}",
+                    angle(&[geokey::PROJ_STD_PARALLEL1]),
+                    angle(&[geokey::PROJ_STD_PARALLEL2]),
+                )
+            }
+            CT_LAMBERT_CONF_CONIC_1SP => {
+                format!("+proj=lcc +lat_1={nat_origin_lat} +lat_0={nat_origin_lat} +lon_0={nat_origin_long} +k_0={scale} {false_offsets}")
+            }
+            CT_LAMBERT_AZIM_EQUAL_AREA => {
+                let lat_0 = angle(&[geokey::PROJ_CENTER_LAT, geokey::PROJ_NAT_ORIGIN_LAT]);
+                let lon_0 = angle(&[geokey::PROJ_CENTER_LONG, geokey::PROJ_NAT_ORIGIN_LONG]);
+                format!("+proj=laea +lat_0={lat_0} +lon_0={lon_0} {false_offsets}")
+            }
+            CT_ALBERS_EQUAL_AREA => format!(
+                "+proj=aea +lat_1={} +lat_2={} +lat_0={nat_origin_lat} +lon_0={nat_origin_long} {false_offsets}",
+                angle(&[geokey::PROJ_STD_PARALLEL1]),
+                angle(&[geokey::PROJ_STD_PARALLEL2]),
+            ),
+            CT_POLAR_STEREOGRAPHIC => {
+                let lat_0 = if nat_origin_lat < 0.0 { -90 } else { 90 };
+                let lon_0 = angle(&[geokey::PROJ_STRAIGHT_VERT_POLE_LONG, geokey::PROJ_NAT_ORIGIN_LONG]);
+                format!("+proj=stere +lat_0={lat_0} +lat_ts={nat_origin_lat} +lon_0={lon_0} +k={scale} {false_offsets}")
+            }
+            CT_OBLIQUE_STEREOGRAPHIC => {
+                format!("+proj=sterea +lat_0={nat_origin_lat} +lon_0={nat_origin_long} +k={scale} {false_offsets}")
+            }
+            CT_EQUIRECTANGULAR => {
+                let lat_0 = angle(&[geokey::PROJ_CENTER_LAT, geokey::PROJ_NAT_ORIGIN_LAT]);
+                let lon_0 = angle(&[geokey::PROJ_CENTER_LONG, geokey::PROJ_NAT_ORIGIN_LONG]);
+                format!(
+                    "+proj=eqc +lat_ts={} +lat_0={lat_0} +lon_0={lon_0} {false_offsets}",
+                    angle(&[geokey::PROJ_STD_PARALLEL1])
+                )
+            }
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a key directory from (key, location, count, value) entries
+    fn key_directory(keys: &[[u16; 4]]) -> Vec<u16> {
+        let mut key_dir = vec![1, 1, 0, keys.len() as u16];
+        key_dir.extend(keys.iter().flatten());
+        key_dir
+    }
+
+    #[test]
+    fn epsg_projection() -> Result<()> {
+        let key_dir = key_directory(&[
+            [geokey::MODEL_TYPE, 0, 1, 1],
+            [geokey::RASTER_TYPE, 0, 1, geokey::RASTER_PIXEL_IS_AREA],
+            [geokey::PROJECTED_TYPE, 0, 1, 31370],
+        ]);
+
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[], "")?.unwrap();
+        assert_eq!(proj_info.epsg(), Some(crs::epsg::BELGIAN_LAMBERT72));
+        assert_eq!(proj_info.projection().as_deref(), Some("EPSG:31370"));
+        assert_eq!(proj_info.raster_type, RasterType::PixelIsArea);
+
+        Ok(())
+    }
+
+    #[test]
+    fn user_defined_lambert() -> Result<()> {
+        let key_dir = key_directory(&[
+            [geokey::MODEL_TYPE, 0, 1, 1],
+            [geokey::RASTER_TYPE, 0, 1, geokey::RASTER_PIXEL_IS_POINT],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, 4258],
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::PROJ_COORD_TRANS, 0, 1, 8],
+            [geokey::PROJ_LINEAR_UNITS, 0, 1, 9001],
+            [geokey::PROJ_STD_PARALLEL1, GEO_DOUBLE_PARAMS_TAG, 1, 0],
+            [geokey::PROJ_STD_PARALLEL2, GEO_DOUBLE_PARAMS_TAG, 1, 1],
+            [geokey::PROJ_FALSE_ORIGIN_LAT, GEO_DOUBLE_PARAMS_TAG, 1, 2],
+            [geokey::PROJ_FALSE_ORIGIN_LONG, GEO_DOUBLE_PARAMS_TAG, 1, 3],
+            [geokey::PROJ_FALSE_ORIGIN_EASTING, GEO_DOUBLE_PARAMS_TAG, 1, 4],
+            [geokey::PROJ_FALSE_ORIGIN_NORTHING, GEO_DOUBLE_PARAMS_TAG, 1, 5],
+        ]);
+
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[49.8333, 51.1667, 50.797815, 4.359215, 649328.0, 665262.0], "")?.unwrap();
+        assert_eq!(proj_info.epsg(), None);
+        assert_eq!(proj_info.raster_type, RasterType::PixelIsPoint);
+        assert_eq!(
+            proj_info.projection().as_deref(),
+            Some(
+                "+proj=lcc +lat_1=49.8333 +lat_2=51.1667 +lat_0=50.797815 +lon_0=4.359215 +x_0=649328 +y_0=665262 +ellps=GRS80 +towgs84=0,0,0,0,0,0,0 +units=m +no_defs"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn user_defined_transverse_mercator_in_feet() -> Result<()> {
+        let key_dir = key_directory(&[
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOG_ELLIPSOID, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOG_SEMI_MAJOR_AXIS, GEO_DOUBLE_PARAMS_TAG, 1, 0],
+            [geokey::GEOG_INV_FLATTENING, GEO_DOUBLE_PARAMS_TAG, 1, 1],
+            [geokey::PROJ_COORD_TRANS, 0, 1, 1],
+            [geokey::PROJ_LINEAR_UNITS, 0, 1, 9002],
+            [geokey::PROJ_NAT_ORIGIN_LAT, GEO_DOUBLE_PARAMS_TAG, 1, 2],
+            [geokey::PROJ_NAT_ORIGIN_LONG, GEO_DOUBLE_PARAMS_TAG, 1, 3],
+            [geokey::PROJ_SCALE_AT_NAT_ORIGIN, GEO_DOUBLE_PARAMS_TAG, 1, 4],
+            [geokey::PROJ_FALSE_EASTING, GEO_DOUBLE_PARAMS_TAG, 1, 5],
+        ]);
+
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[6378137.0, 298.257223563, 0.0, 3.0, 0.9996, 1000.0], "")?.unwrap();
+        assert_eq!(
+            proj_info.projection().as_deref(),
+            Some("+proj=tmerc +lat_0=0 +lon_0=3 +k=0.9996 +x_0=304.8 +y_0=0 +a=6378137 +rf=298.257223563 +units=ft +no_defs")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn utm_projection_and_user_defined_geographic() -> Result<()> {
+        let key_dir = key_directory(&[
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::PROJECTION, 0, 1, 16131],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOG_GEODETIC_DATUM, 0, 1, 6326],
+        ]);
+
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[], "")?.unwrap();
+        assert_eq!(
+            proj_info.projection().as_deref(),
+            Some("+proj=utm +zone=31 +south +datum=WGS84 +units=m +no_defs")
+        );
+
+        let key_dir = key_directory(&[
+            [geokey::MODEL_TYPE, 0, 1, 2],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOG_ELLIPSOID, 0, 1, 7022],
+        ]);
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[], "")?.unwrap();
+        assert_eq!(proj_info.projection().as_deref(), Some("+proj=longlat +ellps=intl +no_defs"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_geographic_type_with_ellipsoid() -> Result<()> {
+        // Amersfoort is not a known geographic coordinate system, the ellipsoid and datum shift keys describe it
+        let key_dir = key_directory(&[
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::PROJECTION, 0, 1, 16031],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, 4289],
+            [geokey::GEOG_ELLIPSOID, 0, 1, 7004],
+            [geokey::GEOG_TOWGS84, GEO_DOUBLE_PARAMS_TAG, 3, 0],
+        ]);
+
+        let proj_info = ProjectionInfo::from_geo_keys(&key_dir, &[565.0, 50.0, 465.0], "")?.unwrap();
+        assert_eq!(
+            proj_info.projection().as_deref(),
+            Some("+proj=utm +zone=31 +ellps=bessel +towgs84=565,50,465 +units=m +no_defs")
+        );
+
+        // Without datum or ellipsoid keys the coordinate system remains unknown
+        let key_dir = key_directory(&[
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::PROJECTION, 0, 1, 16031],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, 4289],
+        ]);
+        assert_eq!(ProjectionInfo::from_geo_keys(&key_dir, &[], "")?.unwrap().projection(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_definitions() -> Result<()> {
+        // Unknown coordinate transformation
+        let key_dir = key_directory(&[
+            [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+            [geokey::GEOGRAPHIC_TYPE, 0, 1, 4326],
+            [geokey::PROJ_COORD_TRANS, 0, 1, 25],
+        ]);
+        assert_eq!(ProjectionInfo::from_geo_keys(&key_dir, &[], "")?.unwrap().projection(), None);
+
+        // Double parameter outside of the double parameters tag
+        let key_dir = key_directory(&[[geokey::PROJ_STD_PARALLEL1, GEO_DOUBLE_PARAMS_TAG, 1, 3]]);
+        assert!(ProjectionInfo::from_geo_keys(&key_dir, &[1.0], "").is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,335 @@
+//! Block cached reading of tiff data from sources where every read results in an expensive request (e.g. HTTP range requests).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+};
+
+use crate::{Error, Result};
+
+/// Default size of the cached blocks, matches the default COG header size so the header is fetched with a single request
+pub const DEFAULT_BLOCK_SIZE: u64 = 16 * 1024;
+/// Default maximum number of cached blocks (64 MiB with the default block size)
+pub const DEFAULT_MAX_CACHED_BLOCKS: usize = 4096;
+
+/// Missing blocks that are separated by at most this number of cached blocks are fetched with a single request
+const MAX_COALESCE_GAP_BLOCKS: u64 = 2;
+
+/// A source of data that can only be accessed by fetching byte ranges
+pub trait RangeSource {
+    /// The total size of the data in bytes
+    fn size(&mut self) -> Result<u64>;
+
+    /// Fetches the bytes in the requested range, the range is guaranteed to be within the size of the source
+    fn fetch_range(&mut self, range: Range<u64>) -> Result<Vec<u8>>;
+}
+
+struct CachedBlock {
+    data: Vec<u8>,
+    last_used: u64,
+}
+
+/// Provides `Read` and `Seek` access to a `RangeSource` so it can be used for parsing the tiff metadata and reading chunks.
+/// The data is fetched in blocks which are cached, consecutive missing blocks are fetched with a single range request.
+/// The least recently used blocks are evicted, so frequently read blocks like the tiff header stay cached during long reads.
+/// Use `prefetch` to fetch the data of multiple chunks (see `TiffChunkLocation::range_to_fetch`) with as few requests as possible.
+pub struct RangeReader<S: RangeSource> {
+    source: S,
+    size: u64,
+    pos: u64,
+    block_size: u64,
+    max_cached_blocks: usize,
+    blocks: HashMap<u64, CachedBlock>,
+    usage_order: BTreeMap<u64, u64>,
+    usage_counter: u64,
+}
+
+impl<S: RangeSource> RangeReader<S> {
+    pub fn new(source: S) -> Result<Self> {
+        Self::with_block_size(source, DEFAULT_BLOCK_SIZE, DEFAULT_MAX_CACHED_BLOCKS)
+    }
+
+    pub fn with_block_size(mut source: S, block_size: u64, max_cached_blocks: usize) -> Result<Self> {
+        if block_size == 0 || max_cached_blocks == 0 {
+            return Err(Error::InvalidArgument("Block size and cache size must be larger than 0".into()));
+        }
+
+        Ok(Self {
+            size: source.size()?,
+            source,
+            pos: 0,
+            block_size,
+            max_cached_blocks,
+            blocks: HashMap::new(),
+            usage_order: BTreeMap::new(),
+            usage_counter: 0,
+        })
+    }
+
+    /// The total size of the source data in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Fetches the blocks of all the ranges that are not cached yet, using as few requests as possible
+    pub fn prefetch(&mut self, ranges: &[Range<u64>]) -> Result<()> {
+        let mut block_indices: Vec<u64> = ranges
+            .iter()
+            .filter(|range| !range.is_empty())
+            .flat_map(|range| self.block_range(range.start, range.end))
+            .filter(|block_index| !self.blocks.contains_key(block_index))
+            .collect();
+        block_indices.sort_unstable();
+        block_indices.dedup();
+
+        // Don't prefetch more than fits in the cache, the blocks would be evicted before they are read
+        block_indices.truncate(self.max_cached_blocks);
+        self.fetch_blocks(&block_indices)?;
+        self.evict_blocks();
+
+        Ok(())
+    }
+
+    fn block_range(&self, start: u64, end: u64) -> Range<u64> {
+        let end = end.min(self.size);
+        if start >= end {
+            return 0..0;
+        }
+
+        start / self.block_size..(end - 1) / self.block_size + 1
+    }
+
+    /// Fetches the sorted list of blocks, runs of blocks that are close together are fetched with a single request
+    fn fetch_blocks(&mut self, block_indices: &[u64]) -> Result<()> {
+        let mut run_start = 0;
+        for i in 1..=block_indices.len() {
+            if i < block_indices.len() && block_indices[i] - block_indices[i - 1] <= MAX_COALESCE_GAP_BLOCKS + 1 {
+                continue;
+            }
+
+            self.fetch_block_run(block_indices[run_start]..block_indices[i - 1] + 1)?;
+            run_start = i;
+        }
+
+        Ok(())
+    }
+
+    fn fetch_block_run(&mut self, blocks: Range<u64>) -> Result<()> {
+        let range = blocks.start * self.block_size..(blocks.end * self.block_size).min(self.size);
+        let data = self.source.fetch_range(range.clone())?;
+        if data.len() as u64 != range.end - range.start {
+            return Err(Error::Runtime(format!(
+                "Range request {}-{} returned {} bytes",
+                range.start,
+                range.end,
+                data.len()
+            )));
+        }
+
+        for (block_index, block_data) in blocks.zip(data.chunks(self.block_size as usize)) {
+            self.usage_counter += 1;
+            let block = CachedBlock {
+                data: block_data.to_vec(),
+                last_used: self.usage_counter,
+            };
+
+            if let Some(previous) = self.blocks.insert(block_index, block) {
+                self.usage_order.remove(&previous.last_used);
+            }
+            self.usage_order.insert(self.usage_counter, block_index);
+        }
+
+        Ok(())
+    }
+
+    /// Marks the block as most recently used and returns its data
+    fn use_block(&mut self, block_index: u64) -> &[u8] {
+        self.usage_counter += 1;
+        let block = self.blocks.get_mut(&block_index).expect("Block should be cached");
+        self.usage_order.remove(&block.last_used);
+        block.last_used = self.usage_counter;
+        self.usage_order.insert(self.usage_counter, block_index);
+        &block.data
+    }
+
+    /// Removes the least recently used blocks from the cache until it fits the maximum size
+    fn evict_blocks(&mut self) {
+        while self.blocks.len() > self.max_cached_blocks {
+            let Some((_, evicted)) = self.usage_order.pop_first() else {
+                break;
+            };
+
+            self.blocks.remove(&evicted);
+        }
+    }
+}
+
+impl<S: RangeSource> Read for RangeReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let end = (self.pos + buf.len() as u64).min(self.size);
+        if self.pos >= end {
+            return Ok(0);
+        }
+
+        // Reads larger than the cache are served in multiple parts
+        let end = end.min(self.pos + self.max_cached_blocks as u64 * self.block_size - self.pos % self.block_size);
+        let block_range = self.block_range(self.pos, end);
+        let missing_blocks: Vec<u64> = block_range.clone().filter(|index| !self.blocks.contains_key(index)).collect();
+        self.fetch_blocks(&missing_blocks).map_err(std::io::Error::other)?;
+
+        let pos = self.pos;
+        let block_size = self.block_size;
+        let mut bytes_read = 0;
+        for block_index in block_range {
+            let block = self.use_block(block_index);
+            let block_start = block_index * block_size;
+            let from = (pos + bytes_read as u64 - block_start) as usize;
+            let to = ((end - block_start) as usize).min(block.len());
+            buf[bytes_read..bytes_read + (to - from)].copy_from_slice(&block[from..to]);
+            bytes_read += to - from;
+        }
+
+        self.pos += bytes_read as u64;
+        self.evict_blocks();
+        Ok(bytes_read)
+    }
+}
+
+impl<S: RangeSource> Seek for RangeReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.size as i64 + offset,
+            SeekFrom::Current(offset) => self.pos as i64 + offset,
+        };
+
+        if new_pos < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Seek before start of the data",
+            ));
+        }
+
+        self.pos = new_pos as u64;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// In memory source that keeps track of the requested ranges
+    struct MemorySource {
+        data: Vec<u8>,
+        requests: Vec<Range<u64>>,
+    }
+
+    impl RangeSource for MemorySource {
+        fn size(&mut self) -> Result<u64> {
+            Ok(self.data.len() as u64)
+        }
+
+        fn fetch_range(&mut self, range: Range<u64>) -> Result<Vec<u8>> {
+            self.requests.push(range.clone());
+            Ok(self.data[range.start as usize..range.end as usize].to_vec())
+        }
+    }
+
+    fn reader(size: usize, block_size: u64, max_cached_blocks: usize) -> RangeReader<MemorySource> {
+        let source = MemorySource {
+            data: (0..size).map(|i| (i % 251) as u8).collect(),
+            requests: Vec::new(),
+        };
+
+        RangeReader::with_block_size(source, block_size, max_cached_blocks).unwrap()
+    }
+
+    #[test]
+    fn read_across_blocks() -> Result<()> {
+        let mut reader = reader(1000, 100, 16);
+
+        let mut buf = vec![0; 250];
+        reader.seek(SeekFrom::Start(50))?;
+        reader.read_exact(&mut buf)?;
+        assert_eq!(buf, reader.source().data[50..300]);
+        assert_eq!(reader.source().requests, vec![0..300]);
+
+        // Cached blocks are not fetched again
+        reader.seek(SeekFrom::Start(120))?;
+        reader.read_exact(&mut buf[..100])?;
+        assert_eq!(buf[..100], reader.source().data[120..220]);
+        assert_eq!(reader.source().requests.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_until_end() -> Result<()> {
+        let mut reader = reader(1000, 128, 16);
+
+        let mut buf = Vec::new();
+        reader.seek(SeekFrom::End(-10))?;
+        reader.read_to_end(&mut buf)?;
+        assert_eq!(buf, reader.source().data[990..]);
+
+        reader.seek(SeekFrom::Start(2000))?;
+        assert_eq!(reader.read(&mut [0; 10])?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prefetch_coalesces_requests() -> Result<()> {
+        let mut reader = reader(10000, 100, 64);
+
+        reader.prefetch(&[0..10, 150..180, 420..430, 5000..5100, 5150..5160])?;
+        assert_eq!(reader.source().requests, vec![0..500, 5000..5200]);
+
+        let mut buf = vec![0; 30];
+        reader.seek(SeekFrom::Start(150))?;
+        reader.read_exact(&mut buf)?;
+        assert_eq!(buf, reader.source().data[150..180]);
+        assert_eq!(reader.source().requests.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_is_bounded() -> Result<()> {
+        let mut reader = reader(10000, 100, 4);
+
+        let mut buf = vec![0; 1000];
+        reader.read_exact(&mut buf)?;
+        assert_eq!(buf, reader.source().data[..1000]);
+        assert!(reader.blocks.len() <= 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recently_used_blocks_stay_cached() -> Result<()> {
+        let mut reader = reader(10000, 100, 4);
+        let mut buf = vec![0; 100];
+
+        // Keep reading the header block in between the reads of other blocks
+        for offset in [0, 100, 200, 300, 0, 400, 500, 0] {
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(&mut buf)?;
+            assert_eq!(buf, reader.source().data[offset as usize..offset as usize + 100]);
+        }
+
+        assert_eq!(
+            reader.source().requests,
+            vec![0..100, 100..200, 200..300, 300..400, 400..500, 500..600],
+            "The header block should not be evicted"
+        );
+
+        Ok(())
+    }
+}
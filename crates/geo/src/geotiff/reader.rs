@@ -1,8 +1,10 @@
 use crate::geotiff::utils;
+#[cfg(feature = "http")]
+use crate::geotiff::{HttpRangeSource, RangeReader};
 use crate::raster::intersection::{CutOut, intersect_georeference};
 use crate::{
     ArrayInterop, ArrayMetadata, ArrayNum, Cell, Columns, DenseArray, GeoReference, RasterSize, Rows,
-    geotiff::{BandIndex, FIRST_BAND, GeoTiffMetadata, io},
+    geotiff::{BandIndex, FIRST_BAND, GeoTiffMetadata, Interleave, io},
 };
 
 use inf::{allocate, cast};
@@ -10,7 +12,13 @@ use num::NumCast;
 use simd_macro::simd_bounds;
 
 use crate::{Error, Result, raster};
-use std::{fs::File, mem::MaybeUninit, ops::Range, path::Path};
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    mem::MaybeUninit,
+    ops::Range,
+    path::Path,
+};
 
 #[cfg(feature = "simd")]
 const LANES: usize = inf::simd::LANES;
@@ -44,103 +52,139 @@ pub struct TiffOverview {
     pub chunk_locations: Vec<TiffChunkLocation>,
 }
 
+impl TiffOverview {
+    /// The chunks that contain the data of the requested band.
+    ///
+    /// For band (and GDAL tile) interleaved tiffs the chunk locations are always logically organized in band order:
+    /// `[Band0_Chunk0, Band0_Chunk1, ..., Band1_Chunk0, Band1_Chunk1, ...]`, the interleave mode only affects the physical
+    /// location of the chunks in the file.
+    /// For pixel interleaved tiffs every chunk contains the values of all bands, so all chunks are needed.
+    pub fn band_chunk_locations(&self, meta: &GeoTiffMetadata, band_index: BandIndex) -> &[TiffChunkLocation] {
+        if meta.interleave == Interleave::Pixel || meta.band_count <= 1 {
+            return &self.chunk_locations;
+        }
+
+        let chunks_per_band = self.chunk_locations.len() / meta.band_count as usize;
+        let band_index0 = band_index.get() - 1;
+        &self.chunk_locations[band_index0 * chunks_per_band..(band_index0 + 1) * chunks_per_band]
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChunkDataLayout {
     Tiled(u32),   // Tile size in pixels
     Striped(u32), // Rows per strip
 }
 
+/// Controls how the chunks (tiles or strips) of a tiff are decoded when reading raster data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChunkDecoding {
+    #[default]
+    Sequential,
+    /// The chunk data is read sequentially, the decompression and merging is done in parallel on the rayon thread pool.
+    /// Falls back to sequential decoding when the `rayon` feature is not enabled.
+    Parallel,
+}
+
+/// Reads the raster data of a GeoTIFF, by default from a file.
+/// Any `Read + Seek` source can be used, e.g. a `RangeReader` to read a COG from an object store.
 #[derive(Debug)]
-pub struct GeoTiffReader {
+pub struct GeoTiffReader<R: Read + Seek = File> {
     meta: GeoTiffMetadata,
-    tiff_file: File,
+    tiff_file: R,
+    decoding: ChunkDecoding,
 }
 
-impl GeoTiffReader {
+impl GeoTiffReader<File> {
     pub fn from_file(path: &Path) -> Result<Self> {
         Ok(GeoTiffReader {
             meta: GeoTiffMetadata::from_file(path)?,
             tiff_file: File::open(path)?,
+            decoding: ChunkDecoding::default(),
         })
     }
+}
 
-    pub fn metadata(&self) -> &GeoTiffMetadata {
-        &self.meta
+#[cfg(feature = "http")]
+impl GeoTiffReader<RangeReader<HttpRangeSource>> {
+    /// Reads the GeoTIFF using HTTP range requests, the fetched data is cached in blocks.
+    /// The requests are blocking, see [`HttpRangeSource`] for the use in async code.
+    pub fn from_url(url: &str) -> Result<Self> {
+        Self::from_reader(RangeReader::new(HttpRangeSource::new(url)?)?)
     }
+}
 
-    pub fn overview_info(&self, index: usize) -> Option<&TiffOverview> {
-        self.meta.overviews.get(index)
+impl<R: Read + Seek> GeoTiffReader<R> {
+    pub fn from_reader(mut reader: R) -> Result<Self> {
+        Ok(GeoTiffReader {
+            meta: GeoTiffMetadata::from_reader(&mut reader)?,
+            tiff_file: reader,
+            decoding: ChunkDecoding::default(),
+        })
     }
 
-    fn geo_ref(&self) -> &GeoReference {
-        &self.meta.geo_reference
+    /// Configures how the chunks are decoded, sequential decoding is used by default
+    pub fn with_chunk_decoding(mut self, decoding: ChunkDecoding) -> Self {
+        self.decoding = decoding;
+        self
     }
 
-    #[simd_bounds]
-    fn read_tiled_raster_band_as<T: ArrayNum, M: ArrayMetadata>(
-        &mut self,
-        overview: &TiffOverview,
-        band_index: BandIndex,
-        tile_size: u32,
-    ) -> Result<DenseArray<T, M>> {
-        let nodata = cast::option::<T>(self.geo_ref().nodata()).unwrap_or(T::NODATA);
-        let mut data = allocate::aligned_vec_filled_with(nodata, overview.raster_size.cell_count());
-        let georef = Self::read_tiled_raster_band_into_buffer(&self.meta, overview, band_index, tile_size, &mut self.tiff_file, &mut data)?;
-        DenseArray::new_init_nodata(M::with_geo_reference(georef), data)
+    /// Access to the underlying reader, e.g. to prefetch chunks when using a `RangeReader`
+    pub fn reader_mut(&mut self) -> &mut R {
+        &mut self.tiff_file
     }
 
-    #[simd_bounds]
-    fn read_tiled_raster_band_into_buffer<T: ArrayNum, M: ArrayMetadata>(
-        meta: &GeoTiffMetadata,
-        overview: &TiffOverview,
-        band_index: BandIndex,
-        tile_size: u32,
-        tiff_file: &mut File,
-        buffer: &mut [T],
-    ) -> Result<M> {
-        io::merge_overview_into_buffer(meta, overview, band_index, tile_size, buffer, |chunk| {
-            let mut buf = vec![0; chunk.size as usize];
-            io::read_chunk(&chunk, tiff_file, &mut buf)?;
-            Ok(buf)
-        })
+    pub fn metadata(&self) -> &GeoTiffMetadata {
+        &self.meta
     }
 
-    #[simd_bounds]
-    fn read_striped_raster_band_as<T: ArrayNum, M: ArrayMetadata>(
-        &mut self,
-        band_index: BandIndex,
-        chunks: &[TiffChunkLocation],
-        rows_per_strip: u32,
-    ) -> Result<DenseArray<T, M>> {
-        let raster_size = self.meta.geo_reference.raster_size();
-        let mut data = allocate::AlignedVecUnderConstruction::new(raster_size.cell_count());
-        let georef =
-            self.read_striped_raster_band_into_buffer::<T, M>(band_index, chunks, rows_per_strip, unsafe { data.as_slice_mut() })?;
-        DenseArray::new_init_nodata(georef, unsafe { data.assume_init() })
+    pub fn overview_info(&self, index: usize) -> Option<&TiffOverview> {
+        self.meta.overviews.get(index)
     }
 
     #[simd_bounds]
-    fn read_striped_raster_band_into_buffer<T: ArrayNum, M: ArrayMetadata>(
-        &mut self,
+    fn read_chunked_raster_band_into_buffer<T: ArrayNum>(
+        meta: &GeoTiffMetadata,
+        overview: &TiffOverview,
         band_index: BandIndex,
-        chunks: &[TiffChunkLocation],
-        rows_per_strip: u32,
+        decoding: ChunkDecoding,
+        tiff_file: &mut R,
         buffer: &mut [T],
-    ) -> Result<M> {
-        if band_index.get() > 1 {
-            return Err(Error::Runtime(format!(
-                "Multiband GeoTIFF with striped layout not implemented. Requested band index: {}",
-                band_index.get()
+    ) -> Result<GeoReference> {
+        if T::TYPE != meta.data_type {
+            return Err(Error::InvalidArgument(format!(
+                "Tile data type mismatch: expected {:?}, got {:?}",
+                meta.data_type,
+                T::TYPE
             )));
         }
 
-        let geo_ref = self.meta.geo_reference.clone();
-        let strip_size = geo_ref.columns().count() as usize * rows_per_strip as usize;
-        for (stripe_offset, stripe_buf) in chunks.iter().zip(buffer.chunks_mut(strip_size)) {
-            Self::read_chunk_data_into_buffer_as(&self.meta, stripe_offset, &mut self.tiff_file, stripe_buf)?;
-        }
+        let chunk_locations = overview.band_chunk_locations(meta, band_index);
+        let chunks = match meta.data_layout {
+            ChunkDataLayout::Tiled(tile_size) => utils::tile_cutouts(overview.raster_size, chunk_locations, tile_size),
+            ChunkDataLayout::Striped(rows_per_strip) => utils::strip_cutouts(overview.raster_size, chunk_locations, rows_per_strip),
+        };
+
+        let mut geo_reference = meta.band_geo_reference(band_index);
+        let sparse_value = cast::option::<T>(geo_reference.nodata()).unwrap_or(T::NODATA);
+        let row_length = match meta.data_layout {
+            ChunkDataLayout::Tiled(tile_size) => tile_size,
+            ChunkDataLayout::Striped(_) => overview.raster_size.cols.count() as u32,
+        };
+
+        utils::merge_chunks_into_buffer(
+            &chunks,
+            overview.raster_size,
+            sparse_value,
+            decoding,
+            tiff_file,
+            buffer,
+            |chunk_data, decoded| io::parse_band_chunk_data_into_buffer(meta, band_index, row_length, chunk_data, decoded),
+        )?;
 
-        Ok(M::with_geo_reference(geo_ref))
+        geo_reference.set_columns(overview.raster_size.cols);
+        geo_reference.set_rows(overview.raster_size.rows);
+        Ok(geo_reference)
     }
 
     #[simd_bounds]
@@ -167,22 +211,13 @@ impl GeoTiffReader {
         overview_index: usize,
         band_index: BandIndex,
     ) -> Result<DenseArray<T, M>> {
-        if let Some(overview) = self.meta.overviews.get(overview_index).cloned() {
-            if overview.chunk_locations.is_empty() {
-                return Err(Error::Runtime("No tiles available in the geotiff".into()));
-            }
-
-            match self.meta.data_layout {
-                ChunkDataLayout::Tiled(tile_size) => {
-                    return self.read_tiled_raster_band_as::<T, M>(&overview, band_index, tile_size);
-                }
-                ChunkDataLayout::Striped(rows_per_strip) => {
-                    return self.read_striped_raster_band_as::<T, M>(band_index, &overview.chunk_locations, rows_per_strip);
-                }
-            }
-        }
+        let Some(overview) = self.meta.overviews.get(overview_index) else {
+            return Err(Error::Runtime(format!("No overview available with index {overview_index}")));
+        };
 
-        Err(Error::Runtime(format!("No overview available with index {overview_index}")))
+        let mut data = allocate::AlignedVecUnderConstruction::new(overview.raster_size.cell_count());
+        let meta = self.read_overview_band_into_buffer::<T, M>(overview_index, band_index, data.as_uninit_slice_mut())?;
+        DenseArray::new_init_nodata(meta, unsafe { data.assume_init() })
     }
 
     /// Reads an overview raster at the specified index
@@ -199,6 +234,7 @@ impl GeoTiffReader {
 
     /// Reads an overview raster at the specified index
     /// overview 0 is the full resolution raster, and each subsequent overview is a downsampled version.
+    /// Cells that are masked by the internal mask or the alpha band are set to nodata.
     #[simd_bounds]
     pub fn read_overview_band_into_buffer<T: ArrayNum, M: ArrayMetadata>(
         &mut self,
@@ -206,36 +242,37 @@ impl GeoTiffReader {
         band_index: BandIndex,
         buffer: &mut [MaybeUninit<T>],
     ) -> Result<M> {
-        if let Some(overview) = self.meta.overviews.get(overview_index).cloned() {
-            if overview.chunk_locations.is_empty() {
-                return Err(Error::Runtime("No tiles available in the geotiff".into()));
-            }
-
-            // Cast away the maybe uninit - we will fill the entire buffer
-            let buffer = raster::utils::cast_away_uninit_mut(buffer);
-
-            match self.meta.data_layout {
-                ChunkDataLayout::Tiled(tile_size) => {
-                    return Self::read_tiled_raster_band_into_buffer(
-                        &self.meta,
-                        &overview,
-                        band_index,
-                        tile_size,
-                        &mut self.tiff_file,
-                        buffer,
-                    );
-                }
-                ChunkDataLayout::Striped(rows_per_strip) => {
-                    return self.read_striped_raster_band_into_buffer(band_index, &overview.chunk_locations, rows_per_strip, buffer);
-                }
-            }
+        self.meta.check_band_index(band_index)?;
+
+        // Cast away the maybe uninit - we will fill the entire buffer
+        let buffer = raster::utils::cast_away_uninit_mut(buffer);
+        let mut geo_reference = Self::read_overview_band_data_into_buffer::<T>(
+            &self.meta,
+            overview_index,
+            band_index,
+            self.decoding,
+            &mut self.tiff_file,
+            buffer,
+        )?;
+        if self.meta.is_masked_band(band_index) {
+            let nodata = Self::apply_mask(
+                &self.meta,
+                overview_index,
+                band_index,
+                None,
+                self.decoding,
+                &mut self.tiff_file,
+                buffer,
+            )?;
+            geo_reference.set_nodata(nodata);
         }
 
-        Err(Error::Runtime(format!("No overview available with index {overview_index}")))
+        Ok(M::with_geo_reference(geo_reference))
     }
 
     /// Reads an overview raster at the specified index
     /// overview 0 is the full resolution raster, and each subsequent overview is a downsampled version.
+    /// Cells that are masked by the internal mask or the alpha band are set to nodata.
     #[simd_bounds]
     pub fn read_overview_region_into_buffer<T: ArrayNum, M: ArrayMetadata>(
         &mut self,
@@ -244,15 +281,78 @@ impl GeoTiffReader {
         extent: &crate::GeoReference,
         buffer: &mut [MaybeUninit<T>],
     ) -> Result<M> {
-        let nodata = MaybeUninit::new(self.geo_ref().nodata().and_then(NumCast::from).unwrap_or(T::NODATA));
+        self.meta.check_band_index(band_index)?;
+
+        // Cast away the maybe uninit - we will fill the entire buffer
+        let buffer = raster::utils::cast_away_uninit_mut(buffer);
+        Self::read_overview_region_data_into_buffer(
+            &self.meta,
+            overview_index,
+            band_index,
+            extent,
+            self.decoding,
+            &mut self.tiff_file,
+            buffer,
+        )?;
+
+        let mut geo_reference = extent.clone();
+        if self.meta.is_masked_band(band_index) {
+            let nodata = Self::apply_mask(
+                &self.meta,
+                overview_index,
+                band_index,
+                Some(extent),
+                self.decoding,
+                &mut self.tiff_file,
+                buffer,
+            )?;
+            if geo_reference.nodata().is_none() {
+                geo_reference.set_nodata(nodata);
+            }
+        }
+
+        Ok(M::with_geo_reference(geo_reference))
+    }
+
+    #[simd_bounds]
+    fn read_overview_band_data_into_buffer<T: ArrayNum>(
+        meta: &GeoTiffMetadata,
+        overview_index: usize,
+        band_index: BandIndex,
+        decoding: ChunkDecoding,
+        tiff_file: &mut R,
+        buffer: &mut [T],
+    ) -> Result<GeoReference> {
+        if let Some(overview) = meta.overviews.get(overview_index) {
+            if overview.chunk_locations.is_empty() {
+                return Err(Error::Runtime("No tiles available in the geotiff".into()));
+            }
+
+            return Self::read_chunked_raster_band_into_buffer(meta, overview, band_index, decoding, tiff_file, buffer);
+        }
+
+        Err(Error::Runtime(format!("No overview available with index {overview_index}")))
+    }
+
+    #[simd_bounds]
+    fn read_overview_region_data_into_buffer<T: ArrayNum>(
+        meta: &GeoTiffMetadata,
+        overview_index: usize,
+        band_index: BandIndex,
+        extent: &crate::GeoReference,
+        decoding: ChunkDecoding,
+        tiff_file: &mut R,
+        buffer: &mut [T],
+    ) -> Result<()> {
+        let nodata = meta.geo_reference.nodata().and_then(NumCast::from).unwrap_or(T::NODATA);
 
-        if let Some(overview) = self.meta.overviews.get(overview_index).cloned() {
+        if let Some(overview) = meta.overviews.get(overview_index) {
             if overview.chunk_locations.is_empty() {
                 buffer.fill(nodata);
-                return Ok(ArrayMetadata::with_geo_reference(extent.clone()));
+                return Ok(());
             }
 
-            let intersection = intersect_georeference(&self.metadata().geo_reference, extent)?;
+            let intersection = intersect_georeference(&meta.geo_reference, extent)?;
             if intersection.dst_col_offset > 0
                 || intersection.dst_row_offset > 0
                 || intersection.cols + intersection.dst_col_offset < extent.columns().count()
@@ -262,28 +362,28 @@ impl GeoTiffReader {
                 buffer.fill(nodata);
             }
 
-            // Cast away the maybe uninit - we will fill the entire buffer
-            let buffer = raster::utils::cast_away_uninit_mut(buffer);
-            match self.meta.data_layout {
+            match meta.data_layout {
                 ChunkDataLayout::Tiled(tile_size) => {
                     let chunk_tiles = Self::calculate_chunk_tiles_for_extent(
-                        &overview,
+                        overview.raster_size,
+                        overview.band_chunk_locations(meta, band_index),
                         overview_index,
-                        band_index,
-                        self.meta.band_count as usize,
-                        self.geo_ref(),
+                        &meta.geo_reference,
                         extent,
                         tile_size,
                     )?;
-                    utils::merge_tile_chunks_into_buffer(&self.meta, extent, tile_size, &chunk_tiles, &mut self.tiff_file, buffer)?;
-                    return Ok(M::with_geo_reference(extent.clone()));
+                    return utils::merge_tile_chunks_into_buffer(meta, band_index, extent, &chunk_tiles, decoding, tiff_file, buffer);
                 }
                 ChunkDataLayout::Striped(rows_per_strip) => {
-                    let chunk_tiles =
-                        Self::calculate_chunk_strips_for_extent(&overview, overview_index, self.geo_ref(), extent, rows_per_strip)?;
-                    utils::merge_strip_chunks_into_buffer(&self.meta, extent, rows_per_strip, &chunk_tiles, &mut self.tiff_file, buffer)?;
-                    return Ok(M::with_geo_reference(extent.clone()));
-                    //return self.read_striped_raster_into_buffer(&overview.chunk_locations, rows_per_strip, buffer);
+                    let chunk_tiles = Self::calculate_chunk_strips_for_extent(
+                        overview.raster_size,
+                        overview.band_chunk_locations(meta, band_index),
+                        overview_index,
+                        &meta.geo_reference,
+                        extent,
+                        rows_per_strip,
+                    )?;
+                    return utils::merge_strip_chunks_into_buffer(meta, band_index, extent, &chunk_tiles, decoding, tiff_file, buffer);
                 }
             }
         }
@@ -291,45 +391,69 @@ impl GeoTiffReader {
         Err(Error::Runtime(format!("No overview available with index {overview_index}")))
     }
 
+    /// Sets the cells that are masked by the internal mask or the alpha band to nodata.
+    /// The mask is read for the full overview or for the extent when provided, returns the nodata value of the masked cells.
     #[simd_bounds]
-    fn read_chunk_data_into_buffer_as<T: ArrayNum>(
+    fn apply_mask<T: ArrayNum>(
         meta: &GeoTiffMetadata,
-        chunk: &TiffChunkLocation,
-        tiff_file: &mut File,
-        chunk_data: &mut [T],
-    ) -> Result<()> {
-        let row_length = meta.chunk_row_length();
-
-        if T::TYPE != meta.data_type {
-            return Err(Error::InvalidArgument(format!(
-                "Tile data type mismatch: expected {:?}, got {:?}",
-                meta.data_type,
-                T::TYPE
-            )));
+        overview_index: usize,
+        band_index: BandIndex,
+        extent: Option<&GeoReference>,
+        decoding: ChunkDecoding,
+        tiff_file: &mut R,
+        buffer: &mut [T],
+    ) -> Result<Option<f64>> {
+        let nodata = cast::option::<T>(meta.geo_reference.nodata()).unwrap_or(T::NODATA);
+
+        if let Some(mask_meta) = meta.mask.as_deref() {
+            if mask_meta
+                .overviews
+                .get(overview_index)
+                .is_some_and(|overview| !overview.chunk_locations.is_empty())
+            {
+                let mut mask = vec![0u8; buffer.len()];
+                Self::read_mask_data_into_buffer(mask_meta, overview_index, FIRST_BAND, extent, decoding, tiff_file, &mut mask)?;
+                utils::apply_validity_mask(buffer, &mask, nodata);
+            }
+        } else if let Some(alpha_band) = meta.alpha_band
+            && alpha_band != band_index
+        {
+            let mut alpha = vec![T::zero(); buffer.len()];
+            Self::read_mask_data_into_buffer(meta, overview_index, alpha_band, extent, decoding, tiff_file, &mut alpha)?;
+            utils::apply_validity_mask(buffer, &alpha, nodata);
         }
 
-        // io function handles the sparse check
-        io::read_chunk_data_into_buffer(
-            chunk,
-            row_length,
-            meta.geo_reference.nodata(),
-            meta.compression,
-            meta.predictor,
-            tiff_file,
-            chunk_data,
-        )?;
+        Ok(NumCast::from(nodata))
+    }
 
-        Ok(())
+    #[simd_bounds]
+    fn read_mask_data_into_buffer<T: ArrayNum>(
+        meta: &GeoTiffMetadata,
+        overview_index: usize,
+        band_index: BandIndex,
+        extent: Option<&GeoReference>,
+        decoding: ChunkDecoding,
+        tiff_file: &mut R,
+        buffer: &mut [T],
+    ) -> Result<()> {
+        match extent {
+            Some(extent) => {
+                // Avoid the conversion of mask values that match the nodata value of the extent
+                let mut extent = extent.clone();
+                extent.set_nodata(None);
+                Self::read_overview_region_data_into_buffer(meta, overview_index, band_index, &extent, decoding, tiff_file, buffer)
+            }
+            None => Self::read_overview_band_data_into_buffer(meta, overview_index, band_index, decoding, tiff_file, buffer).map(|_| ()),
+        }
     }
 
     /// Calculates the chunks needed and their location in the cutout area
     fn calculate_chunk_tiles_for_extent(
-        overview: &TiffOverview,
-        overview_index: usize, // index of the overview to use, 0 is full resolution
-        band_index: BandIndex,
-        band_count: usize,
-        geo_reference: &GeoReference, // georeference of the full cog image
-        cutout: &GeoReference,        // georeference of the cutout area
+        raster_size: RasterSize,               // size of the overview raster
+        chunk_locations: &[TiffChunkLocation], // chunk locations of the requested band in the overview
+        overview_index: usize,                 // index of the overview to use, 0 is full resolution
+        geo_reference: &GeoReference,          // georeference of the full cog image
+        cutout: &GeoReference,                 // georeference of the cutout area
         block_size: u32,
     ) -> Result<Vec<(TiffChunkLocation, CutOut)>> {
         let mut chunk_tiles = Vec::default();
@@ -337,18 +461,15 @@ impl GeoTiffReader {
         let cell_size = geo_reference.cell_size() / (overview_index as f64 + 1.0);
         let geo_ref_overview = utils::change_georef_cell_size(geo_reference, cell_size);
 
-        let tiles_wide = (overview.raster_size.cols.count() as u32).div_ceil(block_size) as usize;
-        let tiles_high = (overview.raster_size.rows.count() as u32).div_ceil(block_size) as usize;
+        let tiles_wide = (raster_size.cols.count() as u32).div_ceil(block_size) as usize;
+        let tiles_high = (raster_size.rows.count() as u32).div_ceil(block_size) as usize;
         assert!(
-            tiles_wide * tiles_high == overview.chunk_locations.len(),
+            tiles_wide * tiles_high == chunk_locations.len(),
             "Expected {} tiles, but got {}",
             tiles_wide * tiles_high,
-            overview.chunk_locations.len()
+            chunk_locations.len()
         );
 
-        assert!(overview.chunk_locations.len().is_multiple_of(band_count));
-        let chunks_per_band = overview.chunk_locations.len() / band_count;
-
         let top_left_cell = geo_reference.point_to_cell(cutout.top_left());
         let bottom_right_cell = geo_reference.point_to_cell(cutout.bottom_right());
 
@@ -361,18 +482,18 @@ impl GeoTiffReader {
         for ty in min_tile_y..=max_tile_y {
             let mut current_source_cell = Cell::from_row_col(ty as i32 * block_size, 0);
             // Calculate the actual height of this chunk (may be smaller at the edges)
-            let chunk_height = Rows(if current_source_cell.row + block_size > overview.raster_size.rows.count() {
+            let chunk_height = Rows(if current_source_cell.row + block_size > raster_size.rows.count() {
                 debug_assert!(ty + 1 == tiles_high);
-                overview.raster_size.rows.count() - current_source_cell.row
+                raster_size.rows.count() - current_source_cell.row
             } else {
                 block_size
             });
 
             for tx in min_tile_x..=max_tile_x {
                 current_source_cell.col = tx as i32 * block_size;
-                let chunk_width = Columns(if current_source_cell.col + block_size > overview.raster_size.cols.count() {
+                let chunk_width = Columns(if current_source_cell.col + block_size > raster_size.cols.count() {
                     debug_assert!(tx + 1 == tiles_wide);
-                    overview.raster_size.cols.count() - current_source_cell.col
+                    raster_size.cols.count() - current_source_cell.col
                 } else {
                     block_size
                 });
@@ -387,8 +508,7 @@ impl GeoTiffReader {
                     Option::<f64>::None,
                 );
 
-                let band_index0 = band_index.get() - 1; // to 0-based index
-                let tiff_chunk = &overview.chunk_locations[(ty * tiles_wide + tx) + (band_index0 * chunks_per_band)];
+                let tiff_chunk = &chunk_locations[ty * tiles_wide + tx];
                 let cutout_offsets = intersect_georeference(&chunk_geo_ref, cutout)?;
                 debug_assert!(cutout_offsets.cols > 0 && cutout_offsets.rows > 0);
 
@@ -401,18 +521,19 @@ impl GeoTiffReader {
 
     /// Calculates the chunks needed and their location in the cutout area
     fn calculate_chunk_strips_for_extent(
-        overview: &TiffOverview,
-        overview_index: usize,        // index of the overview to use, 0 is full resolution
-        geo_reference: &GeoReference, // georeference of the full cog image
-        cutout: &GeoReference,        // georeference of the cutout area
+        raster_size: RasterSize,               // size of the overview raster
+        chunk_locations: &[TiffChunkLocation], // chunk locations of the requested band in the overview
+        overview_index: usize,                 // index of the overview to use, 0 is full resolution
+        geo_reference: &GeoReference,          // georeference of the full cog image
+        cutout: &GeoReference,                 // georeference of the cutout area
         rows_per_strip: u32,
-    ) -> Result<Vec<(TiffChunkLocation, GeoReference, bool)>> {
+    ) -> Result<Vec<(TiffChunkLocation, GeoReference)>> {
         let mut chunk_tiles = Vec::default();
 
         let cell_size = geo_reference.cell_size() / (overview_index as f64 + 1.0);
         let geo_ref_overview = utils::change_georef_cell_size(geo_reference, cell_size);
 
-        let number_of_strips = overview.chunk_locations.len();
+        let number_of_strips = chunk_locations.len();
 
         let top_left_cell = geo_reference.point_to_cell(cutout.top_left());
         let bottom_right_cell = geo_reference.point_to_cell(cutout.bottom_right());
@@ -424,9 +545,9 @@ impl GeoTiffReader {
         for strip in min_strip..=max_strip {
             let current_source_cell = Cell::from_row_col(strip * rows_per_strip, 0);
             // Calculate the actual height of this chunk (may be smaller at the edges)
-            let chunk_height = Rows(if current_source_cell.row + rows_per_strip > overview.raster_size.rows.count() {
+            let chunk_height = Rows(if current_source_cell.row + rows_per_strip > raster_size.rows.count() {
                 debug_assert!(strip + 1 == number_of_strips as i32);
-                overview.raster_size.rows.count() - current_source_cell.row
+                raster_size.rows.count() - current_source_cell.row
             } else {
                 rows_per_strip
             });
@@ -441,11 +562,7 @@ impl GeoTiffReader {
                 Option::<f64>::None,
             );
 
-            chunk_tiles.push((
-                overview.chunk_locations[strip as usize],
-                chunk_geo_ref,
-                strip == number_of_strips as i32 - 1,
-            ));
+            chunk_tiles.push((chunk_locations[strip as usize], chunk_geo_ref));
         }
 
         Ok(chunk_tiles)
@@ -719,4 +836,908 @@ mod tests {
 
         Ok(())
     }
+
+    /// Helpers to write small test tiffs with the native encoder
+    mod fixtures {
+        use crate::{
+            RasterSize,
+            geotiff::{
+                ChunkDataLayout,
+                encoder::{ImageFileDirectory, TagValue},
+            },
+        };
+        use tiff::tags::Tag;
+
+        /// Takes every second cell of every second row
+        pub(super) fn downsample(data: &[u8], raster_size: RasterSize) -> Vec<u8> {
+            let cols = raster_size.cols.count() as usize;
+            data.chunks(cols)
+                .step_by(2)
+                .flat_map(|row| row.iter().step_by(2).copied())
+                .collect()
+        }
+
+        /// Splits pixel interleaved data with the given samples per cell in tiles, the edge tiles are padded with the fill value
+        pub(super) fn split_in_tiles(values: &[u8], raster_size: RasterSize, tile_size: usize, samples: usize, fill: u8) -> Vec<Vec<u8>> {
+            let rows = raster_size.rows.count() as usize;
+            let cols = raster_size.cols.count() as usize;
+
+            let mut tiles = Vec::new();
+            for tile_row in 0..rows.div_ceil(tile_size) {
+                for tile_col in 0..cols.div_ceil(tile_size) {
+                    let mut tile = vec![fill; tile_size * tile_size * samples];
+                    let col_count = tile_size.min(cols - tile_col * tile_size);
+                    for r in 0..tile_size.min(rows - tile_row * tile_size) {
+                        let src_start = ((tile_row * tile_size + r) * cols + tile_col * tile_size) * samples;
+                        tile[r * tile_size * samples..(r * tile_size + col_count) * samples]
+                            .copy_from_slice(&values[src_start..src_start + col_count * samples]);
+                    }
+                    tiles.push(tile);
+                }
+            }
+
+            tiles
+        }
+
+        /// Packs the values of every row starting from the most significant bit, rows start at a byte boundary
+        pub(super) fn pack_rows(values: &[u8], cols: usize, bits: usize) -> Vec<u8> {
+            values
+                .chunks(cols)
+                .flat_map(|row| {
+                    let mut packed = vec![0u8; (cols * bits).div_ceil(8)];
+                    for (col, value) in row.iter().enumerate() {
+                        for bit in 0..bits {
+                            if (value >> (bits - 1 - bit)) & 1 == 1 {
+                                let bit_pos = col * bits + bit;
+                                packed[bit_pos / 8] |= 0x80 >> (bit_pos % 8);
+                            }
+                        }
+                    }
+                    packed
+                })
+                .collect()
+        }
+
+        /// Image directory with the tags shared by the test tiffs, one sample per entry in `bits`
+        pub(super) fn image_directory(
+            raster_size: RasterSize,
+            layout: ChunkDataLayout,
+            bits: &[u16],
+            compression: u16,
+            photometric: u16,
+        ) -> ImageFileDirectory {
+            let mut ifd = ImageFileDirectory::new(layout);
+            ifd.set_tag(Tag::ImageWidth, TagValue::Long(vec![raster_size.cols.count() as u32]));
+            ifd.set_tag(Tag::ImageLength, TagValue::Long(vec![raster_size.rows.count() as u32]));
+            ifd.set_tag(Tag::BitsPerSample, TagValue::Short(bits.to_vec()));
+            ifd.set_tag(Tag::Compression, TagValue::Short(vec![compression]));
+            ifd.set_tag(Tag::PhotometricInterpretation, TagValue::Short(vec![photometric]));
+            ifd.set_tag(Tag::SamplesPerPixel, TagValue::Short(vec![bits.len() as u16]));
+            match layout {
+                ChunkDataLayout::Striped(rows_per_strip) => ifd.set_tag(Tag::RowsPerStrip, TagValue::Long(vec![rows_per_strip])),
+                ChunkDataLayout::Tiled(tile_size) => {
+                    ifd.set_tag(Tag::TileWidth, TagValue::Long(vec![tile_size]));
+                    ifd.set_tag(Tag::TileLength, TagValue::Long(vec![tile_size]));
+                }
+            }
+            ifd
+        }
+    }
+
+    mod multiband {
+        use std::path::Path;
+
+        use crate::{
+            CellSize, Columns, GeoReference, Point, RasterSize, Result, Rows,
+            geotiff::{
+                ChunkDataLayout, FIRST_BAND, GeoTiffReader, Interleave,
+                encoder::{ImageFileDirectory, TagValue, TiffEncodeOptions, write_tiff},
+                io::encode_chunk_data,
+            },
+            raster::{Compression, formats::FormatProvider, io::RasterIO},
+        };
+        use tiff::tags::Tag;
+
+        use super::fixtures::{downsample, image_directory, split_in_tiles};
+
+        const BAND_COUNT: usize = 3;
+        const NODATA: f64 = 255.0;
+
+        fn test_georeference(raster_size: RasterSize) -> GeoReference {
+            GeoReference::with_top_left_origin(
+                "",
+                raster_size,
+                Point::new(22000.0, 245000.0),
+                CellSize::square(100.0),
+                Some(NODATA),
+            )
+        }
+
+        fn band_data(raster_size: RasterSize, band: usize) -> Vec<u8> {
+            (0..raster_size.cell_count())
+                .map(|i| {
+                    if i % 11 == 0 {
+                        NODATA as u8
+                    } else {
+                        ((i * 3 + band * 50) % 250) as u8
+                    }
+                })
+                .collect()
+        }
+
+        fn tile_chunks(
+            bands: &[Vec<u8>],
+            raster_size: RasterSize,
+            tile_size: usize,
+            interleave: Interleave,
+        ) -> Result<Vec<Option<Vec<u8>>>> {
+            let tiles = if interleave == Interleave::Pixel {
+                let interleaved: Vec<u8> = (0..raster_size.cell_count())
+                    .flat_map(|i| bands.iter().map(move |band| band[i]))
+                    .collect();
+                split_in_tiles(&interleaved, raster_size, tile_size, bands.len(), NODATA as u8)
+            } else {
+                bands
+                    .iter()
+                    .flat_map(|band| split_in_tiles(band, raster_size, tile_size, 1, NODATA as u8))
+                    .collect()
+            };
+
+            let row_size = tile_size * if interleave == Interleave::Pixel { bands.len() } else { 1 };
+            tiles
+                .into_iter()
+                .map(|mut tile| Ok(Some(encode_chunk_data(row_size as u32, Some(Compression::Zstd), None, &mut tile)?)))
+                .collect()
+        }
+
+        fn multiband_directory(
+            bands: &[Vec<u8>],
+            raster_size: RasterSize,
+            tile_size: u32,
+            interleave: Interleave,
+        ) -> Result<ImageFileDirectory> {
+            let planar_config = if interleave == Interleave::Pixel { 1 } else { 2 };
+
+            let mut ifd = image_directory(raster_size, ChunkDataLayout::Tiled(tile_size), &[8; BAND_COUNT], 50000, 1);
+            ifd.set_tag(Tag::PlanarConfiguration, TagValue::Short(vec![planar_config]));
+            ifd.set_tag(Tag::SampleFormat, TagValue::Short(vec![1; BAND_COUNT]));
+            ifd.set_tag(Tag::ExtraSamples, TagValue::Short(vec![0; BAND_COUNT - 1]));
+            ifd.chunks = tile_chunks(bands, raster_size, tile_size as usize, interleave)?;
+
+            Ok(ifd)
+        }
+
+        /// Writes a 3 band tiled tiff with one overview using the requested interleaving
+        fn write_multiband_tiff(path: &Path, raster_size: RasterSize, interleave: Interleave) -> Result<Vec<Vec<u8>>> {
+            const TILE_SIZE: u32 = 16;
+
+            let bands: Vec<Vec<u8>> = (0..BAND_COUNT).map(|band| band_data(raster_size, band)).collect();
+            let overview_size = RasterSize::with_rows_cols(
+                Rows((raster_size.rows.count() + 1) / 2),
+                Columns((raster_size.cols.count() + 1) / 2),
+            );
+            let overview_bands: Vec<Vec<u8>> = bands.iter().map(|band| downsample(band, raster_size)).collect();
+
+            let mut full_resolution = multiband_directory(&bands, raster_size, TILE_SIZE, interleave)?;
+            let [x0, cell_size_x, _, y0, _, cell_size_y] = test_georeference(raster_size).geo_transform().coefficients();
+            full_resolution.set_tag(Tag::ModelPixelScaleTag, TagValue::Double(vec![cell_size_x, -cell_size_y, 0.0]));
+            full_resolution.set_tag(Tag::ModelTiepointTag, TagValue::Double(vec![0.0, 0.0, 0.0, x0, y0, 0.0]));
+            full_resolution.set_tag(Tag::GdalNodata, TagValue::Ascii("255".into()));
+
+            let mut overview = multiband_directory(&overview_bands, overview_size, TILE_SIZE, interleave)?;
+            overview.set_tag(Tag::NewSubfileType, TagValue::Long(vec![1]));
+
+            let mut file = std::fs::File::create(path)?;
+            write_tiff(&[full_resolution, overview], &TiffEncodeOptions::default(), &mut file)?;
+
+            Ok(bands)
+        }
+
+        fn read_multiband(interleave: Interleave) -> Result<()> {
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("multiband.tif");
+            let raster_size = RasterSize::with_rows_cols(Rows(37), Columns(45));
+            let bands = write_multiband_tiff(&path, raster_size, interleave)?;
+
+            let mut raster = RasterIO::open_read_only_force_format(&path, FormatProvider::GeoTiff)?;
+            assert_eq!(raster.band_count()?, BAND_COUNT);
+            assert!(raster.read_raster_band::<u8>(BAND_COUNT + 1).is_err());
+
+            let region = GeoReference::with_top_left_origin(
+                "",
+                RasterSize::with_rows_cols(Rows(20), Columns(26)),
+                Point::new(22000.0 + 7.0 * 100.0, 245000.0 - 5.0 * 100.0),
+                CellSize::square(100.0),
+                Some(NODATA),
+            );
+
+            for (band_index, expected) in bands.iter().enumerate() {
+                let (geo_ref, data) = raster.read_raster_band::<u8>(band_index + 1)?;
+                assert_eq!(geo_ref.raster_size(), raster_size);
+                assert_eq!(data.as_slice(), expected.as_slice());
+
+                let (_, region_data) = raster.read_raster_band_region::<u8>(band_index + 1, &region)?;
+                let expected_region: Vec<u8> = expected
+                    .chunks(raster_size.cols.count() as usize)
+                    .skip(5)
+                    .take(20)
+                    .flat_map(|row| row[7..33].iter().copied())
+                    .collect();
+                assert_eq!(region_data.as_slice(), expected_region.as_slice());
+            }
+
+            let mut reader = GeoTiffReader::from_file(&path)?;
+            assert_eq!(reader.metadata().interleave, interleave);
+            for (band_index, expected) in bands.iter().enumerate() {
+                let band = crate::geotiff::BandIndex::new(band_index + 1).unwrap();
+                let overview = reader.read_overview_band_as::<u8, GeoReference>(1, band)?;
+                assert_eq!(overview.as_ref(), downsample(expected, raster_size).as_slice());
+            }
+
+            assert!(reader.read_overview_band_as::<u8, GeoReference>(1, FIRST_BAND).is_ok());
+
+            Ok(())
+        }
+
+        #[test]
+        fn read_multiband_pixel_interleaved() -> Result<()> {
+            read_multiband(Interleave::Pixel)
+        }
+
+        #[test]
+        fn read_multiband_band_interleaved() -> Result<()> {
+            read_multiband(Interleave::Band)
+        }
+
+        #[test]
+        fn read_multiband_pixel_interleaved_striped_with_predictor() -> Result<()> {
+            use tiff::encoder::{TiffEncoder, colortype::RGB16};
+
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("rgb.tif");
+            let raster_size = RasterSize::with_rows_cols(Rows(30), Columns(40));
+            let bands: Vec<Vec<u16>> = (0..BAND_COUNT)
+                .map(|band| (0..raster_size.cell_count()).map(|i| (i * (band + 1) * 37 % 5000) as u16).collect())
+                .collect();
+            let interleaved: Vec<u16> = (0..raster_size.cell_count())
+                .flat_map(|i| bands.iter().map(move |band| band[i]))
+                .collect();
+
+            {
+                // Write the file with the tiff crate encoder to validate against an independent implementation
+                let mut encoder = TiffEncoder::new(std::fs::File::create(&path)?)?.with_predictor(tiff::tags::Predictor::Horizontal);
+                let mut image = encoder.new_image::<RGB16>(raster_size.cols.count() as u32, raster_size.rows.count() as u32)?;
+                image.rows_per_strip(7)?;
+                image.encoder().write_tag(Tag::ModelPixelScaleTag, &[100.0, 100.0, 0.0][..])?;
+                image
+                    .encoder()
+                    .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 22000.0, 245000.0, 0.0][..])?;
+                image.write_data(&interleaved)?;
+            }
+
+            let mut raster = RasterIO::open_read_only_force_format(&path, FormatProvider::GeoTiff)?;
+            assert_eq!(raster.band_count()?, BAND_COUNT);
+            for (band_index, expected) in bands.iter().enumerate() {
+                let (_, data) = raster.read_raster_band::<u16>(band_index + 1)?;
+                assert_eq!(data.as_slice(), expected.as_slice());
+            }
+
+            let region = GeoReference::with_top_left_origin(
+                "",
+                RasterSize::with_rows_cols(Rows(10), Columns(40)),
+                Point::new(22000.0, 245000.0 - 12.0 * 100.0),
+                CellSize::square(100.0),
+                Option::<f64>::None,
+            );
+            let (_, region_data) = raster.read_raster_band_region::<u16>(2, &region)?;
+            assert_eq!(region_data.as_slice(), &bands[1][12 * 40..22 * 40]);
+
+            Ok(())
+        }
+    }
+
+    mod subbyte {
+        use std::path::Path;
+
+        use crate::{
+            ArrayDataType, CellSize, Columns, GeoReference, Point, RasterSize, Result, Rows,
+            geotiff::{
+                ChunkDataLayout, GeoTiffReader,
+                encoder::{TagValue, TiffEncodeOptions, write_tiff},
+                io::encode_chunk_data,
+            },
+            raster::{Compression, formats::FormatProvider, io::RasterIO},
+        };
+        use tiff::tags::Tag;
+
+        use super::fixtures::{image_directory, pack_rows, split_in_tiles};
+
+        const TILE_SIZE: usize = 16;
+        const ROWS_PER_STRIP: usize = 5;
+
+        fn test_data(raster_size: RasterSize, bits: usize) -> Vec<u8> {
+            let cols = raster_size.cols.count() as usize;
+            (0..raster_size.cell_count())
+                .map(|i| ((i * 7 + i / cols) % (1 << bits)) as u8)
+                .collect()
+        }
+
+        fn chunks(
+            data: &[u8],
+            raster_size: RasterSize,
+            layout: ChunkDataLayout,
+            bits: usize,
+            compression: Option<Compression>,
+        ) -> Result<Vec<Option<Vec<u8>>>> {
+            let cols = raster_size.cols.count() as usize;
+
+            let chunk_data: Vec<(Vec<u8>, usize)> = match layout {
+                ChunkDataLayout::Striped(_) => data
+                    .chunks(cols * ROWS_PER_STRIP)
+                    .map(|strip| (pack_rows(strip, cols, bits), cols))
+                    .collect(),
+                ChunkDataLayout::Tiled(_) => split_in_tiles(data, raster_size, TILE_SIZE, 1, 0)
+                    .iter()
+                    .map(|tile| (pack_rows(tile, TILE_SIZE, bits), TILE_SIZE))
+                    .collect(),
+            };
+
+            chunk_data
+                .into_iter()
+                .map(|(mut packed, row_length)| {
+                    let packed_row_size = (row_length * bits).div_ceil(8) as u32;
+                    Ok(Some(encode_chunk_data(packed_row_size, compression, None, &mut packed)?))
+                })
+                .collect()
+        }
+
+        /// Writes a single band tiff with the requested bits per sample, the sample format tag is omitted
+        fn write_sub_byte_tiff(
+            path: &Path,
+            raster_size: RasterSize,
+            bits: usize,
+            layout: ChunkDataLayout,
+            compression: Option<Compression>,
+        ) -> Result<Vec<u8>> {
+            let data = test_data(raster_size, bits);
+            let compression_code = match compression {
+                None => 1,
+                Some(Compression::Lzw) => 5,
+                Some(Compression::Zstd) => 50000,
+                _ => unreachable!("compression not used in the tests"),
+            };
+
+            let mut ifd = image_directory(raster_size, layout, &[bits as u16], compression_code, 1);
+            ifd.set_tag(Tag::ModelPixelScaleTag, TagValue::Double(vec![100.0, 100.0, 0.0]));
+            ifd.set_tag(Tag::ModelTiepointTag, TagValue::Double(vec![0.0, 0.0, 0.0, 22000.0, 245000.0, 0.0]));
+            ifd.chunks = chunks(&data, raster_size, layout, bits, compression)?;
+
+            write_tiff(&[ifd], &TiffEncodeOptions::default(), &mut std::fs::File::create(path)?)?;
+            Ok(data)
+        }
+
+        fn read_sub_byte(layout: ChunkDataLayout, bits: usize, compression: Option<Compression>) -> Result<()> {
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("subbyte.tif");
+            let raster_size = RasterSize::with_rows_cols(Rows(37), Columns(45));
+            let data = write_sub_byte_tiff(&path, raster_size, bits, layout, compression)?;
+
+            let reader = GeoTiffReader::from_file(&path)?;
+            assert_eq!(reader.metadata().data_type, ArrayDataType::Uint8);
+            assert_eq!(reader.metadata().bits_per_sample, bits as u32);
+
+            let mut raster = RasterIO::open_read_only_force_format(&path, FormatProvider::GeoTiff)?;
+            let (_, full_data) = raster.read_raster_band::<u8>(1)?;
+            assert_eq!(full_data.as_slice(), data.as_slice(), "{bits} bit(s) {layout:?}");
+            let (_, converted_data) = raster.read_raster_band::<u16>(1)?;
+            assert!(
+                converted_data
+                    .iter()
+                    .zip(data.iter())
+                    .all(|(&converted, &value)| converted == value as u16)
+            );
+
+            let region = GeoReference::with_top_left_origin(
+                "",
+                RasterSize::with_rows_cols(Rows(20), Columns(26)),
+                Point::new(22000.0 + 7.0 * 100.0, 245000.0 - 5.0 * 100.0),
+                CellSize::square(100.0),
+                Option::<f64>::None,
+            );
+            let (_, region_data) = raster.read_raster_band_region::<u8>(1, &region)?;
+            let expected_region: Vec<u8> = data
+                .chunks(raster_size.cols.count() as usize)
+                .skip(5)
+                .take(20)
+                .flat_map(|row| row[7..33].iter().copied())
+                .collect();
+            assert_eq!(region_data.as_slice(), expected_region.as_slice(), "{bits} bit(s) {layout:?}");
+
+            Ok(())
+        }
+
+        #[test]
+        fn read_sub_byte_striped() -> Result<()> {
+            for bits in [1, 2, 4] {
+                read_sub_byte(ChunkDataLayout::Striped(ROWS_PER_STRIP as u32), bits, None)?;
+                read_sub_byte(ChunkDataLayout::Striped(ROWS_PER_STRIP as u32), bits, Some(Compression::Lzw))?;
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn read_sub_byte_tiled() -> Result<()> {
+            for bits in [1, 3, 4] {
+                read_sub_byte(ChunkDataLayout::Tiled(TILE_SIZE as u32), bits, Some(Compression::Zstd))?;
+            }
+
+            Ok(())
+        }
+    }
+
+    mod mask {
+        use std::path::Path;
+
+        use crate::{
+            Array as _, CellSize, Columns, GeoReference, Nodata as _, Point, RasterSize, Result, Rows, Tile,
+            cog::WebTilesReader,
+            crs,
+            geotiff::{
+                BandIndex, ChunkDataLayout, FIRST_BAND, GeoTiffReader,
+                encoder::{ImageFileDirectory, TagValue, TiffEncodeOptions, write_tiff},
+                io::encode_chunk_data,
+            },
+            raster::{Compression, formats::FormatProvider, io::RasterIO},
+        };
+        use tiff::tags::Tag;
+
+        use super::fixtures::{downsample, image_directory, pack_rows, split_in_tiles};
+
+        const TILE_SIZE: usize = 16;
+        const WEB_TILE_SIZE: usize = Tile::TILE_SIZE as usize;
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum MaskKind {
+            /// Internal mask stored with the given bits per sample
+            Internal(usize),
+            Alpha,
+        }
+
+        fn test_data(raster_size: RasterSize) -> Vec<u8> {
+            (0..raster_size.cell_count()).map(|i| (i % 200 + 1) as u8).collect()
+        }
+
+        fn test_mask(raster_size: RasterSize) -> Vec<u8> {
+            let cols = raster_size.cols.count() as usize;
+            (0..raster_size.cell_count())
+                .map(|i| if (i / cols * 3 + i % cols).is_multiple_of(7) { 0 } else { 255 })
+                .collect()
+        }
+
+        fn masked(data: &[u8], mask: &[u8]) -> Vec<u8> {
+            data.iter()
+                .zip(mask)
+                .map(|(&value, &mask)| if mask == 0 { u8::NODATA } else { value })
+                .collect()
+        }
+
+        fn tiled_directory(raster_size: RasterSize, tile_size: usize, bits: &[u16], photometric: u16) -> ImageFileDirectory {
+            image_directory(raster_size, ChunkDataLayout::Tiled(tile_size as u32), bits, 50000, photometric)
+        }
+
+        fn data_directory(
+            data: &[u8],
+            mask: &[u8],
+            raster_size: RasterSize,
+            tile_size: usize,
+            kind: MaskKind,
+        ) -> Result<ImageFileDirectory> {
+            let bands: Vec<&[u8]> = match kind {
+                MaskKind::Internal(_) => vec![data],
+                MaskKind::Alpha => vec![data, mask],
+            };
+
+            let mut ifd = tiled_directory(raster_size, tile_size, &vec![8; bands.len()], 1);
+            if kind == MaskKind::Alpha {
+                ifd.set_tag(Tag::ExtraSamples, TagValue::Short(vec![2]));
+                ifd.set_tag(Tag::PlanarConfiguration, TagValue::Short(vec![2]));
+            }
+
+            ifd.chunks = bands
+                .iter()
+                .flat_map(|band| split_in_tiles(band, raster_size, tile_size, 1, 0))
+                .map(|mut tile| Ok(Some(encode_chunk_data(tile_size as u32, Some(Compression::Zstd), None, &mut tile)?)))
+                .collect::<Result<_>>()?;
+            Ok(ifd)
+        }
+
+        /// Mask image directory, the mask is stored with the requested bits per sample
+        fn mask_directory(
+            mask: &[u8],
+            raster_size: RasterSize,
+            tile_size: usize,
+            bits: usize,
+            subfile_type: u32,
+        ) -> Result<ImageFileDirectory> {
+            let mut ifd = tiled_directory(raster_size, tile_size, &[bits as u16], 4);
+            ifd.set_tag(Tag::NewSubfileType, TagValue::Long(vec![subfile_type]));
+            ifd.chunks = split_in_tiles(mask, raster_size, tile_size, 1, 0)
+                .into_iter()
+                .map(|tile| {
+                    let mut packed = if bits == 1 {
+                        let bit_values: Vec<u8> = tile.iter().map(|&v| (v != 0) as u8).collect();
+                        pack_rows(&bit_values, tile_size, 1)
+                    } else {
+                        tile
+                    };
+                    Ok(Some(encode_chunk_data(
+                        (TILE_SIZE * bits).div_ceil(8) as u32,
+                        Some(Compression::Zstd),
+                        None,
+                        &mut packed,
+                    )?))
+                })
+                .collect::<Result<_>>()?;
+            Ok(ifd)
+        }
+
+        /// Writes a tiled tiff with one overview, masked using an internal mask or an alpha band.
+        /// Returns the data and the mask of the full resolution image.
+        fn write_masked_tiff(path: &Path, georef: &GeoReference, tile_size: usize, kind: MaskKind) -> Result<(Vec<u8>, Vec<u8>)> {
+            let raster_size = georef.raster_size();
+            let overview_size = RasterSize::with_rows_cols(
+                Rows((raster_size.rows.count() + 1) / 2),
+                Columns((raster_size.cols.count() + 1) / 2),
+            );
+
+            let data = test_data(raster_size);
+            let mask = test_mask(raster_size);
+            let overview_data = downsample(&data, raster_size);
+            let overview_mask = downsample(&mask, raster_size);
+
+            let mut full_resolution = data_directory(&data, &mask, raster_size, tile_size, kind)?;
+            let [x0, cell_size_x, _, y0, _, cell_size_y] = georef.geo_transform().coefficients();
+            full_resolution.set_tag(Tag::ModelPixelScaleTag, TagValue::Double(vec![cell_size_x, -cell_size_y, 0.0]));
+            full_resolution.set_tag(Tag::ModelTiepointTag, TagValue::Double(vec![0.0, 0.0, 0.0, x0, y0, 0.0]));
+
+            let mut overview = data_directory(&overview_data, &overview_mask, overview_size, tile_size, kind)?;
+            overview.set_tag(Tag::NewSubfileType, TagValue::Long(vec![1]));
+
+            // Same directory order as GDAL: every image is followed by its mask
+            let ifds = match kind {
+                MaskKind::Internal(bits) => vec![
+                    full_resolution,
+                    mask_directory(&mask, raster_size, tile_size, bits, 4)?,
+                    overview,
+                    mask_directory(&overview_mask, overview_size, tile_size, bits, 5)?,
+                ],
+                MaskKind::Alpha => vec![full_resolution, overview],
+            };
+
+            write_tiff(&ifds, &TiffEncodeOptions::default(), &mut std::fs::File::create(path)?)?;
+            Ok((data, mask))
+        }
+
+        fn read_masked(kind: MaskKind) -> Result<()> {
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("masked.tif");
+            let raster_size = RasterSize::with_rows_cols(Rows(37), Columns(45));
+            let georef = GeoReference::with_top_left_origin(
+                "",
+                raster_size,
+                Point::new(22000.0, 245000.0),
+                CellSize::square(100.0),
+                Option::<f64>::None,
+            );
+            let (data, mask) = write_masked_tiff(&path, &georef, TILE_SIZE, kind)?;
+            let expected = masked(&data, &mask);
+
+            let mut raster = RasterIO::open_read_only_force_format(&path, FormatProvider::GeoTiff)?;
+            let (geo_ref, full_data) = raster.read_raster_band::<u8>(1)?;
+            assert_eq!(geo_ref.nodata(), Some(u8::NODATA as f64));
+            assert_eq!(full_data.as_slice(), expected.as_slice());
+
+            let region = GeoReference::with_top_left_origin(
+                "",
+                RasterSize::with_rows_cols(Rows(20), Columns(26)),
+                Point::new(22000.0 + 7.0 * 100.0, 245000.0 - 5.0 * 100.0),
+                CellSize::square(100.0),
+                Option::<f64>::None,
+            );
+            let (_, region_data) = raster.read_raster_band_region::<u8>(1, &region)?;
+            let expected_region: Vec<u8> = expected
+                .chunks(raster_size.cols.count() as usize)
+                .skip(5)
+                .take(20)
+                .flat_map(|row| row[7..33].iter().copied())
+                .collect();
+            assert_eq!(region_data.as_slice(), expected_region.as_slice());
+
+            let mut reader = GeoTiffReader::from_file(&path)?;
+            assert_eq!(reader.metadata().overviews.len(), 2);
+            let overview = reader.read_overview_band_as::<u8, GeoReference>(1, FIRST_BAND)?;
+            let expected_overview = masked(&downsample(&data, raster_size), &downsample(&mask, raster_size));
+            assert_eq!(
+                overview.iter_opt().map(|v| v.unwrap_or(u8::NODATA)).collect::<Vec<_>>(),
+                expected_overview
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn read_internal_mask() -> Result<()> {
+            read_masked(MaskKind::Internal(1))?;
+            read_masked(MaskKind::Internal(8))
+        }
+
+        #[test]
+        fn read_alpha_band() -> Result<()> {
+            read_masked(MaskKind::Alpha)?;
+
+            // The alpha band itself is not masked
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("alpha.tif");
+            let raster_size = RasterSize::with_rows_cols(Rows(20), Columns(20));
+            let georef =
+                GeoReference::with_top_left_origin("", raster_size, Point::new(0.0, 0.0), CellSize::square(1.0), Option::<f64>::None);
+            let (_, mask) = write_masked_tiff(&path, &georef, TILE_SIZE, MaskKind::Alpha)?;
+
+            let mut reader = GeoTiffReader::from_file(&path)?;
+            assert_eq!(reader.metadata().alpha_band, BandIndex::new(2));
+            assert!(reader.metadata().mask.is_none());
+            let alpha = reader.read_raster_band_as::<u8, GeoReference>(BandIndex::new(2).unwrap())?;
+            assert_eq!(alpha.as_ref(), mask.as_slice());
+
+            Ok(())
+        }
+
+        #[test]
+        fn read_masked_web_tiles() -> Result<()> {
+            const ZOOM: i32 = 10;
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+
+            // 2x2 tiles at the zoom level, the web tiles match the tiff tiles
+            let top_left_tile = Tile { z: ZOOM, x: 523, y: 343 };
+            let raster_size = RasterSize::with_rows_cols(Rows(2 * WEB_TILE_SIZE as i32), Columns(2 * WEB_TILE_SIZE as i32));
+            let georef = GeoReference::with_top_left_origin(
+                "EPSG:3857",
+                raster_size,
+                crs::lat_lon_to_web_mercator(top_left_tile.upper_left()),
+                CellSize::square(Tile::pixel_size_at_zoom_level(ZOOM, WEB_TILE_SIZE as u32)),
+                Option::<f64>::None,
+            );
+
+            for kind in [MaskKind::Internal(1), MaskKind::Alpha] {
+                let path = tmp.path().join("masked_cog.tif");
+                let (data, mask) = write_masked_tiff(&path, &georef, WEB_TILE_SIZE, kind)?;
+                let expected = masked(&data, &mask);
+
+                let cog = WebTilesReader::new(GeoTiffReader::from_file(&path)?.metadata().clone())?;
+                let mut file = std::fs::File::open(&path)?;
+                for (tile_row, tile_col) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                    let tile = Tile {
+                        z: ZOOM,
+                        x: top_left_tile.x + tile_col,
+                        y: top_left_tile.y + tile_row,
+                    };
+
+                    let tile_data = cog
+                        .read_tile_data_as::<u8>(&tile, FIRST_BAND, &mut file)?
+                        .expect("tile should be available");
+                    let expected_tile: Vec<u8> = expected
+                        .chunks(raster_size.cols.count() as usize)
+                        .skip(tile_row as usize * WEB_TILE_SIZE)
+                        .take(WEB_TILE_SIZE)
+                        .flat_map(|row| {
+                            row[tile_col as usize * WEB_TILE_SIZE..(tile_col as usize + 1) * WEB_TILE_SIZE]
+                                .iter()
+                                .copied()
+                        })
+                        .collect();
+                    assert_eq!(tile_data.as_slice(), expected_tile.as_slice());
+                    assert_eq!(tile_data.metadata().nodata(), Some(u8::NODATA as f64));
+
+                    let (mask_source, mask_band) = cog.mask_tile_source(&tile).expect("mask tile should be available");
+                    let mask_location = match mask_source {
+                        crate::cog::TileSource::Aligned(location) => *location,
+                        crate::cog::TileSource::MultiBandAligned(locations) => locations[mask_band.get() - 1],
+                        _ => panic!("Expected an aligned mask tile"),
+                    };
+                    let data_location = match cog.tile_source(&tile) {
+                        Some(crate::cog::TileSource::Aligned(location)) => *location,
+                        Some(crate::cog::TileSource::MultiBandAligned(locations)) => locations[0],
+                        _ => panic!("Expected an aligned tile"),
+                    };
+
+                    let mut mask_chunk = vec![0; mask_location.size as usize];
+                    crate::geotiff::io::read_chunk(&mask_location, &mut file, &mut mask_chunk)?;
+                    let mut data_chunk = vec![0; data_location.size as usize];
+                    crate::geotiff::io::read_chunk(&data_location, &mut file, &mut data_chunk)?;
+                    let parsed = cog.parse_tile_data(cog.tile_source(&tile).unwrap(), FIRST_BAND, &[&data_chunk])?;
+                    let parsed = cog.apply_tile_mask(parsed, FIRST_BAND, mask_source, &[&mask_chunk])?;
+                    assert_eq!(parsed.as_densearray_ref::<u8>().as_slice(), expected_tile.as_slice());
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    mod parallel {
+        use std::path::Path;
+
+        use inf::allocate::AlignedVecUnderConstruction;
+
+        use crate::{
+            Array as _, CellSize, Columns, GeoReference, Point, RasterSize, Result, Rows,
+            geotiff::{ChunkDecoding, FIRST_BAND, GeoTiffReader, write_geotiff},
+            raster::{Compression, GeoTiffWriteOptions, Predictor, TiffChunkType},
+        };
+
+        const NODATA: f64 = -1.0;
+
+        /// The first 300 rows only contain nodata so they are written as sparse chunks
+        fn write_test_tiff(path: &Path, chunk_type: TiffChunkType) -> Result<(GeoReference, Vec<f32>)> {
+            let raster_size = RasterSize::with_rows_cols(Rows(700), Columns(517));
+            let geo_reference = GeoReference::with_top_left_origin(
+                "",
+                raster_size,
+                Point::new(22000.0, 245000.0),
+                CellSize::square(100.0),
+                Some(NODATA),
+            );
+            let data: Vec<f32> = (0..raster_size.cell_count())
+                .map(|i| if i < 300 * 517 { NODATA as f32 } else { (i % 1000) as f32 * 0.5 })
+                .collect();
+
+            let options = GeoTiffWriteOptions {
+                chunk_type,
+                compression: Some(Compression::Zstd),
+                predictor: Some(Predictor::FloatingPoint),
+                sparse_ok: true,
+            };
+            write_geotiff(path, &geo_reference, &data, &options)?;
+
+            Ok((geo_reference, data))
+        }
+
+        /// Returns the bit patterns of the values so nan values can be compared
+        fn read_region(reader: &mut GeoTiffReader, region: &GeoReference) -> Result<Vec<u32>> {
+            let mut buffer = AlignedVecUnderConstruction::<f32>::new(region.raster_size().cell_count());
+            reader.read_band_region_into_buffer::<f32, GeoReference>(FIRST_BAND, region, buffer.as_uninit_slice_mut())?;
+            Ok(unsafe { buffer.assume_init() }.iter().map(|value| value.to_bits()).collect())
+        }
+
+        fn compare_parallel_decoding(chunk_type: TiffChunkType) -> Result<()> {
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let path = tmp.path().join("parallel.tif");
+            let (geo_reference, data) = write_test_tiff(&path, chunk_type)?;
+
+            let mut sequential = GeoTiffReader::from_file(&path)?;
+            let mut parallel = GeoTiffReader::from_file(&path)?.with_chunk_decoding(ChunkDecoding::Parallel);
+
+            let raster = parallel.read_raster_band_as::<f32, GeoReference>(FIRST_BAND)?;
+            assert_eq!(raster, sequential.read_raster_band_as::<f32, GeoReference>(FIRST_BAND)?);
+            assert_eq!(raster.metadata(), &geo_reference);
+            assert!(raster.as_slice().iter().zip(&data).all(|(&value, &expected)| {
+                if expected == NODATA as f32 {
+                    value.is_nan()
+                } else {
+                    value == expected
+                }
+            }));
+
+            let cell_size = CellSize::square(100.0);
+            let regions = [
+                // Within the raster extent, overlapping with the sparse chunks
+                GeoReference::with_top_left_origin(
+                    "",
+                    RasterSize::with_rows_cols(Rows(400), Columns(300)),
+                    Point::new(22000.0 + 13.0 * 100.0, 245000.0 - 220.0 * 100.0),
+                    cell_size,
+                    Some(NODATA),
+                ),
+                // Partially outside the raster extent
+                GeoReference::with_top_left_origin(
+                    "",
+                    RasterSize::with_rows_cols(Rows(150), Columns(200)),
+                    Point::new(22000.0 + 450.0 * 100.0, 245000.0 - 610.0 * 100.0),
+                    cell_size,
+                    Some(NODATA),
+                ),
+            ];
+
+            for region in &regions {
+                let region_data = read_region(&mut parallel, region)?;
+                assert_eq!(region_data, read_region(&mut sequential, region)?, "{chunk_type:?}");
+                assert!(region_data.iter().any(|&value| f32::from_bits(value) > 0.0));
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn parallel_decoding_tiled() -> Result<()> {
+            compare_parallel_decoding(TiffChunkType::Tiled)
+        }
+
+        #[test]
+        fn parallel_decoding_striped() -> Result<()> {
+            compare_parallel_decoding(TiffChunkType::Striped)
+        }
+    }
+
+    mod projection {
+        use std::fs::File;
+
+        use crate::{
+            CellSize, GeoReference, Point, RasterSize, Result,
+            geotiff::{
+                ChunkDataLayout, GeoTiffMetadata,
+                encoder::{ImageFileDirectory, TagValue, TiffEncodeOptions, write_tiff},
+                projectioninfo::geokey,
+                writer::{add_geo_tags, add_image_structure_tags, encode_chunks},
+            },
+        };
+        use tiff::tags::Tag;
+
+        #[test]
+        fn read_user_defined_projection_pixel_is_point() -> Result<()> {
+            let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+            let output = tmp.path().join("point.tif");
+            let raster_size = RasterSize::square(20);
+            let geo_reference = GeoReference::with_top_left_origin(
+                "",
+                raster_size,
+                Point::new(640000.0, 680000.0),
+                CellSize::square(100.0),
+                Some(255.0),
+            );
+            let data: Vec<u8> = (0..raster_size.cell_count()).map(|i| (i % 200) as u8).collect();
+            let layout = ChunkDataLayout::Striped(raster_size.rows.count() as u32);
+
+            let mut ifd = ImageFileDirectory::new(layout);
+            add_image_structure_tags::<u8>(&mut ifd, raster_size, None, None)?;
+            add_geo_tags(&mut ifd, &geo_reference)?;
+
+            // Belgian Lambert 2008 (EPSG:3812) as a user-defined projection, the tie point refers to the center of the upper left cell
+            let (lat_1, lat_2, lat_0, lon_0) = (49.833333333333336, 51.166666666666664, 50.797815, 4.359215833333333);
+            let keys: [[u16; 4]; 11] = [
+                [geokey::MODEL_TYPE, 0, 1, 1],
+                [geokey::RASTER_TYPE, 0, 1, geokey::RASTER_PIXEL_IS_POINT],
+                [geokey::GEOGRAPHIC_TYPE, 0, 1, 4258],
+                [geokey::PROJECTED_TYPE, 0, 1, geokey::USER_DEFINED],
+                [geokey::PROJ_COORD_TRANS, 0, 1, 8],
+                [geokey::PROJ_STD_PARALLEL1, 34736, 1, 0],
+                [geokey::PROJ_STD_PARALLEL2, 34736, 1, 1],
+                [geokey::PROJ_FALSE_ORIGIN_LAT, 34736, 1, 2],
+                [geokey::PROJ_FALSE_ORIGIN_LONG, 34736, 1, 3],
+                [geokey::PROJ_FALSE_ORIGIN_EASTING, 34736, 1, 4],
+                [geokey::PROJ_FALSE_ORIGIN_NORTHING, 34736, 1, 5],
+            ];
+            let mut key_directory = vec![1, 1, 0, keys.len() as u16];
+            key_directory.extend(keys.iter().flatten());
+            ifd.set_tag(Tag::GeoKeyDirectoryTag, TagValue::Short(key_directory));
+            ifd.set_tag(
+                Tag::GeoDoubleParamsTag,
+                TagValue::Double(vec![lat_1, lat_2, lat_0, lon_0, 649328.0, 665262.0]),
+            );
+            ifd.chunks = encode_chunks(&data, raster_size, layout, geo_reference.nodata(), None, None, false)?;
+            write_tiff(&[ifd], &TiffEncodeOptions::default(), &mut File::create(&output)?)?;
+
+            let meta = GeoTiffMetadata::from_file(&output)?;
+            assert_eq!(meta.geo_reference.top_left(), geo_reference.top_left() - Point::new(50.0, -50.0));
+            assert_eq!(meta.geo_reference.cell_size(), geo_reference.cell_size());
+            assert_eq!(
+                meta.geo_reference.projection(),
+                format!(
+                    "+proj=lcc +lat_1={lat_1} +lat_2={lat_2} +lat_0={lat_0} +lon_0={lon_0} +x_0=649328 +y_0=665262 +ellps=GRS80 +towgs84=0,0,0,0,0,0,0 +units=m +no_defs"
+                )
+            );
+
+            Ok(())
+        }
+    }
 }
@@ -10,8 +10,8 @@ use crate::{
     Array as _, ArrayInterop as _, ArrayMetadata as _, ArrayNum, Cell, Columns, DenseArray, RasterMetadata, RasterSize, RasterWindow,
     Result, Rows,
     geotiff::{
-        TiffChunkLocation,
-        io::{parse_chunk_data_into_buffer, read_chunk},
+        BandIndex, GeoTiffMetadata, TiffChunkLocation,
+        io::{parse_band_chunk_data_into_buffer, parse_chunk_data_into_buffer, read_chunk},
     },
     raster::{Compression, Predictor, intersection::CutOut},
 };
@@ -50,7 +50,9 @@ pub fn parse_tile_data<T: ArrayNum>(
     let mut meta = RasterMetadata::sized_with_nodata(raster_size, nodata);
     let mut tile_data = AlignedVecUnderConstruction::new(raster_size.cell_count());
 
-    parse_tile_data_into_slice(tile_size, compression, predictor, chunk_data, unsafe { tile_data.as_slice_mut() })?;
+    parse_tile_data_into_slice(tile_size, nodata, compression, predictor, chunk_data, unsafe {
+        tile_data.as_slice_mut()
+    })?;
 
     let mut arr = DenseArray::<T>::new_init_nodata(meta, unsafe { tile_data.assume_init() })?;
     if let Some(cutout) = cutout {
@@ -65,15 +67,34 @@ pub fn parse_tile_data<T: ArrayNum>(
     Ok(arr)
 }
 
+/// Parses the tile data of the requested band, taking the interleaving of the tiff into account.
+#[simd_bounds]
+pub fn parse_band_tile_data<T: ArrayNum>(
+    meta: &GeoTiffMetadata,
+    band_index: BandIndex,
+    tile_size: u32,
+    nodata: Option<f64>,
+    chunk_data: &[u8],
+) -> Result<DenseArray<T>> {
+    let raster_size = RasterSize::square(tile_size as i32);
+    let mut tile_data = AlignedVecUnderConstruction::new(raster_size.cell_count());
+
+    parse_band_chunk_data_into_buffer(meta, band_index, tile_size, chunk_data, unsafe { tile_data.as_slice_mut() })?;
+    DenseArray::<T>::new_init_nodata(RasterMetadata::sized_with_nodata(raster_size, nodata), unsafe {
+        tile_data.assume_init()
+    })
+}
+
 #[simd_bounds]
 pub fn parse_tile_data_into_slice<T: ArrayNum>(
     tile_size: u32,
+    nodata: Option<f64>,
     compression: Option<Compression>,
     predictor: Option<Predictor>,
     chunk_data: &[u8],
     tile_data: &mut [T],
 ) -> Result<()> {
     assert_eq!(tile_data.len(), tile_size as usize * tile_size as usize);
-    parse_chunk_data_into_buffer(tile_size, compression, predictor, chunk_data, tile_data)?;
+    parse_chunk_data_into_buffer(tile_size, nodata, compression, predictor, chunk_data, tile_data)?;
     Ok(())
 }
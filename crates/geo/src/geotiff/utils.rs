@@ -1,25 +1,29 @@
-use std::fs::File;
+use std::io::{Read, Seek};
 
 use crate::{
-    Array, ArrayNum, Cell, CellSize, Columns, DenseArray, GeoReference, RasterSize, RasterWindow, Result, Rows,
-    densearrayiterators::{DenserRasterWindowIterator, DenserRasterWindowIteratorMut},
-    geotiff::{GeoTiffMetadata, TiffChunkLocation, io, tileio},
+    Array, ArrayNum, CellSize, Columns, DenseArray, Error, GeoReference, RasterSize, Result, Rows, densearrayutil,
+    geotiff::{BandIndex, ChunkDecoding, GeoTiffMetadata, TiffChunkLocation, io},
     raster::intersection::{CutOut, intersect_georeference},
 };
 
+use inf::cast;
 use simd_macro::simd_bounds;
 
 #[cfg(feature = "simd")]
 const LANES: usize = inf::simd::LANES;
 
+/// Number of chunks per thread that are read before they are decoded in parallel
+#[cfg(feature = "rayon")]
+const CHUNKS_PER_THREAD_IN_BATCH: usize = 4;
+
 macro_rules! impl_horizontal_unpredictable_for_int {
     ($($t:ty),*) => {
         $(
             paste::paste! {
-                fn [<unpredict_horizontal_ $t>](data: &mut [$t], row_size: u32) {
-                    for row in data.chunks_mut(row_size as usize) {
-                        for i in 1..row.len() {
-                            row[i] = row[i].wrapping_add(row[i - 1]);
+                fn [<unpredict_horizontal_ $t>](data: &mut [$t], row_size: usize, stride: usize) {
+                    for row in data.chunks_mut(row_size) {
+                        for i in stride..row.len() {
+                            row[i] = row[i].wrapping_add(row[i - stride]);
                         }
                     }
                 }
@@ -32,10 +36,10 @@ macro_rules! impl_horizontal_unpredictable_for_fp {
     ($($t:ty),*) => {
         $(
             paste::paste! {
-                fn [<unpredict_horizontal_ $t>](data: &mut [$t], row_size: u32) {
-                    for row in data.chunks_mut(row_size as usize) {
-                        for i in 1..row.len() {
-                            row[i] += row[i - 1];
+                fn [<unpredict_horizontal_ $t>](data: &mut [$t], row_size: usize, stride: usize) {
+                    for row in data.chunks_mut(row_size) {
+                        for i in stride..row.len() {
+                            row[i] += row[i - stride];
                         }
                     }
                 }
@@ -47,58 +51,140 @@ macro_rules! impl_horizontal_unpredictable_for_fp {
 impl_horizontal_unpredictable_for_int!(u8, u16, u32, u64, i8, i16, i32, i64);
 impl_horizontal_unpredictable_for_fp!(f32, f64);
 
-pub fn unpredict_horizontal<T: ArrayNum + Copy>(data: &mut [T], row_size: u32) {
+/// Undo the horizontal predictor, for pixel interleaved data every value is the difference with the same sample of the previous pixel.
+/// `row_size` is the number of pixels in a row.
+pub fn unpredict_horizontal<T: ArrayNum + Copy>(data: &mut [T], row_size: u32, samples_per_pixel: usize) {
+    let row_values = row_size as usize * samples_per_pixel;
+    let stride = samples_per_pixel;
+
     // Macro based dispatch to avoid an extra trait bound on T which pollutes the entire call stack.
     match T::TYPE {
-        crate::ArrayDataType::Uint8 => unpredict_horizontal_u8(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Uint16 => unpredict_horizontal_u16(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Uint32 => unpredict_horizontal_u32(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Uint64 => unpredict_horizontal_u64(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Int8 => unpredict_horizontal_i8(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Int16 => unpredict_horizontal_i16(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Int32 => unpredict_horizontal_i32(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Int64 => unpredict_horizontal_i64(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Float32 => unpredict_horizontal_f32(bytemuck::cast_slice_mut(data), row_size),
-        crate::ArrayDataType::Float64 => unpredict_horizontal_f64(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Uint8 => unpredict_horizontal_u8(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Uint16 => unpredict_horizontal_u16(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Uint32 => unpredict_horizontal_u32(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Uint64 => unpredict_horizontal_u64(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Int8 => unpredict_horizontal_i8(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Int16 => unpredict_horizontal_i16(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Int32 => unpredict_horizontal_i32(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Int64 => unpredict_horizontal_i64(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Float32 => unpredict_horizontal_f32(bytemuck::cast_slice_mut(data), row_values, stride),
+        crate::ArrayDataType::Float64 => unpredict_horizontal_f64(bytemuck::cast_slice_mut(data), row_values, stride),
     }
 }
 
-fn decode_delta_bytes(data: &mut [u8], bytes_per_pixel: usize, row_size: u32) {
-    unpredict_horizontal_u8(data, bytes_per_pixel as u32 * row_size);
+/// Undo the floating point predictor: the bytes of every row are stored as big endian byte planes
+/// with a horizontal differencing of the bytes (using the samples per pixel as stride)
+fn unpredict_fp<const N: usize>(bytes: &mut [u8], row_size: u32, samples_per_pixel: usize) -> impl Iterator<Item = [u8; N]> {
+    let row_values = row_size as usize * samples_per_pixel;
+    debug_assert_eq!(bytes.len() % (row_values * N), 0);
+    unpredict_horizontal_u8(bytes, row_values * N, samples_per_pixel);
+
+    bytes
+        .chunks(row_values * N)
+        .flat_map(move |row| (0..row_values).map(move |i| std::array::from_fn(|byte_index| row[byte_index * row_values + i])))
 }
 
-pub fn unpredict_fp32(data: &mut [f32], row_size: u32) {
+pub fn unpredict_fp32(data: &mut [f32], row_size: u32, samples_per_pixel: usize) {
     let mut bytes: Vec<u8> = bytemuck::cast_slice(data).to_vec();
+    for (value, value_bytes) in data.iter_mut().zip(unpredict_fp(&mut bytes, row_size, samples_per_pixel)) {
+        *value = f32::from_be_bytes(value_bytes);
+    }
+}
+
+pub fn unpredict_fp64(data: &mut [f64], row_size: u32, samples_per_pixel: usize) {
+    let mut bytes: Vec<u8> = bytemuck::cast_slice(data).to_vec();
+    for (value, value_bytes) in data.iter_mut().zip(unpredict_fp(&mut bytes, row_size, samples_per_pixel)) {
+        *value = f64::from_be_bytes(value_bytes);
+    }
+}
+
+macro_rules! impl_horizontal_predictable_for_int {
+    ($($t:ty),*) => {
+        $(
+            paste::paste! {
+                fn [<predict_horizontal_ $t>](data: &mut [$t], row_size: u32) {
+                    for row in data.chunks_mut(row_size as usize) {
+                        for i in (1..row.len()).rev() {
+                            row[i] = row[i].wrapping_sub(row[i - 1]);
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_horizontal_predictable_for_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// Inverse of `unpredict_horizontal`, only integer types are supported, the horizontal predictor is not intended for floating point data.
+pub fn predict_horizontal<T: ArrayNum + Copy>(data: &mut [T], row_size: u32) -> Result<()> {
+    match T::TYPE {
+        crate::ArrayDataType::Uint8 => predict_horizontal_u8(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Uint16 => predict_horizontal_u16(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Uint32 => predict_horizontal_u32(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Uint64 => predict_horizontal_u64(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Int8 => predict_horizontal_i8(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Int16 => predict_horizontal_i16(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Int32 => predict_horizontal_i32(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Int64 => predict_horizontal_i64(bytemuck::cast_slice_mut(data), row_size),
+        crate::ArrayDataType::Float32 | crate::ArrayDataType::Float64 => {
+            return Err(Error::InvalidArgument(
+                "Horizontal predictor is not supported for floating point data".into(),
+            ));
+        }
+    }
 
-    debug_assert_eq!(bytes.len() % row_size as usize, 0);
-    decode_delta_bytes(&mut bytes, std::mem::size_of::<f32>(), row_size);
+    Ok(())
+}
 
-    let tile_size = row_size as usize;
-    for (row_nr, row) in bytes.chunks_mut(std::mem::size_of::<f32>() * tile_size).enumerate() {
-        for i in 0..tile_size {
-            data[row_nr * tile_size + i] = f32::from_be_bytes([row[i], row[tile_size + i], row[tile_size * 2 + i], row[tile_size * 3 + i]]);
+/// Inverse of `unpredict_fp32` and `unpredict_fp64`
+/// The bytes of every row are split in big endian byte planes, followed by a horizontal differencing of the bytes
+pub fn predict_fp<const N: usize>(values: impl Iterator<Item = [u8; N]>, value_count: usize, row_size: u32) -> Vec<u8> {
+    let row_size = row_size as usize;
+    let mut bytes = vec![0u8; value_count * N];
+
+    for (index, value_bytes) in values.enumerate() {
+        let row_start = (index / row_size) * row_size * N;
+        let col = index % row_size;
+        for (byte_index, byte) in value_bytes.into_iter().enumerate() {
+            bytes[row_start + byte_index * row_size + col] = byte;
         }
     }
+
+    predict_horizontal_u8(&mut bytes, (row_size * N) as u32);
+    bytes
 }
 
-pub fn unpredict_fp64(data: &mut [f64], row_size: u32) {
-    let mut bytes: Vec<u8> = bytemuck::cast_slice(data).to_vec();
-    debug_assert_eq!(bytes.len() % row_size as usize, 0);
-    decode_delta_bytes(&mut bytes, std::mem::size_of::<f64>(), row_size);
-
-    let tile_size = row_size as usize;
-    for (row_nr, row) in bytes.chunks_mut(std::mem::size_of::<f64>() * tile_size).enumerate() {
-        for i in 0..tile_size {
-            data[row_nr * tile_size + i] = f64::from_be_bytes([
-                row[i],
-                row[tile_size + i],
-                row[tile_size * 2 + i],
-                row[tile_size * 3 + i],
-                row[tile_size * 4 + i],
-                row[tile_size * 5 + i],
-                row[tile_size * 6 + i],
-                row[tile_size * 7 + i],
-            ]);
+/// Unpacks samples stored with less than 8 bits per sample into a byte per sample, only the samples of one band are unpacked.
+/// The samples are packed starting from the most significant bit and every row starts at a byte boundary.
+pub fn unpack_sub_byte_samples(
+    packed: &[u8],
+    bits_per_sample: u32,
+    samples_per_pixel: usize,
+    sample_offset: usize,
+    output: &mut [u8],
+    row_size: usize,
+) {
+    let bits = bits_per_sample as usize;
+    let mask = (1u16 << bits) - 1;
+    let packed_row_size = (row_size * samples_per_pixel * bits).div_ceil(8);
+
+    for (packed_row, output_row) in packed.chunks(packed_row_size).zip(output.chunks_mut(row_size)) {
+        for (col, value) in output_row.iter_mut().enumerate() {
+            // Samples with a bit count that is not a power of 2 can span 2 bytes
+            let bit_pos = (col * samples_per_pixel + sample_offset) * bits;
+            let byte_index = bit_pos / 8;
+            let word = (packed_row[byte_index] as u16) << 8 | packed_row.get(byte_index + 1).copied().unwrap_or(0) as u16;
+            *value = ((word >> (16 - bits - bit_pos % 8)) & mask) as u8;
+        }
+    }
+}
+
+/// Sets the cells with a zero mask value to nodata
+pub fn apply_validity_mask<T: ArrayNum, M: ArrayNum>(data: &mut [T], mask: &[M], nodata: T) {
+    for (value, &mask_value) in data.iter_mut().zip(mask) {
+        if mask_value == M::zero() {
+            *value = nodata;
         }
     }
 }
@@ -118,105 +204,345 @@ pub fn change_georef_cell_size(geo_reference: &GeoReference, cell_size: CellSize
     result
 }
 
+/// A chunk of the tiff and the part of its decoded data that is merged into the destination buffer
+#[derive(Debug, Clone)]
+pub struct ChunkCutOut {
+    pub location: TiffChunkLocation,
+    /// Size of the decoded chunk data
+    pub chunk_size: RasterSize,
+    pub cutout: CutOut,
+}
+
+/// The cutouts of all the tiles of a tiled raster, the tiles at the right and bottom edge are cut off at the raster bounds
+pub fn tile_cutouts(raster_size: RasterSize, chunk_locations: &[TiffChunkLocation], tile_size: u32) -> Vec<ChunkCutOut> {
+    let tile_size = tile_size as i32;
+    let tiles_per_row = (raster_size.cols.count() as usize).div_ceil(tile_size as usize);
+
+    chunk_locations
+        .iter()
+        .enumerate()
+        .map(|(chunk_index, location)| {
+            let row_offset = (chunk_index / tiles_per_row) as i32 * tile_size;
+            let col_offset = (chunk_index % tiles_per_row) as i32 * tile_size;
+            ChunkCutOut {
+                location: *location,
+                chunk_size: RasterSize::square(tile_size),
+                cutout: CutOut {
+                    src_col_offset: 0,
+                    src_row_offset: 0,
+                    dst_col_offset: col_offset,
+                    dst_row_offset: row_offset,
+                    rows: tile_size.min(raster_size.rows.count() - row_offset),
+                    cols: tile_size.min(raster_size.cols.count() - col_offset),
+                },
+            }
+        })
+        .collect()
+}
+
+/// The cutouts of all the strips of a striped raster, the final strip only contains the remaining rows
+pub fn strip_cutouts(raster_size: RasterSize, chunk_locations: &[TiffChunkLocation], rows_per_strip: u32) -> Vec<ChunkCutOut> {
+    let rows_per_strip = rows_per_strip as i32;
+
+    chunk_locations
+        .iter()
+        .enumerate()
+        .map(|(strip_index, location)| {
+            let row_offset = strip_index as i32 * rows_per_strip;
+            let rows = rows_per_strip.min(raster_size.rows.count() - row_offset);
+            ChunkCutOut {
+                location: *location,
+                chunk_size: RasterSize::with_rows_cols(Rows(rows), raster_size.cols),
+                cutout: CutOut {
+                    src_col_offset: 0,
+                    src_row_offset: 0,
+                    dst_col_offset: 0,
+                    dst_row_offset: row_offset,
+                    rows,
+                    cols: raster_size.cols.count(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Merges the tiles that intersect with the provided buffer, cells of sparse tiles are set to nodata.
 #[simd_bounds]
 pub fn merge_tile_chunks_into_buffer<T: ArrayNum>(
     meta: &GeoTiffMetadata,
+    band_index: BandIndex,
     geo_reference: &GeoReference, // The georeference of the provided buffer
-    tile_size: u32,
     tile_sources: &[(TiffChunkLocation, CutOut)],
-    tiff_file: &mut File,
+    decoding: ChunkDecoding,
+    tiff_file: &mut (impl Read + Seek),
     buffer: &mut [T],
 ) -> Result<()> {
-    let nodata = geo_reference.nodata();
-
-    for (cog_location, cutout) in tile_sources {
-        if cog_location.is_sparse() {
-            continue; // Skip sparse tiles, they are already filled with nodata
-        }
-
-        let tile_cutout = tileio::read_tile_data::<T>(cog_location, tile_size, nodata, meta.compression, meta.predictor, tiff_file)?;
-        merge_tile_chunk_into_buffer(cutout, &tile_cutout, buffer, geo_reference.raster_size());
-    }
-
-    Ok(())
+    let tile_size = meta.chunk_row_length();
+    let nodata = cast::option::<T>(geo_reference.nodata());
+    let sparse_value = cast::option::<T>(meta.geo_reference.nodata()).unwrap_or(T::NODATA);
+
+    let chunks: Vec<ChunkCutOut> = tile_sources
+        .iter()
+        .map(|(location, cutout)| ChunkCutOut {
+            location: *location,
+            chunk_size: RasterSize::square(tile_size as i32),
+            cutout: cutout.clone(),
+        })
+        .collect();
+
+    merge_chunks_into_buffer(
+        &chunks,
+        geo_reference.raster_size(),
+        sparse_value,
+        decoding,
+        tiff_file,
+        buffer,
+        |chunk_data, tile| {
+            io::parse_band_chunk_data_into_buffer(meta, band_index, tile_size, chunk_data, tile)?;
+            densearrayutil::init_nodata(tile, nodata);
+            Ok(())
+        },
+    )
 }
 
+/// Copies the cutout of a decoded tile into the buffer
 pub fn merge_tile_chunk_into_buffer<T: ArrayNum>(
     cutout: &CutOut,
     tiff_tile: &DenseArray<T>,
     buffer: &mut [T],
     buffer_size: RasterSize, // The size of the provided buffer
 ) {
-    let dest_window = RasterWindow::new(
-        Cell::from_row_col(cutout.dst_row_offset, cutout.dst_col_offset),
-        RasterSize::with_rows_cols(Rows(cutout.rows), Columns(cutout.cols)),
-    );
-
-    let src_window = RasterWindow::new(
-        Cell::from_row_col(cutout.src_row_offset, cutout.src_col_offset),
-        RasterSize::with_rows_cols(Rows(cutout.rows), Columns(cutout.cols)),
+    copy_cutout_rows(
+        cutout,
+        tiff_tile.as_slice(),
+        tiff_tile.columns().count() as usize,
+        buffer,
+        buffer_size.cols.count() as usize,
+        0,
     );
-
-    let dest_iterator = DenserRasterWindowIteratorMut::from_buffer(buffer, buffer_size, dest_window);
-    for (dest, source) in dest_iterator.zip(tiff_tile.iter_window(src_window)) {
-        *dest = source;
-    }
 }
 
+/// Merges the strips that intersect with the provided buffer, cells of sparse strips are set to nodata.
 #[simd_bounds]
 pub fn merge_strip_chunks_into_buffer<T: ArrayNum>(
     meta: &GeoTiffMetadata,
+    band_index: BandIndex,
     geo_reference: &GeoReference, // The georeference of the provided buffer
-    rows_per_strip: u32,
-    tile_sources: &[(TiffChunkLocation, GeoReference, bool)],
-    tiff_file: &mut File,
+    tile_sources: &[(TiffChunkLocation, GeoReference)],
+    decoding: ChunkDecoding,
+    tiff_file: &mut (impl Read + Seek),
     buffer: &mut [T],
 ) -> Result<()> {
-    let cols_in_source_raster = meta.geo_reference.raster_size().cols.count() as usize;
-    let mut strip_buffer = vec![T::zero(); rows_per_strip as usize * cols_in_source_raster];
+    let cols_in_source_raster = meta.geo_reference.raster_size().cols.count() as u32;
+    let sparse_value = cast::option::<T>(meta.geo_reference.nodata()).unwrap_or(T::NODATA);
 
-    for (chunk_location, chunk_geo_reference, final_strip_in_file) in tile_sources {
-        if chunk_location.is_sparse() {
-            continue; // Skip sparse chunks, they are already filled with nodata
+    let mut chunks = Vec::with_capacity(tile_sources.len());
+    for (location, chunk_geo_reference) in tile_sources {
+        let cutout = intersect_georeference(chunk_geo_reference, geo_reference)?;
+        if cutout.rows > 0 && cutout.cols > 0 {
+            chunks.push(ChunkCutOut {
+                location: *location,
+                chunk_size: chunk_geo_reference.raster_size(),
+                cutout,
+            });
         }
+    }
 
-        let cutout = intersect_georeference(chunk_geo_reference, geo_reference)?;
-        let strip_length = if *final_strip_in_file {
-            (cutout.rows as usize) * cols_in_source_raster
-        } else {
-            strip_buffer.len()
-        };
+    merge_chunks_into_buffer(
+        &chunks,
+        geo_reference.raster_size(),
+        sparse_value,
+        decoding,
+        tiff_file,
+        buffer,
+        |chunk_data, strip| io::parse_band_chunk_data_into_buffer(meta, band_index, cols_in_source_raster, chunk_data, strip),
+    )
+}
 
-        if strip_length == 0 {
+/// Reads the chunks and merges the cutouts of the decoded data into the buffer.
+/// `decode_chunk` decodes the raw chunk data into a buffer with the size of the chunk.
+/// The cutouts of sparse chunks are filled with the `sparse_value`.
+pub fn merge_chunks_into_buffer<T: ArrayNum>(
+    chunks: &[ChunkCutOut],
+    buffer_size: RasterSize, // The size of the provided buffer
+    sparse_value: T,
+    decoding: ChunkDecoding,
+    tiff_file: &mut (impl Read + Seek),
+    buffer: &mut [T],
+    decode_chunk: impl Fn(&[u8], &mut [T]) -> Result<()> + Sync,
+) -> Result<()> {
+    debug_assert_eq!(buffer.len(), buffer_size.cell_count());
+
+    match decoding {
+        ChunkDecoding::Sequential => merge_chunks_sequential(chunks, buffer_size, sparse_value, tiff_file, buffer, decode_chunk),
+        #[cfg(feature = "rayon")]
+        ChunkDecoding::Parallel => merge_chunks_parallel(chunks, buffer_size, sparse_value, tiff_file, buffer, decode_chunk),
+        #[cfg(not(feature = "rayon"))]
+        ChunkDecoding::Parallel => merge_chunks_sequential(chunks, buffer_size, sparse_value, tiff_file, buffer, decode_chunk),
+    }
+}
+
+fn merge_chunks_sequential<T: ArrayNum>(
+    chunks: &[ChunkCutOut],
+    buffer_size: RasterSize,
+    sparse_value: T,
+    tiff_file: &mut (impl Read + Seek),
+    buffer: &mut [T],
+    decode_chunk: impl Fn(&[u8], &mut [T]) -> Result<()>,
+) -> Result<()> {
+    let buffer_cols = buffer_size.cols.count() as usize;
+    let mut chunk_data = Vec::new();
+    let mut decoded = Vec::new();
+
+    for chunk in chunks {
+        if chunk.location.is_sparse() {
+            fill_cutout_rows(&chunk.cutout, sparse_value, buffer, buffer_cols, 0);
             continue;
         }
 
-        io::read_chunk_data_into_buffer(
-            chunk_location,
-            rows_per_strip,
-            geo_reference.nodata(),
-            meta.compression,
-            meta.predictor,
-            tiff_file,
-            &mut strip_buffer[..strip_length],
-        )?;
-
-        let dest_window = RasterWindow::new(
-            Cell::from_row_col(cutout.dst_row_offset, cutout.dst_col_offset),
-            RasterSize::with_rows_cols(Rows(cutout.rows), Columns(cutout.cols)),
+        chunk_data.resize(chunk.location.size as usize, 0);
+        io::read_chunk(&chunk.location, tiff_file, &mut chunk_data)?;
+        decoded.resize(chunk.chunk_size.cell_count(), T::zero());
+        decode_chunk(&chunk_data, &mut decoded)?;
+        copy_cutout_rows(
+            &chunk.cutout,
+            &decoded,
+            chunk.chunk_size.cols.count() as usize,
+            buffer,
+            buffer_cols,
+            0,
         );
+    }
 
-        let src_window = RasterWindow::new(
-            Cell::from_row_col(cutout.src_row_offset, cutout.src_col_offset),
-            RasterSize::with_rows_cols(Rows(cutout.rows), Columns(cutout.cols)),
-        );
+    Ok(())
+}
+
+/// The chunks are processed in batches to limit the memory usage: the raw data of the chunks in a batch is read first,
+/// after which the chunks are decoded and merged in parallel.
+/// Chunks that start on the same buffer row are merged by the same task, the row ranges of these groups don't overlap
+/// (the chunks are laid out in a grid) so every task can write its rows of the buffer independently.
+#[cfg(feature = "rayon")]
+fn merge_chunks_parallel<T: ArrayNum>(
+    chunks: &[ChunkCutOut],
+    buffer_size: RasterSize,
+    sparse_value: T,
+    tiff_file: &mut (impl Read + Seek),
+    buffer: &mut [T],
+    decode_chunk: impl Fn(&[u8], &mut [T]) -> Result<()> + Sync,
+) -> Result<()> {
+    use rayon::prelude::*;
 
-        let dest_iterator = DenserRasterWindowIteratorMut::from_buffer(buffer, geo_reference.raster_size(), dest_window);
-        let src_iterator = DenserRasterWindowIterator::from_buffer(&strip_buffer, chunk_geo_reference.raster_size(), src_window);
-        for (dest, source) in dest_iterator.zip(src_iterator) {
-            *dest = source;
+    let buffer_cols = buffer_size.cols.count() as usize;
+
+    let mut row_groups: std::collections::BTreeMap<i32, Vec<&ChunkCutOut>> = std::collections::BTreeMap::new();
+    for chunk in chunks {
+        row_groups.entry(chunk.cutout.dst_row_offset).or_default().push(chunk);
+    }
+
+    // Split the buffer in the row ranges of the groups
+    let mut groups = Vec::with_capacity(row_groups.len());
+    let mut remaining = buffer;
+    let mut remaining_start_row = 0;
+    for (start_row, group_chunks) in row_groups {
+        let start_row = start_row as usize;
+        let end_row = group_chunks
+            .iter()
+            .map(|chunk| (chunk.cutout.dst_row_offset + chunk.cutout.rows) as usize)
+            .max()
+            .unwrap_or(start_row);
+
+        if start_row < remaining_start_row {
+            return Err(Error::Runtime("Overlapping tiff chunks can not be merged in parallel".into()));
+        }
+
+        let (_, rows) = std::mem::take(&mut remaining).split_at_mut((start_row - remaining_start_row) * buffer_cols);
+        let (group_rows, rest) = rows.split_at_mut((end_row - start_row) * buffer_cols);
+        remaining = rest;
+        remaining_start_row = end_row;
+        groups.push((start_row, group_chunks, group_rows));
+    }
+
+    let batch_size = rayon::current_num_threads() * CHUNKS_PER_THREAD_IN_BATCH;
+    let mut groups = groups.as_mut_slice();
+    while !groups.is_empty() {
+        // Take row groups until the batch contains enough chunks to keep all threads busy
+        let mut batch_len = 0;
+        let mut batch_chunk_count = 0;
+        while batch_len < groups.len() && batch_chunk_count < batch_size {
+            batch_chunk_count += groups[batch_len].1.len();
+            batch_len += 1;
+        }
+
+        let (batch, rest) = std::mem::take(&mut groups).split_at_mut(batch_len);
+        groups = rest;
+
+        let mut batch_data = Vec::with_capacity(batch.len());
+        for (_, group_chunks, _) in batch.iter() {
+            let mut group_data = Vec::with_capacity(group_chunks.len());
+            for chunk in group_chunks {
+                let mut chunk_data = vec![0; chunk.location.size as usize];
+                if !chunk.location.is_sparse() {
+                    io::read_chunk(&chunk.location, tiff_file, &mut chunk_data)?;
+                }
+                group_data.push(chunk_data);
+            }
+            batch_data.push(group_data);
         }
+
+        batch
+            .par_iter_mut()
+            .zip(batch_data)
+            .try_for_each(|((start_row, group_chunks, group_rows), group_data)| -> Result<()> {
+                let decoded = group_chunks
+                    .par_iter()
+                    .zip(group_data)
+                    .map(|(chunk, chunk_data)| {
+                        if chunk.location.is_sparse() {
+                            return Ok(None);
+                        }
+
+                        let mut decoded = vec![T::zero(); chunk.chunk_size.cell_count()];
+                        decode_chunk(&chunk_data, &mut decoded)?;
+                        Ok(Some(decoded))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                for (chunk, decoded) in group_chunks.iter().zip(decoded) {
+                    match decoded {
+                        Some(decoded) => copy_cutout_rows(
+                            &chunk.cutout,
+                            &decoded,
+                            chunk.chunk_size.cols.count() as usize,
+                            group_rows,
+                            buffer_cols,
+                            *start_row,
+                        ),
+                        None => fill_cutout_rows(&chunk.cutout, sparse_value, group_rows, buffer_cols, *start_row),
+                    }
+                }
+
+                Ok(())
+            })?;
     }
 
     Ok(())
 }
+
+/// Copies the cutout of the source chunk into the destination buffer, row by row.
+/// `dst_start_row` is the row of the full destination buffer that corresponds to the first row of `dst`.
+fn copy_cutout_rows<T: Copy>(cutout: &CutOut, src: &[T], src_cols: usize, dst: &mut [T], dst_cols: usize, dst_start_row: usize) {
+    let cols = cutout.cols as usize;
+    for row in 0..cutout.rows as usize {
+        let src_start = (cutout.src_row_offset as usize + row) * src_cols + cutout.src_col_offset as usize;
+        let dst_start = (cutout.dst_row_offset as usize + row - dst_start_row) * dst_cols + cutout.dst_col_offset as usize;
+        dst[dst_start..dst_start + cols].copy_from_slice(&src[src_start..src_start + cols]);
+    }
+}
+
+fn fill_cutout_rows<T: Copy>(cutout: &CutOut, value: T, dst: &mut [T], dst_cols: usize, dst_start_row: usize) {
+    let cols = cutout.cols as usize;
+    for row in 0..cutout.rows as usize {
+        let dst_start = (cutout.dst_row_offset as usize + row - dst_start_row) * dst_cols + cutout.dst_col_offset as usize;
+        dst[dst_start..dst_start + cols].fill(value);
+    }
+}
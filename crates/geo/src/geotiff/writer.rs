@@ -0,0 +1,619 @@
+//! Native GeoTIFF writing, no GDAL required.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use inf::cast;
+use tiff::tags::Tag;
+
+use crate::{
+    ArrayDataType, ArrayNum, Error, GeoReference, RasterScale, RasterSize, Result,
+    crs::Epsg,
+    geotiff::{
+        ChunkDataLayout, GdalMetadata,
+        encoder::{self, ImageFileDirectory, TagValue, TiffEncodeOptions},
+        gdalmetadata, io,
+        projectioninfo::{ModelType, geokey},
+    },
+    raster::{Compression, GeoTiffWriteOptions, Predictor, TiffChunkType},
+};
+
+/// Tile size used when writing tiled GeoTIFF files (same as the GDAL default)
+pub const DEFAULT_TILE_SIZE: u32 = 256;
+/// Target size of a strip in bytes used to determine the number of rows per strip (same as the GDAL default)
+const STRIP_SIZE_BYTES: usize = 8192;
+
+const GDAL_METADATA_TAG: u16 = 42112;
+
+/// Writes a single band raster to a GeoTIFF file without relying on GDAL
+pub fn write_geotiff<T: ArrayNum>(path: &Path, geo_reference: &GeoReference, data: &[T], options: &GeoTiffWriteOptions) -> Result<()> {
+    write_geotiff_with_metadata(path, geo_reference, data, options, &GdalMetadata::default())
+}
+
+/// Writes a single band raster to a GeoTIFF file without relying on GDAL, the metadata is stored in the `GDAL_METADATA` tag.
+/// The scale of the geo reference is stored as offset and scale of the first band when the metadata does not contain them.
+pub fn write_geotiff_with_metadata<T: ArrayNum>(
+    path: &Path,
+    geo_reference: &GeoReference,
+    data: &[T],
+    options: &GeoTiffWriteOptions,
+    metadata: &GdalMetadata,
+) -> Result<()> {
+    let layout = match options.chunk_type {
+        TiffChunkType::Tiled => ChunkDataLayout::Tiled(DEFAULT_TILE_SIZE),
+        TiffChunkType::Striped => ChunkDataLayout::Striped(rows_per_strip::<T>(geo_reference.columns().count() as usize)),
+    };
+
+    let mut ifd = ImageFileDirectory::new(layout);
+    add_image_structure_tags::<T>(&mut ifd, geo_reference.raster_size(), options.compression, options.predictor)?;
+    add_geo_tags(&mut ifd, geo_reference)?;
+    let mut metadata = metadata.clone();
+    apply_raster_scale(&mut metadata, geo_reference.scale());
+    if metadata != GdalMetadata::default() {
+        add_gdal_metadata_tag(&mut ifd, &metadata);
+    }
+
+    ifd.chunks = encode_chunks(
+        data,
+        geo_reference.raster_size(),
+        layout,
+        geo_reference.nodata(),
+        options.compression,
+        options.predictor,
+        options.sparse_ok,
+    )?;
+
+    inf::fs::create_directory_for_file(path)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    encoder::write_tiff(&[ifd], &TiffEncodeOptions::default(), &mut writer)?;
+    std::io::Write::flush(&mut writer)?;
+    Ok(())
+}
+
+/// Number of rows per strip so that a strip is about 8KiB
+fn rows_per_strip<T: ArrayNum>(columns: usize) -> u32 {
+    let row_bytes = (columns * std::mem::size_of::<T>()).max(1);
+    (STRIP_SIZE_BYTES / row_bytes).max(1) as u32
+}
+
+fn tiff_compression_code(compression: Option<Compression>) -> u16 {
+    match compression {
+        None => 1,
+        Some(Compression::Lzw) => 5,
+        Some(Compression::Deflate) => 8,
+        Some(Compression::Zstd) => 50000,
+        Some(Compression::PackBits) => 32773,
+        Some(Compression::Jpeg) => 7,
+        Some(Compression::WebP) => 50001,
+        Some(Compression::Lerc | Compression::LercDeflate | Compression::LercZstd) => 34887,
+    }
+}
+
+fn tiff_sample_format(data_type: ArrayDataType) -> u16 {
+    match data_type {
+        ArrayDataType::Uint8 | ArrayDataType::Uint16 | ArrayDataType::Uint32 | ArrayDataType::Uint64 => 1,
+        ArrayDataType::Int8 | ArrayDataType::Int16 | ArrayDataType::Int32 | ArrayDataType::Int64 => 2,
+        ArrayDataType::Float32 | ArrayDataType::Float64 => 3,
+    }
+}
+
+fn check_predictor(data_type: ArrayDataType, predictor: Option<Predictor>) -> Result<()> {
+    let is_float = matches!(data_type, ArrayDataType::Float32 | ArrayDataType::Float64);
+    match predictor {
+        Some(Predictor::Horizontal) if is_float => Err(Error::InvalidArgument(
+            "Horizontal predictor is not supported for floating point data, use the floating point predictor".into(),
+        )),
+        Some(Predictor::FloatingPoint) if !is_float => Err(Error::InvalidArgument(format!(
+            "Floating point predictor is not supported for {data_type} data"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Adds the tags describing the image dimensions, data type and encoding of the chunks
+pub(crate) fn add_image_structure_tags<T: ArrayNum>(
+    ifd: &mut ImageFileDirectory,
+    raster_size: RasterSize,
+    compression: Option<Compression>,
+    predictor: Option<Predictor>,
+) -> Result<()> {
+    check_predictor(T::TYPE, predictor)?;
+
+    ifd.set_tag(Tag::ImageWidth, TagValue::Long(vec![raster_size.cols.count() as u32]));
+    ifd.set_tag(Tag::ImageLength, TagValue::Long(vec![raster_size.rows.count() as u32]));
+    ifd.set_tag(Tag::BitsPerSample, TagValue::Short(vec![T::TYPE.bytes() as u16 * 8]));
+    ifd.set_tag(Tag::Compression, TagValue::Short(vec![tiff_compression_code(compression)]));
+    ifd.set_tag(Tag::PhotometricInterpretation, TagValue::Short(vec![1])); // Min is black
+    ifd.set_tag(Tag::SamplesPerPixel, TagValue::Short(vec![1]));
+    ifd.set_tag(Tag::PlanarConfiguration, TagValue::Short(vec![1])); // Chunky
+    ifd.set_tag(Tag::SampleFormat, TagValue::Short(vec![tiff_sample_format(T::TYPE)]));
+
+    match ifd.layout {
+        ChunkDataLayout::Tiled(tile_size) => {
+            ifd.set_tag(Tag::TileWidth, TagValue::Long(vec![tile_size]));
+            ifd.set_tag(Tag::TileLength, TagValue::Long(vec![tile_size]));
+        }
+        ChunkDataLayout::Striped(rows_per_strip) => {
+            ifd.set_tag(Tag::RowsPerStrip, TagValue::Long(vec![rows_per_strip]));
+        }
+    }
+
+    match predictor {
+        Some(Predictor::Horizontal) => ifd.set_tag(Tag::Predictor, TagValue::Short(vec![2])),
+        Some(Predictor::FloatingPoint) => ifd.set_tag(Tag::Predictor, TagValue::Short(vec![3])),
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Adds the georeferencing tags: pixel scale, tie points, geo keys and nodata
+pub(crate) fn add_geo_tags(ifd: &mut ImageFileDirectory, geo_reference: &GeoReference) -> Result<()> {
+    let [x0, cell_size_x, rot_x, y0, rot_y, cell_size_y] = geo_reference.geo_transform().coefficients();
+    if rot_x != 0.0 || rot_y != 0.0 {
+        return Err(Error::InvalidArgument("Writing rotated rasters is not supported".into()));
+    }
+
+    ifd.set_tag(Tag::ModelPixelScaleTag, TagValue::Double(vec![cell_size_x, -cell_size_y, 0.0]));
+    ifd.set_tag(Tag::ModelTiepointTag, TagValue::Double(vec![0.0, 0.0, 0.0, x0, y0, 0.0]));
+
+    if let Some((model_type, epsg)) = projection_epsg(geo_reference)? {
+        let (model_type_code, crs_key) = match model_type {
+            ModelType::Geographic => (2, geokey::GEOGRAPHIC_TYPE),
+            _ => (1, geokey::PROJECTED_TYPE),
+        };
+
+        let keys: [[u16; 4]; 3] = [
+            [geokey::MODEL_TYPE, 0, 1, model_type_code],
+            [geokey::RASTER_TYPE, 0, 1, geokey::RASTER_PIXEL_IS_AREA],
+            [crs_key, 0, 1, epsg.code()],
+        ];
+
+        // Header: version, revision, minor revision, number of keys
+        let mut key_directory = vec![1, 1, 0, keys.len() as u16];
+        key_directory.extend(keys.iter().flatten());
+        ifd.set_tag(Tag::GeoKeyDirectoryTag, TagValue::Short(key_directory));
+    }
+
+    if let Some(nodata) = geo_reference.nodata() {
+        ifd.set_tag(Tag::GdalNodata, TagValue::Ascii(nodata_to_string(nodata)));
+    }
+
+    Ok(())
+}
+
+/// Adds the `GDAL_METADATA` tag containing the provided metadata
+pub(crate) fn add_gdal_metadata_tag(ifd: &mut ImageFileDirectory, metadata: &GdalMetadata) {
+    ifd.set_tag(
+        Tag::Unknown(GDAL_METADATA_TAG),
+        TagValue::Ascii(gdalmetadata::serialize_gdal_metadata(metadata)),
+    );
+}
+
+/// Stores the raster scale as offset and scale of the first band, unless the metadata already contains them
+pub(crate) fn apply_raster_scale(metadata: &mut GdalMetadata, scale: Option<RasterScale>) {
+    if let Some(scale) = scale {
+        let band = metadata.band_mut(0);
+        if band.offset.is_none() && band.scale.is_none() {
+            band.offset = Some(scale.offset);
+            band.scale = Some(scale.scale);
+        }
+    }
+}
+
+fn nodata_to_string(nodata: f64) -> String {
+    if nodata.is_nan() { "nan".to_string() } else { nodata.to_string() }
+}
+
+/// Determine the EPSG code of the projection and whether it is a geographic or projected coordinate system.
+/// Only EPSG coordinate systems can be written as geo keys, an error is returned for other projections
+/// so the file is not silently written without a coordinate system.
+fn projection_epsg(geo_reference: &GeoReference) -> Result<Option<(ModelType, Epsg)>> {
+    let projection = geo_reference.projection();
+    if projection.is_empty() {
+        return Ok(None);
+    }
+
+    #[cfg(any(feature = "gdal", feature = "proj4rs"))]
+    let epsg = {
+        let srs = crate::srs::SpatialReference::from_definition(projection)?;
+        if srs.is_projected() {
+            srs.epsg_cs().map(|epsg| (ModelType::Projected, epsg))
+        } else {
+            srs.epsg_geog_cs().map(|epsg| (ModelType::Geographic, epsg))
+        }
+    };
+
+    // Without a spatial reference backend only EPSG definitions of known coordinate system types are supported
+    #[cfg(not(any(feature = "gdal", feature = "proj4rs")))]
+    let epsg = projection
+        .strip_prefix("EPSG:")
+        .and_then(|code| code.trim().parse::<u16>().ok())
+        .and_then(|code| model_type_of_epsg(code).map(|model_type| (model_type, Epsg::new(code))));
+
+    match epsg {
+        Some(epsg) => Ok(Some(epsg)),
+        None => Err(Error::InvalidArgument(format!(
+            "The projection can not be written as GeoTIFF geo keys, only EPSG coordinate systems are supported: {projection}"
+        ))),
+    }
+}
+
+/// The model type of commonly used EPSG codes, used when no spatial reference backend is available.
+/// The EPSG ranges are not reliable (e.g. EPSG:4087 is projected and EPSG:4978 is geocentric), so other codes are unknown.
+#[cfg(not(any(feature = "gdal", feature = "proj4rs")))]
+fn model_type_of_epsg(code: u16) -> Option<ModelType> {
+    match code {
+        // WGS 84, ETRS89, NAD27, NAD83, ED50, Belge 1972
+        4326 | 4258 | 4267 | 4269 | 4230 | 4313 => Some(ModelType::Geographic),
+        // Web mercator, ETRS89 LAEA and LCC, Belgian Lambert 2008 and 72
+        3857 | 3035 | 3034 | 3812 | 31370 => Some(ModelType::Projected),
+        // WGS 84 UTM north and south zones, ETRS89 UTM zones
+        32601..=32660 | 32701..=32760 | 25828..=25838 => Some(ModelType::Projected),
+        _ => None,
+    }
+}
+
+fn is_nodata_chunk<T: ArrayNum>(chunk: &[T], nodata: T) -> bool {
+    // nodata != nodata for NaN nodata values
+    #[allow(clippy::eq_op)]
+    let nodata_is_nan = nodata != nodata;
+    #[allow(clippy::eq_op)]
+    chunk.iter().all(|v| if nodata_is_nan { *v != *v } else { *v == nodata })
+}
+
+/// Splits the raster data in chunks according to the layout and encodes them.
+/// Partial tiles at the edges are padded with nodata.
+/// When `sparse_ok` is set, chunks that only contain nodata are not stored (`None`).
+pub(crate) fn encode_chunks<T: ArrayNum>(
+    data: &[T],
+    raster_size: RasterSize,
+    layout: ChunkDataLayout,
+    nodata: Option<f64>,
+    compression: Option<Compression>,
+    predictor: Option<Predictor>,
+    sparse_ok: bool,
+) -> Result<Vec<Option<Vec<u8>>>> {
+    if raster_size.cell_count() == 0 {
+        return Err(Error::InvalidArgument("Cannot write an empty raster".into()));
+    }
+
+    if data.len() != raster_size.cell_count() {
+        return Err(Error::InvalidArgument(format!(
+            "Raster data size ({}) does not match the raster size ({})",
+            data.len(),
+            raster_size.cell_count()
+        )));
+    }
+
+    let nodata: Option<T> = cast::option(nodata);
+    let sparse_value = if sparse_ok { nodata } else { None };
+    let cols = raster_size.cols.count() as usize;
+    let rows = raster_size.rows.count() as usize;
+
+    let mut chunks = Vec::new();
+    match layout {
+        ChunkDataLayout::Tiled(tile_size) => {
+            let tile_size = tile_size as usize;
+            let mut tile_data = vec![nodata.unwrap_or(T::zero()); tile_size * tile_size];
+            for tile_row in 0..rows.div_ceil(tile_size) {
+                for tile_col in 0..cols.div_ceil(tile_size) {
+                    let row_start = tile_row * tile_size;
+                    let col_start = tile_col * tile_size;
+                    let row_count = tile_size.min(rows - row_start);
+                    let col_count = tile_size.min(cols - col_start);
+
+                    tile_data.fill(nodata.unwrap_or(T::zero()));
+                    for r in 0..row_count {
+                        let src_start = (row_start + r) * cols + col_start;
+                        tile_data[r * tile_size..r * tile_size + col_count].copy_from_slice(&data[src_start..src_start + col_count]);
+                    }
+
+                    if sparse_value.is_some_and(|nod| is_nodata_chunk(&tile_data, nod)) {
+                        chunks.push(None);
+                    } else {
+                        chunks.push(Some(io::encode_chunk_data(
+                            tile_size as u32,
+                            compression,
+                            predictor,
+                            &mut tile_data,
+                        )?));
+                    }
+                }
+            }
+        }
+        ChunkDataLayout::Striped(rows_per_strip) => {
+            for strip in data.chunks(rows_per_strip as usize * cols) {
+                if sparse_value.is_some_and(|nod| is_nodata_chunk(strip, nod)) {
+                    chunks.push(None);
+                } else {
+                    let mut strip_data = strip.to_vec();
+                    chunks.push(Some(io::encode_chunk_data(cols as u32, compression, predictor, &mut strip_data)?));
+                }
+            }
+        }
+    }
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::NumCast;
+
+    use crate::{
+        Array as _, CellSize, Columns, DenseArray, Point, RasterScale, Rows,
+        array::ArrayInterop as _,
+        geotiff::{GeoTiffReader, TiffChunkLocation},
+    };
+
+    use super::*;
+
+    const NODATA: f64 = 99.0;
+
+    /// Creates a raster of 300 rows by 517 columns, the first 256 rows only contain nodata
+    fn test_raster<T: ArrayNum>() -> (GeoReference, Vec<T>) {
+        let raster_size = RasterSize::with_rows_cols(Rows(300), Columns(517));
+        let geo_reference = GeoReference::with_top_left_origin(
+            "EPSG:31370",
+            raster_size,
+            Point::new(22000.0, 245000.0),
+            CellSize::square(100.0),
+            Some(NODATA),
+        );
+        let data = (0..raster_size.cell_count())
+            .map(|i| {
+                if i < 256 * 517 || i % 7 == 0 {
+                    NumCast::from(NODATA).unwrap()
+                } else {
+                    NumCast::from(i % 90).unwrap()
+                }
+            })
+            .collect();
+
+        (geo_reference, data)
+    }
+
+    fn round_trip<T: ArrayNum>(options: GeoTiffWriteOptions) -> Result<Vec<TiffChunkLocation>> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("subdir").join("raster.tif");
+        let (geo_reference, data) = test_raster::<T>();
+
+        write_geotiff(&output, &geo_reference, &data, &options)?;
+
+        let mut reader = GeoTiffReader::from_file(&output)?;
+        assert_eq!(reader.metadata().compression, options.compression);
+        assert_eq!(reader.metadata().predictor, options.predictor);
+        assert_eq!(reader.metadata().data_type, T::TYPE);
+        assert_eq!(reader.metadata().is_tiled(), options.chunk_type == TiffChunkType::Tiled);
+
+        let raster = reader.read_raster_as::<T, GeoReference>()?;
+        assert_eq!(raster.metadata(), &geo_reference);
+        assert_eq!(
+            raster,
+            DenseArray::new_init_nodata(geo_reference, inf::allocate::aligned_vec_from_slice(&data))?
+        );
+
+        Ok(reader.metadata().overviews[0].chunk_locations.clone())
+    }
+
+    fn options(chunk_type: TiffChunkType, compression: Option<Compression>, predictor: Option<Predictor>) -> GeoTiffWriteOptions {
+        GeoTiffWriteOptions {
+            chunk_type,
+            compression,
+            predictor,
+            sparse_ok: false,
+        }
+    }
+
+    #[test]
+    fn write_striped() -> Result<()> {
+        for compression in [None, Some(Compression::Lzw), Some(Compression::Zstd), Some(Compression::PackBits)] {
+            round_trip::<u8>(options(TiffChunkType::Striped, compression, None))?;
+            round_trip::<i16>(options(TiffChunkType::Striped, compression, Some(Predictor::Horizontal)))?;
+            round_trip::<f32>(options(TiffChunkType::Striped, compression, Some(Predictor::FloatingPoint)))?;
+            round_trip::<f64>(options(TiffChunkType::Striped, compression, None))?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "gdal", feature = "proj4rs")))]
+    fn model_type_without_srs_backend() {
+        assert!(matches!(model_type_of_epsg(4326), Some(ModelType::Geographic)));
+        assert!(matches!(model_type_of_epsg(31370), Some(ModelType::Projected)));
+        assert!(matches!(model_type_of_epsg(32631), Some(ModelType::Projected)));
+        // Projected and geocentric codes in the 4000 range are not classified
+        assert!(model_type_of_epsg(4087).is_none());
+        assert!(model_type_of_epsg(4978).is_none());
+    }
+
+    #[test]
+    fn write_projection_without_epsg() {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let (mut geo_reference, data) = test_raster::<u8>();
+        // A custom conic projection that does not match any EPSG coordinate system
+        geo_reference
+            .set_projection("+proj=lcc +lat_1=45 +lat_2=55 +lat_0=50 +lon_0=5 +x_0=0 +y_0=0 +ellps=GRS80 +units=m +no_defs".to_string());
+        let opts = options(TiffChunkType::Striped, None, None);
+        assert!(write_geotiff(&tmp.path().join("custom.tif"), &geo_reference, &data, &opts).is_err());
+    }
+
+    #[test]
+    fn write_tiled() -> Result<()> {
+        for compression in [None, Some(Compression::Lzw), Some(Compression::Zstd), Some(Compression::PackBits)] {
+            round_trip::<u16>(options(TiffChunkType::Tiled, compression, Some(Predictor::Horizontal)))?;
+            round_trip::<i32>(options(TiffChunkType::Tiled, compression, None))?;
+            round_trip::<f32>(options(TiffChunkType::Tiled, compression, None))?;
+            round_trip::<f64>(options(TiffChunkType::Tiled, compression, Some(Predictor::FloatingPoint)))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn write_deflate() -> Result<()> {
+        round_trip::<u32>(options(
+            TiffChunkType::Striped,
+            Some(Compression::Deflate),
+            Some(Predictor::Horizontal),
+        ))?;
+        round_trip::<f32>(options(
+            TiffChunkType::Tiled,
+            Some(Compression::Deflate),
+            Some(Predictor::FloatingPoint),
+        ))?;
+        Ok(())
+    }
+
+    #[test]
+    fn write_sparse() -> Result<()> {
+        for chunk_type in [TiffChunkType::Striped, TiffChunkType::Tiled] {
+            let mut opts = options(chunk_type, Some(Compression::Lzw), None);
+            assert!(!round_trip::<u8>(opts.clone())?.iter().any(|loc| loc.is_sparse()));
+
+            opts.sparse_ok = true;
+            let chunks = round_trip::<u8>(opts)?;
+            assert!(chunks.first().is_some_and(|loc| loc.is_sparse()));
+            assert!(chunks.last().is_some_and(|loc| !loc.is_sparse()));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_raster_scale() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("scaled.tif");
+        let (mut geo_reference, data) = test_raster::<u8>();
+        geo_reference.set_scale(Some(RasterScale { scale: 0.5, offset: 10.0 }));
+
+        write_geotiff(&output, &geo_reference, &data, &GeoTiffWriteOptions::default())?;
+        let reader = GeoTiffReader::from_file(&output)?;
+        assert_eq!(reader.metadata().geo_reference, geo_reference);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_invalid_predictor() {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let (geo_reference, data) = test_raster::<f32>();
+        let opts = options(TiffChunkType::Tiled, Some(Compression::Lzw), Some(Predictor::Horizontal));
+        assert!(write_geotiff(&tmp.path().join("invalid.tif"), &geo_reference, &data, &opts).is_err());
+
+        let (geo_reference, data) = test_raster::<u8>();
+        let opts = options(TiffChunkType::Tiled, Some(Compression::Lzw), Some(Predictor::FloatingPoint));
+        assert!(write_geotiff(&tmp.path().join("invalid.tif"), &geo_reference, &data, &opts).is_err());
+    }
+
+    #[test]
+    fn write_gdal_metadata() -> Result<()> {
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("metadata.tif");
+        let (mut geo_reference, data) = test_raster::<u8>();
+        geo_reference.set_scale(Some(RasterScale { scale: 0.5, offset: 1.0 }));
+
+        let mut metadata = GdalMetadata::default();
+        metadata.set_item("", "AREA_OR_POINT", "Area");
+        metadata.set_item("PROCESSING", "VERSION", "1.2");
+        let band = metadata.band_mut(0);
+        band.description = Some("Land use".to_string());
+        band.unit = Some("class".to_string());
+        band.statistics = Some(crate::geotiff::TiffStats {
+            minimum_value: 0.0,
+            maximum_value: 89.0,
+            mean: 44.5,
+            standard_deviation: 25.9,
+            valid_pixel_percentage: 12.2,
+        });
+
+        write_geotiff_with_metadata(&output, &geo_reference, &data, &GeoTiffWriteOptions::default(), &metadata)?;
+
+        let meta = crate::geotiff::GeoTiffMetadata::from_file(&output)?;
+        assert_eq!(meta.statistics, metadata.band_metadata[0].statistics);
+        assert_eq!(meta.geo_reference.scale(), geo_reference.scale());
+        assert_eq!(meta.metadata_domains, metadata.domains);
+        assert_eq!(meta.band_metadata.len(), 1);
+        assert_eq!(meta.band_metadata[0].description.as_deref(), Some("Land use"));
+        assert_eq!(meta.band_metadata[0].unit.as_deref(), Some("class"));
+        assert_eq!(meta.band_metadata[0].offset, Some(1.0));
+        assert_eq!(meta.band_metadata[0].scale, Some(0.5));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gdal")]
+    #[test]
+    fn write_readable_by_gdal() -> Result<()> {
+        use crate::raster::{DenseRaster, RasterReadWrite};
+
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("gdal.tif");
+        let (geo_reference, data) = test_raster::<f32>();
+        let opts = GeoTiffWriteOptions {
+            chunk_type: TiffChunkType::Tiled,
+            compression: Some(Compression::Zstd),
+            predictor: Some(Predictor::FloatingPoint),
+            sparse_ok: true,
+        };
+
+        write_geotiff(&output, &geo_reference, &data, &opts)?;
+        let gdal_raster = DenseRaster::<f32>::read(&output)?;
+        assert_eq!(gdal_raster.metadata().projected_epsg(), Some(crate::crs::epsg::BELGIAN_LAMBERT72));
+        assert_eq!(gdal_raster.metadata().geo_transform(), geo_reference.geo_transform());
+        assert_eq!(gdal_raster.metadata().nodata(), geo_reference.nodata());
+        assert_eq!(
+            gdal_raster.as_slice(),
+            DenseRaster::new_init_nodata(geo_reference, inf::allocate::aligned_vec_from_slice(&data))?.as_slice()
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gdal")]
+    #[test]
+    fn gdal_metadata_readable_by_gdal() -> Result<()> {
+        use gdal::Metadata as _;
+
+        let tmp = tempfile::tempdir().expect("Failed to create temporary directory");
+        let output = tmp.path().join("gdal_metadata.tif");
+        let (geo_reference, data) = test_raster::<u8>();
+
+        let mut metadata = GdalMetadata::default();
+        metadata.set_item("", "SOURCE", "test");
+        let band = metadata.band_mut(0);
+        band.description = Some("Land use".to_string());
+        band.unit = Some("class".to_string());
+        band.offset = Some(1.0);
+        band.scale = Some(0.5);
+        band.statistics = Some(crate::geotiff::TiffStats {
+            minimum_value: 1.0,
+            maximum_value: 89.0,
+            mean: 44.5,
+            standard_deviation: 25.9,
+            valid_pixel_percentage: 12.2,
+        });
+
+        write_geotiff_with_metadata(&output, &geo_reference, &data, &GeoTiffWriteOptions::default(), &metadata)?;
+
+        let ds = gdal::Dataset::open(&output)?;
+        assert_eq!(ds.metadata_item("SOURCE", "").as_deref(), Some("test"));
+
+        let band = ds.rasterband(1)?;
+        assert_eq!(band.description()?, "Land use");
+        assert_eq!(band.unit(), "class");
+        assert_eq!(band.offset(), Some(1.0));
+        assert_eq!(band.scale(), Some(0.5));
+
+        // The statistics are not recomputed by GDAL
+        let stats = band.get_statistics(false, false)?.expect("Statistics should be available");
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 89.0);
+        assert_eq!(stats.mean, 44.5);
+        assert_eq!(stats.std_dev, 25.9);
+
+        Ok(())
+    }
+}
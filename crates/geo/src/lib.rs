@@ -47,6 +47,7 @@ mod rect;
 mod runtimeconfiguration;
 pub mod srs;
 mod tile;
+mod tilematrixset;
 pub mod tileutils;
 pub mod vector;
 
@@ -72,6 +73,7 @@ pub use {
     cell::CellIterator, coordinate::Coordinate, densearray::DenseArray, error::Error, georeference::CellSize, georeference::GeoReference,
     geotransform::GeoTransform, latlonbounds::LatLonBounds, nodata::Nodata, raster::RasterNodataCompatibility,
     rastermetadata::RasterMetadata, rasterscale::RasterScale, rastersize::RasterSize, rect::Rect, tile::Tile, tile::ZoomLevelStrategy,
+    tilematrixset::TileMatrix, tilematrixset::TileMatrixSet,
 };
 
 #[doc(inline)]
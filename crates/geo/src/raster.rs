@@ -52,6 +52,14 @@ pub enum Compression {
     Lzw,
     Zstd,
     Deflate,
+    PackBits,
+    Jpeg,
+    WebP,
+    Lerc,
+    /// LERC with an additional deflate compression step
+    LercDeflate,
+    /// LERC with an additional zstd compression step
+    LercZstd,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -1,24 +1,34 @@
 //! Algorithms for raster data processing (translate, warp, ...).
 
+mod aggregate;
 mod cast;
 mod conversion;
 mod crop;
 mod distance;
+mod euclidean;
 mod filter;
+mod focal;
 #[cfg(feature = "gdal")]
 mod gdaltranslate;
 #[cfg(feature = "gdal")]
 mod gdalwarp;
+mod hydrology;
 mod limits;
 mod nodata;
 #[cfg(feature = "gdal")]
 mod polygonize;
 mod quantile;
 mod rasterdiff;
+mod reclassify;
+#[cfg(any(feature = "proj", feature = "proj4rs"))]
+mod resample;
 mod scale;
 mod statistics;
+mod terrain;
+mod viewshed;
 #[cfg(any(feature = "proj", feature = "proj4rs"))]
 mod warp;
+mod zonal;
 
 mod clusterid;
 pub(crate) mod clusterutils;
@@ -40,7 +50,10 @@ pub mod gdal {
 pub use {rasterdiff::raster_files_diff, rasterdiff::raster_files_intersection_diff};
 
 #[cfg(any(feature = "proj", feature = "proj4rs"))]
-pub use warp::{NumThreads, TargetPixelAlignment, TargetSrs, WarpOptions, WarpTargetSize, warp, warp_options_to_gdalwarp_cli_args};
+pub use {
+    resample::ResamplingMethod,
+    warp::{NumThreads, TargetPixelAlignment, TargetSrs, WarpOptions, WarpTargetSize, warp, warp_options_to_gdalwarp_cli_args},
+};
 
 pub use {
     clusterid::cluster_id, clusterid::cluster_id_with_obstacles, clusterid::fuzzy_cluster_id, clusterid::fuzzy_cluster_id_with_obstacles,
@@ -61,13 +74,16 @@ pub fn warp_georeference(georef: &crate::GeoReference, opts: &WarpOptions) -> cr
 pub use conversion::replace_value;
 
 pub use {
-    cast::Cast, cast::cast, crop::crop, distance::closest_target, distance::distance, distance::distance_with_obstacles,
-    distance::sum_targets_within_travel_distance, distance::sum_within_travel_distance, distance::travel_distance,
+    cast::Cast, cast::cast, crop::crop, distance::BarrierDiagonals, distance::LeastCostPath, distance::closest_target, distance::distance,
+    distance::distance_with_obstacles, distance::least_cost_path, distance::sum_targets_within_travel_distance,
+    distance::sum_within_travel_distance, distance::travel_distance, distance::travel_distance_with_back_links,
     distance::travel_distances_up_to, distance::value_at_closest_less_than_travel_target, distance::value_at_closest_target,
     distance::value_at_closest_travel_target, filter::filter, filter::filter_value, limits::min_max, quantile::SplitQuantiles,
     quantile::quantiles, quantile::quantiles_neg_pos, scale::Scale, scale::descale, statistics::RasterStats, statistics::statistics,
 };
 
+pub(crate) use statistics::values_statistics;
+
 #[cfg(feature = "simd")]
 pub mod simd {
     pub use super::{
@@ -77,6 +93,30 @@ pub mod simd {
 
 pub use {nodata::is_data, nodata::is_nodata, nodata::replace_nodata, nodata::replace_nodata_in_place, nodata::replace_value_by_nodata};
 
+pub use aggregate::{AggregationMethod, DisaggregationMethod, aggregate, disaggregate};
+
+pub use euclidean::{
+    FeatureTransform, euclidean_closest_target, euclidean_distance, euclidean_feature_transform, euclidean_value_at_closest_target,
+};
+
+pub use focal::{FocalNodata, FocalStatistic, FocalWindow, FocalWindowShape, FocalWindowUnits, focal_statistics};
+
+pub use hydrology::{
+    FlowRouting, extract_streams, fill_depressions, flow_accumulation, flow_direction_d8, flow_direction_dinf, watersheds,
+    watersheds_for_points, weighted_flow_accumulation,
+};
+
+pub use terrain::{
+    CurvatureType, HillshadeOptions, SlopeAlgorithm, SlopeUnits, TerrainOptions, aspect, curvature, hillshade, roughness, slope,
+    terrain_ruggedness_index, topographic_position_index,
+};
+
+pub use viewshed::{Observer, ViewshedOptions, cumulative_viewshed, line_of_sight, viewshed};
+
+pub use zonal::{ZonalStatisticsAccumulator, zonal_statistics};
+
+pub use reclassify::{ReclassRule, ReclassTable, UnmappedValues, reclassify};
+
 pub use rasterdiff::{RasterCellMismatch, RasterDiffResult, array_diff, raster_diff};
 
 pub fn assert_dimensions(r1: &impl Array, r2: &impl Array) {
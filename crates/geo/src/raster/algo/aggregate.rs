@@ -0,0 +1,446 @@
+//! Resampling of a raster to a coarser or finer grid with the same projection.
+//! Source cells are weighted by the fraction of the cell that overlaps the target cell, so non integer factors are supported.
+
+use num::ToPrimitive as _;
+
+use super::cast::checked_pixel_value;
+use crate::{Array, ArrayCopy, ArrayNum, Error, GeoReference, Nodata as _, Result};
+
+/// Overlaps smaller than this fraction of a source cell are ignored
+const MIN_OVERLAP: f64 = 1e-9;
+
+/// The statistic used to calculate the value of an aggregated cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationMethod {
+    /// The sum of the source values, weighted by the fraction of the source cell that overlaps the target cell
+    Sum,
+    /// The mean of the source values, weighted by the overlapping area
+    Mean,
+    /// The source value that covers the largest area of the target cell, the lowest value wins in case of a tie
+    Majority,
+    Min,
+    Max,
+    /// The number of source cells with data, weighted by the fraction of the source cell that overlaps the target cell
+    Count,
+}
+
+/// The method used to distribute a coarse cell value over the finer cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisaggregationMethod {
+    /// Every target cell gets the value of the source cell that covers the largest area of it
+    Replicate,
+    /// The source value is divided over the target cells proportional to their area, the total sum is preserved
+    Split,
+}
+
+/// The source cells overlapping a target cell along one axis, with the overlapping fraction of the source cell
+type AxisOverlaps = Vec<Vec<(usize, f64)>>;
+
+fn snap_to_edge(pos: f64) -> f64 {
+    if (pos - pos.round()).abs() < MIN_OVERLAP {
+        pos.round()
+    } else {
+        pos
+    }
+}
+
+fn axis_overlaps(
+    source_origin: f64,
+    source_cell_size: f64,
+    source_count: usize,
+    target_origin: f64,
+    target_cell_size: f64,
+    target_count: usize,
+) -> AxisOverlaps {
+    (0..target_count)
+        .map(|index| {
+            // The edges of the target cell expressed in source cell units
+            let start = snap_to_edge((target_origin + index as f64 * target_cell_size - source_origin) / source_cell_size);
+            let end = snap_to_edge((target_origin + (index + 1) as f64 * target_cell_size - source_origin) / source_cell_size);
+
+            let first = start.floor().max(0.0) as usize;
+            let last = (end.ceil().max(0.0) as usize).min(source_count);
+
+            (first..last)
+                .filter_map(|source_index| {
+                    let overlap = end.min(source_index as f64 + 1.0) - start.max(source_index as f64);
+                    (overlap > MIN_OVERLAP).then_some((source_index, overlap))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn check_grids(source: &GeoReference, target: &GeoReference) -> Result<()> {
+    if !source.is_north_up() || !target.is_north_up() || source.cell_size_x() <= 0.0 || target.cell_size_x() <= 0.0 {
+        return Err(Error::InvalidArgument("Aggregation is only supported for north up rasters".into()));
+    }
+
+    if source.projection() != target.projection() && (source.epsg().is_none() || source.epsg() != target.epsg()) {
+        return Err(Error::InvalidArgument(
+            "The target grid should have the same projection as the raster, use warp to reproject the raster".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn pick_majority<T: ArrayNum>(value_weights: &[(T, f64)]) -> Option<T> {
+    value_weights
+        .iter()
+        .fold(None, |majority: Option<(T, f64)>, &(value, weight)| match majority {
+            Some((majority_value, majority_weight))
+                if majority_weight > weight || (majority_weight == weight && majority_value < value) =>
+            {
+                majority
+            }
+            _ => Some((value, weight)),
+        })
+        .map(|(value, _)| value)
+}
+
+fn resample_to_grid<TResult, RasterType>(
+    raster: &RasterType,
+    target: &GeoReference,
+    method: AggregationMethod,
+) -> Result<RasterType::WithPixelType<TResult>>
+where
+    TResult: ArrayNum,
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<TResult>: ArrayCopy<TResult, RasterType>,
+{
+    let source = raster.metadata();
+    check_grids(source, target)?;
+
+    let source_rows = raster.rows().count() as usize;
+    let source_cols = raster.columns().count() as usize;
+    let target_rows = target.rows().count() as usize;
+    let target_cols = target.columns().count() as usize;
+
+    let col_overlaps = axis_overlaps(
+        source.top_left().x(),
+        source.cell_size_x(),
+        source_cols,
+        target.top_left().x(),
+        target.cell_size_x(),
+        target_cols,
+    );
+
+    // Rows are measured downwards from the top of the raster
+    let row_overlaps = axis_overlaps(
+        -source.top_left().y(),
+        source.cell_size_y().abs(),
+        source_rows,
+        -target.top_left().y(),
+        target.cell_size_y().abs(),
+        target_rows,
+    );
+
+    let mut result = RasterType::WithPixelType::<TResult>::filled_with_nodata(target.copy_with_nodata(Some(TResult::NODATA)));
+    let source_data = raster.as_slice();
+    let result_data = result.as_mut_slice();
+    let mut value_weights: Vec<(RasterType::Pixel, f64)> = Vec::new();
+
+    for (target_row, row_overlap) in row_overlaps.iter().enumerate() {
+        for (target_col, col_overlap) in col_overlaps.iter().enumerate() {
+            if row_overlap.is_empty() || col_overlap.is_empty() {
+                // Outside of the source extent
+                continue;
+            }
+
+            let mut data_weight = 0.0;
+            let mut sum = 0.0;
+            let mut min = f64::INFINITY;
+            let mut max = f64::NEG_INFINITY;
+            value_weights.clear();
+
+            for &(source_row, row_fraction) in row_overlap {
+                for &(source_col, col_fraction) in col_overlap {
+                    let value = source_data[source_row * source_cols + source_col];
+                    if value.is_nodata() {
+                        continue;
+                    }
+
+                    let Some(val) = value.to_f64() else {
+                        continue;
+                    };
+
+                    let weight = row_fraction * col_fraction;
+                    data_weight += weight;
+                    sum += val * weight;
+                    min = min.min(val);
+                    max = max.max(val);
+
+                    if method == AggregationMethod::Majority {
+                        match value_weights.iter_mut().find(|(v, _)| *v == value) {
+                            Some((_, w)) => *w += weight,
+                            None => value_weights.push((value, weight)),
+                        }
+                    }
+                }
+            }
+
+            let result_value = if data_weight == 0.0 {
+                match method {
+                    AggregationMethod::Count => Some(0.0),
+                    _ => None,
+                }
+            } else {
+                match method {
+                    AggregationMethod::Sum => Some(sum),
+                    AggregationMethod::Mean => Some(sum / data_weight),
+                    AggregationMethod::Min => Some(min),
+                    AggregationMethod::Max => Some(max),
+                    AggregationMethod::Count => Some(data_weight),
+                    AggregationMethod::Majority => pick_majority(&value_weights).and_then(|v| v.to_f64()),
+                }
+            };
+
+            // Integer results are rounded, results that do not fit in the result type are an error
+            let pixel = result_value
+                .map(checked_pixel_value::<TResult>)
+                .transpose()
+                .map_err(|e| Error::InvalidArgument(format!("Aggregation {method:?} result is invalid: {e}")))?;
+            if let Some(value) = pixel.flatten() {
+                result_data[target_row * target_cols + target_col] = value;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Aggregates the raster to the coarser grid of the target `GeoReference`, the projection of the raster is not changed.
+/// The target cell size does not need to be a multiple of the source cell size, source cells are weighted by their overlapping fraction.
+/// Nodata cells are ignored, target cells without source data are nodata (0 for [`AggregationMethod::Count`]).
+/// An error is returned when the target grid has a different projection or when a result does not fit in the result type.
+/// Use [`GeoReference::with_cell_size`] to obtain a target grid that is aligned with the raster.
+pub fn aggregate<TResult, RasterType>(
+    raster: &RasterType,
+    target: &GeoReference,
+    method: AggregationMethod,
+) -> Result<RasterType::WithPixelType<TResult>>
+where
+    TResult: ArrayNum,
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<TResult>: ArrayCopy<TResult, RasterType>,
+{
+    let source = raster.metadata();
+    if target.cell_size_x().abs() < source.cell_size_x().abs() || target.cell_size_y().abs() < source.cell_size_y().abs() {
+        return Err(Error::InvalidArgument(
+            "The target cell size should not be smaller than the raster cell size, use disaggregate instead".into(),
+        ));
+    }
+
+    resample_to_grid(raster, target, method)
+}
+
+/// Disaggregates the raster to the finer grid of the target `GeoReference`, the projection of the raster is not changed.
+/// The target cell size does not need to be a fraction of the source cell size, source cells are weighted by their overlapping fraction.
+/// Use [`GeoReference::with_cell_size`] to obtain a target grid that is aligned with the raster.
+pub fn disaggregate<TResult, RasterType>(
+    raster: &RasterType,
+    target: &GeoReference,
+    method: DisaggregationMethod,
+) -> Result<RasterType::WithPixelType<TResult>>
+where
+    TResult: ArrayNum,
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<TResult>: ArrayCopy<TResult, RasterType>,
+{
+    let source = raster.metadata();
+    if target.cell_size_x().abs() > source.cell_size_x().abs() || target.cell_size_y().abs() > source.cell_size_y().abs() {
+        return Err(Error::InvalidArgument(
+            "The target cell size should not be larger than the raster cell size, use aggregate instead".into(),
+        ));
+    }
+
+    let method = match method {
+        DisaggregationMethod::Replicate => AggregationMethod::Majority,
+        DisaggregationMethod::Split => AggregationMethod::Sum,
+    };
+
+    resample_to_grid(raster, target, method)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use inf::allocate;
+
+    use crate::{
+        ArrayInterop as _, CellSize, Nodata, Point, RasterSize,
+        array::{Columns, Rows},
+        raster::DenseRaster,
+        testutils::NOD,
+    };
+
+    use super::*;
+
+    fn test_raster() -> DenseRaster<f64> {
+        let georef = GeoReference::with_top_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(4), Columns(4)),
+            Point::new(0.0, 40.0),
+            CellSize::square(10.0),
+            Some(NOD),
+        );
+
+        #[rustfmt::skip]
+        let raster = DenseRaster::<f64>::new_init_nodata(georef, allocate::aligned_vec_from_slice(&[
+            1.0, 2.0, 3.0, 3.0,
+            2.0, 2.0, 4.0, NOD,
+            NOD, NOD, 5.0, 6.0,
+            NOD, NOD, 7.0, 8.0,
+        ])).unwrap();
+
+        raster
+    }
+
+    #[test]
+    fn aggregate_integer_factor() -> Result<()> {
+        let raster = test_raster();
+        let target = raster.metadata().with_cell_size(CellSize::square(20.0))?;
+        assert!(target.is_aligned_with(raster.metadata()));
+        assert_eq!(target.raster_size(), RasterSize::with_rows_cols(Rows(2), Columns(2)));
+
+        let sum = aggregate::<f64, _>(&raster, &target, AggregationMethod::Sum)?;
+        assert_eq!(sum.metadata().raster_size(), target.raster_size());
+        assert_eq!(sum.metadata().geo_transform(), target.geo_transform());
+        assert_eq!(sum.iter_opt().collect::<Vec<_>>(), [Some(7.0), Some(10.0), None, Some(26.0)]);
+
+        let mean = aggregate::<f64, _>(&raster, &target, AggregationMethod::Mean)?;
+        assert_eq!(mean.iter_opt().collect::<Vec<_>>(), [Some(1.75), Some(10.0 / 3.0), None, Some(6.5)]);
+
+        let count = aggregate::<u8, _>(&raster, &target, AggregationMethod::Count)?;
+        assert_eq!(count.as_slice(), &[4, 3, 0, 4]);
+
+        let majority = aggregate::<i32, _>(&raster, &target, AggregationMethod::Majority)?;
+        assert_eq!(majority.as_slice(), &[2, 3, i32::NODATA, 5]);
+
+        let min = aggregate::<f64, _>(&raster, &target, AggregationMethod::Min)?;
+        assert_eq!(min.iter_opt().collect::<Vec<_>>(), [Some(1.0), Some(3.0), None, Some(5.0)]);
+
+        let max = aggregate::<f64, _>(&raster, &target, AggregationMethod::Max)?;
+        assert_eq!(max.iter_opt().collect::<Vec<_>>(), [Some(2.0), Some(4.0), None, Some(8.0)]);
+
+        assert!(disaggregate::<f64, _>(&raster, &target, DisaggregationMethod::Split).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_fractional_factor() -> Result<()> {
+        let raster = test_raster();
+        let target = raster.metadata().with_cell_size(CellSize::square(15.0))?;
+        // The extent is grown to fit a whole number of cells
+        assert_eq!(target.raster_size(), RasterSize::with_rows_cols(Rows(3), Columns(3)));
+        assert_eq!(target.top_left(), raster.metadata().top_left());
+
+        // The weighted sum preserves the total of the raster
+        let sum = aggregate::<f64, _>(&raster, &target, AggregationMethod::Sum)?;
+        let total: f64 = sum.as_slice().iter().filter(|v| !v.is_nodata()).sum();
+        assert_relative_eq!(total, 43.0, epsilon = 1e-10);
+
+        // Top left target cell: full cell 1.0, half of 2.0 (right), half of 2.0 (below) and a quarter of 2.0
+        assert_relative_eq!(sum.as_slice()[0], 1.0 + 1.0 + 1.0 + 0.5);
+        let count = aggregate::<f64, _>(&raster, &target, AggregationMethod::Count)?;
+        assert_relative_eq!(count.as_slice()[0], 2.25);
+
+        let mean = aggregate::<f64, _>(&raster, &target, AggregationMethod::Mean)?;
+        assert_relative_eq!(mean.as_slice()[0], 3.5 / 2.25);
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_fractional_factor_to_integer() -> Result<()> {
+        let raster = test_raster();
+        let target = raster.metadata().with_cell_size(CellSize::square(15.0))?;
+
+        // Results are rounded instead of truncated: 3.5, 2.25 and 3.5 / 2.25 for the top left cell
+        let sum = aggregate::<u8, _>(&raster, &target, AggregationMethod::Sum)?;
+        assert_eq!(sum.as_slice()[0], 4);
+        let count = aggregate::<u8, _>(&raster, &target, AggregationMethod::Count)?;
+        assert_eq!(count.as_slice()[0], 2);
+        let mean = aggregate::<i32, _>(&raster, &target, AggregationMethod::Mean)?;
+        assert_eq!(mean.as_slice()[0], 2);
+
+        // Summed overlap fractions that are not exactly integer are rounded as well
+        let target = raster.metadata().with_cell_size(CellSize::square(30.0))?;
+        let count = aggregate::<u8, _>(&raster, &target, AggregationMethod::Count)?;
+        let count_f64 = aggregate::<f64, _>(&raster, &target, AggregationMethod::Count)?;
+        for (&count, &expected) in count.as_slice().iter().zip(count_f64.as_slice()) {
+            assert_eq!(count, expected.round() as u8);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_integer_overflow_is_an_error() -> Result<()> {
+        let georef = GeoReference::with_top_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(4), Columns(4)),
+            Point::new(0.0, 40.0),
+            CellSize::square(10.0),
+            Some(NOD),
+        );
+        let raster = DenseRaster::<f64>::filled_with(Some(100.0), georef);
+        let target = raster.metadata().with_cell_size(CellSize::square(40.0))?;
+
+        // A sum of 1600 does not fit in a byte
+        assert!(aggregate::<u8, _>(&raster, &target, AggregationMethod::Sum).is_err());
+        assert!(aggregate::<u16, _>(&raster, &target, AggregationMethod::Sum).is_ok());
+
+        // A count that equals the nodata value can not be stored
+        let georef = GeoReference::with_top_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(15), Columns(17)),
+            Point::new(0.0, 150.0),
+            CellSize::square(10.0),
+            Some(NOD),
+        );
+        let raster = DenseRaster::<f64>::filled_with(Some(1.0), georef);
+        let target = raster.metadata().with_cell_size(CellSize::square(170.0))?;
+        assert!(aggregate::<u8, _>(&raster, &target, AggregationMethod::Count).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_to_other_projection_is_an_error() -> Result<()> {
+        let raster = test_raster();
+        let mut target = raster.metadata().with_cell_size(CellSize::square(20.0))?;
+        target.set_projection("+proj=longlat +datum=WGS84 +no_defs".to_string());
+
+        assert!(aggregate::<f64, _>(&raster, &target, AggregationMethod::Sum).is_err());
+        assert!(disaggregate::<f64, _>(&raster, &target.with_cell_size(CellSize::square(5.0))?, DisaggregationMethod::Split).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn disaggregate_raster() -> Result<()> {
+        let raster = test_raster();
+        let target = raster.metadata().with_cell_size(CellSize::square(5.0))?;
+        assert!(target.is_aligned_with(raster.metadata()));
+        assert_eq!(target.raster_size(), RasterSize::with_rows_cols(Rows(8), Columns(8)));
+
+        let replicated = disaggregate::<f64, _>(&raster, &target, DisaggregationMethod::Replicate)?;
+        assert_eq!(&replicated.as_slice()[0..8], &[1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(
+            replicated.iter_opt().skip(16).take(8).collect::<Vec<_>>(),
+            [Some(2.0), Some(2.0), Some(2.0), Some(2.0), Some(4.0), Some(4.0), None, None]
+        );
+
+        let split = disaggregate::<f64, _>(&raster, &target, DisaggregationMethod::Split)?;
+        assert_eq!(&split.as_slice()[0..4], &[0.25, 0.25, 0.5, 0.5]);
+        let total: f64 = split.as_slice().iter().filter(|v| !v.is_nodata()).sum();
+        assert_relative_eq!(total, 43.0, epsilon = 1e-10);
+
+        assert!(aggregate::<f64, _>(&raster, &target, AggregationMethod::Sum).is_err());
+
+        Ok(())
+    }
+}
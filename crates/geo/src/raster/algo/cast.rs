@@ -158,6 +158,48 @@ fn cast_vec_reuse<T: ArrayNum, TDest: ArrayNum>(data: AlignedVec<T>) -> AlignedV
     }
 }
 
+/// Converts a calculated value to the pixel type, integer types are rounded and clamped to their range.
+/// The nodata value of the integer types is excluded from the range, so a calculated value never becomes nodata.
+pub(crate) fn to_pixel_value<T: ArrayNum>(value: f64) -> Option<T> {
+    if value.is_nan() {
+        return None;
+    }
+
+    if T::has_nan() {
+        return NumCast::from(value);
+    }
+
+    // The nodata value of the integer types is either their minimum or their maximum
+    let (min, max) = if T::NODATA == T::max_value() {
+        (T::min_value(), T::max_value() - T::one())
+    } else {
+        (T::min_value() + T::one(), T::max_value())
+    };
+
+    let value = value.round();
+    Some(match NumCast::from(value) {
+        Some(v) => num::clamp(v, min, max),
+        None if value > 0.0 => max,
+        None => min,
+    })
+}
+
+/// Converts a calculated value to the pixel type like [`to_pixel_value`], integer types are rounded.
+/// Returns an error instead of clamping when the value does not fit in the pixel type or when it equals the nodata value.
+pub(crate) fn checked_pixel_value<T: ArrayNum>(value: f64) -> Result<Option<T>> {
+    if value.is_nan() {
+        return Ok(None);
+    }
+
+    match to_pixel_value::<T>(value) {
+        Some(pixel) if T::has_nan() || num::ToPrimitive::to_f64(&pixel) == Some(value.round()) => Ok(Some(pixel)),
+        _ => Err(Error::InvalidArgument(format!(
+            "The value {value} does not fit in the result type {} or equals its nodata value",
+            T::TYPE
+        ))),
+    }
+}
+
 /// Free function for casting an array to a different numeric type.
 ///
 /// This is a convenience wrapper around the [`Cast::cast`] method.
@@ -447,4 +489,35 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn integer_values_are_rounded() {
+        assert_eq!(to_pixel_value::<u8>(2.5), Some(3));
+        assert_eq!(to_pixel_value::<i16>(-2.4), Some(-2));
+        assert_eq!(to_pixel_value::<f32>(f64::NAN), None);
+    }
+
+    #[test]
+    fn integer_values_are_clamped_to_valid_range() {
+        assert_eq!(to_pixel_value::<u8>(254.0), Some(254));
+        assert_eq!(to_pixel_value::<u8>(255.0), Some(254));
+        assert_eq!(to_pixel_value::<u8>(300.0), Some(254));
+        assert_eq!(to_pixel_value::<u8>(-3.0), Some(0));
+        assert_eq!(to_pixel_value::<i16>(-40000.0), Some(i16::MIN + 1));
+        assert_eq!(to_pixel_value::<i16>(40000.0), Some(i16::MAX));
+        assert_eq!(to_pixel_value::<u64>(1e30), Some(u64::MAX - 1));
+        assert_eq!(to_pixel_value::<i64>(-1e30), Some(i64::MIN + 1));
+    }
+
+    #[test]
+    fn checked_values_that_do_not_fit_are_an_error() {
+        assert_eq!(checked_pixel_value::<u8>(2.5).unwrap(), Some(3));
+        assert_eq!(checked_pixel_value::<u8>(254.0).unwrap(), Some(254));
+        assert_eq!(checked_pixel_value::<f32>(f64::NAN).unwrap(), None);
+        assert!(checked_pixel_value::<u8>(255.0).is_err());
+        assert!(checked_pixel_value::<u8>(300.0).is_err());
+        assert!(checked_pixel_value::<u8>(-3.0).is_err());
+        assert!(checked_pixel_value::<i16>(40000.0).is_err());
+        assert!(checked_pixel_value::<f32>(1e300).is_err());
+    }
 }
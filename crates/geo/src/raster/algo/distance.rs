@@ -6,8 +6,8 @@ use crate::{
         algo::clusterutils::{MARK_DONE, visit_neighbour_cells, visit_neighbour_diag_cells},
     },
 };
-use crate::{ArrayNum, array};
-use num::{Bounded, NumCast, Zero};
+use crate::{ArrayNum, Nodata as _, array};
+use num::{Bounded, NumCast, ToPrimitive as _, Zero};
 
 use super::clusterutils::{FiLo, MARK_BORDER, MARK_TODO};
 use super::nodata;
@@ -49,9 +49,9 @@ fn handle_sum_le_time_distance_cell(
     travel_time: &impl Array,
     border: &mut FiLo<Cell>,
     cells: &mut Vec<Cell>,
-) {
+) -> bool {
     if travel_time.cell_is_nodata(new_cell) {
-        return;
+        return false;
     }
 
     let cell_travel_time: f32 = NumCast::from(travel_time[cell]).expect("Failed to cast travel time to f32");
@@ -69,7 +69,11 @@ fn handle_sum_le_time_distance_cell(
             *m = MARK_BORDER;
             border.push_back(new_cell);
         }
+
+        return true;
     }
+
+    false
 }
 
 fn handle_cell_value_at_closest_target(
@@ -340,6 +344,23 @@ pub fn travel_distances_up_to<TargetRaster, RasterType>(
     travel_time: &RasterType,
     unreachable: f32,
 ) -> Result<RasterType::WithPixelType<f32>>
+where
+    TargetRaster: Array<Pixel = u8>,
+    RasterType: Array,
+    RasterType::WithPixelType<f32>: ArrayCopy<f32, RasterType>,
+{
+    travel_distances_with_updates(target, travel_time, unreachable, BarrierDiagonals::Include, |_, _| {})
+}
+
+/// Calculates the travel distances to the closest target, `on_update` is called with the cell and the neighbour
+/// it is reached from every time a shorter travel distance is found for a cell.
+fn travel_distances_with_updates<TargetRaster, RasterType>(
+    target: &TargetRaster,
+    travel_time: &RasterType,
+    unreachable: f32,
+    diagonals: BarrierDiagonals,
+    mut on_update: impl FnMut(Cell, Cell),
+) -> Result<RasterType::WithPixelType<f32>>
 where
     TargetRaster: Array<Pixel = u8>,
     RasterType: Array,
@@ -379,7 +400,7 @@ where
         mark[cell] = MARK_DONE;
 
         visit_neighbour_cells(cell, rows, cols, |neighbour| {
-            handle_sum_le_time_distance_cell(
+            if handle_sum_le_time_distance_cell(
                 1.0,
                 cell,
                 neighbour,
@@ -388,11 +409,21 @@ where
                 travel_time,
                 &mut border,
                 &mut Vec::new(),
-            );
+            ) {
+                on_update(neighbour, cell);
+            }
         });
 
         visit_neighbour_diag_cells(cell, rows, cols, |neighbour| {
-            handle_sum_le_time_distance_cell(
+            // Same rule as distance_with_obstacles: no diagonal step next to a barrier cell
+            if diagonals == BarrierDiagonals::Exclude
+                && (travel_time.cell_is_nodata(Cell::from_row_col(cell.row, neighbour.col))
+                    || travel_time.cell_is_nodata(Cell::from_row_col(neighbour.row, cell.col)))
+            {
+                return;
+            }
+
+            if handle_sum_le_time_distance_cell(
                 sqrt2,
                 cell,
                 neighbour,
@@ -401,7 +432,9 @@ where
                 travel_time,
                 &mut border,
                 &mut Vec::new(),
-            );
+            ) {
+                on_update(neighbour, cell);
+            }
         });
     }
 
@@ -418,6 +451,112 @@ where
     travel_distances_up_to(target, travel_time, unreachable)
 }
 
+/// The D8 direction codes (see [`super::flow_direction_d8`]) of the neighbouring cells, indexed by row and column offset + 1
+const BACK_LINK_DIRECTIONS: [[u8; 3]; 3] = [[32, 64, 128], [16, 0, 1], [8, 4, 2]];
+
+/// The D8 direction code of the step from `from` to the neighbouring cell `to`
+fn d8_direction(from: Cell, to: Cell) -> u8 {
+    BACK_LINK_DIRECTIONS[(to.row - from.row + 1) as usize][(to.col - from.col + 1) as usize]
+}
+
+/// The (row offset, column offset) of the D8 direction code
+fn d8_offset(direction: u8) -> Option<(i32, i32)> {
+    BACK_LINK_DIRECTIONS.iter().enumerate().find_map(|(row, codes)| {
+        codes
+            .iter()
+            .position(|&code| code == direction)
+            .map(|col| (row as i32 - 1, col as i32 - 1))
+    })
+}
+
+/// Calculates the travel distance to the closest target like [`travel_distance`] and also returns the back links
+/// that are needed to trace the least cost paths with [`least_cost_path`].
+/// The back link of a cell is the D8 direction (see [`super::flow_direction_d8`]) of the next cell on the path to the
+/// closest target, the targets have back link 0.
+/// Cells with a nodata travel time are barriers, with [`BarrierDiagonals::Exclude`] the path can not step diagonally
+/// past a barrier cell, like in [`distance_with_obstacles`].
+/// Unreachable cells have an infinite travel distance and a nodata back link.
+pub fn travel_distance_with_back_links<TargetRaster, RasterType>(
+    target: &TargetRaster,
+    travel_time: &RasterType,
+    diagonals: BarrierDiagonals,
+) -> Result<(RasterType::WithPixelType<f32>, RasterType::WithPixelType<u8>)>
+where
+    TargetRaster: Array<Pixel = u8>,
+    RasterType: Array,
+    RasterType::WithPixelType<f32>: ArrayCopy<f32, RasterType>,
+    RasterType::WithPixelType<u8>: ArrayCopy<u8, RasterType>,
+{
+    let mut back_links = RasterType::WithPixelType::<u8>::new_with_dimensions_of(travel_time, u8::NODATA);
+    let distance_to_target = travel_distances_with_updates(target, travel_time, f32::INFINITY, diagonals, |cell, from| {
+        back_links[cell] = d8_direction(cell, from);
+    })?;
+
+    for ((back_link, target), distance) in back_links.iter_mut().zip(target.iter_opt()).zip(distance_to_target.iter_opt()) {
+        if target.is_some_and(|t| t != 0) && distance.is_some() {
+            *back_link = 0;
+        }
+    }
+
+    Ok((distance_to_target, back_links))
+}
+
+/// A least cost path from the closest target to a destination
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeastCostPath {
+    /// The cells of the path, starting at the target and ending at the destination
+    pub cells: Vec<Cell>,
+    /// The line through the cell centers of the path in map coordinates
+    pub geometry: geo_types::LineString<f64>,
+}
+
+/// Traces the least cost path from the destination back to the closest target using the back links
+/// calculated by [`travel_distance_with_back_links`].
+pub fn least_cost_path<RasterType>(back_links: &RasterType, destination: Cell) -> Result<LeastCostPath>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let in_raster =
+        |cell: Cell| cell.row >= 0 && cell.row < back_links.rows().count() && cell.col >= 0 && cell.col < back_links.columns().count();
+    if !in_raster(destination) {
+        return Err(Error::InvalidArgument(format!(
+            "Destination {destination:?} is outside of the raster"
+        )));
+    }
+
+    let mut cells = vec![destination];
+    let mut cell = destination;
+    loop {
+        let Some(direction) = back_links.cell_value(cell).and_then(|v| v.to_u8()) else {
+            return Err(Error::InvalidArgument(format!("No path to a target from {destination:?}")));
+        };
+
+        if direction == 0 {
+            break;
+        }
+
+        let Some((row_offset, col_offset)) = d8_offset(direction) else {
+            return Err(Error::Runtime(format!("Invalid back link {direction} at {cell:?}")));
+        };
+
+        cell = Cell::from_row_col(cell.row + row_offset, cell.col + col_offset);
+        if !in_raster(cell) || cells.len() > back_links.len() {
+            return Err(Error::Runtime(format!("Invalid back links on the path from {destination:?}")));
+        }
+
+        cells.push(cell);
+    }
+
+    cells.reverse();
+    let geometry = cells
+        .iter()
+        .map(|&cell| back_links.metadata().cell_center(cell))
+        .collect::<Vec<_>>()
+        .into();
+
+    Ok(LeastCostPath { cells, geometry })
+}
+
 pub fn closest_target<T, RasterType>(target: &RasterType) -> RasterType::WithPixelType<T>
 where
     RasterType: Array,
@@ -1334,3 +1473,112 @@ mod unspecialized_generictests {
     #[instantiate_tests(<DenseRaster<u8>>)]
     mod denseraster {}
 }
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize, Point,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    #[test]
+    fn least_cost_path_around_barrier() -> Result<()> {
+        const BAR: u8 = NOD as u8;
+
+        #[rustfmt::skip]
+        let targets = create_raster::<u8>(5, 5, CellSize::square(100.0), &[
+            1, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+        ]);
+
+        #[rustfmt::skip]
+        let travel_time = create_raster::<u8>(5, 5, CellSize::square(100.0), &[
+            1, 1, BAR, 1, 1,
+            1, 1, BAR, 1, 1,
+            1, 1, BAR, 1, 1,
+            1, 1, BAR, 1, 1,
+            1, 1, 1,   1, 1,
+        ]);
+
+        let (distance, back_links) = travel_distance_with_back_links(&targets, &travel_time, BarrierDiagonals::Include)?;
+        assert_eq!(distance, travel_distance(&targets, &travel_time)?);
+        assert_eq!(back_links[Cell::from_row_col(0, 0)], 0);
+        assert_eq!(back_links.cell_value(Cell::from_row_col(0, 2)), None);
+
+        let destination = Cell::from_row_col(0, 4);
+        let path = least_cost_path(&back_links, destination)?;
+        assert_eq!(path.cells.first(), Some(&Cell::from_row_col(0, 0)));
+        assert_eq!(path.cells.last(), Some(&destination));
+        assert!(path.cells.contains(&Cell::from_row_col(4, 2)));
+        assert!(path.cells.iter().all(|&cell| travel_time.cell_value(cell).is_some()));
+        assert!(
+            path.cells
+                .windows(2)
+                .all(|step| (step[0].row - step[1].row).abs() <= 1 && (step[0].col - step[1].col).abs() <= 1)
+        );
+
+        // The path length matches the travel distance with a travel time of 1
+        let length: f32 = path
+            .cells
+            .windows(2)
+            .map(|step| {
+                if step[0].row != step[1].row && step[0].col != step[1].col {
+                    2.0_f32.sqrt()
+                } else {
+                    1.0
+                }
+            })
+            .sum();
+        assert_relative_eq!(length, distance[destination], epsilon = 1e-5);
+
+        assert_eq!(path.geometry.0.len(), path.cells.len());
+        assert_eq!(path.geometry.0[0], Point::new(50.0, -50.0).into());
+        assert_eq!(path.geometry.0.last().copied(), Some(Point::new(450.0, -50.0).into()));
+
+        assert!(least_cost_path(&back_links, Cell::from_row_col(1, 2)).is_err());
+        assert!(least_cost_path(&back_links, Cell::from_row_col(5, 0)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn least_cost_path_barrier_diagonals() -> Result<()> {
+        const BAR: u8 = NOD as u8;
+
+        let targets = create_raster::<u8>(2, 2, CellSize::square(100.0), &[1, 0, 0, 0]);
+        let travel_time = create_raster::<u8>(2, 2, CellSize::square(100.0), &[1, BAR, BAR, 1]);
+        let destination = Cell::from_row_col(1, 1);
+
+        let (_, back_links) = travel_distance_with_back_links(&targets, &travel_time, BarrierDiagonals::Include)?;
+        assert_eq!(
+            least_cost_path(&back_links, destination)?.cells,
+            [Cell::from_row_col(0, 0), destination]
+        );
+
+        let (distance, back_links) = travel_distance_with_back_links(&targets, &travel_time, BarrierDiagonals::Exclude)?;
+        assert_eq!(distance[destination], f32::INFINITY);
+        assert!(least_cost_path(&back_links, destination).is_err());
+
+        // A single barrier cell next to the diagonal already blocks the diagonal step, like in distance_with_obstacles
+        let travel_time = create_raster::<u8>(2, 2, CellSize::square(100.0), &[1, BAR, 1, 1]);
+        let (distance, back_links) = travel_distance_with_back_links(&targets, &travel_time, BarrierDiagonals::Include)?;
+        assert_relative_eq!(distance[destination], 2.0_f32.sqrt());
+        assert_eq!(least_cost_path(&back_links, destination)?.cells.len(), 2);
+
+        let (distance, back_links) = travel_distance_with_back_links(&targets, &travel_time, BarrierDiagonals::Exclude)?;
+        assert_relative_eq!(distance[destination], 2.0);
+        assert_eq!(
+            least_cost_path(&back_links, destination)?.cells,
+            [Cell::from_row_col(0, 0), Cell::from_row_col(1, 0), destination]
+        );
+
+        Ok(())
+    }
+}
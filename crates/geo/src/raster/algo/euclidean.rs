@@ -0,0 +1,338 @@
+//! Exact euclidean distance transform based on the separable lower envelope algorithm of Felzenszwalb and Huttenlocher.
+//! Distances are measured between cell centers in map units, taking the cell size of both axes into account.
+//! The runtime is linear in the number of cells, regardless of the distance to the targets.
+
+use num::{NumCast, Zero};
+
+use crate::{Array, ArrayNum, Cell, GeoReference, Result, array};
+
+/// The result of the feature transform: the distance to the closest target and the closest target cell
+#[derive(Debug, Clone)]
+pub struct FeatureTransform<RasterType> {
+    /// The distance to the closest target in map units
+    pub distance: RasterType,
+    /// The closest target cell for every cell in row major order, `None` for nodata cells and cells without target in reach
+    pub closest_target: Vec<Option<Cell>>,
+}
+
+impl<RasterType: Array> FeatureTransform<RasterType> {
+    /// The closest target of the given cell
+    pub fn closest_target_of(&self, cell: Cell) -> Option<Cell> {
+        let cols = self.distance.columns().count();
+        self.closest_target[(cell.row * cols + cell.col) as usize]
+    }
+}
+
+/// Calculates the lower envelope of the parabolas rooted at the finite samples of `f`, spaced `spacing` apart.
+/// Stores the squared distance to the envelope and the index of the sample that defines it for every position.
+struct LowerEnvelope {
+    vertices: Vec<usize>,
+    boundaries: Vec<f64>,
+}
+
+impl LowerEnvelope {
+    fn with_capacity(n: usize) -> Self {
+        LowerEnvelope {
+            vertices: vec![0; n],
+            boundaries: vec![0.0; n + 1],
+        }
+    }
+
+    fn transform(&mut self, f: &[f64], spacing: f64, distance: &mut [f64], closest: &mut [Option<usize>]) {
+        let intersection = |q: usize, v: usize| {
+            let (pq, pv) = (q as f64 * spacing, v as f64 * spacing);
+            ((f[q] + pq * pq) - (f[v] + pv * pv)) / (2.0 * (pq - pv))
+        };
+
+        let mut k = 0;
+        let mut samples = f.iter().enumerate().filter(|(_, v)| v.is_finite()).map(|(i, _)| i);
+        let Some(first) = samples.next() else {
+            distance.fill(f64::INFINITY);
+            closest.fill(None);
+            return;
+        };
+
+        self.vertices[0] = first;
+        self.boundaries[0] = f64::NEG_INFINITY;
+        self.boundaries[1] = f64::INFINITY;
+
+        for q in samples {
+            let mut s = intersection(q, self.vertices[k]);
+            while s <= self.boundaries[k] {
+                k -= 1;
+                s = intersection(q, self.vertices[k]);
+            }
+
+            k += 1;
+            self.vertices[k] = q;
+            self.boundaries[k] = s;
+            self.boundaries[k + 1] = f64::INFINITY;
+        }
+
+        k = 0;
+        for (q, (dist, closest)) in distance.iter_mut().zip(closest.iter_mut()).enumerate() {
+            let position = q as f64 * spacing;
+            while self.boundaries[k + 1] < position {
+                k += 1;
+            }
+
+            let vertex = self.vertices[k];
+            let offset = position - vertex as f64 * spacing;
+            *dist = offset * offset + f[vertex];
+            *closest = Some(vertex);
+        }
+    }
+}
+
+/// Calculates the squared distance and closest target cell of every cell, in row major order
+fn squared_feature_transform<RasterType>(target: &RasterType) -> (Vec<f64>, Vec<Option<Cell>>)
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let rows = target.rows().count() as usize;
+    let cols = target.columns().count() as usize;
+    let spacing_x = target.metadata().cell_size_x().abs();
+    let spacing_y = target.metadata().cell_size_y().abs();
+
+    let mut envelope = LowerEnvelope::with_capacity(rows.max(cols));
+
+    // First pass along the columns: the distance to the closest target in the same column
+    let mut column_distance = vec![f64::INFINITY; rows * cols];
+    let mut column_target_row = vec![None; rows * cols];
+    {
+        let mut f = vec![0.0; rows];
+        let mut distance = vec![0.0; rows];
+        let mut closest = vec![None; rows];
+        for c in 0..cols {
+            for (r, value) in f.iter_mut().enumerate() {
+                let is_target = target
+                    .cell_value(Cell::from_row_col(r as i32, c as i32))
+                    .is_some_and(|v| v != RasterType::Pixel::zero());
+                *value = if is_target { 0.0 } else { f64::INFINITY };
+            }
+
+            envelope.transform(&f, spacing_y, &mut distance, &mut closest);
+            for r in 0..rows {
+                column_distance[r * cols + c] = distance[r];
+                column_target_row[r * cols + c] = closest[r];
+            }
+        }
+    }
+
+    // Second pass along the rows: combine the column distances into the distance to the closest target
+    let mut squared_distance = vec![f64::INFINITY; rows * cols];
+    let mut closest_target = vec![None; rows * cols];
+    {
+        let mut closest = vec![None; cols];
+        for r in 0..rows {
+            let row = r * cols..(r + 1) * cols;
+            envelope.transform(
+                &column_distance[row.clone()],
+                spacing_x,
+                &mut squared_distance[row.clone()],
+                &mut closest,
+            );
+            for (c, target_col) in closest.iter().enumerate() {
+                closest_target[r * cols + c] = target_col.and_then(|target_col| {
+                    column_target_row[r * cols + target_col].map(|target_row| Cell::from_row_col(target_row as i32, target_col as i32))
+                });
+            }
+        }
+    }
+
+    (squared_distance, closest_target)
+}
+
+/// Calculates the exact euclidean distance to the closest target and the closest target cell for every cell.
+/// Target cells are the cells with a non zero value. Nodata cells of the target raster remain nodata and
+/// cells that are further away from a target than the optional maximum distance become nodata.
+pub fn euclidean_feature_transform<RasterType>(
+    target: &RasterType,
+    max_distance: Option<f64>,
+) -> Result<FeatureTransform<RasterType::WithPixelType<f32>>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let (squared_distance, mut closest_target) = squared_feature_transform(target);
+
+    let mut distances = Vec::with_capacity(squared_distance.len());
+    for ((target_value, squared_distance), closest) in target.iter_opt().zip(squared_distance).zip(closest_target.iter_mut()) {
+        let distance = squared_distance.sqrt();
+        if target_value.is_none() || !distance.is_finite() || max_distance.is_some_and(|max_distance| distance > max_distance) {
+            *closest = None;
+            distances.push(None);
+        } else {
+            distances.push(Some(distance as f32));
+        }
+    }
+
+    Ok(FeatureTransform {
+        distance: RasterType::WithPixelType::<f32>::from_iter_opt(target.metadata().clone(), distances.into_iter())?,
+        closest_target,
+    })
+}
+
+/// Calculates the exact euclidean distance to the closest target for every cell.
+/// Target cells are the cells with a non zero value. Nodata cells of the target raster remain nodata and
+/// cells that are further away from a target than the optional maximum distance become nodata.
+pub fn euclidean_distance<RasterType>(target: &RasterType, max_distance: Option<f64>) -> Result<RasterType::WithPixelType<f32>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    Ok(euclidean_feature_transform(target, max_distance)?.distance)
+}
+
+/// Assigns the value of the closest target to every cell, using the exact euclidean distance.
+/// Cells without a target within the optional maximum distance become nodata.
+pub fn euclidean_closest_target<T, RasterType>(target: &RasterType, max_distance: Option<f64>) -> Result<RasterType::WithPixelType<T>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    T: ArrayNum,
+{
+    euclidean_value_at_closest_target(target, target, max_distance)
+}
+
+/// Assigns the value at the closest target to every cell, using the exact euclidean distance.
+/// Cells without a target within the optional maximum distance and cells where the value at the closest target is nodata become nodata.
+pub fn euclidean_value_at_closest_target<TResult, TargetRaster, ValueRaster>(
+    target: &TargetRaster,
+    value: &ValueRaster,
+    max_distance: Option<f64>,
+) -> Result<ValueRaster::WithPixelType<TResult>>
+where
+    TResult: ArrayNum,
+    TargetRaster: Array<Metadata = GeoReference>,
+    ValueRaster: Array,
+{
+    array::check_dimensions(target, value)?;
+
+    let transform = euclidean_feature_transform(target, max_distance)?;
+    ValueRaster::WithPixelType::<TResult>::from_iter_opt(
+        value.metadata().clone(),
+        transform.closest_target.iter().map(|closest| {
+            closest
+                .and_then(|cell| value.cell_value(cell))
+                .and_then(|v| <TResult as NumCast>::from(v))
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize,
+        raster::DenseRaster,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    /// Brute force reference implementation
+    fn closest_distance(target: &DenseRaster<u8>, cell: Cell) -> f64 {
+        let georef = target.metadata();
+        let center = georef.cell_center(cell);
+        (0..target.len() as i32)
+            .map(|i| Cell::from_row_col(i / target.columns().count(), i % target.columns().count()))
+            .filter(|&target_cell| target.cell_value(target_cell).is_some_and(|v| v != 0))
+            .map(|target_cell| {
+                let target_center = georef.cell_center(target_cell);
+                (center.x() - target_center.x()).hypot(center.y() - target_center.y())
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn euclidean_distance_matches_brute_force() -> Result<()> {
+        let rows = 23;
+        let cols = 37;
+        let targets = [(0, 0), (5, 30), (17, 3), (22, 36), (11, 18), (12, 18)];
+        let mut data = vec![0u8; (rows * cols) as usize];
+        for (r, c) in targets {
+            data[(r * cols + c) as usize] = 1;
+        }
+
+        for cell_size in [CellSize::square(10.0), CellSize::new(25.0, -10.0), CellSize::new(10.0, -40.0)] {
+            let target = create_raster(rows, cols, cell_size, &data);
+            let transform = euclidean_feature_transform(&target, None)?;
+
+            for r in 0..rows {
+                for c in 0..cols {
+                    let cell = Cell::from_row_col(r, c);
+                    let expected = closest_distance(&target, cell);
+                    assert_relative_eq!(transform.distance[cell], expected as f32, max_relative = 1e-6);
+
+                    let closest = transform.closest_target_of(cell).expect("closest target");
+                    assert_eq!(target[closest], 1);
+                    let center = target.metadata().cell_center(cell);
+                    let closest_center = target.metadata().cell_center(closest);
+                    assert_relative_eq!(
+                        (center.x() - closest_center.x()).hypot(center.y() - closest_center.y()),
+                        expected,
+                        max_relative = 1e-9
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn euclidean_distance_max_distance_and_nodata() -> Result<()> {
+        const ND: u8 = NOD as u8;
+
+        #[rustfmt::skip]
+        let target = create_raster(3, 5, CellSize::square(100.0), &[
+            1, 0, 0, 0, 0,
+            0, ND, 0, 0, 0,
+            0, 0, 0, 0, 0,
+        ]);
+
+        let distances = euclidean_distance(&target, Some(250.0))?;
+        assert_eq!(distances.cell_value(Cell::from_row_col(0, 0)), Some(0.0));
+        assert_eq!(distances.cell_value(Cell::from_row_col(1, 1)), None);
+        assert_relative_eq!(distances[Cell::from_row_col(1, 2)], (100.0_f32 * 100.0 + 200.0 * 200.0).sqrt());
+        assert_eq!(distances.cell_value(Cell::from_row_col(2, 2)), None);
+        assert_eq!(distances.cell_value(Cell::from_row_col(0, 2)), Some(200.0));
+        assert_eq!(distances.cell_value(Cell::from_row_col(0, 3)), None);
+        assert_eq!(distances.cell_value(Cell::from_row_col(2, 4)), None);
+
+        let unlimited = euclidean_distance(&target, None)?;
+        assert_relative_eq!(unlimited[Cell::from_row_col(2, 4)], (200.0_f32 * 200.0 + 400.0 * 400.0).sqrt());
+
+        // Without any target all cells are unreachable
+        let empty = create_raster(2, 2, CellSize::square(1.0), &[0u8, 0, 0, 0]);
+        assert!(euclidean_distance(&empty, None)?.iter_opt().all(|v| v.is_none()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn euclidean_value_at_closest_target_exact() -> Result<()> {
+        #[rustfmt::skip]
+        let target = create_raster(1, 7, CellSize::square(10.0), &[
+            3, 0, 0, 0, 0, 0, 7,
+        ]);
+
+        #[rustfmt::skip]
+        let value = create_raster(1, 7, CellSize::square(10.0), &[
+            1.5, 0.0, 0.0, 0.0, 0.0, 0.0, 2.5,
+        ]);
+
+        let closest = euclidean_closest_target::<u16, _>(&target, None)?;
+        assert_eq!(closest.iter_opt().collect::<Vec<_>>(), [3, 3, 3, 3, 7, 7, 7].map(Some));
+
+        let values = euclidean_value_at_closest_target::<f32, _, _>(&target, &value, Some(20.0))?;
+        assert_eq!(
+            values.iter_opt().collect::<Vec<_>>(),
+            [Some(1.5), Some(1.5), Some(1.5), None, Some(2.5), Some(2.5), Some(2.5)]
+        );
+
+        let wrong_size = create_raster(7, 1, CellSize::square(10.0), &[0.0; 7]);
+        assert!(euclidean_value_at_closest_target::<f32, _, _>(&target, &wrong_size, None).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,596 @@
+//! Focal (moving window) statistics.
+
+use std::cmp::Ordering;
+
+use num::{NumCast, ToPrimitive as _};
+
+use crate::{Array, ArrayCopy, ArrayMetadata as _, ArrayNum, Cell, Error, Result};
+
+/// Windows containing at least this many cells use a summed-area table for the statistics that support it.
+const SUMMED_AREA_TABLE_THRESHOLD: usize = 25;
+
+/// The statistic that is calculated over the cells in the focal window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocalStatistic {
+    Mean,
+    Sum,
+    Min,
+    Max,
+    /// Population standard deviation
+    StdDev,
+    /// The most frequent value, the lowest value wins in case of a tie
+    Majority,
+    /// The number of unique values
+    Variety,
+}
+
+/// The units in which the dimensions of a focal window are expressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocalWindowUnits {
+    #[default]
+    Cells,
+    /// Map units, converted to cells using the cell size of the raster
+    MapUnits,
+}
+
+/// The shape of a focal window, centered on the cell being calculated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FocalWindowShape {
+    /// Rectangular window, even sizes (in cells) are rounded up to the next odd size
+    Rectangle { width: f64, height: f64 },
+    /// All cells with a center within the radius
+    Circle { radius: f64 },
+    /// All cells with a center further than the inner radius and within the outer radius
+    Annulus { inner_radius: f64, outer_radius: f64 },
+}
+
+/// A focal window: a shape and the units of its dimensions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocalWindow {
+    pub shape: FocalWindowShape,
+    pub units: FocalWindowUnits,
+}
+
+impl FocalWindow {
+    pub fn rectangle(width: usize, height: usize) -> Self {
+        Self::new(
+            FocalWindowShape::Rectangle {
+                width: width as f64,
+                height: height as f64,
+            },
+            FocalWindowUnits::Cells,
+        )
+    }
+
+    pub fn circle(radius: f64) -> Self {
+        Self::new(FocalWindowShape::Circle { radius }, FocalWindowUnits::Cells)
+    }
+
+    pub fn annulus(inner_radius: f64, outer_radius: f64) -> Self {
+        Self::new(
+            FocalWindowShape::Annulus {
+                inner_radius,
+                outer_radius,
+            },
+            FocalWindowUnits::Cells,
+        )
+    }
+
+    pub fn rectangle_map_units(width: f64, height: f64) -> Self {
+        Self::new(FocalWindowShape::Rectangle { width, height }, FocalWindowUnits::MapUnits)
+    }
+
+    pub fn circle_map_units(radius: f64) -> Self {
+        Self::new(FocalWindowShape::Circle { radius }, FocalWindowUnits::MapUnits)
+    }
+
+    pub fn annulus_map_units(inner_radius: f64, outer_radius: f64) -> Self {
+        Self::new(
+            FocalWindowShape::Annulus {
+                inner_radius,
+                outer_radius,
+            },
+            FocalWindowUnits::MapUnits,
+        )
+    }
+
+    pub fn new(shape: FocalWindowShape, units: FocalWindowUnits) -> Self {
+        Self { shape, units }
+    }
+
+    /// The (row, column) offsets of the cells in the window relative to the center cell.
+    /// `cell_width` and `cell_height` are the dimensions of a cell in the units of the window.
+    fn cell_offsets(&self, cell_width: f64, cell_height: f64) -> Result<Vec<(i32, i32)>> {
+        let mut offsets = Vec::new();
+
+        match self.shape {
+            FocalWindowShape::Rectangle { width, height } => {
+                if !(width > 0.0 && height > 0.0) {
+                    return Err(Error::InvalidArgument(format!(
+                        "Invalid focal rectangle dimensions: {width}x{height}"
+                    )));
+                }
+
+                let half_cols = ((width / cell_width) / 2.0).floor() as i32;
+                let half_rows = ((height / cell_height) / 2.0).floor() as i32;
+                for r in -half_rows..=half_rows {
+                    for c in -half_cols..=half_cols {
+                        offsets.push((r, c));
+                    }
+                }
+            }
+            FocalWindowShape::Circle { radius } => {
+                if radius.is_nan() || radius < 0.0 {
+                    return Err(Error::InvalidArgument(format!("Invalid focal circle radius: {radius}")));
+                }
+
+                push_ring_offsets(&mut offsets, None, radius, cell_width, cell_height);
+            }
+            FocalWindowShape::Annulus {
+                inner_radius,
+                outer_radius,
+            } => {
+                if !(inner_radius >= 0.0 && inner_radius < outer_radius) {
+                    return Err(Error::InvalidArgument(format!(
+                        "Invalid focal annulus radii: inner {inner_radius}, outer {outer_radius}"
+                    )));
+                }
+
+                push_ring_offsets(&mut offsets, Some(inner_radius), outer_radius, cell_width, cell_height);
+            }
+        }
+
+        Ok(offsets)
+    }
+}
+
+fn push_ring_offsets(offsets: &mut Vec<(i32, i32)>, inner_radius: Option<f64>, outer_radius: f64, cell_width: f64, cell_height: f64) {
+    let half_cols = (outer_radius / cell_width).floor() as i32;
+    let half_rows = (outer_radius / cell_height).floor() as i32;
+
+    for r in -half_rows..=half_rows {
+        for c in -half_cols..=half_cols {
+            let distance = (c as f64 * cell_width).hypot(r as f64 * cell_height);
+            if distance <= outer_radius && inner_radius.is_none_or(|inner| distance > inner) {
+                offsets.push((r, c));
+            }
+        }
+    }
+}
+
+/// Controls how nodata cells in the focal window are handled.
+/// Cells of the window that fall outside of the raster are considered nodata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocalNodata {
+    /// Nodata cells are skipped, the result is nodata when less than `min_count` cells in the window contain data
+    Skip { min_count: usize },
+    /// The result is nodata when any cell in the window is nodata
+    Propagate,
+}
+
+impl Default for FocalNodata {
+    fn default() -> Self {
+        FocalNodata::Skip { min_count: 1 }
+    }
+}
+
+impl FocalNodata {
+    fn accepts(&self, data_count: usize, nodata_count: usize) -> bool {
+        match *self {
+            FocalNodata::Skip { min_count } => data_count > 0 && data_count >= min_count,
+            FocalNodata::Propagate => data_count > 0 && nodata_count == 0,
+        }
+    }
+}
+
+/// Calculates the focal `statistic` for every cell of the raster using the cells within `window`.
+/// Nodata cells in the window are handled according to the `nodata` policy.
+/// Windows in map units require a raster with a valid cell size in its metadata.
+pub fn focal_statistics<TResult, RasterType>(
+    raster: &RasterType,
+    statistic: FocalStatistic,
+    window: FocalWindow,
+    nodata: FocalNodata,
+) -> Result<RasterType::WithPixelType<TResult>>
+where
+    TResult: ArrayNum,
+    RasterType: Array,
+    RasterType::WithPixelType<TResult>: ArrayCopy<TResult, RasterType>,
+{
+    let (cell_width, cell_height) = match window.units {
+        FocalWindowUnits::Cells => (1.0, 1.0),
+        FocalWindowUnits::MapUnits => {
+            let cell_size = raster.metadata().geo_reference().cell_size();
+            let (width, height) = (cell_size.x().abs(), cell_size.y().abs());
+            if width == 0.0 || height == 0.0 {
+                return Err(Error::InvalidArgument(
+                    "A raster cell size is required for focal windows in map units".into(),
+                ));
+            }
+
+            (width, height)
+        }
+    };
+
+    let offsets = window.cell_offsets(cell_width, cell_height)?;
+    let mut result = RasterType::WithPixelType::<TResult>::new_with_dimensions_of(raster, TResult::NODATA);
+
+    let use_summed_area_table = matches!(window.shape, FocalWindowShape::Rectangle { .. })
+        && matches!(statistic, FocalStatistic::Mean | FocalStatistic::Sum | FocalStatistic::StdDev)
+        && offsets.len() >= SUMMED_AREA_TABLE_THRESHOLD;
+
+    if use_summed_area_table {
+        focal_summed_area(raster, statistic, &offsets, nodata, &mut result)?;
+    } else {
+        focal_direct(raster, statistic, &offsets, nodata, &mut result)?;
+    }
+
+    Ok(result)
+}
+
+/// Converts the calculated statistic to the result type, statistics that don't fit in the result type are an error
+fn to_result_value<T: ArrayNum>(statistic: FocalStatistic, value: Option<f64>) -> Result<Option<T>> {
+    match value {
+        Some(v) => match NumCast::from(v) {
+            Some(result) => Ok(Some(result)),
+            None => Err(Error::InvalidArgument(format!(
+                "Focal {statistic:?} value {v} does not fit in the result type {:?}",
+                T::TYPE
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn focal_direct<RasterType, ResultType>(
+    raster: &RasterType,
+    statistic: FocalStatistic,
+    offsets: &[(i32, i32)],
+    nodata: FocalNodata,
+    result: &mut ResultType,
+) -> Result<()>
+where
+    RasterType: Array,
+    ResultType: Array,
+{
+    let rows = raster.rows().count();
+    let cols = raster.columns().count();
+    let mut values = Vec::with_capacity(offsets.len());
+
+    for r in 0..rows {
+        for c in 0..cols {
+            values.clear();
+            let mut nodata_count = 0;
+
+            for &(dr, dc) in offsets {
+                let (row, col) = (r + dr, c + dc);
+                if row < 0 || row >= rows || col < 0 || col >= cols {
+                    nodata_count += 1;
+                    continue;
+                }
+
+                match raster.cell_value(Cell::from_row_col(row, col)) {
+                    Some(v) => values.push(v),
+                    None => nodata_count += 1,
+                }
+            }
+
+            let value = if nodata.accepts(values.len(), nodata_count) {
+                calculate_statistic(statistic, &mut values)
+            } else {
+                None
+            };
+
+            result.set_cell_value(Cell::from_row_col(r, c), to_result_value(statistic, value)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn calculate_statistic<T: ArrayNum>(statistic: FocalStatistic, values: &mut [T]) -> Option<f64> {
+    let to_f64 = |v: &T| v.to_f64().unwrap_or(0.0);
+    let count = values.len() as f64;
+
+    match statistic {
+        FocalStatistic::Sum => Some(values.iter().map(to_f64).sum()),
+        FocalStatistic::Mean => Some(values.iter().map(to_f64).sum::<f64>() / count),
+        FocalStatistic::StdDev => {
+            let mean = values.iter().map(to_f64).sum::<f64>() / count;
+            let variance = values.iter().map(|v| (to_f64(v) - mean).powi(2)).sum::<f64>() / count;
+            Some(variance.sqrt())
+        }
+        FocalStatistic::Min => values
+            .iter()
+            .copied()
+            .reduce(|a, b| if b < a { b } else { a })
+            .and_then(|v| v.to_f64()),
+        FocalStatistic::Max => values
+            .iter()
+            .copied()
+            .reduce(|a, b| if b > a { b } else { a })
+            .and_then(|v| v.to_f64()),
+        FocalStatistic::Majority | FocalStatistic::Variety => {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            let mut variety = 0;
+            let mut majority = None;
+            let mut majority_count = 0;
+            for run in values.chunk_by(|a, b| a == b) {
+                variety += 1;
+                if run.len() > majority_count {
+                    majority_count = run.len();
+                    majority = Some(run[0]);
+                }
+            }
+
+            match statistic {
+                FocalStatistic::Majority => majority.and_then(|v| v.to_f64()),
+                _ => Some(variety as f64),
+            }
+        }
+    }
+}
+
+/// Summed-area tables of the values, the squared values and the data cell count.
+/// The values are shifted by the mean of the raster to avoid cancellation in the variance of large values.
+/// Each table has an extra leading row and column of zeros.
+struct SummedAreaTables {
+    cols: usize,
+    shift: f64,
+    sum: Vec<f64>,
+    sum_squared: Vec<f64>,
+    count: Vec<usize>,
+}
+
+impl SummedAreaTables {
+    fn new<RasterType: Array>(raster: &RasterType) -> Self {
+        let rows = raster.rows().count() as usize;
+        let cols = raster.columns().count() as usize;
+        let stride = cols + 1;
+
+        let (total, data_count) = raster
+            .iter_opt()
+            .flatten()
+            .fold((0.0, 0usize), |(total, n), v| (total + v.to_f64().unwrap_or(0.0), n + 1));
+        let shift = if data_count > 0 { total / data_count as f64 } else { 0.0 };
+
+        let mut sum = vec![0.0; (rows + 1) * stride];
+        let mut sum_squared = vec![0.0; (rows + 1) * stride];
+        let mut count = vec![0; (rows + 1) * stride];
+
+        for r in 0..rows {
+            for c in 0..cols {
+                let (value, present) = match raster.cell_value(Cell::from_row_col(r as i32, c as i32)) {
+                    Some(v) => (v.to_f64().unwrap_or(0.0) - shift, 1),
+                    None => (0.0, 0),
+                };
+
+                let index = (r + 1) * stride + c + 1;
+                let above = r * stride + c + 1;
+                let left = index - 1;
+                let above_left = above - 1;
+
+                sum[index] = value + sum[above] + sum[left] - sum[above_left];
+                sum_squared[index] = value * value + sum_squared[above] + sum_squared[left] - sum_squared[above_left];
+                count[index] = present + count[above] + count[left] - count[above_left];
+            }
+        }
+
+        Self {
+            cols,
+            shift,
+            sum,
+            sum_squared,
+            count,
+        }
+    }
+
+    /// Shifted sum, shifted sum of squares and data count of the cells in the inclusive row and column ranges
+    fn area(&self, row_start: usize, row_end: usize, col_start: usize, col_end: usize) -> (f64, f64, usize) {
+        let stride = self.cols + 1;
+        let bottom_right = (row_end + 1) * stride + col_end + 1;
+        let bottom_left = (row_end + 1) * stride + col_start;
+        let top_right = row_start * stride + col_end + 1;
+        let top_left = row_start * stride + col_start;
+
+        (
+            self.sum[bottom_right] - self.sum[bottom_left] - self.sum[top_right] + self.sum[top_left],
+            self.sum_squared[bottom_right] - self.sum_squared[bottom_left] - self.sum_squared[top_right] + self.sum_squared[top_left],
+            self.count[bottom_right] + self.count[top_left] - self.count[bottom_left] - self.count[top_right],
+        )
+    }
+}
+
+fn focal_summed_area<RasterType, ResultType>(
+    raster: &RasterType,
+    statistic: FocalStatistic,
+    offsets: &[(i32, i32)],
+    nodata: FocalNodata,
+    result: &mut ResultType,
+) -> Result<()>
+where
+    RasterType: Array,
+    ResultType: Array,
+{
+    let rows = raster.rows().count();
+    let cols = raster.columns().count();
+    let half_rows = offsets.iter().map(|&(r, _)| r).max().unwrap_or(0);
+    let half_cols = offsets.iter().map(|&(_, c)| c).max().unwrap_or(0);
+
+    let tables = SummedAreaTables::new(raster);
+
+    for r in 0..rows {
+        for c in 0..cols {
+            let row_start = (r - half_rows).max(0) as usize;
+            let row_end = (r + half_rows).min(rows - 1) as usize;
+            let col_start = (c - half_cols).max(0) as usize;
+            let col_end = (c + half_cols).min(cols - 1) as usize;
+
+            let (sum, sum_squared, count) = tables.area(row_start, row_end, col_start, col_end);
+
+            let value = if nodata.accepts(count, offsets.len() - count) {
+                let n = count as f64;
+                match statistic {
+                    FocalStatistic::Sum => Some(sum + tables.shift * n),
+                    FocalStatistic::Mean => Some(sum / n + tables.shift),
+                    FocalStatistic::StdDev => {
+                        // The variance does not depend on the shift
+                        let mean = sum / n;
+                        Some((sum_squared / n - mean * mean).max(0.0).sqrt())
+                    }
+                    _ => unreachable!("Statistic not supported by the summed-area table"),
+                }
+            } else {
+                None
+            };
+
+            result.set_cell_value(Cell::from_row_col(r, c), to_result_value(statistic, value)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize,
+        raster::DenseRaster,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    #[test]
+    fn window_cell_offsets() -> Result<()> {
+        assert_eq!(FocalWindow::rectangle(3, 3).cell_offsets(1.0, 1.0)?.len(), 9);
+        assert_eq!(FocalWindow::rectangle(4, 1).cell_offsets(1.0, 1.0)?.len(), 5);
+        assert_eq!(FocalWindow::circle(1.0).cell_offsets(1.0, 1.0)?.len(), 5);
+        assert_eq!(FocalWindow::circle(1.5).cell_offsets(1.0, 1.0)?.len(), 9);
+        assert_eq!(FocalWindow::annulus(1.0, 1.5).cell_offsets(1.0, 1.0)?.len(), 4);
+        assert_eq!(FocalWindow::circle_map_units(150.0).cell_offsets(100.0, 100.0)?.len(), 9);
+
+        assert!(FocalWindow::annulus(2.0, 1.0).cell_offsets(1.0, 1.0).is_err());
+        assert!(FocalWindow::rectangle(0, 3).cell_offsets(1.0, 1.0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn focal_mean_skips_nodata() -> Result<()> {
+        #[rustfmt::skip]
+        let raster = create_raster(3, 3, CellSize::square(100.0), &[
+            1.0, 2.0, 3.0,
+            4.0, NOD, 6.0,
+            7.0, 8.0, 9.0,
+        ]);
+
+        let result = focal_statistics::<f64, _>(&raster, FocalStatistic::Mean, FocalWindow::rectangle(3, 3), FocalNodata::default())?;
+        assert_relative_eq!(result.cell_value(Cell::from_row_col(1, 1)).unwrap(), 5.0);
+        assert_relative_eq!(result.cell_value(Cell::from_row_col(0, 0)).unwrap(), 7.0 / 3.0);
+
+        let result = focal_statistics::<f64, _>(
+            &raster,
+            FocalStatistic::Sum,
+            FocalWindow::rectangle_map_units(300.0, 300.0),
+            FocalNodata::Skip { min_count: 4 },
+        )?;
+        assert_eq!(result.cell_value(Cell::from_row_col(0, 0)), None);
+        assert_eq!(result.cell_value(Cell::from_row_col(0, 1)), Some(16.0));
+
+        let result = focal_statistics::<f64, _>(&raster, FocalStatistic::Max, FocalWindow::rectangle(3, 3), FocalNodata::Propagate)?;
+        assert_eq!(result.nodata_count(), 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn focal_majority_and_variety() -> Result<()> {
+        #[rustfmt::skip]
+        let raster = create_raster(3, 3, CellSize::square(100.0), &[
+            1.0, 2.0, 2.0,
+            3.0, 2.0, 1.0,
+            1.0, NOD, 3.0,
+        ]);
+
+        let window = FocalWindow::rectangle(3, 3);
+        let majority = focal_statistics::<u8, _>(&raster, FocalStatistic::Majority, window, FocalNodata::default())?;
+        assert_eq!(majority.cell_value(Cell::from_row_col(1, 1)), Some(1));
+        assert_eq!(majority.cell_value(Cell::from_row_col(0, 2)), Some(2));
+
+        let variety = focal_statistics::<u8, _>(&raster, FocalStatistic::Variety, window, FocalNodata::default())?;
+        assert_eq!(variety.cell_value(Cell::from_row_col(1, 1)), Some(3));
+        assert_eq!(variety.cell_value(Cell::from_row_col(0, 0)), Some(3));
+
+        let min = focal_statistics::<u8, _>(&raster, FocalStatistic::Min, FocalWindow::annulus(0.0, 1.0), FocalNodata::default())?;
+        assert_eq!(min.cell_value(Cell::from_row_col(1, 1)), Some(1));
+        assert_eq!(min.cell_value(Cell::from_row_col(2, 2)), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn summed_area_table_matches_direct_calculation() -> Result<()> {
+        let (rows, cols) = (20, 17);
+        let data: Vec<f64> = (0..rows * cols)
+            .map(|i| if i % 7 == 3 { NOD } else { ((i * 37) % 23) as f64 })
+            .collect();
+        let raster = create_raster(rows, cols, CellSize::square(100.0), &data);
+
+        let window = FocalWindow::rectangle(7, 5);
+        let offsets = window.cell_offsets(1.0, 1.0)?;
+        assert!(offsets.len() >= SUMMED_AREA_TABLE_THRESHOLD);
+
+        for statistic in [FocalStatistic::Sum, FocalStatistic::Mean, FocalStatistic::StdDev] {
+            for nodata in [FocalNodata::Skip { min_count: 20 }, FocalNodata::Propagate] {
+                let summed = focal_statistics::<f64, _>(&raster, statistic, window, nodata)?;
+                let mut direct = DenseRaster::<f64>::new_with_dimensions_of(&raster, NOD);
+                focal_direct(&raster, statistic, &offsets, nodata, &mut direct)?;
+
+                assert_eq!(summed.nodata_count(), direct.nodata_count());
+                for (s, d) in summed.iter_opt().zip(direct.iter_opt()) {
+                    assert_eq!(s.is_some(), d.is_some());
+                    if let (Some(s), Some(d)) = (s, d) {
+                        assert_relative_eq!(s, d, epsilon = 1e-9);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn summed_area_std_dev_of_large_values() -> Result<()> {
+        let (rows, cols) = (30, 30);
+        let data: Vec<f64> = (0..rows * cols).map(|i| 1.0e9 + ((i * 37) % 23) as f64 * 0.01).collect();
+        let raster = create_raster(rows, cols, CellSize::square(100.0), &data);
+
+        let window = FocalWindow::rectangle(5, 5);
+        let offsets = window.cell_offsets(1.0, 1.0)?;
+        let summed = focal_statistics::<f64, _>(&raster, FocalStatistic::StdDev, window, FocalNodata::default())?;
+        let mut direct = DenseRaster::<f64>::new_with_dimensions_of(&raster, NOD);
+        focal_direct(&raster, FocalStatistic::StdDev, &offsets, FocalNodata::default(), &mut direct)?;
+
+        for (s, d) in summed.iter().zip(direct.iter()) {
+            assert_relative_eq!(*s, *d, epsilon = 1e-6);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn result_that_does_not_fit_is_an_error() -> Result<()> {
+        let raster = create_raster(3, 3, CellSize::square(100.0), &[100.0; 9]);
+
+        for window in [FocalWindow::rectangle(3, 3), FocalWindow::rectangle(5, 5)] {
+            assert!(focal_statistics::<u8, _>(&raster, FocalStatistic::Sum, window, FocalNodata::default()).is_err());
+            assert!(focal_statistics::<u16, _>(&raster, FocalStatistic::Sum, window, FocalNodata::default()).is_ok());
+        }
+
+        Ok(())
+    }
+}
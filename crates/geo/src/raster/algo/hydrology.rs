@@ -0,0 +1,670 @@
+//! Hydrological analysis of digital elevation models: depression filling, flow directions, flow accumulation,
+//! stream extraction and watershed delineation.
+//!
+//! D8 flow directions are encoded as powers of two, starting east and going clockwise (like ArcGIS):
+//! ```text
+//!  32 64 128
+//!  16  x   1
+//!   8  4   2
+//! ```
+//! Cells without a downslope neighbour get direction 0.
+//! D-infinity flow directions are angles in radians, counter clockwise starting from the east (0 to 2π).
+//! Cells without a downslope direction get direction -1, because 0 is the east direction.
+//! Pits and flat areas are not nodata in either model: their flow is accumulated but not passed on.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+
+use num::{NumCast, ToPrimitive as _};
+
+use crate::{Array, ArrayCopy, Cell, DenseArray, Error, GeoReference, Nodata as _, Point, Result};
+
+use super::clusterutils::{FiLo, MARK_DONE, MARK_TODO, visit_neighbour_cells, visit_neighbour_diag_cells};
+
+/// A neighbour position as (row offset, column offset)
+type Offset = (i32, i32);
+
+/// The D8 neighbours as (row offset, column offset, direction code), clockwise starting from the east
+const D8_NEIGHBOURS: [(i32, i32, u8); 8] = [
+    (0, 1, 1),
+    (1, 1, 2),
+    (1, 0, 4),
+    (1, -1, 8),
+    (0, -1, 16),
+    (-1, -1, 32),
+    (-1, 0, 64),
+    (-1, 1, 128),
+];
+
+/// The D-infinity direction of cells without a downslope direction (pits and flat areas)
+const DINF_PIT: f32 = -1.0;
+
+/// The neighbours as (row offset, column offset), counter clockwise starting from the east
+const COUNTER_CLOCKWISE_NEIGHBOURS: [Offset; 8] = [(0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1), (1, 0), (1, 1)];
+
+/// The D-infinity triangular facets as (cardinal neighbour, diagonal neighbour, multiple of π/2, angle sign)
+const DINF_FACETS: [(Offset, Offset, f64, f64); 8] = [
+    ((0, 1), (-1, 1), 0.0, 1.0),
+    ((-1, 0), (-1, 1), 1.0, -1.0),
+    ((-1, 0), (-1, -1), 1.0, 1.0),
+    ((0, -1), (-1, -1), 2.0, -1.0),
+    ((0, -1), (1, -1), 2.0, 1.0),
+    ((1, 0), (1, -1), 3.0, -1.0),
+    ((1, 0), (1, 1), 3.0, 1.0),
+    ((0, 1), (1, 1), 4.0, -1.0),
+];
+
+/// The flow direction model of a flow direction raster
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowRouting {
+    /// All the flow goes to the steepest downslope neighbour, see [`flow_direction_d8`]
+    D8,
+    /// The flow is divided between the two neighbours closest to the steepest downslope angle, see [`flow_direction_dinf`]
+    DInfinity,
+}
+
+/// A cell in the priority queue of the depression filling, ordered by elevation and insertion order
+#[derive(Debug, Clone, Copy)]
+struct FloodCell {
+    elevation: f64,
+    order: u64,
+    cell: Cell,
+}
+
+impl PartialEq for FloodCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FloodCell {}
+
+impl PartialOrd for FloodCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FloodCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.elevation.total_cmp(&other.elevation).then(self.order.cmp(&other.order))
+    }
+}
+
+fn neighbour_cell<RasterType: Array>(raster: &RasterType, cell: Cell, row_offset: i32, col_offset: i32) -> Option<Cell> {
+    let row = cell.row + row_offset;
+    let col = cell.col + col_offset;
+
+    (row >= 0 && row < raster.rows().count() && col >= 0 && col < raster.columns().count()).then(|| Cell::from_row_col(row, col))
+}
+
+fn data_value<RasterType: Array>(raster: &RasterType, cell: Cell) -> Option<f64> {
+    raster.cell_value(cell).and_then(|v| v.to_f64())
+}
+
+/// The horizontal and vertical distance between cell centers, 1 for rasters without a cell size
+fn cell_distances(georef: &GeoReference) -> (f64, f64) {
+    let or_one = |size: f64| if size == 0.0 { 1.0 } else { size.abs() };
+    (or_one(georef.cell_size_x()), or_one(georef.cell_size_y()))
+}
+
+/// Fills the depressions of the elevation model using the priority-flood algorithm, so every cell drains to the edge
+/// of the raster or to a nodata cell.
+/// The filled cells are raised `epsilon` above the cell they drain to, so the flow directions within the filled
+/// depressions are defined. An epsilon of 0 results in flat areas.
+/// An error is returned when the epsilon is too small to be representable at the elevations of the raster (e.g. for f32 rasters)
+/// or when a raised elevation does not fit in the raster type.
+pub fn fill_depressions<RasterType: Array>(dem: &RasterType, epsilon: f64) -> Result<RasterType> {
+    if !epsilon.is_finite() || epsilon < 0.0 {
+        return Err(Error::InvalidArgument(format!("Invalid depression filling epsilon: {epsilon}")));
+    }
+
+    let rows = dem.rows();
+    let cols = dem.columns();
+
+    let mut filled = dem.clone();
+    let mut mark = DenseArray::<u8, RasterType::Metadata>::filled_with(Some(MARK_TODO), dem.metadata().clone());
+    let mut open = BinaryHeap::new();
+    let mut pit = FiLo::new(rows, cols);
+    let mut order = 0;
+
+    // The cells on the edge of the raster or next to nodata are the outlets
+    for r in 0..rows.count() {
+        for c in 0..cols.count() {
+            let cell = Cell::from_row_col(r, c);
+            let Some(elevation) = data_value(dem, cell) else {
+                mark[cell] = MARK_DONE;
+                continue;
+            };
+
+            let mut is_outlet = r == 0 || c == 0 || r == rows.count() - 1 || c == cols.count() - 1;
+            let mut check_nodata = |neighbour: Cell| is_outlet |= dem.cell_value(neighbour).is_none();
+            visit_neighbour_cells(cell, rows, cols, &mut check_nodata);
+            visit_neighbour_diag_cells(cell, rows, cols, &mut check_nodata);
+
+            if is_outlet {
+                mark[cell] = MARK_DONE;
+                open.push(Reverse(FloodCell { elevation, order, cell }));
+                order += 1;
+            }
+        }
+    }
+
+    loop {
+        let (cell, elevation) = if !pit.is_empty() {
+            let cell = pit.pop_head();
+            (cell, data_value(&filled, cell).unwrap_or_default())
+        } else if let Some(Reverse(flood_cell)) = open.pop() {
+            (flood_cell.cell, flood_cell.elevation)
+        } else {
+            break;
+        };
+
+        let mut error = None;
+        let mut handle_neighbour = |neighbour: Cell| {
+            if mark[neighbour] != MARK_TODO {
+                return;
+            }
+
+            mark[neighbour] = MARK_DONE;
+            let neighbour_elevation = data_value(&filled, neighbour).unwrap_or_default();
+            if neighbour_elevation <= elevation {
+                // The neighbour is part of a depression
+                let raised: Option<RasterType::Pixel> = NumCast::from(elevation + epsilon);
+                match raised {
+                    Some(raised) if epsilon == 0.0 || raised.to_f64().is_some_and(|raised| raised > elevation) => {
+                        filled[neighbour] = raised
+                    }
+                    _ => {
+                        error.get_or_insert(Error::InvalidArgument(format!(
+                            "The filled elevation {} can not be represented in the raster type, use a larger epsilon",
+                            elevation + epsilon
+                        )));
+                    }
+                }
+                pit.push_back(neighbour);
+            } else {
+                open.push(Reverse(FloodCell {
+                    elevation: neighbour_elevation,
+                    order,
+                    cell: neighbour,
+                }));
+                order += 1;
+            }
+        };
+
+        visit_neighbour_cells(cell, rows, cols, &mut handle_neighbour);
+        visit_neighbour_diag_cells(cell, rows, cols, &mut handle_neighbour);
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Calculates the D8 flow direction of every cell: the direction of the steepest downslope neighbour.
+/// Cells without a lower neighbour (pits and flat areas) get direction 0, use [`fill_depressions`] to avoid them.
+pub fn flow_direction_d8<RasterType>(dem: &RasterType) -> Result<RasterType::WithPixelType<u8>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<u8>: ArrayCopy<u8, RasterType>,
+{
+    let (ew, ns) = cell_distances(dem.metadata());
+    let mut result = RasterType::WithPixelType::<u8>::new_with_dimensions_of(dem, 0);
+
+    for r in 0..dem.rows().count() {
+        for c in 0..dem.columns().count() {
+            let cell = Cell::from_row_col(r, c);
+            let Some(elevation) = data_value(dem, cell) else {
+                result.set_cell_value(cell, None);
+                continue;
+            };
+
+            let mut steepest_slope = 0.0;
+            let mut direction = 0;
+            for (row_offset, col_offset, code) in D8_NEIGHBOURS {
+                let Some(neighbour_elevation) = neighbour_cell(dem, cell, row_offset, col_offset).and_then(|n| data_value(dem, n)) else {
+                    continue;
+                };
+
+                let distance = (row_offset as f64 * ns).hypot(col_offset as f64 * ew);
+                let slope = (elevation - neighbour_elevation) / distance;
+                if slope > steepest_slope {
+                    steepest_slope = slope;
+                    direction = code;
+                }
+            }
+
+            result[cell] = direction;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Calculates the D-infinity flow direction of every cell as defined by Tarboton: the angle of the steepest
+/// downslope direction on the eight triangular facets around the cell.
+/// Cells without a downslope direction (pits and flat areas) get direction -1, use [`fill_depressions`] to avoid them.
+pub fn flow_direction_dinf<RasterType>(dem: &RasterType) -> Result<RasterType::WithPixelType<f32>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<f32>: ArrayCopy<f32, RasterType>,
+{
+    let (ew, ns) = cell_distances(dem.metadata());
+    let mut result = RasterType::WithPixelType::<f32>::new_with_dimensions_of(dem, f32::NODATA);
+
+    for r in 0..dem.rows().count() {
+        for c in 0..dem.columns().count() {
+            let cell = Cell::from_row_col(r, c);
+            let Some(e0) = data_value(dem, cell) else {
+                continue;
+            };
+
+            let mut steepest_slope = 0.0;
+            let mut angle = None;
+            for ((r1, c1), (r2, c2), multiple, sign) in DINF_FACETS {
+                let e1 = neighbour_cell(dem, cell, r1, c1).and_then(|n| data_value(dem, n));
+                let e2 = neighbour_cell(dem, cell, r2, c2).and_then(|n| data_value(dem, n));
+                let (Some(e1), Some(e2)) = (e1, e2) else {
+                    continue;
+                };
+
+                // The distance to the cardinal neighbour and between the cardinal and the diagonal neighbour
+                let (d1, d2) = if r1 == 0 { (ew, ns) } else { (ns, ew) };
+                let s1 = (e0 - e1) / d1;
+                let s2 = (e1 - e2) / d2;
+
+                let max_facet_angle = d2.atan2(d1);
+                let mut facet_angle = s2.atan2(s1);
+                let mut slope = s1.hypot(s2);
+                if facet_angle < 0.0 {
+                    facet_angle = 0.0;
+                    slope = s1;
+                } else if facet_angle > max_facet_angle {
+                    facet_angle = max_facet_angle;
+                    slope = (e0 - e2) / d1.hypot(d2);
+                }
+
+                if slope > steepest_slope {
+                    steepest_slope = slope;
+                    angle = Some((sign * facet_angle + multiple * FRAC_PI_2).rem_euclid(TAU));
+                }
+            }
+
+            result[cell] = angle.map_or(DINF_PIT, |angle| angle as f32);
+        }
+    }
+
+    Ok(result)
+}
+
+/// The cells that receive the flow of the cell, with the fraction of the flow they receive
+fn flow_receivers<RasterType: Array>(directions: &RasterType, cell: Cell, routing: FlowRouting) -> [Option<(Cell, f64)>; 2] {
+    let Some(direction) = data_value(directions, cell) else {
+        return [None, None];
+    };
+
+    let receiver = |(row_offset, col_offset): Offset, fraction: f64| {
+        neighbour_cell(directions, cell, row_offset, col_offset)
+            .filter(|&n| fraction > 0.0 && directions.cell_value(n).is_some())
+            .map(|n| (n, fraction))
+    };
+
+    match routing {
+        FlowRouting::D8 => {
+            let offset = D8_NEIGHBOURS
+                .iter()
+                .find(|(_, _, code)| direction == *code as f64)
+                .map(|&(row_offset, col_offset, _)| (row_offset, col_offset));
+            [offset.and_then(|offset| receiver(offset, 1.0)), None]
+        }
+        FlowRouting::DInfinity if direction == DINF_PIT as f64 => [None, None],
+        FlowRouting::DInfinity => {
+            let sector = direction.rem_euclid(TAU) / FRAC_PI_4;
+            let index = (sector.floor() as usize).min(7);
+            let fraction = sector - index as f64;
+
+            [
+                receiver(COUNTER_CLOCKWISE_NEIGHBOURS[index], 1.0 - fraction),
+                receiver(COUNTER_CLOCKWISE_NEIGHBOURS[(index + 1) % 8], fraction),
+            ]
+        }
+    }
+}
+
+fn accumulate<RasterType: Array>(
+    directions: &RasterType,
+    routing: FlowRouting,
+    cell_weight: impl Fn(Cell) -> f64,
+) -> Result<RasterType::WithPixelType<f64>> {
+    let rows = directions.rows();
+    let cols = directions.columns();
+    let index = |cell: Cell| (cell.row * cols.count() + cell.col) as usize;
+
+    let mut receivers = Vec::with_capacity(directions.len());
+    let mut upstream_count = vec![0u8; directions.len()];
+    let mut accumulation = vec![0.0; directions.len()];
+
+    for r in 0..rows.count() {
+        for c in 0..cols.count() {
+            let cell = Cell::from_row_col(r, c);
+            let cell_receivers = flow_receivers(directions, cell, routing);
+            for (receiver, _) in cell_receivers.iter().flatten() {
+                upstream_count[index(*receiver)] += 1;
+            }
+
+            if directions.cell_value(cell).is_some() {
+                accumulation[index(cell)] = cell_weight(cell);
+            }
+            receivers.push(cell_receivers);
+        }
+    }
+
+    // Process the cells from upstream to downstream, starting with the cells without inflow
+    let mut queue = FiLo::new(rows, cols);
+    for r in 0..rows.count() {
+        for c in 0..cols.count() {
+            let cell = Cell::from_row_col(r, c);
+            if upstream_count[index(cell)] == 0 && directions.cell_value(cell).is_some() {
+                queue.push_back(cell);
+            }
+        }
+    }
+
+    while !queue.is_empty() {
+        let cell = queue.pop_head();
+        let flow = accumulation[index(cell)];
+
+        for (receiver, fraction) in receivers[index(cell)].iter().flatten() {
+            let receiver_index = index(*receiver);
+            accumulation[receiver_index] += flow * fraction;
+            upstream_count[receiver_index] -= 1;
+            if upstream_count[receiver_index] == 0 {
+                queue.push_back(*receiver);
+            }
+        }
+    }
+
+    RasterType::WithPixelType::<f64>::from_iter_opt(
+        directions.metadata().clone(),
+        directions
+            .iter_opt()
+            .zip(accumulation)
+            .map(|(direction, acc)| direction.map(|_| acc)),
+    )
+}
+
+/// Calculates the number of cells that drain through every cell, including the cell itself.
+pub fn flow_accumulation<RasterType: Array>(directions: &RasterType, routing: FlowRouting) -> Result<RasterType::WithPixelType<f64>> {
+    accumulate(directions, routing, |_| 1.0)
+}
+
+/// Calculates the sum of the weights of the cells that drain through every cell, including the cell itself.
+/// Nodata weights do not contribute to the accumulation.
+pub fn weighted_flow_accumulation<RasterType, WeightRaster>(
+    directions: &RasterType,
+    routing: FlowRouting,
+    weights: &WeightRaster,
+) -> Result<RasterType::WithPixelType<f64>>
+where
+    RasterType: Array,
+    WeightRaster: Array,
+{
+    if directions.size() != weights.size() {
+        return Err(Error::InvalidArgument(
+            "Weight raster dimensions should match flow direction raster dimensions".into(),
+        ));
+    }
+
+    accumulate(directions, routing, |cell| data_value(weights, cell).unwrap_or(0.0))
+}
+
+/// Extracts the stream network: cells with a flow accumulation of at least the threshold are 1, other cells are 0.
+pub fn extract_streams<RasterType: Array>(accumulation: &RasterType, threshold: f64) -> Result<RasterType::WithPixelType<u8>> {
+    RasterType::WithPixelType::<u8>::from_iter_opt(
+        accumulation.metadata().clone(),
+        accumulation
+            .iter_opt()
+            .map(|acc| acc.map(|acc| if acc.to_f64().is_some_and(|acc| acc >= threshold) { 1 } else { 0 })),
+    )
+}
+
+/// Delineates the watersheds of the pour points based on D8 flow directions.
+/// The cells of the watershed of a pour point get the index of the pour point plus 1, other cells are 0.
+/// A cell that drains through multiple pour points belongs to the first pour point downstream.
+pub fn watersheds<RasterType>(directions: &RasterType, pour_points: &[Cell]) -> Result<RasterType::WithPixelType<i32>>
+where
+    RasterType: Array,
+    RasterType::WithPixelType<i32>: ArrayCopy<i32, RasterType>,
+{
+    let rows = directions.rows();
+    let cols = directions.columns();
+    let mut result = RasterType::WithPixelType::<i32>::new_with_dimensions_of(directions, 0);
+
+    for (index, &cell) in pour_points.iter().enumerate() {
+        if cell.row < 0 || cell.row >= rows.count() || cell.col < 0 || cell.col >= cols.count() {
+            return Err(Error::InvalidArgument(format!("Pour point {cell:?} is outside of the raster")));
+        }
+
+        result[cell] = index as i32 + 1;
+    }
+
+    let mut border = FiLo::new(rows, cols);
+    for &pour_point in pour_points {
+        let watershed_id = result[pour_point];
+
+        border.clear();
+        border.push_back(pour_point);
+        while !border.is_empty() {
+            let cell = border.pop_head();
+
+            let mut handle_upstream = |neighbour: Cell| {
+                if result[neighbour] != 0 {
+                    return;
+                }
+
+                if let [Some((receiver, _)), _] = flow_receivers(directions, neighbour, FlowRouting::D8)
+                    && receiver == cell
+                {
+                    result[neighbour] = watershed_id;
+                    border.push_back(neighbour);
+                }
+            };
+
+            visit_neighbour_cells(cell, rows, cols, &mut handle_upstream);
+            visit_neighbour_diag_cells(cell, rows, cols, &mut handle_upstream);
+        }
+    }
+
+    for r in 0..rows.count() {
+        for c in 0..cols.count() {
+            let cell = Cell::from_row_col(r, c);
+            if directions.cell_value(cell).is_none() {
+                result.set_cell_value(cell, None);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Delineates the watersheds of the pour points given as coordinates in the projection of the flow direction raster.
+/// See [`watersheds`] for the details.
+pub fn watersheds_for_points<RasterType>(directions: &RasterType, pour_points: &[Point]) -> Result<RasterType::WithPixelType<i32>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::WithPixelType<i32>: ArrayCopy<i32, RasterType>,
+{
+    let cells: Vec<Cell> = pour_points
+        .iter()
+        .map(|&point| directions.metadata().point_to_cell(point))
+        .collect();
+    watersheds(directions, &cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize,
+        raster::DenseRaster,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    const NOD_F32: f32 = NOD as f32;
+
+    fn values<T: crate::ArrayNum>(raster: &DenseRaster<T>) -> Vec<Option<T>> {
+        raster.iter_opt().collect()
+    }
+
+    #[test]
+    fn fill_depression() -> Result<()> {
+        #[rustfmt::skip]
+        let dem = create_raster::<f64>(3, 4, CellSize::square(10.0), &[
+            5.0, 5.0, 5.0, 5.0,
+            5.0, 1.0, 2.0, 4.0,
+            5.0, 5.0, 5.0, 5.0,
+        ]);
+
+        let filled = fill_depressions(&dem, 0.0)?;
+        assert_eq!(values(&filled)[4..8], [Some(5.0), Some(4.0), Some(4.0), Some(4.0)]);
+
+        let filled = fill_depressions(&dem, 0.5)?;
+        assert_eq!(values(&filled)[4..8], [Some(5.0), Some(5.0), Some(4.5), Some(4.0)]);
+
+        // Nodata cells act as outlets
+        #[rustfmt::skip]
+        let dem = create_raster::<f64>(3, 3, CellSize::square(10.0), &[
+            5.0, 5.0, 5.0,
+            5.0, 1.0, NOD,
+            5.0, 5.0, 5.0,
+        ]);
+        assert_eq!(values(&fill_depressions(&dem, 0.0)?), values(&dem));
+
+        // The raised elevations must be representable in the raster type
+        #[rustfmt::skip]
+        let dem = create_raster::<f32>(3, 3, CellSize::square(10.0), &[
+            1e8, 1e8, 1e8,
+            1e8, 1.0, 1e8,
+            1e8, 1e8, 1e8,
+        ]);
+        assert!(fill_depressions(&dem, 1e-3).is_err());
+        assert!(fill_depressions(&dem, 10.0).is_ok());
+        assert!(fill_depressions(&dem, -1.0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn d8_flow_direction() -> Result<()> {
+        #[rustfmt::skip]
+        let dem = create_raster::<f32>(3, 3, CellSize::square(10.0), &[
+            9.0, 8.0, 7.0,
+            8.0, 5.0, 6.0,
+            7.0, 6.0, NOD_F32,
+        ]);
+
+        let directions = flow_direction_d8(&dem)?;
+        assert_eq!(
+            values(&directions),
+            [Some(2), Some(4), Some(8), Some(1), Some(0), Some(16), Some(128), Some(64), None]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dinf_flow_direction() -> Result<()> {
+        // A plane that descends in the direction of the angle
+        for angle in [0.0_f64, 0.3, 2.0, 4.0, 5.5] {
+            let data: Vec<f64> = (0..25)
+                .map(|i| {
+                    let x = (i % 5) as f64 * 10.0;
+                    let y = -(i / 5) as f64 * 10.0;
+                    100.0 - x * angle.cos() - y * angle.sin()
+                })
+                .collect();
+
+            let directions = flow_direction_dinf(&create_raster(5, 5, CellSize::square(10.0), &data))?;
+            assert_relative_eq!(directions[Cell::from_row_col(2, 2)], angle as f32, epsilon = 1e-5);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn accumulation_streams_and_watersheds() -> Result<()> {
+        // Flows east and then south along the last column
+        #[rustfmt::skip]
+        let directions = create_raster::<u8>(3, 3, CellSize::square(10.0), &[
+            1, 1, 4,
+            1, 1, 4,
+            1, 1, 0,
+        ]);
+
+        let accumulation = flow_accumulation(&directions, FlowRouting::D8)?;
+        assert_eq!(values(&accumulation), [1.0, 2.0, 3.0, 1.0, 2.0, 6.0, 1.0, 2.0, 9.0].map(Some));
+
+        let weights = create_raster::<f32>(3, 3, CellSize::square(10.0), &[2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, NOD_F32]);
+        let weighted = weighted_flow_accumulation(&directions, FlowRouting::D8, &weights)?;
+        assert_eq!(values(&weighted)[8], Some(16.0));
+
+        let streams = extract_streams(&accumulation, 3.0)?;
+        assert_eq!(values(&streams), [0, 0, 1, 0, 0, 1, 0, 0, 1].map(Some));
+
+        let sheds = watersheds(&directions, &[Cell::from_row_col(2, 2), Cell::from_row_col(0, 2)])?;
+        assert_eq!(values(&sheds), [2, 2, 2, 1, 1, 1, 1, 1, 1].map(Some));
+
+        let sheds = watersheds_for_points(&directions, &[Point::new(15.0, -15.0)])?;
+        assert_eq!(values(&sheds), [0, 0, 0, 1, 1, 0, 0, 0, 0].map(Some));
+
+        assert!(watersheds(&directions, &[Cell::from_row_col(3, 0)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dinf_accumulation_splits_flow() -> Result<()> {
+        // The center cell flows halfway between east and north-east
+        #[rustfmt::skip]
+        let directions = create_raster::<f32>(3, 3, CellSize::square(10.0), &[
+            NOD_F32, NOD_F32, 0.0,
+            NOD_F32, std::f32::consts::FRAC_PI_8, 0.0,
+            NOD_F32, NOD_F32, NOD_F32,
+        ]);
+
+        let accumulation = flow_accumulation(&directions, FlowRouting::DInfinity)?;
+        assert_relative_eq!(accumulation[Cell::from_row_col(0, 2)], 1.5, epsilon = 1e-6);
+        assert_relative_eq!(accumulation[Cell::from_row_col(1, 2)], 1.5, epsilon = 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn dinf_pit_keeps_its_flow() -> Result<()> {
+        #[rustfmt::skip]
+        let dem = create_raster::<f32>(3, 3, CellSize::square(10.0), &[
+            5.0, 5.0, 5.0,
+            5.0, 1.0, 5.0,
+            5.0, 5.0, 5.0,
+        ]);
+
+        let directions = flow_direction_dinf(&dem)?;
+        assert_eq!(directions[Cell::from_row_col(1, 1)], DINF_PIT);
+
+        // The pit is not nodata, it collects the flow of the cells that drain into it like a D8 pit
+        let accumulation = flow_accumulation(&directions, FlowRouting::DInfinity)?;
+        let d8_accumulation = flow_accumulation(&flow_direction_d8(&dem)?, FlowRouting::D8)?;
+        assert_eq!(flow_direction_d8(&dem)?[Cell::from_row_col(1, 1)], 0);
+        assert_relative_eq!(accumulation[Cell::from_row_col(1, 1)], 9.0, epsilon = 1e-6);
+        assert_relative_eq!(d8_accumulation[Cell::from_row_col(1, 1)], 9.0);
+
+        Ok(())
+    }
+}
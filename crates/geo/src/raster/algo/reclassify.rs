@@ -0,0 +1,342 @@
+//! Reclassification of raster values using exact value mappings and half-open value ranges.
+
+use num::{NumCast, ToPrimitive as _};
+
+use crate::{Array, ArrayNum, Error, Nodata as _, Result};
+
+/// A single reclassification rule, a new value of `None` maps the matching values to nodata
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReclassRule {
+    /// Maps an exact value to a new value
+    Value { value: f64, new_value: Option<f64> },
+    /// Maps the values in the half-open range `[from, to)` to a new value
+    Range { from: f64, to: f64, new_value: Option<f64> },
+}
+
+impl ReclassRule {
+    fn matches(&self, value: f64) -> bool {
+        match *self {
+            ReclassRule::Value { value: rule_value, .. } => value == rule_value,
+            ReclassRule::Range { from, to, .. } => value >= from && value < to,
+        }
+    }
+
+    fn new_value(&self) -> Option<f64> {
+        match *self {
+            ReclassRule::Value { new_value, .. } | ReclassRule::Range { new_value, .. } => new_value,
+        }
+    }
+}
+
+/// Specifies how the data values that do not match any rule are reclassified
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UnmappedValues {
+    /// Keep the original value, an error is returned for values that can not be represented in the output type
+    #[default]
+    Keep,
+    /// Unmapped values become nodata
+    Nodata,
+    /// Unmapped values are set to the provided value
+    Value(f64),
+}
+
+/// The rules of a reclassification
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReclassTable {
+    /// The rules are evaluated in order, the first matching rule is applied
+    pub rules: Vec<ReclassRule>,
+    /// How to handle data values that do not match any rule
+    pub unmapped: UnmappedValues,
+    /// The value assigned to the nodata cells, `None` keeps them nodata
+    pub nodata: Option<f64>,
+}
+
+impl ReclassTable {
+    pub fn new(rules: Vec<ReclassRule>) -> Self {
+        ReclassTable {
+            rules,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the rules from a table based data source (e.g. csv, xlsx) with the columns `from`, `to` and `value`.
+    /// Rows without a `to` value map the exact `from` value, other rows map the range `[from, to)`.
+    /// Rows without a `value` map the matching values to nodata.
+    #[cfg(feature = "vector-io")]
+    pub fn from_file(path: &std::path::Path) -> Result<Self> {
+        let rows: Vec<ReclassRow> = crate::vector::read_dataframe_rows(&path, Default::default())?;
+        Ok(ReclassTable::new(rows.into_iter().map(|row| row.0).collect()))
+    }
+
+    fn validate(&self) -> Result<()> {
+        for rule in &self.rules {
+            match *rule {
+                ReclassRule::Value { value, .. } if value.is_nan() => {
+                    return Err(Error::InvalidArgument("Reclassification value can not be NaN".into()));
+                }
+                ReclassRule::Range { from, to, .. } if from.is_nan() || to.is_nan() || from >= to => {
+                    return Err(Error::InvalidArgument(format!("Invalid reclassification range [{from}, {to})")));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts the value to the output type, values that are not integral for integer output types, that do not fit
+/// or that equal the nodata value of the output type can not be represented
+fn to_output_value<T: ArrayNum>(value: f64) -> Result<T> {
+    let output = if !T::has_nan() && value.fract() != 0.0 {
+        None
+    } else {
+        NumCast::from(value).filter(|v: &T| !v.is_nodata())
+    };
+
+    output.ok_or_else(|| Error::InvalidArgument(format!("Reclassification value {value} can not be represented in {}", T::TYPE)))
+}
+
+fn cast_new_value<T: ArrayNum>(value: Option<f64>) -> Result<Option<T>> {
+    value.map(to_output_value).transpose()
+}
+
+/// Reclassifies the raster values using the rules of the reclassification table.
+/// The output pixel type can differ from the input pixel type, an error is returned when a new value
+/// of the table or a kept value can not be represented in the output type.
+pub fn reclassify<TDest, RasterType>(ras: &RasterType, table: &ReclassTable) -> Result<RasterType::WithPixelType<TDest>>
+where
+    TDest: ArrayNum,
+    RasterType: Array,
+{
+    table.validate()?;
+
+    let rules = table
+        .rules
+        .iter()
+        .map(|rule| Ok((rule, cast_new_value::<TDest>(rule.new_value())?)))
+        .collect::<Result<Vec<_>>>()?;
+    let nodata = cast_new_value::<TDest>(table.nodata)?;
+    let unmapped = match table.unmapped {
+        UnmappedValues::Value(v) => cast_new_value::<TDest>(Some(v))?,
+        UnmappedValues::Keep | UnmappedValues::Nodata => None,
+    };
+
+    let mut error = None;
+    let result = RasterType::WithPixelType::<TDest>::from_iter_opt(
+        ras.metadata().clone(),
+        ras.iter_opt().map(|value| {
+            let Some(value) = value.and_then(|v| v.to_f64()) else {
+                return nodata;
+            };
+
+            match rules.iter().find(|(rule, _)| rule.matches(value)) {
+                Some((_, new_value)) => *new_value,
+                None => match table.unmapped {
+                    UnmappedValues::Keep => match to_output_value(value) {
+                        Ok(value) => Some(value),
+                        Err(err) => {
+                            error.get_or_insert(err);
+                            None
+                        }
+                    },
+                    UnmappedValues::Nodata | UnmappedValues::Value(_) => unmapped,
+                },
+            }
+        }),
+    )?;
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(result),
+    }
+}
+
+#[cfg(feature = "vector-io")]
+struct ReclassRow(ReclassRule);
+
+#[cfg(feature = "vector-io")]
+impl crate::vector::datarow::DataRow for ReclassRow {
+    fn field_names() -> Vec<&'static str> {
+        vec!["from", "to", "value"]
+    }
+
+    fn from_dataframe_row(row: crate::vector::dataframe::DataFrameRow) -> Result<Self> {
+        let from =
+            field_value(row.field(0)?)?.ok_or_else(|| Error::InvalidArgument("Reclassification rule without 'from' value".into()))?;
+        let to = field_value(row.field(1)?)?;
+        let new_value = field_value(row.field(2)?)?;
+
+        Ok(ReclassRow(match to {
+            Some(to) => ReclassRule::Range { from, to, new_value },
+            None => ReclassRule::Value { value: from, new_value },
+        }))
+    }
+}
+
+#[cfg(feature = "vector-io")]
+fn field_value(field: Option<crate::vector::dataframe::Field>) -> Result<Option<f64>> {
+    use crate::vector::dataframe::Field;
+
+    match field {
+        None => Ok(None),
+        Some(Field::Integer(v)) => Ok(Some(v as f64)),
+        Some(Field::Float(v)) => Ok(Some(v)),
+        Some(Field::String(v)) if v.trim().is_empty() => Ok(None),
+        Some(Field::String(v)) => match v.trim().parse() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(Error::InvalidArgument(format!("Invalid reclassification value: '{v}'"))),
+        },
+        Some(field) => Err(Error::InvalidArgument(format!("Invalid reclassification value: {field:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inf::allocate;
+
+    use crate::{
+        ArrayInterop as _, ArrayMetadata as _, DenseArray, RasterSize,
+        array::{Columns, Rows},
+        rastermetadata::RasterMetadata,
+        testutils::NOD,
+    };
+
+    use super::*;
+
+    fn create_array<T: ArrayNum>(data: &[T]) -> DenseArray<T> {
+        let meta = RasterMetadata::sized_with_nodata(RasterSize::with_rows_cols(Rows(1), Columns(data.len() as i32)), Some(NOD));
+        DenseArray::new_init_nodata(meta, allocate::aligned_vec_from_slice(data)).unwrap()
+    }
+
+    #[test]
+    fn reclassify_values_and_ranges() -> Result<()> {
+        const ND: f32 = NOD as f32;
+        let ras = create_array(&[0.5_f32, 1.0, 2.0, 2.5, 3.0, ND, 7.0, 10.0]);
+
+        let mut table = ReclassTable::new(vec![
+            ReclassRule::Value {
+                value: 2.0,
+                new_value: Some(20.0),
+            },
+            ReclassRule::Range {
+                from: 0.0,
+                to: 3.0,
+                new_value: Some(1.0),
+            },
+            ReclassRule::Range {
+                from: 3.0,
+                to: 10.0,
+                new_value: None,
+            },
+        ]);
+
+        let result = reclassify::<u8, _>(&ras, &table)?;
+        assert_eq!(
+            result.iter_opt().collect::<Vec<_>>(),
+            [Some(1), Some(1), Some(20), Some(1), None, None, None, Some(10)]
+        );
+
+        table.unmapped = UnmappedValues::Value(99.0);
+        table.nodata = Some(0.0);
+        let result = reclassify::<i32, _>(&ras, &table)?;
+        assert_eq!(
+            result.iter_opt().collect::<Vec<_>>(),
+            [Some(1), Some(1), Some(20), Some(1), None, Some(0), None, Some(99)]
+        );
+
+        table.unmapped = UnmappedValues::Nodata;
+        let result = reclassify::<i32, _>(&ras, &table)?;
+        assert_eq!(result.cell_value(crate::Cell::from_row_col(0, 7)), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reclassify_invalid_rules() {
+        let ras = create_array(&[1_i32, 2, 3]);
+
+        let out_of_range = ReclassTable::new(vec![ReclassRule::Value {
+            value: 1.0,
+            new_value: Some(300.0),
+        }]);
+        assert!(reclassify::<u8, _>(&ras, &out_of_range).is_err());
+        assert!(reclassify::<u16, _>(&ras, &out_of_range).is_ok());
+
+        let empty_range = ReclassTable::new(vec![ReclassRule::Range {
+            from: 2.0,
+            to: 2.0,
+            new_value: Some(1.0),
+        }]);
+        assert!(reclassify::<u8, _>(&ras, &empty_range).is_err());
+
+        let fraction = ReclassTable::new(vec![ReclassRule::Value {
+            value: 1.0,
+            new_value: Some(1.5),
+        }]);
+        assert!(reclassify::<u8, _>(&ras, &fraction).is_err());
+        assert!(reclassify::<f32, _>(&ras, &fraction).is_ok());
+
+        let nodata = ReclassTable::new(vec![ReclassRule::Value {
+            value: 1.0,
+            new_value: Some(255.0),
+        }]);
+        assert!(reclassify::<u8, _>(&ras, &nodata).is_err());
+    }
+
+    #[test]
+    fn reclassify_kept_values_that_can_not_be_represented() -> Result<()> {
+        let table = ReclassTable::new(vec![ReclassRule::Value {
+            value: 1.0,
+            new_value: Some(10.0),
+        }]);
+
+        // Kept values are not truncated
+        assert!(reclassify::<u8, _>(&create_array(&[1.0_f32, 2.7]), &table).is_err());
+        let result = reclassify::<f64, _>(&create_array(&[1.0_f32, 2.5]), &table)?;
+        assert_eq!(result.iter_opt().collect::<Vec<_>>(), [Some(10.0), Some(2.5)]);
+
+        // Kept values that equal the output nodata value or do not fit are an error
+        assert!(reclassify::<i16, _>(&create_array(&[1_i32, i16::MIN as i32]), &table).is_err());
+        assert!(reclassify::<u8, _>(&create_array(&[1_u16, 300]), &table).is_err());
+        assert!(reclassify::<u16, _>(&create_array(&[1_u16, 300]), &table).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "vector-io-csv")]
+    fn reclassify_table_from_csv() -> Result<()> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/reclassify.csv");
+        let table = ReclassTable::from_file(&path)?;
+
+        assert_eq!(
+            table.rules,
+            [
+                ReclassRule::Value {
+                    value: 0.0,
+                    new_value: None
+                },
+                ReclassRule::Range {
+                    from: 0.0,
+                    to: 10.0,
+                    new_value: Some(1.0)
+                },
+                ReclassRule::Range {
+                    from: 10.0,
+                    to: 25.5,
+                    new_value: Some(2.0)
+                },
+                ReclassRule::Value {
+                    value: 100.0,
+                    new_value: Some(3.0)
+                },
+            ]
+        );
+
+        let result = reclassify::<u8, _>(&create_array(&[0_u16, 5, 25, 26, 100]), &table)?;
+        assert_eq!(result.iter_opt().collect::<Vec<_>>(), [None, Some(1), Some(2), Some(26), Some(3)]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+//! Resampling kernels used by the native warp implementation.
+
+use std::cmp::Ordering;
+
+use super::cast::to_pixel_value;
+use crate::{Array, ArrayNum, Cell, Point, raster::DenseRaster};
+
+/// The resampling method used to calculate the value of a warped cell (matches the gdalwarp `-r` options)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResamplingMethod {
+    /// The value of the source cell that contains the target cell center
+    #[default]
+    NearestNeighbour,
+    Bilinear,
+    Cubic,
+    CubicSpline,
+    Lanczos,
+    /// Average of the covered source cells, weighted by the covered area
+    Average,
+    /// Most frequent value of the covered source cells
+    Mode,
+    Min,
+    Max,
+    Median,
+    /// Sum of the covered source cells, weighted by the covered area
+    Sum,
+}
+
+impl ResamplingMethod {
+    /// The name of the resampling method as used by gdalwarp
+    pub fn gdal_name(&self) -> &'static str {
+        match self {
+            ResamplingMethod::NearestNeighbour => "near",
+            ResamplingMethod::Bilinear => "bilinear",
+            ResamplingMethod::Cubic => "cubic",
+            ResamplingMethod::CubicSpline => "cubicspline",
+            ResamplingMethod::Lanczos => "lanczos",
+            ResamplingMethod::Average => "average",
+            ResamplingMethod::Mode => "mode",
+            ResamplingMethod::Min => "min",
+            ResamplingMethod::Max => "max",
+            ResamplingMethod::Median => "med",
+            ResamplingMethod::Sum => "sum",
+        }
+    }
+
+    /// The radius of the interpolation kernel in source cells, `None` for the non interpolating methods
+    fn kernel_radius(&self) -> Option<f64> {
+        match self {
+            ResamplingMethod::Bilinear => Some(1.0),
+            ResamplingMethod::Cubic | ResamplingMethod::CubicSpline => Some(2.0),
+            ResamplingMethod::Lanczos => Some(3.0),
+            _ => None,
+        }
+    }
+
+    fn kernel_weight(&self, distance: f64) -> f64 {
+        let x = distance.abs();
+        match self {
+            ResamplingMethod::Bilinear => (1.0 - x).max(0.0),
+            ResamplingMethod::Cubic => {
+                // Keys cubic convolution with a = -0.5
+                const A: f64 = -0.5;
+                if x <= 1.0 {
+                    ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                } else {
+                    0.0
+                }
+            }
+            ResamplingMethod::CubicSpline => {
+                if x <= 1.0 {
+                    (3.0 * x * x * x - 6.0 * x * x + 4.0) / 6.0
+                } else if x < 2.0 {
+                    (2.0 - x).powi(3) / 6.0
+                } else {
+                    0.0
+                }
+            }
+            ResamplingMethod::Lanczos => {
+                const A: f64 = 3.0;
+                if x == 0.0 {
+                    1.0
+                } else if x < A {
+                    let pi_x = std::f64::consts::PI * x;
+                    A * pi_x.sin() * (pi_x / A).sin() / (pi_x * pi_x)
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Samples a source raster at arbitrary locations using a resampling method.
+/// The scale is the size of a target cell expressed in source cells, it determines the footprint of the
+/// area based methods and widens the interpolation kernels when downsampling.
+/// Cloning is cheap, use a clone per thread as sampling reuses an internal buffer.
+#[derive(Clone)]
+pub(crate) struct Resampler<'a, T: ArrayNum> {
+    src: &'a DenseRaster<T>,
+    method: ResamplingMethod,
+    origin: Point,
+    cell_width: f64,
+    cell_height: f64,
+    rows: i32,
+    cols: i32,
+    x_scale: f64,
+    y_scale: f64,
+    /// The covered source cells and their weights of the area based methods, reused between samples
+    values: Vec<(T, f64)>,
+}
+
+impl<'a, T: ArrayNum> Resampler<'a, T> {
+    pub fn new(src: &'a DenseRaster<T>, method: ResamplingMethod, x_scale: f64, y_scale: f64) -> Self {
+        let georef = src.metadata();
+
+        Self {
+            src,
+            method,
+            origin: georef.top_left(),
+            cell_width: georef.cell_size_x(),
+            cell_height: georef.cell_size_y(),
+            rows: src.rows().count(),
+            cols: src.columns().count(),
+            x_scale: if x_scale.is_finite() && x_scale > 0.0 { x_scale } else { 1.0 },
+            y_scale: if y_scale.is_finite() && y_scale > 0.0 { y_scale } else { 1.0 },
+            values: Vec::new(),
+        }
+    }
+
+    /// The value at the given location in the coordinate system of the source raster,
+    /// `None` if the location is outside of the source raster or no data is available.
+    pub fn sample(&mut self, point: Point) -> Option<T> {
+        let col = (point.x() - self.origin.x()) / self.cell_width;
+        let row = (point.y() - self.origin.y()) / self.cell_height;
+        if !(col >= 0.0 && row >= 0.0 && col < self.cols as f64 && row < self.rows as f64) {
+            return None;
+        }
+
+        match self.method {
+            ResamplingMethod::NearestNeighbour => self.src.cell_value(Cell::from_row_col(row as i32, col as i32)),
+            ResamplingMethod::Bilinear | ResamplingMethod::Cubic | ResamplingMethod::CubicSpline | ResamplingMethod::Lanczos => {
+                self.interpolate(col, row).and_then(to_pixel_value)
+            }
+            _ => self.aggregate(col, row),
+        }
+    }
+
+    fn cell_value_f64(&self, row: i32, col: i32) -> Option<f64> {
+        if row < 0 || col < 0 || row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        self.src.cell_value(Cell::from_row_col(row, col)).and_then(|v| v.to_f64())
+    }
+
+    /// Separable kernel interpolation, nodata cells are excluded and the remaining weights are normalized
+    fn interpolate(&self, col: f64, row: f64) -> Option<f64> {
+        let radius = self.method.kernel_radius()?;
+
+        // Widen the kernel when downsampling so all the covered source cells contribute
+        let x_scale = self.x_scale.max(1.0);
+        let y_scale = self.y_scale.max(1.0);
+
+        // Distances are measured between cell centers
+        let x = col - 0.5;
+        let y = row - 0.5;
+
+        let col_start = (x - radius * x_scale).ceil() as i32;
+        let col_end = (x + radius * x_scale).floor() as i32;
+        let row_start = (y - radius * y_scale).ceil() as i32;
+        let row_end = (y + radius * y_scale).floor() as i32;
+
+        let mut weighted_sum = 0.0;
+        let mut weight_sum = 0.0;
+        for r in row_start..=row_end {
+            let row_weight = self.method.kernel_weight((r as f64 - y) / y_scale);
+            if row_weight == 0.0 {
+                continue;
+            }
+
+            for c in col_start..=col_end {
+                let weight = row_weight * self.method.kernel_weight((c as f64 - x) / x_scale);
+                if weight == 0.0 {
+                    continue;
+                }
+
+                if let Some(value) = self.cell_value_f64(r, c) {
+                    weighted_sum += weight * value;
+                    weight_sum += weight;
+                }
+            }
+        }
+
+        if weight_sum.abs() < 1e-10 {
+            return None;
+        }
+
+        Some(weighted_sum / weight_sum)
+    }
+
+    /// The area based methods, applied to the source cells covered by the target cell footprint
+    fn aggregate(&mut self, col: f64, row: f64) -> Option<T> {
+        let left = col - self.x_scale / 2.0;
+        let right = col + self.x_scale / 2.0;
+        let top = row - self.y_scale / 2.0;
+        let bottom = row + self.y_scale / 2.0;
+
+        self.values.clear();
+        for r in (top.floor() as i32).max(0)..(bottom.ceil() as i32).min(self.rows) {
+            let row_overlap = bottom.min(r as f64 + 1.0) - top.max(r as f64);
+            if row_overlap <= 0.0 {
+                continue;
+            }
+
+            for c in (left.floor() as i32).max(0)..(right.ceil() as i32).min(self.cols) {
+                let col_overlap = right.min(c as f64 + 1.0) - left.max(c as f64);
+                if col_overlap <= 0.0 {
+                    continue;
+                }
+
+                if let Some(value) = self.src.cell_value(Cell::from_row_col(r, c)) {
+                    self.values.push((value, row_overlap * col_overlap));
+                }
+            }
+        }
+
+        let values = &mut self.values;
+        if values.is_empty() {
+            return None;
+        }
+
+        let weighted_sum = |values: &[(T, f64)]| {
+            values
+                .iter()
+                .map(|(value, weight)| value.to_f64().unwrap_or(0.0) * weight)
+                .sum::<f64>()
+        };
+
+        match self.method {
+            ResamplingMethod::Average => to_pixel_value(weighted_sum(values) / values.iter().map(|(_, weight)| weight).sum::<f64>()),
+            ResamplingMethod::Sum => to_pixel_value(weighted_sum(values)),
+            ResamplingMethod::Min => values.iter().map(|(value, _)| *value).reduce(|a, b| if b < a { b } else { a }),
+            ResamplingMethod::Max => values.iter().map(|(value, _)| *value).reduce(|a, b| if b > a { b } else { a }),
+            ResamplingMethod::Median | ResamplingMethod::Mode => {
+                values.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+                if self.method == ResamplingMethod::Median {
+                    return Some(values[(values.len() - 1) / 2].0);
+                }
+
+                // Most frequent value, the lowest value wins in case of a tie
+                values
+                    .chunk_by(|(a, _), (b, _)| a == b)
+                    .fold(None, |mode: Option<&[(T, f64)]>, run| match mode {
+                        Some(mode) if mode.len() >= run.len() => Some(mode),
+                        _ => Some(run),
+                    })
+                    .map(|run| run[0].0)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize, Result,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    #[test]
+    fn kernel_weights() {
+        for method in [
+            ResamplingMethod::Bilinear,
+            ResamplingMethod::Cubic,
+            ResamplingMethod::CubicSpline,
+            ResamplingMethod::Lanczos,
+        ] {
+            let radius = method.kernel_radius().unwrap();
+            assert_eq!(method.kernel_weight(radius), 0.0);
+
+            // The weights of the integer offsets should sum to one for any fractional position
+            for fraction in [0.0, 0.25, 0.5] {
+                let sum: f64 = (-4..=4).map(|i| method.kernel_weight(i as f64 - fraction)).sum();
+                assert_relative_eq!(sum, 1.0, epsilon = if method == ResamplingMethod::Lanczos { 0.02 } else { 1e-10 });
+            }
+        }
+    }
+
+    #[test]
+    fn interpolation() -> Result<()> {
+        #[rustfmt::skip]
+        let raster = create_raster(2, 3, CellSize::square(1.0), &[
+            0.0, 10.0, 20.0,
+            10.0, 20.0, NOD,
+        ]);
+
+        let mut bilinear = Resampler::new(&raster, ResamplingMethod::Bilinear, 1.0, 1.0);
+        // Cell centers return the cell value
+        assert_eq!(bilinear.sample(Point::new(0.5, -0.5)), Some(0.0));
+        assert_eq!(bilinear.sample(Point::new(1.0, -1.0)), Some(10.0));
+        assert_relative_eq!(bilinear.sample(Point::new(0.75, -0.5)).unwrap(), 2.5);
+        // The nodata cell is excluded
+        assert_relative_eq!(bilinear.sample(Point::new(2.0, -1.0)).unwrap(), 50.0 / 3.0);
+        assert_eq!(bilinear.sample(Point::new(3.5, -0.5)), None);
+
+        let mut nearest = Resampler::new(&raster, ResamplingMethod::NearestNeighbour, 1.0, 1.0);
+        assert_eq!(nearest.sample(Point::new(2.5, -1.5)), None);
+        assert_eq!(nearest.sample(Point::new(1.9, -0.1)), Some(10.0));
+
+        let mut cubic = Resampler::new(&raster, ResamplingMethod::Cubic, 1.0, 1.0);
+        assert_relative_eq!(cubic.sample(Point::new(1.5, -0.5)).unwrap(), 10.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregation() -> Result<()> {
+        #[rustfmt::skip]
+        let raster = create_raster(2, 4, CellSize::square(1.0), &[
+            1.0, 2.0, 2.0, 8.0,
+            3.0, 1.0, NOD, 5.0,
+        ]);
+
+        // Target cells of 2x2 source cells
+        let sample = |method, x| Resampler::new(&raster, method, 2.0, 2.0).sample(Point::new(x, -1.0));
+
+        assert_eq!(sample(ResamplingMethod::Average, 1.0), Some(1.75));
+        assert_eq!(sample(ResamplingMethod::Sum, 1.0), Some(7.0));
+        assert_eq!(sample(ResamplingMethod::Min, 1.0), Some(1.0));
+        assert_eq!(sample(ResamplingMethod::Max, 1.0), Some(3.0));
+        assert_eq!(sample(ResamplingMethod::Mode, 1.0), Some(1.0));
+        assert_eq!(sample(ResamplingMethod::Median, 1.0), Some(1.0));
+        assert_eq!(sample(ResamplingMethod::Average, 3.0), Some(5.0));
+        assert_eq!(sample(ResamplingMethod::Median, 3.0), Some(5.0));
+
+        // Partially covered cells are weighted by the covered area
+        let mut average = Resampler::new(&raster, ResamplingMethod::Average, 1.0, 1.0);
+        assert_eq!(average.sample(Point::new(1.0, -0.5)), Some(1.5));
+
+        Ok(())
+    }
+}
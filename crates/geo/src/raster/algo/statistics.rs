@@ -19,14 +19,25 @@ pub struct RasterStats<T: ArrayNum> {
 /// Use this if multiple statistics are needed, as it avoids multiple iterations over the data.
 /// Returns `None` if the raster is empty or contains only nodata values.
 pub fn statistics<T: ArrayNum, Meta: ArrayMetadata>(raster: &DenseArray<T, Meta>, quantile_vals: &[f64]) -> Result<Option<RasterStats<T>>> {
+    // Assume roughly 75% of the pixels will be valid data, to avoid excessive memory reallocations while iterating.
+    let mut pixel_values = Vec::with_capacity((raster.len() as f64 * 0.75) as usize);
+    pixel_values.extend(raster.into_iter().flatten());
+
+    values_statistics(pixel_values, quantile_vals)
+}
+
+/// Calculates the statistics of a list of (non nodata) values.
+/// Returns `None` if the list is empty.
+pub(crate) fn values_statistics<T: ArrayNum>(mut pixel_values: Vec<T>, quantile_vals: &[f64]) -> Result<Option<RasterStats<T>>> {
+    if pixel_values.is_empty() {
+        return Ok(None);
+    }
+
     let mut min = T::max_value();
     let mut max = T::min_value();
     let mut sum = 0.0;
 
-    // Assume roughly 75% of the pixels will be valid data, to avoid excessive memory reallocations while iterating.
-    let mut pixel_values = Vec::with_capacity((raster.len() as f64 * 0.75) as usize);
-
-    for val in raster.into_iter().flatten() {
+    for &val in &pixel_values {
         if val < min {
             min = val;
         }
@@ -36,11 +47,6 @@ pub fn statistics<T: ArrayNum, Meta: ArrayMetadata>(raster: &DenseArray<T, Meta>
         }
 
         sum += val.to_f64().unwrap_or(0.0);
-        pixel_values.push(val);
-    }
-
-    if pixel_values.is_empty() {
-        return Ok(None);
     }
 
     pixel_values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
@@ -48,7 +54,7 @@ pub fn statistics<T: ArrayNum, Meta: ArrayMetadata>(raster: &DenseArray<T, Meta>
     let mean = sum / pixel_values.len() as f64;
     let stddev = std_deviation(&pixel_values, mean);
 
-    let median = if pixel_values.len() % 2 == 0 {
+    let median = if pixel_values.len().is_multiple_of(2) {
         let mid1 = pixel_values[pixel_values.len() / 2 - 1].to_f64().unwrap_or(f64::NAN);
         let mid2 = pixel_values[pixel_values.len() / 2].to_f64().unwrap_or(f64::NAN);
         (mid1 + mid2) / 2.0
@@ -0,0 +1,548 @@
+//! Terrain analysis of digital elevation models: slope, aspect, hillshade, curvature and ruggedness indices.
+//! The algorithms operate on a 3x3 window around every cell, similar to `gdaldem`.
+
+use num::{Float, NumCast, ToPrimitive as _};
+
+use crate::{Array, Error, GeoReference, Nodata, Result};
+
+/// Approximate length of one degree along the equator in meters, used for rasters in a geographic coordinate system
+const METERS_PER_DEGREE: f64 = 111_319.490_793_273_57;
+
+/// The method used to calculate the elevation gradient
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlopeAlgorithm {
+    /// Horn's formula uses all 8 neighbours, best suited for rough terrain
+    #[default]
+    Horn,
+    /// Zevenbergen and Thorne's formula only uses the 4 direct neighbours, best suited for smooth terrain
+    ZevenbergenThorne,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlopeUnits {
+    #[default]
+    Degrees,
+    /// The rise over run in percent (45 degrees is 100 percent)
+    Percent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurvatureType {
+    /// The curvature perpendicular to the direction of the steepest slope
+    Plan,
+    /// The curvature in the direction of the steepest slope
+    Profile,
+}
+
+/// Options shared by the terrain algorithms
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainOptions {
+    pub algorithm: SlopeAlgorithm,
+    /// Ratio of vertical units to horizontal units, the elevations are multiplied by this factor.
+    /// For rasters in a geographic coordinate system the cell sizes are converted to meters based on the latitude,
+    /// so the z factor should only be used to convert the elevation units to meters.
+    pub z_factor: f64,
+    /// Calculate values for the raster edges and for cells next to nodata.
+    /// Missing neighbours are replaced by the value of the center cell, otherwise these cells are nodata.
+    pub compute_edges: bool,
+}
+
+impl Default for TerrainOptions {
+    fn default() -> Self {
+        TerrainOptions {
+            algorithm: SlopeAlgorithm::default(),
+            z_factor: 1.0,
+            compute_edges: false,
+        }
+    }
+}
+
+/// The light source used for hillshading
+#[derive(Debug, Clone, PartialEq)]
+pub struct HillshadeOptions {
+    /// The direction of the light source in degrees clockwise from the north
+    pub azimuth: f64,
+    /// The angle of the light source above the horizon in degrees
+    pub altitude: f64,
+    /// Combine the illumination of light sources at 225, 270, 315 and 360 degrees azimuth, weighted by the aspect.
+    /// The azimuth is ignored in this case.
+    pub multidirectional: bool,
+}
+
+impl Default for HillshadeOptions {
+    fn default() -> Self {
+        HillshadeOptions {
+            azimuth: 315.0,
+            altitude: 45.0,
+            multidirectional: false,
+        }
+    }
+}
+
+/// The 3x3 window around a cell, in row major order
+/// ```text
+/// a b c
+/// d e f
+/// g h i
+/// ```
+type Window = [f64; 9];
+
+/// The horizontal and vertical size of a cell in the horizontal units
+#[derive(Debug, Clone, Copy)]
+struct CellResolution {
+    ew: f64,
+    ns: f64,
+}
+
+fn cell_resolution(georef: &GeoReference, row: i32, geographic: bool) -> CellResolution {
+    let ew = georef.cell_size_x().abs();
+    let ns = georef.cell_size_y().abs();
+
+    if geographic {
+        let latitude = georef.top_left().y() + (row as f64 + 0.5) * georef.cell_size_y();
+        CellResolution {
+            ew: ew * METERS_PER_DEGREE * latitude.to_radians().cos(),
+            ns: ns * METERS_PER_DEGREE,
+        }
+    } else {
+        CellResolution { ew, ns }
+    }
+}
+
+/// The elevation gradient towards the east and the north
+fn gradient(w: &Window, res: CellResolution, options: &TerrainOptions) -> (f64, f64) {
+    let [a, b, c, d, _, f, g, h, i] = *w;
+
+    let (dx, dy) = match options.algorithm {
+        SlopeAlgorithm::Horn => (
+            ((c + 2.0 * f + i) - (a + 2.0 * d + g)) / (8.0 * res.ew),
+            ((a + 2.0 * b + c) - (g + 2.0 * h + i)) / (8.0 * res.ns),
+        ),
+        SlopeAlgorithm::ZevenbergenThorne => ((f - d) / (2.0 * res.ew), (b - h) / (2.0 * res.ns)),
+    };
+
+    (dx * options.z_factor, dy * options.z_factor)
+}
+
+fn process_windows<RasterType>(
+    dem: &RasterType,
+    options: &TerrainOptions,
+    window_value: impl Fn(&Window, CellResolution) -> Option<f64>,
+) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    let georef = dem.metadata();
+    if georef.cell_size_x() == 0.0 || georef.cell_size_y() == 0.0 {
+        return Err(Error::InvalidArgument("Terrain analysis requires a raster cell size".into()));
+    }
+
+    let geographic = georef.is_geographic();
+    let rows = dem.rows().count();
+    let cols = dem.columns().count();
+    let data = dem.as_slice();
+
+    let mut result = RasterType::filled_with_nodata(georef.copy_with_nodata(Some(RasterType::Pixel::NODATA)));
+    let result_data = result.as_mut_slice();
+
+    for row in 0..rows {
+        let res = cell_resolution(georef, row, geographic);
+
+        for col in 0..cols {
+            let index = (row * cols + col) as usize;
+            if data[index].is_nodata() {
+                continue;
+            }
+
+            let Some(center) = data[index].to_f64() else {
+                continue;
+            };
+
+            let mut window = [center; 9];
+            let mut complete = true;
+            for (window_index, value) in window.iter_mut().enumerate() {
+                let r = row + window_index as i32 / 3 - 1;
+                let c = col + window_index as i32 % 3 - 1;
+
+                let neighbour = (r >= 0 && r < rows && c >= 0 && c < cols)
+                    .then(|| data[(r * cols + c) as usize])
+                    .filter(|v| !v.is_nodata())
+                    .and_then(|v| v.to_f64());
+
+                match neighbour {
+                    Some(v) => *value = v,
+                    None if options.compute_edges => {}
+                    None => {
+                        complete = false;
+                        break;
+                    }
+                }
+            }
+
+            if !complete {
+                continue;
+            }
+
+            if let Some(value) = window_value(&window, res).and_then(NumCast::from) {
+                result_data[index] = value;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Calculates the slope of the elevation model
+pub fn slope<RasterType>(dem: &RasterType, units: SlopeUnits, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, res| {
+        let (dx, dy) = gradient(w, res, options);
+        let rise = dx.hypot(dy);
+
+        Some(match units {
+            SlopeUnits::Degrees => rise.atan().to_degrees(),
+            SlopeUnits::Percent => rise * 100.0,
+        })
+    })
+}
+
+/// Calculates the direction the slope faces in degrees clockwise from the north (0 to 360).
+/// Flat cells have no aspect and are nodata.
+pub fn aspect<RasterType>(dem: &RasterType, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, res| {
+        let (dx, dy) = gradient(w, res, options);
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+
+        // The downhill direction is the opposite of the gradient
+        let aspect = (-dx).atan2(-dy).to_degrees();
+        Some(if aspect < 0.0 { aspect + 360.0 } else { aspect })
+    })
+}
+
+/// The illumination of a cell with the given gradient (0 to 1)
+fn illumination(dx: f64, dy: f64, azimuth: f64, altitude: f64) -> f64 {
+    let (sin_az, cos_az) = azimuth.to_radians().sin_cos();
+    let (sin_alt, cos_alt) = altitude.to_radians().sin_cos();
+
+    ((sin_alt - dx * sin_az * cos_alt - dy * cos_az * cos_alt) / (1.0 + dx * dx + dy * dy).sqrt()).max(0.0)
+}
+
+/// Calculates the shaded relief of the elevation model.
+/// The values range from 1 (no illumination) to 255 (full illumination), like the output of `gdaldem hillshade`.
+pub fn hillshade<RasterType>(dem: &RasterType, hillshade_options: &HillshadeOptions, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    const MULTIDIRECTIONAL_AZIMUTHS: [f64; 4] = [225.0, 270.0, 315.0, 360.0];
+
+    process_windows(dem, options, |w, res| {
+        let (dx, dy) = gradient(w, res, options);
+
+        let shade = if hillshade_options.multidirectional {
+            // Port of the gdaldem multidirectional hillshade, which uses the gradient convention of gdaldem:
+            // x is the elevation decrease towards the east and y the elevation increase towards the north
+            let (x, y) = (-dx, dy);
+            let xx_plus_yy = x * x + y * y;
+            if xx_plus_yy == 0.0 {
+                hillshade_options.altitude.to_radians().sin()
+            } else {
+                // The weight of each light source is sin²(aspect - azimuth) with aspect = atan2(y, x),
+                // the weights sum to 2 so the weighted sum is halved
+                let weights = [0.5 * xx_plus_yy - x * y, x * x, 0.5 * xx_plus_yy + x * y, y * y];
+                MULTIDIRECTIONAL_AZIMUTHS
+                    .iter()
+                    .zip(weights)
+                    .map(|(&azimuth, weight)| weight * illumination(dx, dy, azimuth, hillshade_options.altitude))
+                    .sum::<f64>()
+                    / (2.0 * xx_plus_yy)
+            }
+        } else {
+            illumination(dx, dy, hillshade_options.azimuth, hillshade_options.altitude)
+        };
+
+        Some(1.0 + 254.0 * shade)
+    })
+}
+
+/// Calculates the plan or profile curvature of the elevation model using the Zevenbergen and Thorne polynomial.
+/// The curvature is expressed in 1/horizontal units, positive values indicate an upwardly convex surface.
+/// The slope algorithm of the options is not used.
+pub fn curvature<RasterType>(dem: &RasterType, curvature_type: CurvatureType, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, res| {
+        let [a, b, c, d, e, f, g, h, i] = w.map(|v| v * options.z_factor);
+
+        let dd = ((d + f) / 2.0 - e) / (res.ew * res.ew);
+        let ee = ((b + h) / 2.0 - e) / (res.ns * res.ns);
+        let ff = (-a + c + g - i) / (4.0 * res.ew * res.ns);
+        let gg = (f - d) / (2.0 * res.ew);
+        let hh = (b - h) / (2.0 * res.ns);
+
+        let gradient_squared = gg * gg + hh * hh;
+        if gradient_squared == 0.0 {
+            return Some(0.0);
+        }
+
+        Some(match curvature_type {
+            CurvatureType::Profile => -2.0 * (dd * gg * gg + ee * hh * hh + ff * gg * hh) / gradient_squared,
+            CurvatureType::Plan => -2.0 * (dd * hh * hh + ee * gg * gg - ff * gg * hh) / gradient_squared,
+        })
+    })
+}
+
+/// Calculates the topographic position index: the difference between the cell and the mean of its 8 neighbours.
+pub fn topographic_position_index<RasterType>(dem: &RasterType, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, _| {
+        let neighbour_sum: f64 = w.iter().sum::<f64>() - w[4];
+        Some(w[4] - neighbour_sum / 8.0)
+    })
+}
+
+/// Calculates the terrain ruggedness index as defined by Riley: the square root of the summed squared differences
+/// between the cell and its 8 neighbours.
+pub fn terrain_ruggedness_index<RasterType>(dem: &RasterType, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, _| Some(w.iter().map(|v| (v - w[4]).powi(2)).sum::<f64>().sqrt()))
+}
+
+/// Calculates the roughness: the difference between the highest and the lowest value in the 3x3 window.
+pub fn roughness<RasterType>(dem: &RasterType, options: &TerrainOptions) -> Result<RasterType>
+where
+    RasterType: Array<Metadata = GeoReference>,
+    RasterType::Pixel: Float,
+{
+    process_windows(dem, options, |w, _| {
+        let (min, max) = w
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)));
+        Some(max - min)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        Cell, CellSize, Point, RasterSize,
+        array::{Columns, Rows},
+        testutils::{NOD, create_dem},
+    };
+
+    use super::*;
+
+    #[test]
+    fn slope_and_aspect_of_plane() -> Result<()> {
+        // Rises 20 meters per 10 meter cell towards the east
+        let dem = create_dem::<f64>(4, 4, 10.0, |_, col| col as f64 * 20.0);
+
+        for algorithm in [SlopeAlgorithm::Horn, SlopeAlgorithm::ZevenbergenThorne] {
+            let options = TerrainOptions {
+                algorithm,
+                ..Default::default()
+            };
+
+            let degrees = slope(&dem, SlopeUnits::Degrees, &options)?;
+            assert_relative_eq!(degrees.cell_value(Cell::from_row_col(1, 1)).unwrap(), 2.0_f64.atan().to_degrees());
+            assert_eq!(degrees.cell_value(Cell::from_row_col(0, 0)), None);
+
+            let percent = slope(&dem, SlopeUnits::Percent, &options)?;
+            assert_relative_eq!(percent.cell_value(Cell::from_row_col(2, 2)).unwrap(), 200.0);
+
+            // The slope faces west
+            let aspect = aspect(&dem, &options)?;
+            assert_relative_eq!(aspect.cell_value(Cell::from_row_col(1, 2)).unwrap(), 270.0);
+        }
+
+        let z_factor = TerrainOptions {
+            z_factor: 0.5,
+            ..Default::default()
+        };
+        let percent = slope(&dem, SlopeUnits::Percent, &z_factor)?;
+        assert_relative_eq!(percent.cell_value(Cell::from_row_col(1, 1)).unwrap(), 100.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn edges_and_nodata() -> Result<()> {
+        let mut dem = create_dem::<f32>(4, 4, 10.0, |row, _| row as f64 * 10.0);
+        dem.set_cell_value(Cell::from_row_col(2, 2), None);
+
+        let slope_without_edges = slope(&dem, SlopeUnits::Percent, &TerrainOptions::default())?;
+        assert_eq!(slope_without_edges.cell_value(Cell::from_row_col(0, 1)), None);
+        assert_eq!(slope_without_edges.cell_value(Cell::from_row_col(1, 1)), None);
+        assert_eq!(slope_without_edges.cell_value(Cell::from_row_col(2, 2)), None);
+        assert_eq!(slope_without_edges.iter_opt().filter(Option::is_some).count(), 0);
+
+        let options = TerrainOptions {
+            compute_edges: true,
+            ..Default::default()
+        };
+
+        let slope_with_edges = slope(&dem, SlopeUnits::Percent, &options)?;
+        assert_eq!(slope_with_edges.iter_opt().filter(Option::is_some).count(), 15);
+        assert_eq!(slope_with_edges.cell_value(Cell::from_row_col(2, 2)), None);
+        // Slope faces north, south of the cell is the nodata cell that is replaced by the center value
+        assert_relative_eq!(slope_with_edges.cell_value(Cell::from_row_col(1, 2)).unwrap(), 75.0);
+
+        // The aspect of a flat area is undefined
+        let flat = create_dem::<f32>(3, 3, 10.0, |_, _| 5.0);
+        assert_eq!(aspect(&flat, &options)?.iter_opt().filter(Option::is_some).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hillshade_of_flat_and_sloped_terrain() -> Result<()> {
+        let flat = create_dem::<f64>(3, 3, 10.0, |_, _| 100.0);
+        let expected = 1.0 + 254.0 * 45.0_f64.to_radians().sin();
+
+        let shade = hillshade(&flat, &HillshadeOptions::default(), &TerrainOptions::default())?;
+        assert_relative_eq!(shade.cell_value(Cell::from_row_col(1, 1)).unwrap(), expected);
+
+        let multidirectional = HillshadeOptions {
+            multidirectional: true,
+            ..Default::default()
+        };
+        let shade = hillshade(&flat, &multidirectional, &TerrainOptions::default())?;
+        assert_relative_eq!(shade.cell_value(Cell::from_row_col(1, 1)).unwrap(), expected);
+
+        // A steep slope facing west is fully lit by a light source in the west and in the shade for a light source in the east
+        let slope = create_dem::<f64>(3, 3, 1.0, |_, col| col as f64 * 10.0);
+        let lit = HillshadeOptions {
+            azimuth: 270.0,
+            altitude: 10.0,
+            ..Default::default()
+        };
+        assert!(
+            hillshade(&slope, &lit, &TerrainOptions::default())?
+                .cell_value(Cell::from_row_col(1, 1))
+                .unwrap()
+                > 200.0
+        );
+
+        let shaded = HillshadeOptions { azimuth: 90.0, ..lit };
+        assert_eq!(
+            hillshade(&slope, &shaded, &TerrainOptions::default())?.cell_value(Cell::from_row_col(1, 1)),
+            Some(1.0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hillshade_matches_gdaldem() -> Result<()> {
+        // Reference values calculated with the gdaldem hillshade formulas (Horn gradient)
+        let dem = create_dem::<f64>(4, 4, 10.0, |row, col| {
+            let (row, col) = (row as f64, col as f64);
+            15.0 * row * row + 25.0 * col + 10.0 * row * col
+        });
+
+        let options = TerrainOptions::default();
+        let multi = HillshadeOptions {
+            multidirectional: true,
+            ..Default::default()
+        };
+        let low_sun = HillshadeOptions {
+            altitude: 30.0,
+            ..multi.clone()
+        };
+
+        for (cell, single, multidirectional, multidirectional_low_sun) in [
+            (Cell::from_row_col(1, 1), 210.326_311, 184.669_946, 208.758_582),
+            (Cell::from_row_col(2, 2), 192.388_215, 169.090_483, 197.164_712),
+            (Cell::from_row_col(1, 2), 204.585_232, 179.676_968, 205.028_269),
+        ] {
+            assert_relative_eq!(
+                hillshade(&dem, &HillshadeOptions::default(), &options)?.cell_value(cell).unwrap(),
+                single,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                hillshade(&dem, &multi, &options)?.cell_value(cell).unwrap(),
+                multidirectional,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                hillshade(&dem, &low_sun, &options)?.cell_value(cell).unwrap(),
+                multidirectional_low_sun,
+                epsilon = 1e-6
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn curvature_of_valley() -> Result<()> {
+        // A valley along the north-south axis
+        let dem = create_dem::<f64>(5, 5, 1.0, |_, col| (col as f64).powi(2));
+        let cell = Cell::from_row_col(2, 3);
+
+        let profile = curvature(&dem, CurvatureType::Profile, &TerrainOptions::default())?;
+        assert_relative_eq!(profile.cell_value(cell).unwrap(), -2.0);
+
+        let plan = curvature(&dem, CurvatureType::Plan, &TerrainOptions::default())?;
+        assert_relative_eq!(plan.cell_value(cell).unwrap(), 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ruggedness_indices_of_peak() -> Result<()> {
+        let dem = create_dem::<f64>(3, 3, 10.0, |row, col| if row == 1 && col == 1 { 9.0 } else { 1.0 });
+        let options = TerrainOptions::default();
+        let center = Cell::from_row_col(1, 1);
+
+        assert_relative_eq!(topographic_position_index(&dem, &options)?.cell_value(center).unwrap(), 8.0);
+        assert_relative_eq!(
+            terrain_ruggedness_index(&dem, &options)?.cell_value(center).unwrap(),
+            (8.0 * 64.0_f64).sqrt()
+        );
+        assert_relative_eq!(roughness(&dem, &options)?.cell_value(center).unwrap(), 8.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn geographic_cell_resolution() {
+        let georef = GeoReference::with_top_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(2), Columns(2)),
+            Point::new(0.0, 61.0),
+            CellSize::square(2.0),
+            Some(NOD),
+        );
+
+        let res = cell_resolution(&georef, 0, true);
+        assert_relative_eq!(res.ns, 2.0 * METERS_PER_DEGREE);
+        // The center of the first row is at 60 degrees latitude
+        assert_relative_eq!(res.ew, METERS_PER_DEGREE, epsilon = 1e-6);
+
+        let res = cell_resolution(&georef, 0, false);
+        assert_relative_eq!(res.ew, 2.0);
+        assert_relative_eq!(res.ns, 2.0);
+    }
+}
@@ -0,0 +1,475 @@
+//! Visibility analysis on digital elevation models: viewsheds and line of sight queries.
+//! Distances are measured in the units of the raster projection, so the elevation model should use a projected
+//! coordinate system with elevations in the same units.
+
+use num::ToPrimitive as _;
+
+use crate::{Array, Cell, Error, GeoReference, Point, Result};
+
+/// Mean earth radius in meters, used for the earth curvature correction
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// An observation point with its height above the terrain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    pub location: Point,
+    pub height: f64,
+}
+
+impl Observer {
+    pub fn new(location: Point, height: f64) -> Self {
+        Observer { location, height }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewshedOptions {
+    /// The height of the targets above the terrain
+    pub target_height: f64,
+    /// Cells further away from the observer are not visible
+    pub max_distance: Option<f64>,
+    /// Lower the terrain further away from the observer to account for the curvature of the earth
+    pub curvature_correction: bool,
+    /// The atmospheric refraction coefficient that reduces the curvature correction, only used with `curvature_correction`
+    pub refraction_coefficient: f64,
+}
+
+impl Default for ViewshedOptions {
+    fn default() -> Self {
+        ViewshedOptions {
+            target_height: 0.0,
+            max_distance: None,
+            curvature_correction: false,
+            refraction_coefficient: 1.0 / 7.0,
+        }
+    }
+}
+
+impl ViewshedOptions {
+    /// The apparent lowering of the terrain at the given distance from the observer
+    fn curvature_drop(&self, distance: f64) -> f64 {
+        if self.curvature_correction {
+            distance * distance * (1.0 - self.refraction_coefficient) / (2.0 * EARTH_RADIUS)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The location and absolute elevation of the eye of an observer
+struct Eye {
+    location: Point,
+    cell: Cell,
+    elevation: f64,
+}
+
+fn cell_in_raster(georef: &GeoReference, cell: Cell) -> bool {
+    cell.row >= 0 && cell.row < georef.rows().count() && cell.col >= 0 && cell.col < georef.columns().count()
+}
+
+fn observer_eye<RasterType: Array<Metadata = GeoReference>>(dem: &RasterType, observer: &Observer) -> Result<Eye> {
+    let cell = dem.metadata().point_to_cell(observer.location);
+    if !cell_in_raster(dem.metadata(), cell) {
+        return Err(Error::InvalidArgument(format!(
+            "Observer {:?} is outside of the elevation model",
+            observer.location
+        )));
+    }
+
+    let terrain = dem
+        .cell_value(cell)
+        .and_then(|v| v.to_f64())
+        .ok_or_else(|| Error::InvalidArgument(format!("Observer {:?} is located on a nodata cell", observer.location)))?;
+
+    Ok(Eye {
+        location: observer.location,
+        cell,
+        elevation: terrain + observer.height,
+    })
+}
+
+/// Checks if the terrain between the eye and the target blocks the view.
+/// The terrain is sampled once for every row or column that is crossed, nodata cells do not block the view.
+fn is_visible<RasterType>(
+    dem: &RasterType,
+    eye: &Eye,
+    target: Point,
+    target_cell: Cell,
+    target_elevation: f64,
+    options: &ViewshedOptions,
+) -> bool
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let georef = dem.metadata();
+    let dx = target.x() - eye.location.x();
+    let dy = target.y() - eye.location.y();
+    let distance = dx.hypot(dy);
+    if distance == 0.0 || target_cell == eye.cell {
+        return true;
+    }
+
+    let target_slope = (target_elevation - options.curvature_drop(distance) - eye.elevation) / distance;
+
+    let steps = (dx.abs() / georef.cell_size_x().abs())
+        .max(dy.abs() / georef.cell_size_y().abs())
+        .ceil() as usize;
+    for step in 1..steps {
+        let fraction = step as f64 / steps as f64;
+        let cell = georef.point_to_cell(Point::new(eye.location.x() + fraction * dx, eye.location.y() + fraction * dy));
+        if cell == eye.cell || cell == target_cell || !cell_in_raster(georef, cell) {
+            continue;
+        }
+
+        let Some(terrain) = dem.cell_value(cell).and_then(|v| v.to_f64()) else {
+            continue;
+        };
+
+        let sample_distance = fraction * distance;
+        if (terrain - options.curvature_drop(sample_distance) - eye.elevation) / sample_distance > target_slope {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// The part of the elevation model that can be visible from the eye, limited by the maximum distance
+struct Window {
+    top: i32,
+    bottom: i32,
+    left: i32,
+    right: i32,
+}
+
+impl Window {
+    fn new(georef: &GeoReference, eye: &Eye, max_distance: Option<f64>) -> Self {
+        let (row_radius, col_radius) = match max_distance {
+            Some(distance) => (
+                (distance / georef.cell_size_y().abs()).ceil() as i32,
+                (distance / georef.cell_size_x().abs()).ceil() as i32,
+            ),
+            None => (georef.rows().count(), georef.columns().count()),
+        };
+
+        Window {
+            top: (eye.cell.row - row_radius).max(0),
+            bottom: (eye.cell.row + row_radius + 1).min(georef.rows().count()),
+            left: (eye.cell.col - col_radius).max(0),
+            right: (eye.cell.col + col_radius + 1).min(georef.columns().count()),
+        }
+    }
+
+    fn width(&self) -> usize {
+        (self.right - self.left) as usize
+    }
+}
+
+/// Linear interpolation between two horizon slopes, the slope of cells next to the eye is negative infinity
+fn interpolate(a: f64, b: f64, weight_b: f64) -> f64 {
+    if weight_b <= 0.0 {
+        a
+    } else if weight_b >= 1.0 {
+        b
+    } else {
+        a * (1.0 - weight_b) + b * weight_b
+    }
+}
+
+/// Calculates the visibility of the cells of a row of the window, the rows have to be processed outward from the eye.
+/// Every cell derives the horizon on its line of sight from the neighbouring cells in the direction of the eye:
+/// the line of sight crosses the previous row or the previous column in this row between two of those cells.
+/// The horizon is stored as the slope from the eye, the steepest of the horizon and the terrain of the cell itself.
+/// This is the line propagation approach of gdal_viewshed which shares the horizon between neighbouring lines of sight.
+#[allow(clippy::too_many_arguments)]
+fn process_row<RasterType>(
+    dem: &RasterType,
+    eye: &Eye,
+    window: &Window,
+    row: i32,
+    previous: &[f64],
+    current: &mut [f64],
+    options: &ViewshedOptions,
+    counts: &mut [u32],
+) where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let georef = dem.metadata();
+    let (cell_width, cell_height) = (georef.cell_size_x().abs(), georef.cell_size_y().abs());
+    let cols = dem.columns().count();
+    let dy = (row - eye.cell.row).abs();
+
+    let eye_col = eye.cell.col;
+    let left_of_eye = (window.left..eye_col).rev();
+    let right_of_eye = eye_col..window.right;
+    for col in right_of_eye.chain(left_of_eye) {
+        let index = (col - window.left) as usize;
+        let dx = (col - eye_col).abs();
+        // The neighbouring column in the direction of the eye
+        let toward = (col - (col - eye_col).signum() - window.left) as usize;
+
+        let horizon = if dx.max(dy) <= 1 {
+            f64::NEG_INFINITY
+        } else if dy == 0 {
+            current[toward]
+        } else if dx > dy {
+            interpolate(previous[toward], current[toward], 1.0 - dy as f64 / dx as f64)
+        } else if dy > dx {
+            interpolate(previous[toward], previous[index], 1.0 - dx as f64 / dy as f64)
+        } else {
+            previous[toward]
+        };
+
+        let cell = Cell::from_row_col(row, col);
+        let Some(terrain) = dem.cell_value(cell).and_then(|v| v.to_f64()) else {
+            // Nodata cells do not block the view, the horizon in front of them is carried on
+            current[index] = horizon;
+            continue;
+        };
+
+        let distance = (dx as f64 * cell_width).hypot(dy as f64 * cell_height);
+        if distance == 0.0 {
+            current[index] = f64::NEG_INFINITY;
+            counts[(row * cols + col) as usize] += 1;
+            continue;
+        }
+
+        let elevation = terrain - options.curvature_drop(distance) - eye.elevation;
+        let within_distance = options.max_distance.is_none_or(|max_distance| distance <= max_distance);
+        if within_distance && (elevation + options.target_height) / distance >= horizon {
+            counts[(row * cols + col) as usize] += 1;
+        }
+
+        current[index] = horizon.max(elevation / distance);
+    }
+}
+
+/// Adds 1 to the count of every cell that is visible from the observer.
+/// The rows are processed outward from the observer row, first upwards and then downwards.
+fn add_visible_cells<RasterType>(dem: &RasterType, observer: &Observer, options: &ViewshedOptions, counts: &mut [u32]) -> Result<()>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let eye = observer_eye(dem, observer)?;
+    let window = Window::new(dem.metadata(), &eye, options.max_distance);
+
+    let mut eye_row = vec![f64::NEG_INFINITY; window.width()];
+    process_row(dem, &eye, &window, eye.cell.row, &[], &mut eye_row, options, counts);
+
+    let mut previous = vec![f64::NEG_INFINITY; window.width()];
+    let mut current = vec![f64::NEG_INFINITY; window.width()];
+    let upwards: Vec<i32> = (window.top..eye.cell.row).rev().collect();
+    let downwards: Vec<i32> = (eye.cell.row + 1..window.bottom).collect();
+    for rows in [upwards, downwards] {
+        previous.copy_from_slice(&eye_row);
+        for row in rows {
+            process_row(dem, &eye, &window, row, &previous, &mut current, options, counts);
+            std::mem::swap(&mut previous, &mut current);
+        }
+    }
+
+    Ok(())
+}
+
+/// Calculates the cells of the elevation model that are visible from the observer.
+/// Visible cells are 1, other cells are 0 and nodata cells of the elevation model remain nodata.
+/// Nodata cells are considered transparent: they do not block the view of the cells behind them.
+/// Only the cells within the maximum distance of the options are processed.
+pub fn viewshed<RasterType>(dem: &RasterType, observer: &Observer, options: &ViewshedOptions) -> Result<RasterType::WithPixelType<u8>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let mut counts = vec![0; dem.len()];
+    add_visible_cells(dem, observer, options, &mut counts)?;
+
+    RasterType::WithPixelType::<u8>::from_iter_opt(
+        dem.metadata().clone(),
+        dem.iter_opt().zip(counts).map(|(terrain, count)| terrain.map(|_| count as u8)),
+    )
+}
+
+/// Calculates for every cell of the elevation model the number of observers it is visible from.
+/// Nodata cells of the elevation model remain nodata.
+pub fn cumulative_viewshed<RasterType>(
+    dem: &RasterType,
+    observers: &[Observer],
+    options: &ViewshedOptions,
+) -> Result<RasterType::WithPixelType<u32>>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let mut counts = vec![0; dem.len()];
+    for observer in observers {
+        add_visible_cells(dem, observer, options, &mut counts)?;
+    }
+
+    RasterType::WithPixelType::<u32>::from_iter_opt(
+        dem.metadata().clone(),
+        dem.iter_opt().zip(counts).map(|(terrain, count)| terrain.map(|_| count)),
+    )
+}
+
+/// Checks if the target point, raised by the target height of the options, is visible from the observer.
+/// The maximum distance of the options is also taken into account.
+pub fn line_of_sight<RasterType>(dem: &RasterType, observer: &Observer, target: Point, options: &ViewshedOptions) -> Result<bool>
+where
+    RasterType: Array<Metadata = GeoReference>,
+{
+    let eye = observer_eye(dem, observer)?;
+
+    let target_cell = dem.metadata().point_to_cell(target);
+    if !cell_in_raster(dem.metadata(), target_cell) {
+        return Err(Error::InvalidArgument(format!(
+            "Target {target:?} is outside of the elevation model"
+        )));
+    }
+
+    let Some(terrain) = dem.cell_value(target_cell).and_then(|v| v.to_f64()) else {
+        return Err(Error::InvalidArgument(format!("Target {target:?} is located on a nodata cell")));
+    };
+
+    if options
+        .max_distance
+        .is_some_and(|max_distance| (target.x() - eye.location.x()).hypot(target.y() - eye.location.y()) > max_distance)
+    {
+        return Ok(false);
+    }
+
+    Ok(is_visible(dem, &eye, target, target_cell, terrain + options.target_height, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        raster::DenseRaster,
+        testutils::{NOD, create_dem},
+    };
+
+    use super::*;
+
+    /// A flat terrain with a 10 meter high north-south wall in column 5
+    fn wall_dem() -> DenseRaster<f32> {
+        create_dem::<f32>(5, 10, 10.0, |_, col| if col == 5 { 10.0 } else { 0.0 })
+    }
+
+    #[test]
+    fn viewshed_blocked_by_wall() -> Result<()> {
+        let dem = wall_dem();
+        let observer = Observer::new(Point::new(15.0, -25.0), 2.0);
+
+        let visible = viewshed(&dem, &observer, &ViewshedOptions::default())?;
+        for row in 0..5 {
+            for col in 0..10 {
+                let expected = u8::from(col <= 5);
+                assert_eq!(visible[Cell::from_row_col(row, col)], expected, "row {row} col {col}");
+            }
+        }
+
+        // A high observer looks over the wall
+        let high_observer = Observer::new(Point::new(15.0, -25.0), 200.0);
+        let visible = viewshed(&dem, &high_observer, &ViewshedOptions::default())?;
+        assert!(visible.iter().all(|&v| v == 1));
+
+        // Targets raised above the wall are visible
+        let tall_targets = ViewshedOptions {
+            target_height: 30.0,
+            ..Default::default()
+        };
+        let visible = viewshed(&dem, &observer, &tall_targets)?;
+        assert_eq!(visible[Cell::from_row_col(2, 9)], 1);
+
+        let nearby = ViewshedOptions {
+            max_distance: Some(25.0),
+            ..Default::default()
+        };
+        let visible = viewshed(&dem, &observer, &nearby)?;
+        assert_eq!(visible[Cell::from_row_col(2, 3)], 1);
+        assert_eq!(visible[Cell::from_row_col(2, 4)], 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn viewshed_nodata_does_not_block() -> Result<()> {
+        let dem = create_dem::<f32>(5, 10, 10.0, |row, col| match (row, col) {
+            (2, 5) => NOD,
+            (_, 5) => 10.0,
+            _ => 0.0,
+        });
+        let observer = Observer::new(Point::new(15.0, -25.0), 2.0);
+
+        let visible = viewshed(&dem, &observer, &ViewshedOptions::default())?;
+        assert_eq!(visible.cell_value(Cell::from_row_col(2, 5)), None);
+        assert_eq!(visible[Cell::from_row_col(2, 9)], 1);
+        assert_eq!(visible[Cell::from_row_col(1, 9)], 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn viewshed_matches_line_of_sight() -> Result<()> {
+        let dem = create_dem::<f32>(41, 41, 10.0, |row, col| {
+            20.0 * (row as f64 / 4.0).sin() * (col as f64 / 5.0).cos() + row as f64 * 0.5
+        });
+        let observer = Observer::new(Point::new(205.0, -205.0), 5.0);
+        let options = ViewshedOptions::default();
+
+        // The sweep interpolates the horizon between neighbouring lines of sight, so allow a few differences
+        let visible = viewshed(&dem, &observer, &options)?;
+        let mut differences = 0;
+        for row in 0..41 {
+            for col in 0..41 {
+                let cell = Cell::from_row_col(row, col);
+                let exact = line_of_sight(&dem, &observer, dem.metadata().cell_center(cell), &options)?;
+                if exact != (visible[cell] == 1) {
+                    differences += 1;
+                }
+            }
+        }
+
+        assert!(differences < 41 * 41 / 20, "{differences} cells differ from the line of sight");
+        Ok(())
+    }
+
+    #[test]
+    fn cumulative_viewshed_counts_observers() -> Result<()> {
+        let dem = wall_dem();
+        let observers = [
+            Observer::new(Point::new(15.0, -25.0), 2.0),
+            Observer::new(Point::new(85.0, -25.0), 2.0),
+        ];
+
+        let counts = cumulative_viewshed(&dem, &observers, &ViewshedOptions::default())?;
+        assert_eq!(counts[Cell::from_row_col(2, 0)], 1);
+        assert_eq!(counts[Cell::from_row_col(2, 5)], 2);
+        assert_eq!(counts[Cell::from_row_col(2, 9)], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn line_of_sight_with_earth_curvature() -> Result<()> {
+        let dem = create_dem::<f32>(1, 101, 1000.0, |_, _| 0.0);
+        let observer = Observer::new(Point::new(500.0, -500.0), 2.0);
+        let target = Point::new(50_500.0, -500.0);
+
+        assert!(line_of_sight(&dem, &observer, target, &ViewshedOptions::default())?);
+
+        let curvature = ViewshedOptions {
+            curvature_correction: true,
+            ..Default::default()
+        };
+        assert!(!line_of_sight(&dem, &observer, target, &curvature)?);
+        assert!(line_of_sight(&dem, &observer, Point::new(2_500.0, -500.0), &curvature)?);
+
+        let wind_turbine = ViewshedOptions {
+            target_height: 200.0,
+            ..curvature
+        };
+        assert!(line_of_sight(&dem, &observer, target, &wind_turbine)?);
+
+        assert!(line_of_sight(&dem, &observer, Point::new(-500.0, -500.0), &curvature).is_err());
+
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use crate::{
     raster::DenseRaster, srs::CoordinateTransformer,
 };
 
+use super::resample::{Resampler, ResamplingMethod};
+
 const DEFAULT_EDGE_SAMPLE_COUNT: usize = 25;
 const MIN_EDGE_POINTS: usize = 2;
 
@@ -61,6 +63,8 @@ pub struct WarpOptions {
     pub num_threads: NumThreads,
     /// The target SRS to warp to
     pub target_srs: TargetSrs,
+    /// The resampling method used to calculate the target cell values (default = `ResamplingMethod::NearestNeighbour`)
+    pub resampling: ResamplingMethod,
 }
 
 impl Default for WarpOptions {
@@ -70,12 +74,18 @@ impl Default for WarpOptions {
             error_threshold: 0.125,
             num_threads: NumThreads::Count(1),
             target_srs: TargetSrs::Epsg(crs::epsg::WGS84_WEB_MERCATOR),
+            resampling: ResamplingMethod::default(),
         }
     }
 }
 
 pub fn warp_options_to_gdalwarp_cli_args(opts: &WarpOptions) -> Vec<String> {
-    let mut args = vec!["-r".to_string(), "near".to_string(), "-ovr".to_string(), "none".to_string()];
+    let mut args = vec![
+        "-r".to_string(),
+        opts.resampling.gdal_name().to_string(),
+        "-ovr".to_string(),
+        "none".to_string(),
+    ];
 
     // Handle target size based on WarpTargetSize
     match &opts.target_size {
@@ -321,15 +331,31 @@ pub fn warp<T: ArrayNum>(src: &DenseRaster<T>, opts: &WarpOptions) -> Result<Den
     let mut dst = DenseRaster::<T>::filled_with_nodata(target_georef);
 
     let coord_trans = CoordinateTransformer::new(&opts.target_srs.to_string(), src.metadata().projection())?;
+    let (x_scale, y_scale) = match opts.resampling {
+        ResamplingMethod::NearestNeighbour => (1.0, 1.0),
+        _ => resampling_scale(src.metadata(), dst.metadata(), &coord_trans)?,
+    };
+
+    let mut resampler = Resampler::new(src, opts.resampling, x_scale, y_scale);
     if opts.error_threshold > 0.0 {
-        warp_with_interpolation(src, &mut dst, &coord_trans, opts)?;
+        warp_with_interpolation(&mut resampler, &mut dst, &coord_trans, opts)?;
     } else {
-        warp_exact(src, &mut dst, &coord_trans, opts)?;
+        warp_exact(&mut resampler, &mut dst, &coord_trans, opts)?;
     }
 
     Ok(dst)
 }
 
+/// The size of a target cell expressed in source cells, based on the target extent in the source coordinate system
+fn resampling_scale(src_georef: &GeoReference, dst_georef: &GeoReference, coord_trans: &CoordinateTransformer) -> Result<(f64, f64)> {
+    let bbox = warp_bounding_box_with_edge_sampling(&dst_georef.bounding_box(), coord_trans, DEFAULT_EDGE_SAMPLE_COUNT)?;
+
+    let x_scale = bbox.width() / src_georef.cell_size_x().abs() / dst_georef.columns().count() as f64;
+    let y_scale = bbox.height() / src_georef.cell_size_y().abs() / dst_georef.rows().count() as f64;
+
+    Ok((x_scale, y_scale))
+}
+
 #[cfg(all(feature = "rayon", feature = "proj4rs"))]
 fn create_scoped_thread_pool(thread_count: Option<usize>) -> Result<rayon::ThreadPool> {
     let mut pool_builder = rayon::ThreadPoolBuilder::new();
@@ -342,7 +368,7 @@ fn create_scoped_thread_pool(thread_count: Option<usize>) -> Result<rayon::Threa
 }
 
 fn warp_exact<T: ArrayNum>(
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     dst: &mut DenseRaster<T>,
     coord_trans: &CoordinateTransformer,
     opts: &WarpOptions,
@@ -359,7 +385,7 @@ fn warp_exact<T: ArrayNum>(
         // Working buffer for transformed points outside the loop to avoid allocation overhead
         let mut points = Vec::with_capacity(dst.size().cols.count() as usize);
         for (row, row_slice) in dst.as_mut_slice().chunks_mut(cols).enumerate() {
-            transform_row_exact(row_slice, &meta, row as i32, coord_trans, src, &mut points)?;
+            transform_row_exact(row_slice, &meta, row as i32, coord_trans, resampler, &mut points)?;
         }
     } else {
         // proj is not threadsafe so only allow parallel warp with proj4rs
@@ -367,10 +393,11 @@ fn warp_exact<T: ArrayNum>(
         {
             use rayon::prelude::*;
             let _pool = create_scoped_thread_pool(thread_count)?;
-            dst.as_mut_slice().par_chunks_mut(cols).enumerate().try_for_each(|(r, row)| {
-                let mut points = Vec::with_capacity(cols);
-                transform_row_exact(row, &meta, r as i32, coord_trans, src, &mut points)
-            })?;
+            let resampler = &*resampler;
+            dst.as_mut_slice().par_chunks_mut(cols).enumerate().try_for_each_init(
+                || (resampler.clone(), Vec::with_capacity(cols)),
+                |(resampler, points), (r, row)| transform_row_exact(row, &meta, r as i32, coord_trans, resampler, points),
+            )?;
         }
     }
 
@@ -379,7 +406,7 @@ fn warp_exact<T: ArrayNum>(
 
 /// Optimized warp function using error threshold strategy for row based linear interpolation
 fn warp_with_interpolation<T: ArrayNum>(
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     dst: &mut DenseRaster<T>,
     coord_trans: &CoordinateTransformer,
     opts: &WarpOptions,
@@ -414,7 +441,16 @@ fn warp_with_interpolation<T: ArrayNum>(
 
     if thread_count.is_some_and(|count| count <= 1) || !cfg!(feature = "rayon") {
         for (row, (row_slice, row_points)) in dst.as_mut_slice().chunks_mut(cols).zip(row_points_chunks).enumerate() {
-            process_row_with_interpolation(row_slice, row_points, row, &target_georef, coord_trans, src, error_threshold, cols)?;
+            process_row_with_interpolation(
+                row_slice,
+                row_points,
+                row,
+                &target_georef,
+                coord_trans,
+                resampler,
+                error_threshold,
+                cols,
+            )?;
         }
     } else {
         #[cfg(all(feature = "rayon", feature = "proj4rs"))]
@@ -422,13 +458,26 @@ fn warp_with_interpolation<T: ArrayNum>(
             use rayon::prelude::*;
             let _pool = create_scoped_thread_pool(thread_count)?;
 
+            let resampler = &*resampler;
             dst.as_mut_slice()
                 .par_chunks_mut(cols)
                 .zip(row_points_chunks)
                 .enumerate()
-                .try_for_each(|(row, (row_slice, row_points))| {
-                    process_row_with_interpolation(row_slice, row_points, row, &target_georef, coord_trans, src, error_threshold, cols)
-                })?;
+                .try_for_each_init(
+                    || resampler.clone(),
+                    |resampler, (row, (row_slice, row_points))| {
+                        process_row_with_interpolation(
+                            row_slice,
+                            row_points,
+                            row,
+                            &target_georef,
+                            coord_trans,
+                            resampler,
+                            error_threshold,
+                            cols,
+                        )
+                    },
+                )?;
         }
     }
 
@@ -441,20 +490,17 @@ fn transform_row_exact<T: ArrayNum>(
     target_georef: &GeoReference,
     row: i32,
     coord_trans: &CoordinateTransformer,
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     points: &mut Vec<Point>,
 ) -> Result<()> {
     let num_columns = row_slice.len() as i32;
-    let source_georef = src.metadata();
-
     points.clear();
     points.extend((0..num_columns).map(|col| target_georef.cell_center(Cell::from_row_col(row, col))));
     coord_trans.transform_points_in_place(points)?;
 
     for (col, point) in points.iter().enumerate() {
-        let src_cell = source_georef.point_to_cell(*point);
-        if source_georef.is_cell_on_map(src_cell) {
-            row_slice[col] = src.cell_value(src_cell).unwrap_or(T::NODATA);
+        if let Some(value) = resampler.sample(*point) {
+            row_slice[col] = value;
         }
     }
 
@@ -472,14 +518,14 @@ fn process_row_with_interpolation<T: ArrayNum>(
     row: usize,
     target_georef: &GeoReference,
     coord_trans: &CoordinateTransformer,
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     error_threshold: f64,
     cols: usize,
 ) -> Result<()> {
     if cols <= 2 {
         // For very narrow rows, fall back to exact transformation
         let mut points = Vec::with_capacity(cols);
-        transform_row_exact(row_slice, target_georef, row as i32, coord_trans, src, &mut points)?;
+        transform_row_exact(row_slice, target_georef, row as i32, coord_trans, resampler, &mut points)?;
         return Ok(());
     }
 
@@ -493,27 +539,24 @@ fn process_row_with_interpolation<T: ArrayNum>(
 
     if error < error_threshold {
         // Use linear interpolation for the entire row
-        interpolate_row(row_slice, first_transformed, last_transformed, src);
+        interpolate_row(row_slice, first_transformed, last_transformed, resampler);
     } else {
         // Use recursive subdivision or fall back to exact transformation
-        subdivide_and_transform_row(row_slice, target_georef, row as i32, coord_trans, src, error_threshold)?;
+        subdivide_and_transform_row(row_slice, target_georef, row as i32, coord_trans, resampler, error_threshold)?;
     }
 
     Ok(())
 }
 
 /// Interpolate values across a row using linear interpolation between endpoints
-fn interpolate_row<T: ArrayNum>(dest: &mut [T], first_transformed: Point, last_transformed: Point, src: &DenseRaster<T>) {
+fn interpolate_row<T: ArrayNum>(dest: &mut [T], first_transformed: Point, last_transformed: Point, resampler: &mut Resampler<T>) {
     let row_width = dest.len();
-    let source_georef = src.metadata();
-
     for (col, dest_cell) in dest.iter_mut().enumerate() {
         let t = if row_width == 1 { 0.0 } else { col as f64 / (row_width - 1) as f64 };
         let interpolated_point = linear_interpolate(first_transformed, last_transformed, t);
 
-        let src_cell = source_georef.point_to_cell(interpolated_point);
-        if source_georef.is_cell_on_map(src_cell) {
-            *dest_cell = src.cell_value(src_cell).unwrap_or(T::NODATA);
+        if let Some(value) = resampler.sample(interpolated_point) {
+            *dest_cell = value;
         }
     }
 }
@@ -524,13 +567,21 @@ fn subdivide_and_transform_row<T: ArrayNum>(
     result_georef: &GeoReference,
     row: i32,
     coord_trans: &CoordinateTransformer,
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     error_threshold: f64,
 ) -> Result<()> {
     let start_col = 0;
     let end_col = result.len() as i32 - 1;
 
-    subdivide_segment(result, result_georef, row, start_col..=end_col, coord_trans, src, error_threshold)
+    subdivide_segment(
+        result,
+        result_georef,
+        row,
+        start_col..=end_col,
+        coord_trans,
+        resampler,
+        error_threshold,
+    )
 }
 
 /// Recursively subdivide a segment of a row
@@ -540,7 +591,7 @@ fn subdivide_segment<T: ArrayNum>(
     row: i32,
     columns: RangeInclusive<i32>,
     coord_trans: &CoordinateTransformer,
-    src: &DenseRaster<T>,
+    resampler: &mut Resampler<T>,
     error_threshold: f64,
 ) -> Result<()> {
     let start_col = *columns.start();
@@ -550,17 +601,14 @@ fn subdivide_segment<T: ArrayNum>(
 
     debug_assert!(result.len() == column_count);
 
-    let source_georef = src.metadata();
-
     if column_count <= 2 {
         // Transform remaining pixels exactly
         for (i, col) in columns.enumerate() {
             let cell = Cell::from_row_col(row, col);
             let pixel = result_georef.cell_center(cell);
             let transformed = coord_trans.transform_point(pixel)?;
-            let src_cell = source_georef.point_to_cell(transformed);
-            if source_georef.is_cell_on_map(src_cell) {
-                result[i] = src.cell_value(src_cell).unwrap_or(T::NODATA);
+            if let Some(value) = resampler.sample(transformed) {
+                result[i] = value;
             }
         }
         return Ok(());
@@ -594,9 +642,8 @@ fn subdivide_segment<T: ArrayNum>(
             };
 
             let interpolated_point = linear_interpolate(start_pixel, end_pixel, t);
-            let src_cell = source_georef.point_to_cell(interpolated_point);
-            if source_georef.is_cell_on_map(src_cell) {
-                result[i] = src.cell_value(src_cell).unwrap_or(T::NODATA);
+            if let Some(value) = resampler.sample(interpolated_point) {
+                result[i] = value;
             }
         }
     } else {
@@ -608,7 +655,7 @@ fn subdivide_segment<T: ArrayNum>(
             row,
             start_col..=middle_col,
             coord_trans,
-            src,
+            resampler,
             error_threshold,
         )?;
         subdivide_segment(
@@ -617,7 +664,7 @@ fn subdivide_segment<T: ArrayNum>(
             row,
             middle_col + 1..=end_col,
             coord_trans,
-            src,
+            resampler,
             error_threshold,
         )?;
     }
@@ -689,7 +736,7 @@ mod tests {
             RasterSize::with_rows_cols(Rows(1491), Columns(3800)),
             [281100.0, 100.0, 0.0, 6712800.0, 0.0, -100.0].into(),
             Some(255.0),
-            None
+            None,
         );
 
         let gdal_bbox = georef_gdal.bounding_box();
@@ -705,4 +752,55 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn warp_with_resampling() -> Result<()> {
+        use crate::{ArrayInterop as _, crs::Epsg};
+
+        let georef = GeoReference::with_top_left_origin(
+            "EPSG:31370",
+            RasterSize::with_rows_cols(Rows(4), Columns(4)),
+            Point::new(100000.0, 200000.0),
+            CellSize::square(100.0),
+            Some(-1.0),
+        );
+
+        #[rustfmt::skip]
+        let src = DenseRaster::<f32>::new_init_nodata(georef, inf::allocate::aligned_vec_from_slice(&[
+             1.0,  2.0,  3.0,  4.0,
+             5.0,  6.0,  7.0,  8.0,
+             9.0, 10.0, 11.0, 12.0,
+            13.0, 14.0, 15.0, -1.0,
+        ]))?;
+
+        let warp_with = |resampling| {
+            warp(
+                &src,
+                &WarpOptions {
+                    target_size: WarpTargetSize::CellSize(CellSize::square(200.0), TargetPixelAlignment::No),
+                    target_srs: TargetSrs::Epsg(Epsg::from(31370)),
+                    error_threshold: 0.0,
+                    resampling,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let expect = |resampling, expected: [f32; 4]| -> Result<()> {
+            let result = warp_with(resampling)?;
+            assert_eq!(result.size(), RasterSize::with_rows_cols(Rows(2), Columns(2)));
+            for (actual, expected) in result.iter_opt().zip(expected) {
+                assert_relative_eq!(actual.expect("Unexpected nodata"), expected, epsilon = 1e-4);
+            }
+
+            Ok(())
+        };
+
+        expect(ResamplingMethod::Average, [3.5, 5.5, 11.5, 38.0 / 3.0])?;
+        expect(ResamplingMethod::Sum, [14.0, 22.0, 46.0, 38.0])?;
+        expect(ResamplingMethod::Min, [1.0, 3.0, 9.0, 11.0])?;
+        expect(ResamplingMethod::Max, [6.0, 8.0, 14.0, 15.0])?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,343 @@
+//! Zonal statistics: statistics of a value raster per zone of a categorical zone raster.
+
+use std::collections::BTreeMap;
+
+use num::NumCast;
+
+use crate::{Array, ArrayNum, Error, Result};
+
+use super::statistics::{RasterStats, values_statistics};
+
+fn check_inputs<ZoneRaster: Array, ValueRaster: Array>(zones: &ZoneRaster, values: &ValueRaster, quantile_vals: &[f64]) -> Result<()> {
+    if zones.size() != values.size() {
+        return Err(Error::InvalidArgument(
+            "Zone raster dimensions should match value raster dimensions".into(),
+        ));
+    }
+
+    if quantile_vals.iter().any(|&q| !(0.0..=1.0).contains(&q)) {
+        return Err(Error::InvalidArgument("Quantile values must be between 0 and 1".to_string()));
+    }
+
+    Ok(())
+}
+
+fn zone_id<T: ArrayNum>(zone: T) -> Result<i64> {
+    match (zone.to_i64(), zone.to_f64()) {
+        (Some(id), Some(value)) if id as f64 == value => Ok(id),
+        _ => Err(Error::InvalidArgument(format!(
+            "Invalid zone value: '{zone:?}', zones must be integers"
+        ))),
+    }
+}
+
+/// Calculates the statistics of the value raster for every zone in the zone raster.
+/// Zone values must be integers, cells that are nodata in either raster are ignored.
+/// All the values of a zone are kept in memory and sorted, use [`ZonalStatisticsAccumulator`] for rasters that are too large for this.
+pub fn zonal_statistics<ZoneRaster, ValueRaster>(
+    zones: &ZoneRaster,
+    values: &ValueRaster,
+    quantile_vals: &[f64],
+) -> Result<BTreeMap<i64, RasterStats<ValueRaster::Pixel>>>
+where
+    ZoneRaster: Array,
+    ValueRaster: Array,
+{
+    check_inputs(zones, values, quantile_vals)?;
+
+    let mut zone_values: BTreeMap<i64, Vec<ValueRaster::Pixel>> = BTreeMap::new();
+    for (zone, value) in zones.iter_opt().zip(values.iter_opt()) {
+        if let (Some(zone), Some(value)) = (zone, value) {
+            zone_values.entry(zone_id(zone)?).or_default().push(value);
+        }
+    }
+
+    let mut result = BTreeMap::new();
+    for (zone, values) in zone_values {
+        if let Some(stats) = values_statistics(values, quantile_vals)? {
+            result.insert(zone, stats);
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone)]
+struct ZoneAccumulator<T: ArrayNum> {
+    count: usize,
+    sum: f64,
+    mean: f64,
+    squared_diff_sum: f64,
+    min: T,
+    max: T,
+    histogram: Vec<u64>,
+}
+
+impl<T: ArrayNum> ZoneAccumulator<T> {
+    fn new(bin_count: usize) -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            mean: 0.0,
+            squared_diff_sum: 0.0,
+            min: T::max_value(),
+            max: T::min_value(),
+            histogram: vec![0; bin_count],
+        }
+    }
+
+    fn add(&mut self, value: T, bin: usize) {
+        let val = value.to_f64().unwrap_or(0.0);
+
+        // Welford's online algorithm for the variance
+        self.count += 1;
+        self.sum += val;
+        let delta = val - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squared_diff_sum += delta * (val - self.mean);
+
+        if value < self.min {
+            self.min = value;
+        }
+
+        if value > self.max {
+            self.max = value;
+        }
+
+        self.histogram[bin] += 1;
+    }
+}
+
+/// Streaming zonal statistics that do not keep the raster values in memory.
+/// The zone and value rasters can be added in chunks (e.g. per tile or per block of rows).
+/// Count, sum, mean, min, max and standard deviation are exact, the median and quantiles are estimated
+/// from a histogram with a fixed number of bins over the expected value range.
+#[derive(Debug, Clone)]
+pub struct ZonalStatisticsAccumulator<T: ArrayNum> {
+    range_start: f64,
+    bin_width: f64,
+    bin_count: usize,
+    zones: BTreeMap<i64, ZoneAccumulator<T>>,
+}
+
+impl<T: ArrayNum> ZonalStatisticsAccumulator<T> {
+    /// Values outside of the `value_range` are counted in the first or last histogram bin.
+    pub fn new(value_range: std::ops::RangeInclusive<T>, bin_count: usize) -> Result<Self> {
+        let start = value_range.start().to_f64().unwrap_or(f64::NAN);
+        let end = value_range.end().to_f64().unwrap_or(f64::NAN);
+
+        if bin_count == 0 || start.is_nan() || end.is_nan() || start > end {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid zonal statistics histogram: {bin_count} bins over [{start}, {end}]"
+            )));
+        }
+
+        Ok(Self {
+            range_start: start,
+            bin_width: (end - start) / bin_count as f64,
+            bin_count,
+            zones: BTreeMap::new(),
+        })
+    }
+
+    fn bin(&self, value: T) -> usize {
+        if self.bin_width == 0.0 {
+            return 0;
+        }
+
+        let bin = ((value.to_f64().unwrap_or(0.0) - self.range_start) / self.bin_width).floor();
+        (bin.max(0.0) as usize).min(self.bin_count - 1)
+    }
+
+    /// Adds the values of a chunk, the zone and value rasters should cover the same area.
+    pub fn add<ZoneRaster, ValueRaster>(&mut self, zones: &ZoneRaster, values: &ValueRaster) -> Result<()>
+    where
+        ZoneRaster: Array,
+        ValueRaster: Array<Pixel = T>,
+    {
+        check_inputs(zones, values, &[])?;
+
+        for (zone, value) in zones.iter_opt().zip(values.iter_opt()) {
+            if let (Some(zone), Some(value)) = (zone, value) {
+                let bin = self.bin(value);
+                self.zones
+                    .entry(zone_id(zone)?)
+                    .or_insert_with(|| ZoneAccumulator::new(self.bin_count))
+                    .add(value, bin);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calculates the statistics of every zone that contained data.
+    pub fn finish(self, quantile_vals: &[f64]) -> Result<BTreeMap<i64, RasterStats<T>>> {
+        if quantile_vals.iter().any(|&q| !(0.0..=1.0).contains(&q)) {
+            return Err(Error::InvalidArgument("Quantile values must be between 0 and 1".to_string()));
+        }
+
+        let mut result = BTreeMap::new();
+        for (zone, acc) in &self.zones {
+            let quantiles = quantile_vals.iter().map(|&q| self.histogram_quantile(acc, q)).collect();
+
+            result.insert(
+                *zone,
+                RasterStats {
+                    min: acc.min,
+                    max: acc.max,
+                    median: self.histogram_quantile(acc, 0.5),
+                    mean: acc.mean,
+                    stddev: (acc.squared_diff_sum / acc.count as f64).sqrt(),
+                    quantiles: Some(quantiles),
+                    value_count: acc.count,
+                    sum: acc.sum,
+                },
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// Estimates the quantile in the same way as the exact calculation, the ordered values are estimated
+    /// by assuming the values are evenly spread within their histogram bin.
+    fn histogram_quantile(&self, acc: &ZoneAccumulator<T>, quantile: f64) -> f64 {
+        let position = quantile * (acc.count - 1) as f64;
+        let lower = position.floor();
+        let upper = position.ceil();
+
+        let lower_val = self.histogram_value(acc, lower as u64);
+        if lower == upper {
+            return lower_val;
+        }
+
+        let weight = position - lower;
+        lower_val * (1.0 - weight) + self.histogram_value(acc, upper as u64) * weight
+    }
+
+    /// Estimates the value at the given index of the ordered zone values.
+    fn histogram_value(&self, acc: &ZoneAccumulator<T>, index: u64) -> f64 {
+        let min = NumCast::from(acc.min).unwrap_or(f64::NAN);
+        let max = NumCast::from(acc.max).unwrap_or(f64::NAN);
+
+        let mut preceding = 0;
+        for (bin, &bin_count) in acc.histogram.iter().enumerate() {
+            if preceding + bin_count > index {
+                let fraction = ((index - preceding) as f64 + 0.5) / bin_count as f64;
+                let value = self.range_start + (bin as f64 + fraction) * self.bin_width;
+                return value.clamp(min, max);
+            }
+
+            preceding += bin_count;
+        }
+
+        max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        CellSize,
+        testutils::{NOD, create_raster},
+    };
+
+    use super::*;
+
+    #[test]
+    fn zonal_statistics_per_zone() -> Result<()> {
+        #[rustfmt::skip]
+        let zones = create_raster(3, 3, CellSize::square(100.0), &[
+            1.0, 1.0, 2.0,
+            1.0, 2.0, 2.0,
+            NOD, 3.0, 2.0,
+        ]);
+
+        #[rustfmt::skip]
+        let values = create_raster(3, 3, CellSize::square(100.0), &[
+            1.0, 2.0, 10.0,
+            3.0, 20.0, NOD,
+            50.0, NOD, 30.0,
+        ]);
+
+        let stats = zonal_statistics(&zones, &values, &[0.0, 1.0])?;
+        assert_eq!(stats.len(), 2);
+
+        let zone1 = &stats[&1];
+        assert_eq!(zone1.value_count, 3);
+        assert_eq!(zone1.min, 1.0);
+        assert_eq!(zone1.max, 3.0);
+        assert_relative_eq!(zone1.sum, 6.0);
+        assert_relative_eq!(zone1.mean, 2.0);
+        assert_relative_eq!(zone1.median, 2.0);
+        assert_relative_eq!(zone1.stddev, (2.0_f64 / 3.0).sqrt());
+        assert_eq!(zone1.quantiles, Some(vec![1.0, 3.0]));
+
+        let zone2 = &stats[&2];
+        assert_eq!(zone2.value_count, 3);
+        assert_relative_eq!(zone2.sum, 60.0);
+        assert_relative_eq!(zone2.median, 20.0);
+
+        let other_size = create_raster(1, 2, CellSize::square(100.0), &[1.0, 2.0]);
+        assert!(zonal_statistics(&zones, &other_size, &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_integral_zones_are_an_error() -> Result<()> {
+        let zones = create_raster(1, 2, CellSize::square(100.0), &[1.2, 1.7]);
+        let values = create_raster(1, 2, CellSize::square(100.0), &[1.0, 2.0]);
+
+        assert!(zonal_statistics(&zones, &values, &[]).is_err());
+        assert!(ZonalStatisticsAccumulator::new(0.0..=10.0, 10)?.add(&zones, &values).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_zonal_statistics() -> Result<()> {
+        let (rows, cols) = (10, 8);
+        let zone_data: Vec<f64> = (0..rows * cols).map(|i| if i % 11 == 0 { NOD } else { (i % 3) as f64 }).collect();
+        let value_data: Vec<f64> = (0..rows * cols)
+            .map(|i| if i % 13 == 5 { NOD } else { ((i * 17) % 50) as f64 })
+            .collect();
+
+        let zones = create_raster(rows, cols, CellSize::square(100.0), &zone_data);
+        let values = create_raster(rows, cols, CellSize::square(100.0), &value_data);
+        let exact = zonal_statistics(&zones, &values, &[0.25])?;
+
+        // Add the data in two chunks of rows
+        let chunk_len = (rows / 2 * cols) as usize;
+        let mut accumulator = ZonalStatisticsAccumulator::new(0.0..=50.0, 100)?;
+        for chunk in 0..2 {
+            let range = chunk * chunk_len..(chunk + 1) * chunk_len;
+            accumulator.add(
+                &create_raster(rows / 2, cols, CellSize::square(100.0), &zone_data[range.clone()]),
+                &create_raster(rows / 2, cols, CellSize::square(100.0), &value_data[range]),
+            )?;
+        }
+
+        let streamed = accumulator.finish(&[0.25])?;
+        assert_eq!(streamed.keys().collect::<Vec<_>>(), exact.keys().collect::<Vec<_>>());
+
+        for (zone, exact) in &exact {
+            let streamed = &streamed[zone];
+            assert_eq!(streamed.value_count, exact.value_count);
+            assert_eq!(streamed.min, exact.min);
+            assert_eq!(streamed.max, exact.max);
+            assert_relative_eq!(streamed.sum, exact.sum);
+            assert_relative_eq!(streamed.mean, exact.mean, epsilon = 1e-9);
+            assert_relative_eq!(streamed.stddev, exact.stddev, epsilon = 1e-9);
+            assert_relative_eq!(streamed.median, exact.median, epsilon = 1.0);
+            assert_relative_eq!(
+                streamed.quantiles.as_ref().unwrap()[0],
+                exact.quantiles.as_ref().unwrap()[0],
+                epsilon = 1.0
+            );
+        }
+
+        Ok(())
+    }
+}
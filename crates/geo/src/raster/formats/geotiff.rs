@@ -5,15 +5,15 @@ use std::{mem::MaybeUninit, path::Path};
 const LANES: usize = inf::simd::LANES;
 
 use crate::{
-    ArrayDataType, ArrayNum, Error, GeoReference, RasterSize, Result,
+    ArrayDataType, ArrayNum, Error, GeoReference, RasterSize, Result, geotiff,
     raster::{
-        WriteRasterOptions,
+        GeoTiffWriteOptions, WriteRasterOptions,
         formats::{RasterFormat, RasterFormatDyn},
         utils::cast_uninit_byte_slice_mut,
     },
 };
 
-use crate::geotiff::{FIRST_BAND, GeoTiffReader};
+use crate::geotiff::{BandIndex, GeoTiffReader};
 
 pub struct GeotiffRasterIO {
     reader: GeoTiffReader,
@@ -21,8 +21,7 @@ pub struct GeotiffRasterIO {
 
 impl RasterFormatDyn for GeotiffRasterIO {
     fn band_count(&self) -> Result<usize> {
-        //self.reader.band_count()
-        Ok(1)
+        Ok(self.reader.metadata().band_count as usize)
     }
 
     fn raster_size(&self) -> Result<RasterSize> {
@@ -30,17 +29,17 @@ impl RasterFormatDyn for GeotiffRasterIO {
     }
 
     fn georeference(&mut self, band_index: usize) -> Result<GeoReference> {
-        assert_eq!(1, band_index, "Geotiff format currently only supports single raster band");
-        Ok(self.reader.metadata().geo_reference.clone())
+        let band_index = self.band_index(band_index)?;
+        Ok(self.reader.metadata().band_geo_reference(band_index))
     }
 
     fn data_type(&self, band_index: usize) -> Result<ArrayDataType> {
-        assert_eq!(1, band_index, "Geotiff format currently only supports single raster band");
+        self.band_index(band_index)?;
         Ok(self.reader.metadata().data_type)
     }
 
     fn overview_count(&self, band_index: usize) -> Result<usize> {
-        assert_eq!(1, band_index, "Geotiff format currently only supports single raster band");
+        self.band_index(band_index)?;
         let overview_count = self.reader.metadata().overviews.len();
         Ok(if overview_count > 0 { overview_count - 1 } else { 0 })
     }
@@ -101,16 +100,27 @@ impl RasterFormat for GeotiffRasterIO {
     }
 
     fn write_band<T: ArrayNum>(
-        _path: impl AsRef<Path>,
-        _geo_reference: &GeoReference,
-        _data: &[T],
-        _options: WriteRasterOptions,
+        path: impl AsRef<Path>,
+        geo_reference: &GeoReference,
+        data: &[T],
+        options: WriteRasterOptions,
     ) -> Result<()> {
-        Err(Error::Runtime("Writing GeoTiff not yet implemented".to_string()))
+        let options = match options {
+            WriteRasterOptions::Default => GeoTiffWriteOptions::default(),
+            WriteRasterOptions::GeoTiff(opts) => opts,
+        };
+
+        geotiff::write_geotiff(path.as_ref(), geo_reference, data, &options)
     }
 }
 
 impl GeotiffRasterIO {
+    fn band_index(&self, band_index: usize) -> Result<BandIndex> {
+        let band = BandIndex::new(band_index).ok_or_else(|| Error::InvalidArgument("Band indices are 1-based".into()))?;
+        self.reader.metadata().check_band_index(band)?;
+        Ok(band)
+    }
+
     #[simd_bounds]
     fn read_raster_band_as<T: ArrayNum>(
         &mut self,
@@ -118,14 +128,15 @@ impl GeotiffRasterIO {
         data_type: crate::ArrayDataType,
         dst_data: &mut [std::mem::MaybeUninit<T>],
     ) -> Result<GeoReference> {
-        assert_eq!(1, band_index, "Geotiff format currently only supports single raster band");
+        let band_index = self.band_index(band_index)?;
         assert_eq!(
             data_type,
             self.reader.metadata().data_type,
             "Geotiff format currently does not support on-the-fly data type conversion"
         );
 
-        self.reader.read_raster_into_buffer::<T, GeoReference>(dst_data)
+        self.reader
+            .read_overview_band_into_buffer::<T, GeoReference>(0, band_index, dst_data)
     }
 
     #[simd_bounds]
@@ -136,13 +147,13 @@ impl GeotiffRasterIO {
         data_type: ArrayDataType,
         dst_data: &mut [MaybeUninit<T>],
     ) -> Result<GeoReference> {
-        assert_eq!(1, band_index, "Geotiff format currently only supports single raster band");
+        let band_index = self.band_index(band_index)?;
         debug_assert_eq!(
             data_type,
             self.reader.metadata().data_type,
             "Geotiff format currently does not support on-the-fly data type conversion"
         );
 
-        self.reader.read_band_region_into_buffer(FIRST_BAND, region, dst_data)
+        self.reader.read_band_region_into_buffer(band_index, region, dst_data)
     }
 }
@@ -46,8 +46,8 @@ mod generictests {
     }
 
     #[test]
-    fn read_write_raster_nodata_handling<T: ArrayNum + fmt::Debug, R: Array<Pixel = T, Metadata = GeoReference> + RasterReadWrite>() -> Result<()>
-    {
+    fn read_write_raster_nodata_handling<T: ArrayNum + fmt::Debug, R: Array<Pixel = T, Metadata = GeoReference> + RasterReadWrite>()
+    -> Result<()> {
         let tmp_dir = tempfile::TempDir::new().unwrap();
         let raster_path = tmp_dir.path().join("test.asc");
 
@@ -232,3 +232,86 @@ mod tests {
         Ok(())
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "raster-io-geotiff")]
+mod geokeytests {
+    use std::path::Path;
+
+    use approx::assert_relative_eq;
+    use gdal::Metadata as _;
+
+    use crate::{
+        Point, Result,
+        raster::{formats::FormatProvider, io::RasterIO},
+        srs::CoordinateTransformer,
+    };
+
+    /// Creates a GeoTIFF with GDAL that uses a user-defined projection
+    fn create_gdal_geotiff(path: &Path, proj_definition: &str, area_or_point: &str) -> Result<()> {
+        let driver = gdal::DriverManager::get_driver_by_name("GTiff")?;
+        let mut ds = driver.create_with_band_type::<u8, _>(path, 20, 10, 1)?;
+        ds.set_geo_transform(&[150000.0, 10.0, 0.0, 200000.0, 0.0, -10.0])?;
+        ds.set_spatial_ref(&gdal::spatial_ref::SpatialRef::from_proj4(proj_definition)?)?;
+        ds.set_metadata_item("AREA_OR_POINT", area_or_point, "")?;
+        Ok(())
+    }
+
+    /// Compares the georeference read by GDAL and by the GeoTIFF reader, the projections are compared by transforming the raster corners
+    fn compare_geotiff_vs_gdal_georeference(path: &Path) -> Result<()> {
+        let gdal_georef = RasterIO::open_read_only_force_format(path, FormatProvider::Gdal)?.georeference(1)?;
+        let gtif_georef = RasterIO::open_read_only_force_format(path, FormatProvider::GeoTiff)?.georeference(1)?;
+
+        assert_eq!(gdal_georef.geo_transform(), gtif_georef.geo_transform());
+
+        let gdal_transformer = CoordinateTransformer::new(gdal_georef.projection(), "EPSG:4326")?;
+        let gtif_transformer = CoordinateTransformer::new(gtif_georef.projection(), "EPSG:4326")?;
+        for point in [gdal_georef.top_left(), gdal_georef.bottom_right(), Point::new(0.0, 0.0)] {
+            let gdal_point = gdal_transformer.transform_point(point)?;
+            let gtif_point = gtif_transformer.transform_point(point)?;
+            assert_relative_eq!(gdal_point.x(), gtif_point.x(), epsilon = 1e-8);
+            assert_relative_eq!(gdal_point.y(), gtif_point.y(), epsilon = 1e-8);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn user_defined_projections() -> Result<()> {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+
+        let projections = [
+            "+proj=lcc +lat_1=49.8333 +lat_2=51.1667 +lat_0=50.797815 +lon_0=4.359215 +x_0=649328 +y_0=665262 +ellps=GRS80 +units=m +no_defs",
+            "+proj=tmerc +lat_0=0 +lon_0=4.5 +k=0.9995 +x_0=500000 +y_0=-5000000 +datum=WGS84 +units=m +no_defs",
+            "+proj=sterea +lat_0=52.15616055555555 +lon_0=5.38763888888889 +k=0.9999079 +x_0=155000 +y_0=463000 +ellps=bessel +units=m +no_defs",
+            "+proj=laea +lat_0=52 +lon_0=10 +x_0=4321000 +y_0=3210000 +ellps=GRS80 +units=m +no_defs",
+        ];
+
+        for (index, projection) in projections.iter().enumerate() {
+            let path = tmp_dir.path().join(format!("user_defined_{index}.tif"));
+            create_gdal_geotiff(&path, projection, "Area")?;
+            compare_geotiff_vs_gdal_georeference(&path)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn pixel_is_point() -> Result<()> {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let path = tmp_dir.path().join("pixel_is_point.tif");
+
+        create_gdal_geotiff(
+            &path,
+            "+proj=tmerc +lat_0=0 +lon_0=3 +k=0.9996 +x_0=500000 +y_0=0 +datum=WGS84 +units=m +no_defs",
+            "Point",
+        )?;
+        compare_geotiff_vs_gdal_georeference(&path)?;
+
+        // GDAL stores the center of the upper left cell as tie point, the reader shifts it back to the corner
+        let gtif_georef = RasterIO::open_read_only_force_format(&path, FormatProvider::GeoTiff)?.georeference(1)?;
+        assert_eq!(gtif_georef.top_left(), Point::new(150000.0, 200000.0));
+
+        Ok(())
+    }
+}
@@ -7,8 +7,9 @@ use path_macro::path;
 use rand::distr::{Uniform, uniform::SampleUniform};
 
 use crate::{
-    ArrayNum, GeoReference, RasterSize,
-    array::{Columns, Rows},
+    ArrayNum, CellSize, GeoReference, Point, RasterSize,
+    array::{ArrayInterop as _, Columns, Rows},
+    raster::DenseRaster,
 };
 
 pub const NOD: f64 = 255.0;
@@ -38,6 +39,25 @@ pub fn create_vec<T: num::NumCast + ArrayNum>(data: &[f64]) -> AlignedVec<T> {
     vec
 }
 
+/// Creates a raster with its top left corner at the origin, cells that contain the `NOD` value are nodata
+pub fn create_raster<T: ArrayNum>(rows: i32, cols: i32, cell_size: CellSize, data: &[T]) -> DenseRaster<T> {
+    let geo_reference = GeoReference::with_top_left_origin(
+        "",
+        RasterSize::with_rows_cols(Rows(rows), Columns(cols)),
+        Point::new(0.0, 0.0),
+        cell_size,
+        Some(NOD),
+    );
+
+    DenseRaster::new_init_nodata(geo_reference, allocate::aligned_vec_from_slice(data)).expect("Invalid test raster")
+}
+
+/// Creates an elevation raster with square cells, the elevation is calculated from the row and column of the cell
+pub fn create_dem<T: ArrayNum>(rows: i32, cols: i32, cell_size: f64, elevation: impl Fn(i32, i32) -> f64) -> DenseRaster<T> {
+    let data: Vec<T> = (0..rows * cols).map(|i| number_cast(elevation(i / cols, i % cols))).collect();
+    create_raster(rows, cols, CellSize::square(cell_size), &data)
+}
+
 pub fn create_random_vec<T: num::NumCast + ArrayNum + SampleUniform>(size: RasterSize, value_range: RangeInclusive<f64>) -> AlignedVec<T> {
     use rand::distr::Distribution;
 
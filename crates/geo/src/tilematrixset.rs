@@ -0,0 +1,618 @@
+//! OGC two dimensional tile matrix sets, used to map tile indices to coordinates in the tile matrix set CRS.
+//!
+//! `GoogleMapsCompatible` (`WebMercatorQuad`) is the default tiling scheme, other tile matrix sets can be loaded
+//! from their OGC TMS 2.0 JSON encoding.
+
+use crate::{
+    CellSize, Columns, Error, GeoReference, LatLonBounds, Point, RasterSize, Rect, Result, Rows, Tile, ZoomLevelStrategy,
+    constants::EARTH_CIRCUMFERENCE_M,
+    coordinate::Coordinate,
+    crs::{self, Epsg},
+};
+
+/// Tolerance (in tiles) used when a coordinate lies on a tile edge
+const TILE_EDGE_EPSILON: f64 = 1e-9;
+/// Relative tolerance when comparing the cell sizes and origins of tile matrices
+const RELATIVE_EPSILON: f64 = 1e-9;
+
+/// A single zoom level of a `TileMatrixSet`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileMatrix {
+    pub id: String,
+    /// Size of a cell in units of the tile matrix set CRS
+    pub cell_size: f64,
+    /// Top left corner of the matrix (x = easting or longitude, y = northing or latitude)
+    pub point_of_origin: Point,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub matrix_width: u32,
+    pub matrix_height: u32,
+}
+
+impl TileMatrix {
+    /// Width of a tile in units of the tile matrix set CRS
+    pub fn tile_span_x(&self) -> f64 {
+        self.cell_size * self.tile_width as f64
+    }
+
+    /// Height of a tile in units of the tile matrix set CRS
+    pub fn tile_span_y(&self) -> f64 {
+        self.cell_size * self.tile_height as f64
+    }
+
+    /// Equal to the other tile matrix, the cell size and origin are compared with a relative tolerance
+    pub fn matches(&self, other: &TileMatrix) -> bool {
+        let nearly_equal = |a: f64, b: f64| (a - b).abs() <= RELATIVE_EPSILON * a.abs().max(b.abs());
+
+        nearly_equal(self.cell_size, other.cell_size)
+            && nearly_equal(self.point_of_origin.x(), other.point_of_origin.x())
+            && nearly_equal(self.point_of_origin.y(), other.point_of_origin.y())
+            && self.tile_width == other.tile_width
+            && self.tile_height == other.tile_height
+            && self.matrix_width == other.matrix_width
+            && self.matrix_height == other.matrix_height
+    }
+
+    /// The extent covered by the full matrix
+    pub fn bounds(&self) -> Rect<f64> {
+        Rect::from_nw_se(
+            self.point_of_origin,
+            Point::new(
+                self.point_of_origin.x() + self.tile_span_x() * self.matrix_width as f64,
+                self.point_of_origin.y() - self.tile_span_y() * self.matrix_height as f64,
+            ),
+        )
+    }
+}
+
+/// An OGC tile matrix set: a list of tile matrices (zoom levels) in a common CRS.
+/// The zoom level of a `Tile` is the index in the list of tile matrices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileMatrixSet {
+    pub id: String,
+    pub crs: Epsg,
+    pub tile_matrices: Vec<TileMatrix>,
+}
+
+impl Default for TileMatrixSet {
+    fn default() -> Self {
+        Self::google_maps_compatible()
+    }
+}
+
+impl TileMatrixSet {
+    /// The `WebMercatorQuad` tile matrix set used by XYZ web tiles (EPSG:3857)
+    pub fn google_maps_compatible() -> Self {
+        let origin = EARTH_CIRCUMFERENCE_M / 2.0;
+        Self::quad(
+            "WebMercatorQuad",
+            crs::epsg::WGS84_WEB_MERCATOR,
+            Point::new(-origin, origin),
+            EARTH_CIRCUMFERENCE_M / Tile::TILE_SIZE as f64,
+            (1, 1),
+            24,
+        )
+    }
+
+    /// The `WorldCRS84Quad` tile matrix set, two tiles wide at zoom level 0 (longitude, latitude)
+    pub fn world_crs84_quad() -> Self {
+        Self::quad(
+            "WorldCRS84Quad",
+            crs::epsg::WGS84,
+            Point::new(-180.0, 90.0),
+            180.0 / Tile::TILE_SIZE as f64,
+            (2, 1),
+            23,
+        )
+    }
+
+    /// The `EuropeanETRS89_LAEAQuad` tile matrix set on the EPSG:3035 grid
+    pub fn european_etrs89_laea_quad() -> Self {
+        Self::quad(
+            "EuropeanETRS89_LAEAQuad",
+            crs::epsg::ETRS89,
+            Point::new(2_000_000.0, 5_500_000.0),
+            4_500_000.0 / Tile::TILE_SIZE as f64,
+            (1, 1),
+            15,
+        )
+    }
+
+    /// Looks up one of the built-in tile matrix sets by its OGC identifier
+    pub fn from_id(id: &str) -> Result<Self> {
+        match id {
+            "WebMercatorQuad" | "GoogleMapsCompatible" => Ok(Self::google_maps_compatible()),
+            "WorldCRS84Quad" => Ok(Self::world_crs84_quad()),
+            "EuropeanETRS89_LAEAQuad" => Ok(Self::european_etrs89_laea_quad()),
+            _ => Err(Error::InvalidArgument(format!("Unknown tile matrix set: {id}"))),
+        }
+    }
+
+    /// Creates a tile matrix set where every zoom level halves the cell size of the previous one
+    fn quad(id: &str, crs: Epsg, origin: Point, level0_cell_size: f64, level0_tiles: (u32, u32), max_zoom: u32) -> Self {
+        let tile_matrices = (0..=max_zoom)
+            .map(|zoom| TileMatrix {
+                id: zoom.to_string(),
+                cell_size: level0_cell_size / f64::powi(2.0, zoom as i32),
+                point_of_origin: origin,
+                tile_width: Tile::TILE_SIZE,
+                tile_height: Tile::TILE_SIZE,
+                matrix_width: level0_tiles.0 << zoom,
+                matrix_height: level0_tiles.1 << zoom,
+            })
+            .collect();
+
+        TileMatrixSet {
+            id: id.to_string(),
+            crs,
+            tile_matrices,
+        }
+    }
+
+    /// Parses a tile matrix set from its OGC TMS 2.0 JSON encoding
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        json::parse_tile_matrix_set(json)
+    }
+
+    /// Reads a tile matrix set from an OGC TMS 2.0 JSON file
+    #[cfg(feature = "serde")]
+    pub fn from_json_file(path: &std::path::Path) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The OGC TMS 2.0 JSON encoding of the tile matrix set, the easting is the first axis
+    pub fn to_json(&self) -> String {
+        let json_string = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+
+        let tile_matrices: Vec<String> = self
+            .tile_matrices
+            .iter()
+            .map(|matrix| {
+                format!(
+                    r#"{{"id":{},"cellSize":{},"cornerOfOrigin":"topLeft","pointOfOrigin":[{},{}],"tileWidth":{},"tileHeight":{},"matrixWidth":{},"matrixHeight":{}}}"#,
+                    json_string(&matrix.id),
+                    matrix.cell_size,
+                    matrix.point_of_origin.x(),
+                    matrix.point_of_origin.y(),
+                    matrix.tile_width,
+                    matrix.tile_height,
+                    matrix.matrix_width,
+                    matrix.matrix_height,
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"id":{},"crs":"http://www.opengis.net/def/crs/EPSG/0/{}","orderedAxes":["X","Y"],"tileMatrices":[{}]}}"#,
+            json_string(&self.id),
+            self.crs.code(),
+            tile_matrices.join(",")
+        )
+    }
+
+    /// The same tiling as the other tile matrix set (the ids are not compared)
+    pub fn matches(&self, other: &TileMatrixSet) -> bool {
+        self.crs == other.crs
+            && self.tile_matrices.len() == other.tile_matrices.len()
+            && self.tile_matrices.iter().zip(&other.tile_matrices).all(|(a, b)| a.matches(b))
+    }
+
+    pub fn is_google_maps_compatible(&self) -> bool {
+        self.matches(&Self::google_maps_compatible())
+    }
+
+    pub fn max_zoom(&self) -> i32 {
+        self.tile_matrices.len() as i32 - 1
+    }
+
+    pub fn tile_matrix(&self, zoom_level: i32) -> Result<&TileMatrix> {
+        usize::try_from(zoom_level)
+            .ok()
+            .and_then(|zoom| self.tile_matrices.get(zoom))
+            .ok_or_else(|| Error::InvalidArgument(format!("Zoom level {zoom_level} is not part of tile matrix set {}", self.id)))
+    }
+
+    /// The cell size at the given zoom level when the tiles are rendered at `tile_size` pixels.
+    pub fn cell_size(&self, zoom_level: i32, tile_size: u32) -> Result<f64> {
+        Ok(self.tile_matrix(zoom_level)?.tile_span_x() / tile_size as f64)
+    }
+
+    /// Calculates the zoom level for a given cell size, the strategy has the same meaning as in `Tile::zoom_level_for_pixel_size`.
+    /// The result is limited to the zoom levels of the tile matrix set.
+    pub fn zoom_level_for_cell_size(&self, cell_size: f64, strategy: ZoomLevelStrategy, tile_size: u32) -> i32 {
+        const RELATIVE_TOLERANCE: f64 = 1e-9;
+
+        let zoom_cell_size = |zoom: i32| self.tile_matrices[zoom as usize].tile_span_x() / tile_size as f64;
+        let mut zoom_levels = 0..=self.max_zoom();
+
+        match strategy {
+            ZoomLevelStrategy::PreferHigher => zoom_levels
+                .find(|&zoom| zoom_cell_size(zoom) <= cell_size * (1.0 + RELATIVE_TOLERANCE))
+                .unwrap_or(self.max_zoom()),
+            ZoomLevelStrategy::PreferLower => zoom_levels
+                .rev()
+                .find(|&zoom| zoom_cell_size(zoom) >= cell_size * (1.0 - RELATIVE_TOLERANCE))
+                .unwrap_or(0),
+            ZoomLevelStrategy::Closest => zoom_levels
+                .min_by(|&lhs, &rhs| {
+                    let lhs_diff = (zoom_cell_size(lhs) / cell_size).ln().abs();
+                    let rhs_diff = (zoom_cell_size(rhs) / cell_size).ln().abs();
+                    lhs_diff.total_cmp(&rhs_diff)
+                })
+                .unwrap_or(0),
+            ZoomLevelStrategy::Manual(zoom) => zoom,
+        }
+    }
+
+    /// The tile at the given zoom level that contains the point, points outside of the matrix are clamped to the edge tiles.
+    pub fn tile_for_point(&self, point: Point, zoom_level: i32) -> Result<Tile> {
+        let matrix = self.tile_matrix(zoom_level)?;
+        let col = ((point.x() - matrix.point_of_origin.x()) / matrix.tile_span_x() + TILE_EDGE_EPSILON).floor();
+        let row = ((matrix.point_of_origin.y() - point.y()) / matrix.tile_span_y() + TILE_EDGE_EPSILON).floor();
+
+        Ok(Tile {
+            x: col.clamp(0.0, matrix.matrix_width as f64 - 1.0) as i32,
+            y: row.clamp(0.0, matrix.matrix_height as f64 - 1.0) as i32,
+            z: zoom_level,
+        })
+    }
+
+    /// The bounds of the tile in the tile matrix set CRS
+    pub fn tile_bounds(&self, tile: &Tile) -> Result<Rect<f64>> {
+        let matrix = self.tile_matrix(tile.z)?;
+        let left = matrix.point_of_origin.x() + tile.x as f64 * matrix.tile_span_x();
+        let top = matrix.point_of_origin.y() - tile.y as f64 * matrix.tile_span_y();
+
+        Ok(Rect::from_nw_se(
+            Point::new(left, top),
+            Point::new(left + matrix.tile_span_x(), top - matrix.tile_span_y()),
+        ))
+    }
+
+    /// The georeference of the tile when it is rendered at `tile_size` pixels
+    pub fn tile_georeference(&self, tile: &Tile, tile_size: u32) -> Result<GeoReference> {
+        let bounds = self.tile_bounds(tile)?;
+        let raster_size = RasterSize::with_rows_cols(Rows(tile_size as i32), Columns(tile_size as i32));
+
+        Ok(GeoReference::with_bottom_left_origin(
+            self.crs.to_string(),
+            raster_size,
+            bounds.bottom_left(),
+            CellSize::square(self.cell_size(tile.z, tile_size)?),
+            Some(f64::NAN),
+        ))
+    }
+
+    /// Converts a rectangle in the tile matrix set CRS to WGS84 bounds
+    pub fn lat_lon_bounds(&self, bounds: &Rect<f64>) -> Result<LatLonBounds> {
+        let (top_left, bottom_right) = if self.crs == crs::epsg::WGS84_WEB_MERCATOR {
+            (
+                crs::web_mercator_to_lat_lon(bounds.top_left()),
+                crs::web_mercator_to_lat_lon(bounds.bottom_right()),
+            )
+        } else if self.crs == crs::epsg::WGS84 {
+            (
+                Coordinate::latlon(bounds.top_left().y(), bounds.top_left().x()),
+                Coordinate::latlon(bounds.bottom_right().y(), bounds.bottom_right().x()),
+            )
+        } else {
+            return self.transformed_lat_lon_bounds(bounds);
+        };
+
+        Ok(LatLonBounds::hull(top_left, bottom_right))
+    }
+
+    #[cfg(any(feature = "proj", feature = "proj4rs"))]
+    fn transformed_lat_lon_bounds(&self, bounds: &Rect<f64>) -> Result<LatLonBounds> {
+        let transformer = crate::srs::CoordinateTransformer::from_epsg(self.crs, crs::epsg::WGS84)?;
+        let mut corners = [bounds.top_left(), bounds.top_right(), bounds.bottom_left(), bounds.bottom_right()];
+        transformer.transform_points_in_place(&mut corners)?;
+
+        let mut result = LatLonBounds::hull(
+            Coordinate::latlon(corners[0].y(), corners[0].x()),
+            Coordinate::latlon(corners[1].y(), corners[1].x()),
+        );
+        for corner in &corners[2..] {
+            result.extend(Coordinate::latlon(corner.y(), corner.x()));
+        }
+
+        Ok(result)
+    }
+
+    #[cfg(not(any(feature = "proj", feature = "proj4rs")))]
+    fn transformed_lat_lon_bounds(&self, _bounds: &Rect<f64>) -> Result<LatLonBounds> {
+        Err(Error::Runtime(format!(
+            "Converting {} bounds to WGS84 requires a projection backend",
+            self.crs
+        )))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod json {
+    use super::{Point, TileMatrix, TileMatrixSet};
+    use crate::{Error, Result, crs::Epsg};
+
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum CrsDefinition {
+        Uri(String),
+        Object { uri: String },
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TileMatrixDefinition {
+        id: String,
+        cell_size: f64,
+        #[serde(default)]
+        corner_of_origin: Option<String>,
+        point_of_origin: [f64; 2],
+        tile_width: u32,
+        tile_height: u32,
+        matrix_width: u32,
+        matrix_height: u32,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TileMatrixSetDefinition {
+        #[serde(default)]
+        id: Option<String>,
+        crs: CrsDefinition,
+        #[serde(default)]
+        ordered_axes: Option<Vec<String>>,
+        tile_matrices: Vec<TileMatrixDefinition>,
+    }
+
+    /// Returns the EPSG code of the CRS uri and whether the first axis is the easting/longitude
+    fn parse_crs(uri: &str) -> Result<(Epsg, bool)> {
+        let uri = uri.trim();
+        if uri.ends_with("/CRS84") || uri.ends_with(":CRS84") {
+            return Ok((crate::crs::epsg::WGS84, true));
+        }
+
+        let is_epsg = uri.contains("/EPSG/") || uri.starts_with("EPSG:") || uri.contains(":EPSG:");
+        let code = uri.rsplit(['/', ':']).next().and_then(|code| code.parse::<u16>().ok());
+        match code {
+            Some(code) if is_epsg => Ok((Epsg::new(code), !has_northing_first_axis_order(code))),
+            _ => Err(Error::InvalidArgument(format!("Unsupported tile matrix set CRS: {uri}"))),
+        }
+    }
+
+    /// EPSG geographic and LAEA/LCC Europe systems define the northing (latitude) as first axis
+    fn has_northing_first_axis_order(code: u16) -> bool {
+        matches!(code, 4326 | 4258 | 3034 | 3035)
+    }
+
+    fn is_northing_axis(axis: &str) -> bool {
+        matches!(axis.to_ascii_lowercase().as_str(), "y" | "n" | "lat" | "latitude" | "northing")
+    }
+
+    pub(super) fn parse_tile_matrix_set(json: &str) -> Result<TileMatrixSet> {
+        let definition: TileMatrixSetDefinition =
+            serde_json::from_str(json).map_err(|err| Error::InvalidArgument(format!("Invalid tile matrix set JSON: {err}")))?;
+
+        let crs_uri = match &definition.crs {
+            CrsDefinition::Uri(uri) | CrsDefinition::Object { uri } => uri,
+        };
+        let (crs, mut easting_first) = parse_crs(crs_uri)?;
+        if let Some(axis) = definition.ordered_axes.as_ref().and_then(|axes| axes.first()) {
+            easting_first = !is_northing_axis(axis);
+        }
+
+        let tile_matrices = definition
+            .tile_matrices
+            .into_iter()
+            .map(|matrix| {
+                let [first, second] = matrix.point_of_origin;
+                let mut origin = if easting_first {
+                    Point::new(first, second)
+                } else {
+                    Point::new(second, first)
+                };
+
+                match matrix.corner_of_origin.as_deref() {
+                    None | Some("topLeft") => {}
+                    Some("bottomLeft") => {
+                        origin.set_y(origin.y() + matrix.cell_size * matrix.tile_height as f64 * matrix.matrix_height as f64);
+                    }
+                    Some(corner) => {
+                        return Err(Error::InvalidArgument(format!(
+                            "Unsupported tile matrix corner of origin: {corner}"
+                        )));
+                    }
+                }
+
+                Ok(TileMatrix {
+                    id: matrix.id,
+                    cell_size: matrix.cell_size,
+                    point_of_origin: origin,
+                    tile_width: matrix.tile_width,
+                    tile_height: matrix.tile_height,
+                    matrix_width: matrix.matrix_width,
+                    matrix_height: matrix.matrix_height,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if tile_matrices.is_empty() {
+            return Err(Error::InvalidArgument("Tile matrix set does not contain tile matrices".to_string()));
+        }
+
+        Ok(TileMatrixSet {
+            id: definition.id.unwrap_or_default(),
+            crs,
+            tile_matrices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn google_maps_compatible_matches_xyz_tiles() -> Result<()> {
+        let tms = TileMatrixSet::google_maps_compatible();
+
+        for tile in [
+            Tile { x: 0, y: 0, z: 0 },
+            Tile { x: 5, y: 3, z: 3 },
+            Tile { x: 8452, y: 5496, z: 14 },
+        ] {
+            let expected = tile.web_mercator_bounds();
+            let bounds = tms.tile_bounds(&tile)?;
+            assert_relative_eq!(bounds.top_left(), expected.top_left(), epsilon = 1e-6);
+            assert_relative_eq!(bounds.bottom_right(), expected.bottom_right(), epsilon = 1e-6);
+
+            let center = Point::new(
+                (bounds.top_left().x() + bounds.bottom_right().x()) / 2.0,
+                (bounds.top_left().y() + bounds.bottom_right().y()) / 2.0,
+            );
+            assert_eq!(tms.tile_for_point(center, tile.z)?, tile);
+            assert_eq!(tms.tile_for_point(bounds.top_left(), tile.z)?, tile);
+        }
+
+        for zoom in 0..20 {
+            assert_relative_eq!(tms.cell_size(zoom, 256)?, Tile::pixel_size_at_zoom_level(zoom, 256), epsilon = 1e-9);
+            assert_relative_eq!(tms.cell_size(zoom, 512)?, Tile::pixel_size_at_zoom_level(zoom, 512), epsilon = 1e-9);
+
+            let cell_size = Tile::pixel_size_at_zoom_level(zoom, 256);
+            for strategy in [
+                ZoomLevelStrategy::Closest,
+                ZoomLevelStrategy::PreferHigher,
+                ZoomLevelStrategy::PreferLower,
+            ] {
+                assert_eq!(tms.zoom_level_for_cell_size(cell_size, strategy, 256), zoom);
+            }
+        }
+
+        assert_eq!(tms.zoom_level_for_cell_size(100.0, ZoomLevelStrategy::PreferHigher, 256), 11);
+        assert_eq!(tms.zoom_level_for_cell_size(100.0, ZoomLevelStrategy::PreferLower, 256), 10);
+
+        assert!(TileMatrixSet::default().is_google_maps_compatible());
+        // Definitions with rounded cell sizes (e.g. from JSON) are still compatible
+        let mut rounded = TileMatrixSet::google_maps_compatible();
+        rounded.tile_matrices[0].cell_size *= 1.0 + 1e-12;
+        assert!(rounded.is_google_maps_compatible());
+        assert!(!TileMatrixSet::world_crs84_quad().is_google_maps_compatible());
+        assert!(tms.tile_matrix(25).is_err());
+        assert!(tms.tile_matrix(-1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn world_crs84_quad() -> Result<()> {
+        let tms = TileMatrixSet::world_crs84_quad();
+
+        let east = tms.tile_bounds(&Tile { x: 1, y: 0, z: 0 })?;
+        assert_eq!(east.top_left(), Point::new(0.0, 90.0));
+        assert_eq!(east.bottom_right(), Point::new(180.0, -90.0));
+
+        assert_eq!(tms.tile_for_point(Point::new(4.4, 51.2), 2)?, Tile { x: 4, y: 0, z: 2 });
+        // Points outside of the matrix are clamped
+        assert_eq!(tms.tile_for_point(Point::new(200.0, -100.0), 1)?, Tile { x: 3, y: 1, z: 1 });
+
+        let bounds = tms.lat_lon_bounds(&east)?;
+        assert_relative_eq!(bounds.northwest().latitude, 90.0);
+        assert_relative_eq!(bounds.southeast().longitude, 180.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn european_etrs89_laea_quad() -> Result<()> {
+        let tms = TileMatrixSet::european_etrs89_laea_quad();
+        assert_eq!(tms.max_zoom(), 15);
+        assert_relative_eq!(tms.cell_size(0, 256)?, 17578.125);
+
+        // Brussels in EPSG:3035
+        let tile = tms.tile_for_point(Point::new(3_930_000.0, 3_100_000.0), 5)?;
+        assert_eq!(tile, Tile { x: 13, y: 17, z: 5 });
+
+        let bounds = tms.tile_bounds(&tile)?;
+        assert_relative_eq!(bounds.top_left(), Point::new(3_828_125.0, 3_109_375.0));
+        assert_relative_eq!(bounds.bottom_right(), Point::new(3_968_750.0, 2_968_750.0));
+
+        let geo_ref = tms.tile_georeference(&tile, 512)?;
+        assert_eq!(geo_ref.projection(), "EPSG:3035");
+        assert_relative_eq!(geo_ref.cell_size_x(), 140_625.0 / 512.0);
+        assert_relative_eq!(geo_ref.top_left(), bounds.top_left());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tile_matrix_set_from_json() -> Result<()> {
+        let json = r#"{
+            "id": "EuropeanETRS89_LAEAQuad",
+            "title": "Lambert Azimuthal Equal Area ETRS89 for Europe",
+            "crs": "http://www.opengis.net/def/crs/EPSG/0/3035",
+            "orderedAxes": ["Y", "X"],
+            "tileMatrices": [
+                {
+                    "id": "0",
+                    "scaleDenominator": 62779017.857142866,
+                    "cellSize": 17578.125,
+                    "cornerOfOrigin": "topLeft",
+                    "pointOfOrigin": [5500000.0, 2000000.0],
+                    "tileWidth": 256,
+                    "tileHeight": 256,
+                    "matrixWidth": 1,
+                    "matrixHeight": 1
+                },
+                {
+                    "id": "1",
+                    "scaleDenominator": 31389508.928571433,
+                    "cellSize": 8789.0625,
+                    "pointOfOrigin": [5500000.0, 2000000.0],
+                    "tileWidth": 256,
+                    "tileHeight": 256,
+                    "matrixWidth": 2,
+                    "matrixHeight": 2
+                }
+            ]
+        }"#;
+
+        let tms = TileMatrixSet::from_json(json)?;
+        let builtin = TileMatrixSet::european_etrs89_laea_quad();
+        assert!(TileMatrixSet::from_json(&builtin.to_json())?.matches(&builtin));
+        assert_eq!(tms.id, builtin.id);
+        assert_eq!(tms.crs, builtin.crs);
+        assert_eq!(tms.tile_matrices[..], builtin.tile_matrices[..2]);
+
+        let crs84 = TileMatrixSet::from_json(
+            r#"{
+                "crs": {"uri": "http://www.opengis.net/def/crs/OGC/1.3/CRS84"},
+                "tileMatrices": [{
+                    "id": "0", "cellSize": 0.703125, "cornerOfOrigin": "bottomLeft", "pointOfOrigin": [-180.0, -90.0],
+                    "tileWidth": 256, "tileHeight": 256, "matrixWidth": 2, "matrixHeight": 1
+                }]
+            }"#,
+        )?;
+        assert_eq!(crs84.crs, crs::epsg::WGS84);
+        assert_eq!(crs84.tile_matrices[0], TileMatrixSet::world_crs84_quad().tile_matrices[0]);
+
+        // Deep zoom levels where the height in cells does not fit in 32 bits
+        let deep = TileMatrixSet::from_json(
+            r#"{
+                "crs": "http://www.opengis.net/def/crs/EPSG/0/3857",
+                "tileMatrices": [{
+                    "id": "24", "cellSize": 0.0093, "cornerOfOrigin": "bottomLeft", "pointOfOrigin": [0.0, 0.0],
+                    "tileWidth": 256, "tileHeight": 256, "matrixWidth": 16777216, "matrixHeight": 16777216
+                }]
+            }"#,
+        )?;
+        assert_relative_eq!(deep.tile_matrices[0].point_of_origin.y(), 0.0093 * 256.0 * 16777216.0);
+
+        assert!(TileMatrixSet::from_json(r#"{"crs": "http://www.opengis.net/def/crs/EPSG/0/3035", "tileMatrices": []}"#).is_err());
+        assert!(TileMatrixSet::from_json("{").is_err());
+
+        Ok(())
+    }
+}
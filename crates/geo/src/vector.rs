@@ -26,6 +26,9 @@ pub mod io;
 #[cfg(feature = "vector-processing")]
 #[cfg_attr(docsrs, doc(cfg(feature = "vector-processing")))]
 pub mod polygoncoverage;
+#[cfg(feature = "vector-processing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vector-processing")))]
+pub mod polygonstatistics;
 pub mod readers;
 
 #[doc(inline)]
@@ -1,6 +1,7 @@
 use crate::Cell;
 use crate::gdalinterop;
 use gdal::vector::Feature;
+use gdal::vector::FieldValue;
 use gdal::vector::LayerAccess;
 
 use crate::Error;
@@ -27,6 +28,18 @@ impl VectorBuilder {
         Ok(Self { layer: ds.into_layer(0)? })
     }
 
+    /// Create a builder for a layer without geometries
+    pub fn with_attribute_layer(name: &str) -> Result<Self> {
+        let mut ds = gdalio::dataset::create_in_memory()?;
+        ds.create_layer(gdal::vector::LayerOptions {
+            name,
+            ty: gdal::vector::OGRwkbGeometryType::wkbNone,
+            ..Default::default()
+        })?;
+
+        Ok(Self { layer: ds.into_layer(0)? })
+    }
+
     /// Add a field to the layer and return the index of the field
     pub fn add_field(&mut self, name: &str, field_type: gdal::vector::OGRFieldType::Type) -> Result<usize> {
         self.layer.create_defn_fields(&[(name, field_type)])?;
@@ -88,6 +101,21 @@ impl VectorBuilder {
         Ok(())
     }
 
+    /// Add a feature without geometry, fields without a value are left empty
+    pub fn add_attribute_feature(&mut self, fields: &[(&str, Option<FieldValue>)]) -> Result<()> {
+        let defn = self.layer.defn();
+        let mut ft = Feature::new(defn)?;
+        for (name, value) in fields {
+            if let Some(value) = value {
+                ft.set_field(defn.field_index(*name)?, value)?;
+            }
+        }
+
+        ft.create(&self.layer)?;
+
+        Ok(())
+    }
+
     pub fn store(self, path: &std::path::Path) -> Result<()> {
         let ds = self.layer.into_dataset();
         algo::translate_ds_to_disk(&ds, path, &[])?;
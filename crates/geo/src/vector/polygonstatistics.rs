@@ -0,0 +1,278 @@
+//! Exact zonal statistics of a raster for polygons, based on the polygon cell coverages.
+//! Cells are weighted by the fraction of the cell that is covered by the polygon.
+
+use std::cmp::Ordering;
+use std::path::Path;
+
+use gdal::vector::{FieldValue, OGRFieldType};
+use inf::progressinfo::AsyncProgressNotification;
+use num::ToPrimitive as _;
+
+use crate::{Array, ArrayMetadata as _, Cell, Error, GeoReference, Result, constants::EARTH_RADIUS_M};
+
+use super::coveragetools::VectorBuilder;
+use super::polygoncoverage::{CoverageConfiguration, CoverageData, PolygonCellCoverage, create_polygon_coverages};
+
+/// The statistics of the raster values within a polygon
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PolygonStatistics {
+    pub id: u64,
+    pub name: String,
+    /// The number of covered cells with data, weighted by their coverage fraction
+    pub count: f64,
+    /// The sum of the cell values, weighted by their coverage fraction
+    pub sum: f64,
+    /// The coverage weighted mean, `None` when the polygon covers no cells with data
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// The value that covers the largest area of the polygon, the lowest value wins in case of a tie
+    pub majority: Option<f64>,
+    /// The covered area per value, sorted on value.
+    /// The area is in square meters for rasters in a geographic coordinate system and in square map units otherwise.
+    pub histogram: Vec<(f64, f64)>,
+}
+
+/// The area of a cell in the given row of the extent.
+/// Geographic cells are projected on a spherical earth, so their area in square meters shrinks towards the poles.
+fn row_cell_area(extent: &GeoReference, row: i32, geographic: bool) -> f64 {
+    let cell_size = extent.cell_size();
+    if !geographic {
+        return f64::abs(cell_size.x() * cell_size.y());
+    }
+
+    let bbox = extent.cell_bounding_box(Cell::from_row_col(row, 0));
+    let lat_top = bbox.top_left().y().clamp(-90.0, 90.0).to_radians();
+    let lat_bottom = bbox.bottom_left().y().clamp(-90.0, 90.0).to_radians();
+    EARTH_RADIUS_M * EARTH_RADIUS_M * cell_size.x().abs().to_radians() * f64::abs(lat_top.sin() - lat_bottom.sin())
+}
+
+fn polygon_statistics<RasterType: Array>(polygon: &PolygonCellCoverage, raster: &RasterType, row_areas: &[f64]) -> PolygonStatistics {
+    let rows = raster.rows().count();
+    let cols = raster.columns().count();
+
+    let mut stats = PolygonStatistics {
+        id: polygon.id,
+        name: polygon.name.clone(),
+        ..Default::default()
+    };
+
+    let mut value_areas = Vec::with_capacity(polygon.cells.len());
+    for cell_info in &polygon.cells {
+        let cell = cell_info.compute_grid_cell;
+        if cell_info.cell_coverage <= 0.0 || cell.row < 0 || cell.row >= rows || cell.col < 0 || cell.col >= cols {
+            continue;
+        }
+
+        let Some(value) = raster.cell_value(cell).and_then(|v| v.to_f64()) else {
+            continue;
+        };
+
+        stats.count += cell_info.cell_coverage;
+        stats.sum += value * cell_info.cell_coverage;
+        stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+        stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+        value_areas.push((value, cell_info.cell_coverage * row_areas[cell.row as usize]));
+    }
+
+    if stats.count > 0.0 {
+        stats.mean = Some(stats.sum / stats.count);
+    }
+
+    value_areas.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    for (value, area) in value_areas {
+        match stats.histogram.last_mut() {
+            Some((last_value, last_area)) if *last_value == value => *last_area += area,
+            _ => stats.histogram.push((value, area)),
+        }
+    }
+
+    stats.majority = stats
+        .histogram
+        .iter()
+        .fold(None, |majority: Option<(f64, f64)>, &(value, area)| match majority {
+            Some((_, majority_area)) if majority_area >= area => majority,
+            _ => Some((value, area)),
+        })
+        .map(|(value, _)| value);
+
+    stats
+}
+
+/// Calculates the statistics of the raster for every polygon in the coverage data.
+/// The raster should have the same dimensions as the extent of the coverage data, nodata cells are ignored.
+/// The histogram areas are in square meters for geographic extents and in square map units otherwise.
+pub fn zonal_statistics<RasterType: Array>(coverages: &CoverageData, raster: &RasterType) -> Result<Vec<PolygonStatistics>> {
+    if coverages.extent.raster_size() != raster.size() {
+        return Err(Error::InvalidArgument(
+            "Raster dimensions should match the polygon coverage extent".into(),
+        ));
+    }
+
+    let geographic = coverages.extent.is_geographic();
+    let row_areas: Vec<f64> = (0..coverages.extent.rows().count())
+        .map(|row| row_cell_area(&coverages.extent, row, geographic))
+        .collect();
+
+    let mut result: Vec<PolygonStatistics> = coverages
+        .polygons
+        .iter()
+        .map(|polygon| polygon_statistics(polygon, raster, &row_areas))
+        .collect();
+
+    // The coverages are processed in parallel, so sort the results to obtain a stable output order
+    result.sort_by_key(|stats| stats.id);
+    Ok(result)
+}
+
+/// Calculates the statistics of the raster for every polygon in the vector dataset.
+/// The polygon coverages are calculated on the grid of the raster.
+pub fn zonal_statistics_for_dataset<RasterType: Array>(
+    vector_ds: &gdal::Dataset,
+    raster: &RasterType,
+    config: CoverageConfiguration,
+    progress_cb: impl AsyncProgressNotification,
+) -> Result<Vec<PolygonStatistics>> {
+    let coverages = create_polygon_coverages(vector_ds, &raster.metadata().geo_reference(), config, progress_cb)?;
+    zonal_statistics(&coverages, raster)
+}
+
+fn histogram_to_string(histogram: &[(f64, f64)]) -> String {
+    histogram
+        .iter()
+        .map(|(value, area)| format!("{value}:{area}"))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Writes the polygon statistics to disk, the output format is derived from the file extension (e.g. csv or gpkg).
+/// The histogram is stored as a text field of `value:area` pairs separated by semicolons.
+pub fn write_zonal_statistics(stats: &[PolygonStatistics], path: &Path) -> Result<()> {
+    let mut builder = VectorBuilder::with_attribute_layer("zonal_statistics")?;
+    builder.add_field("id", OGRFieldType::OFTInteger64)?;
+    builder.add_field("name", OGRFieldType::OFTString)?;
+    builder.add_field("count", OGRFieldType::OFTReal)?;
+    builder.add_field("sum", OGRFieldType::OFTReal)?;
+    builder.add_field("mean", OGRFieldType::OFTReal)?;
+    builder.add_field("min", OGRFieldType::OFTReal)?;
+    builder.add_field("max", OGRFieldType::OFTReal)?;
+    builder.add_field("majority", OGRFieldType::OFTReal)?;
+    builder.add_field("histogram", OGRFieldType::OFTString)?;
+
+    for polygon in stats {
+        builder.add_attribute_feature(&[
+            ("id", Some(FieldValue::Integer64Value(polygon.id as i64))),
+            ("name", Some(FieldValue::StringValue(polygon.name.clone()))),
+            ("count", Some(FieldValue::RealValue(polygon.count))),
+            ("sum", Some(FieldValue::RealValue(polygon.sum))),
+            ("mean", polygon.mean.map(FieldValue::RealValue)),
+            ("min", polygon.min.map(FieldValue::RealValue)),
+            ("max", polygon.max.map(FieldValue::RealValue)),
+            ("majority", polygon.majority.map(FieldValue::RealValue)),
+            ("histogram", Some(FieldValue::StringValue(histogram_to_string(&polygon.histogram)))),
+        ])?;
+    }
+
+    builder.store(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use inf::allocate;
+
+    use crate::{
+        ArrayInterop as _, Cell, CellSize, GeoReference, Point, RasterSize,
+        array::{Columns, Rows},
+        raster::DenseRaster,
+        testutils::NOD,
+    };
+
+    use super::super::polygoncoverage::CellInfo;
+    use super::*;
+
+    fn cell_info(row: i32, col: i32, cell_coverage: f64) -> CellInfo {
+        CellInfo {
+            compute_grid_cell: Cell::from_row_col(row, col),
+            cell_coverage,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn coverage_weighted_statistics() -> Result<()> {
+        let extent = GeoReference::with_bottom_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(2), Columns(2)),
+            Point::new(0.0, 0.0),
+            CellSize::square(10.0),
+            Some(NOD),
+        );
+
+        #[rustfmt::skip]
+        let raster = DenseRaster::<f64>::new_init_nodata(extent.clone(), allocate::aligned_vec_from_slice(&[
+            1.0, 3.0,
+            3.0, NOD,
+        ]))?;
+
+        let coverages = CoverageData {
+            extent,
+            polygons: vec![
+                PolygonCellCoverage {
+                    id: 2,
+                    name: "empty".to_string(),
+                    cells: vec![cell_info(1, 1, 1.0)],
+                    ..Default::default()
+                },
+                PolygonCellCoverage {
+                    id: 1,
+                    name: "poly".to_string(),
+                    cells: vec![
+                        cell_info(0, 0, 1.0),
+                        cell_info(0, 1, 0.5),
+                        cell_info(1, 0, 0.25),
+                        cell_info(1, 1, 1.0),
+                    ],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let stats = zonal_statistics(&coverages, &raster)?;
+        assert_eq!(stats.len(), 2);
+
+        let poly = &stats[0];
+        assert_eq!(poly.name, "poly");
+        assert_relative_eq!(poly.count, 1.75);
+        assert_relative_eq!(poly.sum, 1.0 + 1.5 + 0.75);
+        assert_relative_eq!(poly.mean.unwrap(), 3.25 / 1.75);
+        assert_eq!(poly.min, Some(1.0));
+        assert_eq!(poly.max, Some(3.0));
+        assert_eq!(poly.majority, Some(1.0));
+        assert_eq!(poly.histogram, vec![(1.0, 100.0), (3.0, 75.0)]);
+
+        let empty = &stats[1];
+        assert_eq!(empty.count, 0.0);
+        assert_eq!(empty.mean, None);
+        assert_eq!(empty.majority, None);
+        assert!(empty.histogram.is_empty());
+
+        Ok(())
+    }
+    #[test]
+    fn geographic_cell_area() {
+        // Two rows of cells that span a quarter of the hemisphere, from the equator to the poles
+        let extent = GeoReference::with_top_left_origin(
+            "",
+            RasterSize::with_rows_cols(Rows(2), Columns(1)),
+            Point::new(0.0, 90.0),
+            CellSize::square(90.0),
+            Some(NOD),
+        );
+
+        let quarter_hemisphere = std::f64::consts::PI * EARTH_RADIUS_M * EARTH_RADIUS_M / 2.0;
+        assert_relative_eq!(row_cell_area(&extent, 0, true), quarter_hemisphere, max_relative = 1e-12);
+        assert_relative_eq!(row_cell_area(&extent, 1, true), quarter_hemisphere, max_relative = 1e-12);
+        assert_relative_eq!(row_cell_area(&extent, 1, false), 8100.0);
+    }
+}
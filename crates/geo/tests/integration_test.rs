@@ -249,4 +249,50 @@ mod tests {
             assert_relative_eq!(p.cells.iter().map(|c| c.coverage).sum::<f64>(), 1.0, epsilon = 1e-10);
         }
     }
+
+    #[test_log::test]
+    #[cfg(all(feature = "gdal", feature = "vector-processing"))]
+    fn integration_polygon_zonal_statistics() {
+        use gdal::vector::LayerAccess as _;
+        use geo::{Array as _, raster::DenseRaster};
+
+        let path = path!(env!("CARGO_MANIFEST_DIR") / "tests" / "data" / "boundaries.gpkg");
+
+        let config = CoverageConfiguration {
+            name_field: Some("Code3".to_string()),
+            ..Default::default()
+        };
+
+        let ds = vector::gdalio::dataset::open_read_only(&path).unwrap();
+        let extent = GeoReference::with_bottom_left_origin(
+            SpatialReference::from_epsg(Epsg::from(31370)).unwrap().to_wkt().unwrap(),
+            RasterSize::with_rows_cols(Rows(120), Columns(260)),
+            (11000.0, 140000.0).into(),
+            CellSize::square(1000.0),
+            Some(-1.0),
+        )
+        .warped_to_epsg(Epsg::from(4326))
+        .unwrap();
+
+        let raster = DenseRaster::<f32>::filled_with(Some(2.0), extent);
+        let stats = vector::polygonstatistics::zonal_statistics_for_dataset(&ds, &raster, config, DummyProgress).unwrap();
+
+        assert_eq!(stats.len(), 3);
+        for polygon in &stats {
+            assert!(polygon.count > 0.0);
+            assert_relative_eq!(polygon.sum, polygon.count * 2.0, epsilon = 1e-6);
+            assert_relative_eq!(polygon.mean.unwrap(), 2.0, epsilon = 1e-6);
+            assert_eq!(polygon.majority, Some(2.0));
+            assert_eq!(polygon.histogram.len(), 1);
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        for output in ["stats.csv", "stats.gpkg"] {
+            let output_path = tmp.path().join(output);
+            vector::polygonstatistics::write_zonal_statistics(&stats, &output_path).unwrap();
+
+            let output_ds = vector::gdalio::dataset::open_read_only(&output_path).unwrap();
+            assert_eq!(output_ds.layer(0).unwrap().feature_count(), 3);
+        }
+    }
 }
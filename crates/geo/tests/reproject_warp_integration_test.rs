@@ -8,7 +8,7 @@ mod tests {
         crs::{self},
         raster::{
             self, DenseRaster, RasterReadWrite as _,
-            algo::{self, NumThreads, TargetPixelAlignment, TargetSrs, WarpOptions, WarpTargetSize, warp},
+            algo::{self, NumThreads, ResamplingMethod, TargetPixelAlignment, TargetSrs, WarpOptions, WarpTargetSize, warp},
         },
     };
     use path_macro::path;
@@ -167,6 +167,34 @@ mod tests {
         Ok(())
     }
 
+    /// Cells are considered equal when the values differ less than the value tolerance (e.g. rounding differences of interpolated values)
+    fn compare_raster_contents_with_value_tolerance<T>(
+        raster1: &DenseRaster<T>,
+        raster2: &DenseRaster<T>,
+        value_tolerance: f64,
+        cell_diff_percentage_tolerance: f64,
+    ) where
+        T: geo::ArrayNum,
+    {
+        let mismatches = raster1
+            .iter_opt()
+            .zip(raster2.iter_opt())
+            .filter(|(v1, v2)| match (v1, v2) {
+                (Some(v1), Some(v2)) => (v1.to_f64().unwrap_or(f64::NAN) - v2.to_f64().unwrap_or(f64::NAN)).abs() > value_tolerance,
+                (None, None) => false,
+                _ => true,
+            })
+            .count();
+
+        assert!(
+            mismatches as f64 / raster1.len() as f64 * 100.0 < cell_diff_percentage_tolerance,
+            "Raster contents differ too much: {} mismatches out of {} cells ({:.2}%)",
+            mismatches,
+            raster1.len(),
+            mismatches as f64 / raster1.len() as f64 * 100.0
+        );
+    }
+
     #[geo::simd_bounds]
     #[cfg(feature = "gdal")]
     fn warp_using_linked_gdal<T: ArrayNum>(input: &Path, tmp_dir: &TempDir, opts: &WarpOptions) -> Result<DenseRaster<T>> {
@@ -278,6 +306,7 @@ mod tests {
                 target_size: WarpTargetSize::Source,
                 target_srs: TargetSrs::Epsg(crs::epsg::WGS84_WEB_MERCATOR),
                 num_threads: NumThreads::AllCpus,
+                ..Default::default()
             },
             "source_size_et_0_mt",
             1.0,
@@ -312,6 +341,7 @@ mod tests {
                 target_size: WarpTargetSize::Source,
                 target_srs: TargetSrs::Epsg(crs::epsg::WGS84_WEB_MERCATOR),
                 num_threads: NumThreads::AllCpus,
+                ..Default::default()
             },
             "source_size_et_0.125_mt",
             1.0,
@@ -416,6 +446,7 @@ mod tests {
                 target_size: WarpTargetSize::CellSize(CellSize::square(10.0), TargetPixelAlignment::Yes),
                 target_srs: TargetSrs::Epsg(crs::epsg::WGS84_WEB_MERCATOR),
                 num_threads: NumThreads::AllCpus,
+                ..Default::default()
             },
             "cell_size_tap_10m_mt_et_0.125",
             1e-6,
@@ -424,6 +455,66 @@ mod tests {
         )
     }
 
+    /// The value tolerance and the percentage of cells that are allowed to differ from the gdalwarp result.
+    /// The methods that select one of the source values must produce the same values, the remaining budget covers the
+    /// cells with a footprint on a source cell edge (as in the nearest neighbour comparisons).
+    /// Computed values are allowed to differ by one because of the rounding to the integer output type.
+    fn resampling_tolerance(resampling: ResamplingMethod) -> (f64, f64) {
+        match resampling {
+            ResamplingMethod::NearestNeighbour | ResamplingMethod::Mode | ResamplingMethod::Min | ResamplingMethod::Max => (0.0, 0.5),
+            ResamplingMethod::Bilinear
+            | ResamplingMethod::Cubic
+            | ResamplingMethod::CubicSpline
+            | ResamplingMethod::Lanczos
+            | ResamplingMethod::Average
+            | ResamplingMethod::Median
+            | ResamplingMethod::Sum => (1.0, 1.0),
+        }
+    }
+
+    fn integration_warp_vs_gdalwarp_resampling(timings: &mut HashMap<String, (Duration, Duration)>) -> Result<()> {
+        let input_path = workspace_test_data_dir().join("landusebyte.tif");
+        let src = DenseRaster::<u8>::read(&input_path)?;
+
+        for resampling in [
+            ResamplingMethod::NearestNeighbour,
+            ResamplingMethod::Bilinear,
+            ResamplingMethod::Cubic,
+            ResamplingMethod::CubicSpline,
+            ResamplingMethod::Lanczos,
+            ResamplingMethod::Average,
+            ResamplingMethod::Mode,
+            ResamplingMethod::Min,
+            ResamplingMethod::Max,
+            ResamplingMethod::Median,
+            ResamplingMethod::Sum,
+        ] {
+            let name = format!("cell_size_tap_500m_{}", resampling.gdal_name());
+            let opts = WarpOptions {
+                error_threshold: 0.0,
+                target_size: WarpTargetSize::CellSize(CellSize::square(500.0), TargetPixelAlignment::Yes),
+                target_srs: TargetSrs::Epsg(crs::epsg::WGS84_WEB_MERCATOR),
+                resampling,
+                ..Default::default()
+            };
+
+            let start = std::time::Instant::now();
+            let geo_raster = warp(&src, &opts)?;
+            let geo_duration = start.elapsed();
+
+            let start = std::time::Instant::now();
+            let gdal_raster = warp_using_gdal::<u8>(&input_path, &opts)?;
+            timings.insert(name.clone(), (geo_duration, start.elapsed()));
+
+            compare_raster_metadata(&geo_raster, &gdal_raster, 1e-6);
+            let (value_tolerance, cell_diff_percentage_tolerance) = resampling_tolerance(resampling);
+            compare_raster_contents_with_value_tolerance(&geo_raster, &gdal_raster, value_tolerance, cell_diff_percentage_tolerance);
+            store_test_output(geo_raster, gdal_raster, &name)?;
+        }
+
+        Ok(())
+    }
+
     #[test_log::test]
     fn run_all_warp_integration_tests() -> Result<()> {
         println!("Running all warp integration tests sequentially...\n");
@@ -440,6 +531,7 @@ mod tests {
         integration_warp_vs_gdalwarp_cell_size_error_threshold(&mut timings)?;
         integration_warp_vs_gdalwarp_cell_size_target_aligned_pixels(&mut timings)?;
         slow_test_integration_warp_vs_gdalwarp_cell_size_target_aligned_pixels_10m_mt(&mut timings)?;
+        integration_warp_vs_gdalwarp_resampling(&mut timings)?;
 
         dump_comparison_timings(timings);
 
@@ -306,6 +306,10 @@ impl Legend {
         )?))
     }
 
+    pub fn categoric(categories: HashMap<i64, LegendCategory>, mapping_config: Option<MappingConfig>) -> Result<Self> {
+        Ok(Legend::CategoricNumeric(create_categoric(categories, mapping_config)?))
+    }
+
     pub fn categoric_string(string_map: HashMap<String, LegendCategory>, mapping_config: Option<MappingConfig>) -> Result<Self> {
         Ok(Legend::CategoricString(create_categoric_string(string_map, mapping_config)?))
     }
@@ -501,6 +505,15 @@ pub fn create_categoric_for_value_list(
     })
 }
 
+/// Create a categoric legend where the color and name of each category value are provided
+pub fn create_categoric(categories: HashMap<i64, LegendCategory>, mapping_config: Option<MappingConfig>) -> Result<CategoricNumericLegend> {
+    Ok(MappedLegend {
+        mapper: colormapper::CategoricNumeric::new(categories),
+        mapping_config: mapping_config.unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
 /// Create a categoric legend with string value mapping
 pub fn create_categoric_string(
     string_map: HashMap<String, LegendCategory>,
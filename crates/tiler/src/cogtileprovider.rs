@@ -28,12 +28,26 @@ pub struct CogTileProvider {
     meta: LayerMetadata,
 }
 
+/// The provider data of a COG layer, stored in the layer metadata
+struct CogLayerData {
+    cog: WebTilesReader,
+    /// The legend of the png tiles, created once when the layer is created
+    default_legend: Legend,
+}
+
+impl CogLayerData {
+    fn from_layer(meta: &LayerMetadata) -> Option<&CogLayerData> {
+        meta.tileprovider_data.as_ref().and_then(|data| data.downcast_ref::<CogLayerData>())
+    }
+}
+
 impl CogTileProvider {
-    pub fn new(path: &Path, _opts: &TileProviderOptions) -> Result<Self> {
-        let cog = WebTilesReader::new(GeoTiffMetadata::from_file(path)?)?;
+    pub fn new(path: &Path, opts: &TileProviderOptions) -> Result<Self> {
+        let cog = WebTilesReader::new(GeoTiffMetadata::from_file(path)?)?.with_chunk_cache(opts.chunk_cache.clone(), path);
         let meta = cog.cog_metadata();
         let tile_info = cog.tile_info();
         let wgs84_meta = meta.geo_reference.warped_to_epsg(crs::epsg::WGS84)?;
+        let default_legend = Self::default_legend(meta);
 
         let meta = LayerMetadata {
             id: unique_layer_id(),
@@ -59,7 +73,7 @@ impl CogTileProvider {
             scheme: "xyz".into(),
             additional_data: HashMap::new(),
             band_nr: Some(1), // Default to band 1 for single-band COGs
-            tileprovider_data: Some(Box::new(Arc::new(cog))),
+            tileprovider_data: Some(Box::new(Arc::new(CogLayerData { cog, default_legend }))),
         };
 
         log::info!(
@@ -85,11 +99,8 @@ impl CogTileProvider {
         let band = meta.band_nr.unwrap_or(1);
         let band =
             geotiff::BandIndex::new(band).ok_or_else(|| Error::InvalidArgument("Band index is 1-based and must be >= 1".to_string()))?;
-        let tile = meta
-            .tileprovider_data
-            .as_ref()
-            .and_then(|data| data.downcast_ref::<WebTilesReader>())
-            .map(|cog| cog.read_tile_data_as::<T>(tile, band, &mut std::fs::File::open(&meta.path)?));
+        let tile =
+            CogLayerData::from_layer(meta).map(|data| data.cog.read_tile_data_as::<T>(tile, band, &mut std::fs::File::open(&meta.path)?));
 
         match tile {
             Some(Ok(Some(tile_data))) => Ok(tile_data),
@@ -116,16 +127,31 @@ impl CogTileProvider {
             return Ok(TileData::default());
         }
 
-        // The default legend is with grayscale colors in range 0-255
+        let Some(data) = CogLayerData::from_layer(meta) else {
+            return Ok(TileData::default());
+        };
+
         imageprocessing::raw_tile_to_png_color_mapped::<T>(
             raw_tile_data.as_ref(),
             (tile_size * dpi_ratio as u32) as usize,
             (tile_size * dpi_ratio as u32) as usize,
             Some(T::NODATA),
-            &Legend::default(),
+            &data.default_legend,
         )
     }
 
+    /// The legend of the embedded palette or raster attribute table (native colors of categoric rasters),
+    /// otherwise the default legend with grayscale colors in range 0-255
+    fn default_legend(meta: &GeoTiffMetadata) -> Legend {
+        match meta.categoric_legend(None) {
+            Ok(legend) => legend.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to create legend from the tiff palette: {e}");
+                Legend::default()
+            }
+        }
+    }
+
     #[geo::simd_bounds]
     fn read_tile_data_color_mappped<T: ArrayNum>(meta: &LayerMetadata, tile_req: &ColorMappedTileRequest) -> Result<TileData> {
         log::debug!(
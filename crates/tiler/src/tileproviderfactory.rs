@@ -1,18 +1,36 @@
-use geo::{ZoomLevelStrategy, raster::formats::RasterFileFormat};
+use geo::{ZoomLevelStrategy, cog::ChunkCache, raster::formats::RasterFileFormat};
 
 use crate::{Result, cogtileprovider::CogTileProvider};
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     Error, directorytileprovider::DirectoryTileProvider, mbtilestileprovider::MbtilesTileProvider, tileprovider::TileProvider,
     warpingtileprovider::WarpingTileProvider,
 };
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct TileProviderOptions {
     pub calculate_stats: bool,
     // when calculating the max zoom levelm prefer the higher value when the cellsize is between two zoom levels
     pub zoom_level_strategy: ZoomLevelStrategy,
+    // cache of decoded COG chunks, shared by all the layers that are created with these options
+    pub chunk_cache: Arc<ChunkCache>,
+}
+
+impl Default for TileProviderOptions {
+    /// The default options all share the same process wide chunk cache
+    fn default() -> Self {
+        static SHARED_CHUNK_CACHE: OnceLock<Arc<ChunkCache>> = OnceLock::new();
+
+        Self {
+            calculate_stats: false,
+            zoom_level_strategy: ZoomLevelStrategy::default(),
+            chunk_cache: SHARED_CHUNK_CACHE.get_or_init(|| Arc::new(ChunkCache::default())).clone(),
+        }
+    }
 }
 
 /// Create a tile provider for hosting a single file
@@ -49,3 +67,18 @@ pub fn create_tile_provider(path: &Path, opts: &TileProviderOptions) -> Result<B
 
     Err(Error::Runtime(format!("Invalid location provided: {}", path.to_string_lossy())))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_options_share_the_chunk_cache() {
+        let opts = TileProviderOptions {
+            calculate_stats: true,
+            ..Default::default()
+        };
+
+        assert!(Arc::ptr_eq(&opts.chunk_cache, &TileProviderOptions::default().chunk_cache));
+    }
+}
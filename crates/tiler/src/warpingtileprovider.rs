@@ -278,6 +278,7 @@ mod tests {
                 &TileProviderOptions {
                     calculate_stats: false,
                     zoom_level_strategy: ZoomLevelStrategy::PreferLower,
+                    ..Default::default()
                 },
             )?;
 
@@ -289,6 +290,7 @@ mod tests {
                 &TileProviderOptions {
                     calculate_stats: false,
                     zoom_level_strategy: ZoomLevelStrategy::PreferHigher,
+                    ..Default::default()
                 },
             )?;
 
@@ -306,6 +308,7 @@ mod tests {
             &TileProviderOptions {
                 calculate_stats: false,
                 zoom_level_strategy: ZoomLevelStrategy::PreferHigher,
+                ..Default::default()
             },
         )?;
         let layer_meta = provider.layers().first().unwrap().clone();
@@ -357,6 +360,7 @@ mod tests {
             &TileProviderOptions {
                 calculate_stats: false,
                 zoom_level_strategy: ZoomLevelStrategy::PreferHigher,
+                ..Default::default()
             },
         )?;
         let layer_meta = provider.layers().first().unwrap().clone();
@@ -456,6 +460,7 @@ mod tests {
             &TileProviderOptions {
                 calculate_stats: false,
                 zoom_level_strategy: ZoomLevelStrategy::PreferLower,
+                ..Default::default()
             },
         )?;
         let layer_id = provider.layers().first().unwrap().id;
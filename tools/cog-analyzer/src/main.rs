@@ -2,7 +2,7 @@
 
 use std::io;
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
@@ -23,12 +23,23 @@ use cog_analyzer::ui;
 struct Cli {
     /// Path to the GeoTIFF file to analyze
     file_path: PathBuf,
+
+    /// Validate the COG layout and print the errors and warnings instead of starting the interactive interface
+    #[arg(long)]
+    validate: bool,
+
+    /// Also validate that the levels match the GoogleMapsCompatible web tiling scheme
+    #[arg(long, requires = "validate")]
+    web_tiling: bool,
 }
 
 fn main() -> Result<()> {
     env_logger::init();
 
     let cli = Cli::parse();
+    if cli.validate {
+        return validate(&cli.file_path, cli.web_tiling);
+    }
 
     // Initialize the application
     let mut app = App::new(cli.file_path)?;
@@ -48,6 +59,26 @@ fn main() -> Result<()> {
     result
 }
 
+fn validate(path: &Path, web_tiling: bool) -> Result<()> {
+    let options = geo::cog::debug::CogValidationOptions { web_tiling };
+    let report = geo::cog::debug::validate_cog_file(path, &options)?;
+
+    for warning in &report.warnings {
+        println!("Warning: {warning}");
+    }
+
+    for error in &report.errors {
+        println!("Error: {error}");
+    }
+
+    if !report.is_valid() {
+        anyhow::bail!("{} is not a valid COG", path.display());
+    }
+
+    println!("{} is a valid COG", path.display());
+    Ok(())
+}
+
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     terminal::enable_raw_mode()?;
     crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
@@ -106,6 +106,7 @@ pub fn create_mbtiles(
     let tiler_options = tileproviderfactory::TileProviderOptions {
         calculate_stats: true,
         zoom_level_strategy: opts.zoom_level_strategy,
+        ..Default::default()
     };
 
     let tiler = WarpingTileProvider::new(input, &tiler_options)?;
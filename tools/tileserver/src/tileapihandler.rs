@@ -204,6 +204,7 @@ impl TileApiHandler {
         let opts = TileProviderOptions {
             calculate_stats: true,
             zoom_level_strategy: ZoomLevelStrategy::PreferHigher,
+            ..Default::default()
         };
 
         let tile_provider = create_tile_provider(gis_dir, &opts)?;